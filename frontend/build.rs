@@ -0,0 +1,22 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Clients only, without tonic's transport (the browser talks gRPC-Web)
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(true)
+        .build_transport(false)
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .compile(
+            &[
+                "../protos/common.proto",
+                "../protos/trading.proto",
+                "../protos/pricing.proto",
+            ],
+            &["../protos"],
+        )?;
+    
+    println!("cargo:rerun-if-changed=../protos/common.proto");
+    println!("cargo:rerun-if-changed=../protos/trading.proto");
+    println!("cargo:rerun-if-changed=../protos/pricing.proto");
+    
+    Ok(())
+}
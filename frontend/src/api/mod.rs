@@ -0,0 +1,309 @@
+// frontend/src/api/mod.rs
+use tonic::{Code, Status, Streaming};
+use tonic_web_wasm_client::Client;
+use crate::proto::common::Side;
+use crate::proto::pricing::{
+    pricing_service_client::PricingServiceClient, AmericanRequest, AsianRequest, BarrierRequest,
+    DigitalRequest, EuropeanRequest, ImpliedVolRequest, ImpliedVolResponse, MarketPriceRequest,
+    PriceResponse, SpreadRequest,
+};
+use crate::proto::trading::{
+    trading_service_client::TradingServiceClient, BookUpdate, CancelAllRequest, CancelAllResponse,
+    CancelRequest, CancelResponse, EstimateFillRequest, EstimateFillResponse, ExecutionReport,
+    MassQuoteRequest, MassQuoteResponse, OrderBookRequest, OrderBookSnapshot, OrderRequest, OrderResponse, OrderStatusRequest,
+    OrderStatusResponse, QuoteReport, ReplaceRequest, ReplaceResponse, StreamRequest, TradeReport,
+};
+
+/// A failed RPC, grouped by what the UI should tell the user
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiError {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    
+    #[error("Please sign in again: {0}")]
+    Unauthenticated(String),
+    
+    #[error("Not allowed: {0}")]
+    PermissionDenied(String),
+    
+    #[error("Not found: {0}")]
+    NotFound(String),
+    
+    #[error("Too many requests: {0}")]
+    RateLimited(String),
+    
+    #[error("The server took too long to respond: {0}")]
+    Timeout(String),
+    
+    /// Server or network down; retrying later may succeed
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+    
+    #[error("Server error: {0}")]
+    Server(String),
+}
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        let message = status.message().to_string();
+        match status.code() {
+            Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange | Code::AlreadyExists => {
+                ApiError::InvalidRequest(message)
+            }
+            Code::Unauthenticated => ApiError::Unauthenticated(message),
+            Code::PermissionDenied => ApiError::PermissionDenied(message),
+            Code::NotFound => ApiError::NotFound(message),
+            Code::ResourceExhausted => ApiError::RateLimited(message),
+            Code::DeadlineExceeded => ApiError::Timeout(message),
+            Code::Unavailable | Code::Cancelled => ApiError::Unavailable(message),
+            _ => ApiError::Server(message),
+        }
+    }
+}
+
+/// TradingService over gRPC-Web. Cheap to clone; provide one via Leptos
+/// context with `provide_context(TradingClient::new(url))`.
+#[derive(Clone)]
+pub struct TradingClient {
+    inner: TradingServiceClient<Client>,
+}
+
+impl TradingClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            inner: TradingServiceClient::new(Client::new(base_url.into())),
+        }
+    }
+    
+    pub async fn submit_order(&self, request: OrderRequest) -> Result<OrderResponse, ApiError> {
+        Ok(self.inner.clone().submit_order(request).await?.into_inner())
+    }
+    
+    pub async fn cancel_order(&self, request: CancelRequest) -> Result<CancelResponse, ApiError> {
+        Ok(self.inner.clone().cancel_order(request).await?.into_inner())
+    }
+    
+    /// Cancel every live order for `user_id`, or only those in `symbol`
+    pub async fn cancel_all_orders(
+        &self,
+        user_id: u64,
+        symbol: String,
+    ) -> Result<CancelAllResponse, ApiError> {
+        let request = CancelAllRequest { user_id, symbol };
+        Ok(self.inner.clone().cancel_all_orders(request).await?.into_inner())
+    }
+    
+    pub async fn replace_order(&self, request: ReplaceRequest) -> Result<ReplaceResponse, ApiError> {
+        Ok(self.inner.clone().replace_order(request).await?.into_inner())
+    }
+    
+    /// Requote several symbols at once; each side's outcome is in `legs`
+    pub async fn mass_quote(&self, request: MassQuoteRequest) -> Result<MassQuoteResponse, ApiError> {
+        Ok(self.inner.clone().mass_quote(request).await?.into_inner())
+    }
+    
+    pub async fn get_order_book(
+        &self,
+        symbol: String,
+        depth: u32,
+    ) -> Result<OrderBookSnapshot, ApiError> {
+        let request = OrderBookRequest { symbol, depth };
+        Ok(self.inner.clone().get_order_book(request).await?.into_inner())
+    }
+    
+    pub async fn get_order_status(
+        &self,
+        client_order_id: u64,
+        user_id: u64,
+    ) -> Result<OrderStatusResponse, ApiError> {
+        let request = OrderStatusRequest {
+            client_order_id,
+            user_id,
+        };
+        Ok(self.inner.clone().get_order_status(request).await?.into_inner())
+    }
+    
+    /// Average price `quantity` would fill at if it swept `symbol`'s book
+    /// now, and how much of it the book can't absorb
+    pub async fn estimate_fill(
+        &self,
+        symbol: String,
+        side: Side,
+        quantity: u64,
+    ) -> Result<EstimateFillResponse, ApiError> {
+        let request = EstimateFillRequest {
+            symbol,
+            side: side as i32,
+            quantity,
+        };
+        Ok(self.inner.clone().estimate_fill(request).await?.into_inner())
+    }
+    
+    /// Trades for `symbol`, or every symbol when empty
+    pub async fn stream_trades(&self, symbol: String) -> Result<Streaming<TradeReport>, ApiError> {
+        let request = StreamRequest { symbol, user_id: 0, resume_from_sequence: 0 };
+        Ok(self.inner.clone().stream_trades(request).await?.into_inner())
+    }
+    
+    /// Best bid and offer for `symbol`, at most one quote per server-side
+    /// quote interval
+    pub async fn stream_quotes(&self, symbol: String) -> Result<Streaming<QuoteReport>, ApiError> {
+        let request = StreamRequest { symbol, user_id: 0, resume_from_sequence: 0 };
+        Ok(self.inner.clone().stream_quotes(request).await?.into_inner())
+    }
+    
+    /// The book for `symbol`: a snapshot, then level deltas. A fresh
+    /// snapshot follows whenever the server's deltas fell out of step.
+    pub async fn stream_order_book(
+        &self,
+        symbol: String,
+    ) -> Result<Streaming<BookUpdate>, ApiError> {
+        let request = StreamRequest { symbol, user_id: 0, resume_from_sequence: 0 };
+        Ok(self.inner.clone().stream_order_book(request).await?.into_inner())
+    }
+    
+    /// Executions for `user_id`'s orders, optionally limited to `symbol`.
+    /// After a reconnect, pass the last `sequence` received to have the
+    /// missed executions replayed first (0 for live only).
+    pub async fn stream_executions(
+        &self,
+        symbol: String,
+        user_id: u64,
+        resume_from_sequence: u64,
+    ) -> Result<Streaming<ExecutionReport>, ApiError> {
+        let request = StreamRequest { symbol, user_id, resume_from_sequence };
+        Ok(self.inner.clone().stream_executions(request).await?.into_inner())
+    }
+}
+
+/// PricingService over gRPC-Web. Cheap to clone; provide one via Leptos
+/// context with `provide_context(PricingClient::new(url))`.
+#[derive(Clone)]
+pub struct PricingClient {
+    inner: PricingServiceClient<Client>,
+}
+
+impl PricingClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            inner: PricingServiceClient::new(Client::new(base_url.into())),
+        }
+    }
+    
+    pub async fn price_european_call(&self, request: EuropeanRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_european_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_european_put(&self, request: EuropeanRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_european_put(request).await?.into_inner())
+    }
+    
+    /// Running estimates of a European call, ending with one marked
+    /// `complete`; drop the stream to stop the simulation early
+    pub async fn price_european_call_progressive(
+        &self,
+        request: EuropeanRequest,
+    ) -> Result<Streaming<PriceResponse>, ApiError> {
+        Ok(self.inner.clone().price_european_call_progressive(request).await?.into_inner())
+    }
+    
+    pub async fn price_american_call(&self, request: AmericanRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_american_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_american_put(&self, request: AmericanRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_american_put(request).await?.into_inner())
+    }
+    
+    pub async fn price_asian_call(&self, request: AsianRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_asian_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_asian_put(&self, request: AsianRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_asian_put(request).await?.into_inner())
+    }
+    
+    pub async fn price_barrier_call(&self, request: BarrierRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_barrier_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_barrier_put(&self, request: BarrierRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_barrier_put(request).await?.into_inner())
+    }
+    
+    pub async fn price_digital_call(&self, request: DigitalRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_digital_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_digital_put(&self, request: DigitalRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_digital_put(request).await?.into_inner())
+    }
+    
+    pub async fn price_spread_call(&self, request: SpreadRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_spread_call(request).await?.into_inner())
+    }
+    
+    pub async fn price_spread_put(&self, request: SpreadRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_spread_put(request).await?.into_inner())
+    }
+    
+    pub async fn price_from_market(&self, request: MarketPriceRequest) -> Result<PriceResponse, ApiError> {
+        Ok(self.inner.clone().price_from_market(request).await?.into_inner())
+    }
+    
+    pub async fn implied_volatility(
+        &self,
+        request: ImpliedVolRequest,
+    ) -> Result<ImpliedVolResponse, ApiError> {
+        Ok(self.inner.clone().implied_volatility(request).await?.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    
+    wasm_bindgen_test_configure!(run_in_browser);
+    
+    #[wasm_bindgen_test]
+    fn status_codes_map_to_api_errors() {
+        let cases = [
+            (Code::InvalidArgument, ApiError::InvalidRequest("bad".to_string())),
+            (Code::FailedPrecondition, ApiError::InvalidRequest("bad".to_string())),
+            (Code::OutOfRange, ApiError::InvalidRequest("bad".to_string())),
+            (Code::AlreadyExists, ApiError::InvalidRequest("bad".to_string())),
+            (Code::Unauthenticated, ApiError::Unauthenticated("bad".to_string())),
+            (Code::PermissionDenied, ApiError::PermissionDenied("bad".to_string())),
+            (Code::NotFound, ApiError::NotFound("bad".to_string())),
+            (Code::ResourceExhausted, ApiError::RateLimited("bad".to_string())),
+            (Code::DeadlineExceeded, ApiError::Timeout("bad".to_string())),
+            (Code::Unavailable, ApiError::Unavailable("bad".to_string())),
+            (Code::Cancelled, ApiError::Unavailable("bad".to_string())),
+            (Code::Internal, ApiError::Server("bad".to_string())),
+            (Code::Unknown, ApiError::Server("bad".to_string())),
+            (Code::DataLoss, ApiError::Server("bad".to_string())),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(ApiError::from(Status::new(code, "bad")), expected, "{:?}", code);
+        }
+    }
+    
+    #[wasm_bindgen_test]
+    fn error_message_keeps_the_server_text() {
+        let error = ApiError::from(Status::resource_exhausted("AAPL has too many orders in flight"));
+        assert_eq!(error.to_string(), "Too many requests: AAPL has too many orders in flight");
+        
+        let error = ApiError::from(Status::unavailable("gateway down"));
+        assert_eq!(error.to_string(), "Service unavailable: gateway down");
+    }
+    
+    #[wasm_bindgen_test]
+    fn clients_are_cheap_to_share() {
+        // Cloned into every component's actions via Leptos context
+        let trading = TradingClient::new("http://localhost:50051");
+        let _shared = trading.clone();
+        let pricing = PricingClient::new("http://localhost:50051");
+        let _shared = pricing.clone();
+    }
+}
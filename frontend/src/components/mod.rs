@@ -0,0 +1,4 @@
+// frontend/src/components/mod.rs
+pub mod order_book;
+pub mod order_entry;
+pub mod pricing_panel;
@@ -0,0 +1,365 @@
+// frontend/src/components/order_book.rs
+use gloo_timers::future::TimeoutFuture;
+use leptos::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use crate::api::TradingClient;
+use crate::proto::common::Side as ProtoSide;
+use crate::proto::trading::{
+    book_update, BookAction as ProtoBookAction, BookDelta, OrderBookSnapshot, PriceLevel,
+};
+use shared::book::{BookAction, BookError, Level, LevelDelta, OrderBook as Book};
+use shared::{Price, Side};
+
+/// Delay before resubscribing after the stream drops
+const RECONNECT_DELAY_MS: u32 = 2000;
+
+/// One rendered price level
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookRow {
+    pub price: f64,
+    pub quantity: u64,
+    pub order_count: u32,
+    /// Quantity at this level and every better one
+    pub cumulative_quantity: u64,
+    /// Cumulative quantity as a percentage of the deepest side, for the depth bar
+    pub depth_percent: f64,
+}
+
+/// Both sides of the book, best price first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookView {
+    pub bids: Vec<BookRow>,
+    pub asks: Vec<BookRow>,
+}
+
+/// Keep `depth` levels per side, best first, and compute cumulative depth
+pub fn build_book_view(book: &Book, depth: usize) -> BookView {
+    let bids: Vec<Level> = book.bids().take(depth).copied().collect();
+    let asks: Vec<Level> = book.asks().take(depth).copied().collect();
+    
+    let total = |levels: &[Level]| levels.iter().map(|level| level.quantity).sum::<u64>();
+    let deepest = total(&bids).max(total(&asks));
+    
+    let rows = |levels: Vec<Level>| {
+        let mut cumulative_quantity = 0;
+        levels
+            .into_iter()
+            .map(|level| {
+                cumulative_quantity += level.quantity;
+                BookRow {
+                    price: level.price.to_dollars(),
+                    quantity: level.quantity,
+                    order_count: level.order_count,
+                    cumulative_quantity,
+                    depth_percent: if deepest == 0 {
+                        0.0
+                    } else {
+                        cumulative_quantity as f64 * 100.0 / deepest as f64
+                    },
+                }
+            })
+            .collect()
+    };
+    
+    BookView {
+        bids: rows(bids),
+        asks: rows(asks),
+    }
+}
+
+fn to_level(level: &PriceLevel) -> Level {
+    Level {
+        price: Price::from_dollars(level.price).unwrap_or(Price::ZERO),
+        quantity: level.quantity,
+        order_count: level.order_count,
+    }
+}
+
+/// The book a snapshot describes
+fn to_book(snapshot: &OrderBookSnapshot) -> Book {
+    Book::from_snapshot(
+        snapshot.sequence as u64,
+        snapshot.bids.iter().map(to_level),
+        snapshot.asks.iter().map(to_level),
+    )
+}
+
+fn to_level_delta(delta: &BookDelta) -> LevelDelta {
+    LevelDelta {
+        side: match delta.side() {
+            ProtoSide::Buy => Side::Buy,
+            ProtoSide::Sell => Side::Sell,
+        },
+        action: match delta.action() {
+            ProtoBookAction::Add => BookAction::Add,
+            ProtoBookAction::Change => BookAction::Change,
+            ProtoBookAction::Delete => BookAction::Delete,
+        },
+        level: delta.level.as_ref().map(to_level).unwrap_or(Level {
+            price: Price::ZERO,
+            quantity: 0,
+            order_count: 0,
+        }),
+    }
+}
+
+/// What the component is showing
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookState {
+    /// Waiting for the first snapshot
+    Loading,
+    Live(Book),
+    /// Stream dropped; the last book, if any, stays on screen until the
+    /// next snapshot
+    Reconnecting(Option<Book>),
+}
+
+impl BookState {
+    /// Take an update from the stream: a snapshot replaces the book, a
+    /// delta changes it. An error means the book no longer matches the
+    /// server's and the stream should be restarted for a fresh snapshot.
+    pub fn apply(&mut self, update: book_update::Update) -> Result<(), BookError> {
+        match update {
+            book_update::Update::Snapshot(snapshot) => *self = BookState::Live(to_book(&snapshot)),
+            book_update::Update::Delta(delta) => {
+                // Every subscription starts with a snapshot, so a delta
+                // before one can only belong to an old subscription
+                if let BookState::Live(book) = self {
+                    book.apply(delta.sequence as u64, &to_level_delta(&delta))?;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// The stream ended. Sequences restart with the next subscription, whose
+    /// snapshot replaces the book whatever its sequence.
+    pub fn disconnect(&mut self) {
+        *self = match std::mem::replace(self, BookState::Loading) {
+            BookState::Live(book) => BookState::Reconnecting(Some(book)),
+            BookState::Reconnecting(book) => BookState::Reconnecting(book),
+            BookState::Loading => BookState::Reconnecting(None),
+        };
+    }
+    
+    fn book(&self) -> Option<&Book> {
+        match self {
+            BookState::Live(book) | BookState::Reconnecting(Some(book)) => Some(book),
+            _ => None,
+        }
+    }
+}
+
+#[component]
+pub fn OrderBook(symbol: String, #[prop(default = 10)] depth: usize) -> impl IntoView {
+    let (state, set_state) = create_signal(BookState::Loading);
+    let client = use_context::<TradingClient>().expect("TradingClient must be provided");
+    
+    // Stop resubscribing once the component is gone
+    let mounted = Rc::new(Cell::new(true));
+    on_cleanup({
+        let mounted = Rc::clone(&mounted);
+        move || mounted.set(false)
+    });
+    
+    spawn_local(async move {
+        while mounted.get() {
+            if let Ok(mut stream) = client.stream_order_book(symbol.clone()).await {
+                while let Ok(Some(update)) = stream.message().await {
+                    if !mounted.get() {
+                        return;
+                    }
+                    let Some(update) = update.update else {
+                        continue;
+                    };
+                    let in_sync = set_state
+                        .try_update(|state| state.apply(update).is_ok())
+                        .unwrap_or(true);
+                    // Out of step with the server; resubscribe for a fresh
+                    // snapshot
+                    if !in_sync {
+                        break;
+                    }
+                }
+            }
+            
+            if !mounted.get() {
+                return;
+            }
+            set_state.update(BookState::disconnect);
+            TimeoutFuture::new(RECONNECT_DELAY_MS).await;
+        }
+    });
+    
+    let view_model = create_memo(move |_| {
+        state.with(|state| state.book().map(|book| build_book_view(book, depth)))
+    });
+    
+    let render_rows = |rows: Vec<BookRow>, side: &'static str| {
+        rows.into_iter()
+            .map(|row| view! {
+                <tr class=format!("book-row {}", side)>
+                    <td class="price">{format!("{:.2}", row.price)}</td>
+                    <td class="quantity">{row.quantity}</td>
+                    <td class="orders">{row.order_count}</td>
+                    <td class="depth">
+                        <div class="depth-bar" style=format!("width: {:.1}%", row.depth_percent)></div>
+                    </td>
+                </tr>
+            })
+            .collect_view()
+    };
+    
+    view! {
+        <div class="order-book">
+            <h2>"Order Book"</h2>
+            
+            {move || matches!(state.get(), BookState::Reconnecting(_)).then(|| view! {
+                <p class="reconnecting">"Disconnected, reconnecting..."</p>
+            })}
+            
+            {move || match view_model.get() {
+                None => view! { <p class="loading">"Loading order book..."</p> }.into_view(),
+                Some(book) => view! {
+                    <table class="asks">
+                        // Asks read downwards towards the spread
+                        {render_rows(book.asks.into_iter().rev().collect(), "ask")}
+                    </table>
+                    <table class="bids">
+                        {render_rows(book.bids, "bid")}
+                    </table>
+                }.into_view(),
+            }}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn price_level(price: f64, quantity: u64) -> PriceLevel {
+        PriceLevel {
+            price,
+            quantity,
+            order_count: 1,
+        }
+    }
+    
+    fn snapshot(sequence: u32) -> book_update::Update {
+        book_update::Update::Snapshot(OrderBookSnapshot {
+            symbol: "AAPL".to_string(),
+            // Out of order, as nothing promises the server sorts them
+            bids: vec![price_level(99.0, 300), price_level(100.0, 100)],
+            asks: vec![price_level(101.5, 50), price_level(101.0, 150)],
+            sequence,
+            ..Default::default()
+        })
+    }
+    
+    fn delta(sequence: u32, action: ProtoBookAction, level: PriceLevel) -> book_update::Update {
+        book_update::Update::Delta(BookDelta {
+            symbol: "AAPL".to_string(),
+            sequence,
+            side: ProtoSide::Buy as i32,
+            action: action as i32,
+            level: Some(level),
+            ..Default::default()
+        })
+    }
+    
+    fn live_view(state: &BookState) -> BookView {
+        build_book_view(state.book().expect("a book"), 10)
+    }
+    
+    #[test]
+    fn levels_are_sorted_best_first_with_cumulative_depth() {
+        let mut state = BookState::Loading;
+        state.apply(snapshot(1)).unwrap();
+        
+        let view = live_view(&state);
+        let prices = |rows: &[BookRow]| rows.iter().map(|row| row.price).collect::<Vec<_>>();
+        assert_eq!(prices(&view.bids), vec![100.0, 99.0]);
+        assert_eq!(prices(&view.asks), vec![101.0, 101.5]);
+        
+        let cumulative = |rows: &[BookRow]| {
+            rows.iter().map(|row| row.cumulative_quantity).collect::<Vec<_>>()
+        };
+        assert_eq!(cumulative(&view.bids), vec![100, 400]);
+        assert_eq!(cumulative(&view.asks), vec![150, 200]);
+        // Bars are scaled to the deeper side, the bids' 400
+        let depth = |rows: &[BookRow]| rows.iter().map(|row| row.depth_percent).collect::<Vec<_>>();
+        assert_eq!(depth(&view.bids), vec![25.0, 100.0]);
+        assert_eq!(depth(&view.asks), vec![37.5, 50.0]);
+    }
+    
+    #[test]
+    fn depth_keeps_only_the_best_levels() {
+        let mut state = BookState::Loading;
+        state.apply(snapshot(1)).unwrap();
+        
+        let view = build_book_view(state.book().unwrap(), 1);
+        assert_eq!(view.bids.len(), 1);
+        assert_eq!(view.bids[0].price, 100.0);
+        assert_eq!(view.asks[0].price, 101.0);
+        // Depth counts only the levels shown, so the asks are now the deeper side
+        assert_eq!(view.asks[0].depth_percent, 100.0);
+    }
+    
+    #[test]
+    fn empty_book_has_no_depth() {
+        assert_eq!(build_book_view(&Book::default(), 10), BookView::default());
+    }
+    
+    #[test]
+    fn deltas_update_the_snapshot() {
+        let mut state = BookState::Loading;
+        assert!(state.book().is_none());
+        // Left over from an earlier subscription
+        state.apply(delta(9, ProtoBookAction::Add, price_level(98.0, 10))).unwrap();
+        assert_eq!(state, BookState::Loading);
+        
+        state.apply(snapshot(1)).unwrap();
+        state.apply(delta(2, ProtoBookAction::Add, price_level(100.5, 20))).unwrap();
+        state.apply(delta(3, ProtoBookAction::Change, price_level(99.0, 250))).unwrap();
+        state.apply(delta(4, ProtoBookAction::Delete, price_level(100.0, 0))).unwrap();
+        
+        let bids = live_view(&state).bids;
+        let levels: Vec<_> = bids.iter().map(|row| (row.price, row.quantity)).collect();
+        assert_eq!(levels, vec![(100.5, 20), (99.0, 250)]);
+    }
+    
+    #[test]
+    fn missed_delta_is_a_gap() {
+        let mut state = BookState::Loading;
+        state.apply(snapshot(1)).unwrap();
+        
+        let error = state
+            .apply(delta(3, ProtoBookAction::Add, price_level(100.5, 20)))
+            .unwrap_err();
+        assert_eq!(error, BookError::Gap { expected: 2, received: 3 });
+        // The book is left as it was until the fresh snapshot
+        assert_eq!(live_view(&state).bids.len(), 2);
+    }
+    
+    #[test]
+    fn disconnect_keeps_the_last_book_until_the_next_snapshot() {
+        let mut state = BookState::Loading;
+        state.disconnect();
+        assert_eq!(state, BookState::Reconnecting(None));
+        
+        state.apply(snapshot(1)).unwrap();
+        state.disconnect();
+        assert!(matches!(state, BookState::Reconnecting(Some(_))));
+        assert_eq!(live_view(&state).bids.len(), 2);
+        state.disconnect();
+        assert!(matches!(state, BookState::Reconnecting(Some(_))));
+        
+        // The new subscription's sequences start again from its snapshot
+        state.apply(snapshot(1)).unwrap();
+        assert!(matches!(state, BookState::Live(_)));
+        state.apply(delta(2, ProtoBookAction::Add, price_level(100.5, 20))).unwrap();
+        assert_eq!(live_view(&state).bids[0].price, 100.5);
+    }
+}
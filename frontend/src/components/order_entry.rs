@@ -1,18 +1,107 @@
 // frontend/src/components/order_entry.rs
 use leptos::*;
+use shared::{OrderType, Price, Side};
 use crate::api::TradingClient;
+use crate::proto::common::{OrderType as ProtoOrderType, Side as ProtoSide};
+use crate::proto::trading::OrderRequest;
+
+/// The signed-in user, provided via context by the app root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentUser {
+    pub user_id: u64,
+}
+
+impl CurrentUser {
+    /// The user to trade as when the app root doesn't provide one, set with
+    /// `TRADING_USER_ID` at build time. Without it the user is 0, which
+    /// can't submit orders.
+    pub fn from_build_env() -> Self {
+        Self {
+            user_id: option_env!("TRADING_USER_ID")
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Check an order before it's sent, with the same rules and messages as
+/// the server's `submit_order`, so users see the error without a round trip
+pub fn validate_order(
+    user_id: u64,
+    symbol: &str,
+    order_type: OrderType,
+    price: f64,
+    quantity: u64,
+) -> Result<(), &'static str> {
+    if user_id == 0 {
+        return Err("Invalid user ID");
+    }
+    
+    if symbol.is_empty() {
+        return Err("Symbol cannot be empty");
+    }
+    
+    if quantity == 0 {
+        return Err("Quantity must be greater than 0");
+    }
+    
+    if order_type == OrderType::Limit && price <= 0.0 {
+        return Err("Limit orders must have positive price");
+    }
+    
+    if Price::from_dollars_exact(price).is_none() {
+        return Err("Price must be a whole number of cents");
+    }
+    
+    Ok(())
+}
+
+/// Build the request sent by the Submit button
+pub fn build_order_request(
+    user_id: u64,
+    symbol: String,
+    side: Side,
+    order_type: OrderType,
+    price: f64,
+    quantity: u64,
+) -> OrderRequest {
+    let mut request = OrderRequest {
+        symbol,
+        user_id,
+        price,
+        quantity,
+        ..Default::default()
+    };
+    request.set_side(match side {
+        Side::Buy => ProtoSide::Buy,
+        Side::Sell => ProtoSide::Sell,
+    });
+    request.set_order_type(match order_type {
+        OrderType::Limit => ProtoOrderType::Limit,
+        OrderType::Market => ProtoOrderType::Market,
+    });
+    request
+}
 
 #[component]
 pub fn OrderEntry() -> impl IntoView {
     let (symbol, set_symbol) = create_signal("AAPL".to_string());
     let (price, set_price) = create_signal(150.0);
-    let (quantity, set_quantity) = create_signal(100);
+    let (quantity, set_quantity) = create_signal(100u64);
     let (side, set_side) = create_signal(Side::Buy);
+    let order_type = OrderType::Limit;
+    
+    let user = use_context::<CurrentUser>().unwrap_or_else(CurrentUser::from_build_env);
+    
+    let validation = create_memo(move |_| {
+        validate_order(user.user_id, &symbol.get(), order_type, price.get(), quantity.get())
+    });
     
     let submit_order = create_action(|order: &OrderRequest| {
         let client = use_context::<TradingClient>().unwrap();
+        let order = order.clone();
         async move {
-            client.submit_order(order.clone()).await
+            client.submit_order(order).await
         }
     });
     
@@ -42,23 +131,98 @@ pub fn OrderEntry() -> impl IntoView {
             />
             
             <select on:change=move |ev| {
-                set_side(if event_target_value(&ev) == "BUY" { Side::Buy } else { Side::Sell })
+                if let Ok(value) = event_target_value(&ev).parse() {
+                    set_side(value);
+                }
             }>
-                <option value="BUY">"Buy"</option>
-                <option value="SELL">"Sell"</option>
+                <option value=Side::Buy.to_string()>"Buy"</option>
+                <option value=Side::Sell.to_string()>"Sell"</option>
             </select>
             
-            <button on:click=move |_| {
-                submit_order.dispatch(OrderRequest {
-                    symbol: symbol.get(),
-                    price: price.get(),
-                    quantity: quantity.get(),
-                    side: side.get() as i32,
-                    ..Default::default()
-                });
+            {move || validation.get().err().map(|error| view! {
+                <p class="order-error">{error}</p>
+            })}
+            
+            <button disabled=move || validation.get().is_err() on:click=move |_| {
+                submit_order.dispatch(build_order_request(
+                    user.user_id,
+                    symbol.get(),
+                    side.get(),
+                    order_type,
+                    price.get(),
+                    quantity.get(),
+                ));
             }>
                 "Submit Order"
             </button>
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn submit_builds_a_complete_order_request() {
+        let request =
+            build_order_request(7, "AAPL".to_string(), Side::Sell, OrderType::Limit, 150.25, 100);
+        
+        assert_eq!(request.user_id, 7);
+        assert_eq!(request.symbol, "AAPL");
+        assert_eq!(request.side(), ProtoSide::Sell);
+        assert_eq!(request.order_type(), ProtoOrderType::Limit);
+        assert_eq!(request.price, 150.25);
+        assert_eq!(request.quantity, 100);
+        assert_eq!(request.stop_price, 0.0);
+    }
+    
+    #[test]
+    fn each_side_and_order_type_maps_to_its_proto_value() {
+        let request = |side, order_type| {
+            build_order_request(7, "AAPL".to_string(), side, order_type, 0.0, 100)
+        };
+        
+        assert_eq!(request(Side::Buy, OrderType::Limit).side(), ProtoSide::Buy);
+        assert_eq!(request(Side::Sell, OrderType::Limit).side(), ProtoSide::Sell);
+        let market = request(Side::Buy, OrderType::Market);
+        assert_eq!(market.order_type(), ProtoOrderType::Market);
+        // The raw fields hold the proto numbers, not the shared enums' discriminants
+        assert_eq!(market.side, ProtoSide::Buy as i32);
+        assert_eq!(market.order_type, ProtoOrderType::Market as i32);
+    }
+    
+    #[test]
+    fn invalid_orders_get_the_server_message() {
+        use OrderType::{Limit, Market};
+        
+        let cases = [
+            (0, "AAPL", Limit, 150.0, 100, "Invalid user ID"),
+            (7, "", Limit, 150.0, 100, "Symbol cannot be empty"),
+            (7, "AAPL", Limit, 150.0, 0, "Quantity must be greater than 0"),
+            (7, "AAPL", Limit, 0.0, 100, "Limit orders must have positive price"),
+            (7, "AAPL", Limit, -1.5, 100, "Limit orders must have positive price"),
+            (7, "AAPL", Limit, 150.001, 100, "Price must be a whole number of cents"),
+            (7, "AAPL", Market, f64::NAN, 100, "Price must be a whole number of cents"),
+        ];
+        for (user_id, symbol, order_type, price, quantity, expected) in cases {
+            assert_eq!(
+                validate_order(user_id, symbol, order_type, price, quantity),
+                Err(expected),
+                "{} {} {} @ {} x {}",
+                user_id,
+                symbol,
+                order_type,
+                price,
+                quantity
+            );
+        }
+    }
+    
+    #[test]
+    fn valid_orders_pass() {
+        assert_eq!(validate_order(7, "AAPL", OrderType::Limit, 150.25, 100), Ok(()));
+        // A market order needs no price
+        assert_eq!(validate_order(7, "AAPL", OrderType::Market, 0.0, 100), Ok(()));
+    }
+}
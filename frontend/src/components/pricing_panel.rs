@@ -0,0 +1,414 @@
+// frontend/src/components/pricing_panel.rs
+use leptos::*;
+use crate::api::{ApiError, PricingClient};
+use crate::proto::pricing::{
+    AmericanRequest, AsianRequest, BarrierRequest, BarrierType, EuropeanRequest, PriceResponse,
+};
+
+/// Option styles offered by the panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionStyle {
+    European,
+    American,
+    Asian,
+    Barrier,
+}
+
+impl OptionStyle {
+    pub const ALL: [OptionStyle; 4] = [
+        OptionStyle::European,
+        OptionStyle::American,
+        OptionStyle::Asian,
+        OptionStyle::Barrier,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            OptionStyle::European => "European",
+            OptionStyle::American => "American",
+            OptionStyle::Asian => "Asian",
+            OptionStyle::Barrier => "Barrier",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+
+/// Everything the user has entered
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingForm {
+    pub style: OptionStyle,
+    pub kind: OptionKind,
+    pub spot: f64,
+    pub strike: f64,
+    pub rate: f64,
+    pub dividend_yield: f64,
+    pub volatility: f64,
+    pub time_to_maturity: f64,
+    /// American only; 0 lets the server choose
+    pub num_exercise_points: u32,
+    /// Asian only
+    pub num_observations: u32,
+    /// Barrier only
+    pub barrier_level: f64,
+    pub barrier_type: BarrierType,
+    pub rebate: f64,
+    /// European only
+    pub compute_greeks: bool,
+}
+
+impl Default for PricingForm {
+    fn default() -> Self {
+        Self {
+            style: OptionStyle::European,
+            kind: OptionKind::Call,
+            spot: 100.0,
+            strike: 100.0,
+            rate: 0.05,
+            dividend_yield: 0.0,
+            volatility: 0.2,
+            time_to_maturity: 1.0,
+            num_exercise_points: 0,
+            num_observations: 12,
+            barrier_level: 120.0,
+            barrier_type: BarrierType::UpAndOut,
+            rebate: 0.0,
+            compute_greeks: true,
+        }
+    }
+}
+
+/// The RPC to call and its request, built from the form
+#[derive(Debug, Clone, PartialEq)]
+pub enum PricingRequest {
+    European(OptionKind, EuropeanRequest),
+    American(OptionKind, AmericanRequest),
+    Asian(OptionKind, AsianRequest),
+    Barrier(OptionKind, BarrierRequest),
+}
+
+impl PricingRequest {
+    /// Call the matching PricingService RPC
+    pub async fn send(self, client: &PricingClient) -> Result<PriceResponse, ApiError> {
+        use OptionKind::{Call, Put};
+        
+        match self {
+            PricingRequest::European(Call, request) => client.price_european_call(request).await,
+            PricingRequest::European(Put, request) => client.price_european_put(request).await,
+            PricingRequest::American(Call, request) => client.price_american_call(request).await,
+            PricingRequest::American(Put, request) => client.price_american_put(request).await,
+            PricingRequest::Asian(Call, request) => client.price_asian_call(request).await,
+            PricingRequest::Asian(Put, request) => client.price_asian_put(request).await,
+            PricingRequest::Barrier(Call, request) => client.price_barrier_call(request).await,
+            PricingRequest::Barrier(Put, request) => client.price_barrier_put(request).await,
+        }
+    }
+}
+
+/// Build the request for the selected style. Simulation settings are left
+/// unset so the server defaults apply.
+pub fn build_pricing_request(form: &PricingForm) -> PricingRequest {
+    match form.style {
+        OptionStyle::European => PricingRequest::European(
+            form.kind,
+            EuropeanRequest {
+                spot: form.spot,
+                strike: form.strike,
+                rate: form.rate,
+                volatility: form.volatility,
+                time_to_maturity: form.time_to_maturity,
+                config: None,
+                compute_greeks: form.compute_greeks,
+                dividend_yield: form.dividend_yield,
+            },
+        ),
+        OptionStyle::American => PricingRequest::American(
+            form.kind,
+            AmericanRequest {
+                spot: form.spot,
+                strike: form.strike,
+                rate: form.rate,
+                volatility: form.volatility,
+                time_to_maturity: form.time_to_maturity,
+                num_exercise_points: form.num_exercise_points,
+                config: None,
+                dividend_yield: form.dividend_yield,
+            },
+        ),
+        OptionStyle::Asian => PricingRequest::Asian(
+            form.kind,
+            AsianRequest {
+                spot: form.spot,
+                strike: form.strike,
+                rate: form.rate,
+                volatility: form.volatility,
+                time_to_maturity: form.time_to_maturity,
+                num_observations: form.num_observations,
+                config: None,
+                dividend_yield: form.dividend_yield,
+            },
+        ),
+        OptionStyle::Barrier => PricingRequest::Barrier(
+            form.kind,
+            BarrierRequest {
+                spot: form.spot,
+                strike: form.strike,
+                rate: form.rate,
+                volatility: form.volatility,
+                time_to_maturity: form.time_to_maturity,
+                barrier_level: form.barrier_level,
+                barrier_type: form.barrier_type as i32,
+                rebate: form.rebate,
+                config: None,
+                dividend_yield: form.dividend_yield,
+            },
+        ),
+    }
+}
+
+/// Greeks the server filled in, as (name, value) pairs
+pub fn greeks(response: &PriceResponse) -> Vec<(&'static str, f64)> {
+    [
+        ("Delta", response.delta),
+        ("Gamma", response.gamma),
+        ("Vega", response.vega),
+        ("Theta", response.theta),
+        ("Rho", response.rho),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((name, value?)))
+    .collect()
+}
+
+#[component]
+pub fn PricingPanel() -> impl IntoView {
+    let (form, set_form) = create_signal(PricingForm::default());
+    
+    let price = create_action(|request: &PricingRequest| {
+        let client = use_context::<PricingClient>().unwrap();
+        let request = request.clone();
+        async move {
+            request.send(&client).await
+        }
+    });
+    
+    let number_field = move |label: &'static str,
+                             get: fn(&PricingForm) -> f64,
+                             set: fn(&mut PricingForm, f64)| {
+        view! {
+            <label>
+                {label}
+                <input
+                    type="number"
+                    step="any"
+                    prop:value=move || form.with(get)
+                    on:input=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse() {
+                            set_form.update(|form| set(form, value));
+                        }
+                    }
+                />
+            </label>
+        }
+    };
+    
+    let style = move || form.with(|form| form.style);
+    
+    view! {
+        <div class="pricing-panel">
+            <h2>"Option Pricing"</h2>
+            
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                if let Some(style) = OptionStyle::ALL.into_iter().find(|style| style.label() == value) {
+                    set_form.update(|form| form.style = style);
+                }
+            }>
+                {OptionStyle::ALL
+                    .into_iter()
+                    .map(|style| view! { <option value=style.label()>{style.label()}</option> })
+                    .collect_view()}
+            </select>
+            
+            <select on:change=move |ev| {
+                let kind = if event_target_value(&ev) == "Put" { OptionKind::Put } else { OptionKind::Call };
+                set_form.update(|form| form.kind = kind);
+            }>
+                <option value="Call">"Call"</option>
+                <option value="Put">"Put"</option>
+            </select>
+            
+            {number_field("Spot", |form| form.spot, |form, value| form.spot = value)}
+            {number_field("Strike", |form| form.strike, |form, value| form.strike = value)}
+            {number_field("Rate", |form| form.rate, |form, value| form.rate = value)}
+            {number_field("Dividend yield", |form| form.dividend_yield, |form, value| form.dividend_yield = value)}
+            {number_field("Volatility", |form| form.volatility, |form, value| form.volatility = value)}
+            {number_field("Time to maturity (years)", |form| form.time_to_maturity, |form, value| form.time_to_maturity = value)}
+            
+            <Show when=move || style() == OptionStyle::Asian>
+                {number_field(
+                    "Observations",
+                    |form| form.num_observations as f64,
+                    |form, value| form.num_observations = value as u32,
+                )}
+            </Show>
+            
+            <Show when=move || style() == OptionStyle::Barrier>
+                {number_field("Barrier level", |form| form.barrier_level, |form, value| form.barrier_level = value)}
+                {number_field("Rebate", |form| form.rebate, |form, value| form.rebate = value)}
+                <select on:change=move |ev| {
+                    if let Some(barrier_type) = BarrierType::from_str_name(&event_target_value(&ev)) {
+                        set_form.update(|form| form.barrier_type = barrier_type);
+                    }
+                }>
+                    <option value="UP_AND_OUT">"Up and out"</option>
+                    <option value="UP_AND_IN">"Up and in"</option>
+                    <option value="DOWN_AND_OUT">"Down and out"</option>
+                    <option value="DOWN_AND_IN">"Down and in"</option>
+                </select>
+            </Show>
+            
+            <Show when=move || style() == OptionStyle::European>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || form.with(|form| form.compute_greeks)
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            set_form.update(|form| form.compute_greeks = checked);
+                        }
+                    />
+                    "Compute Greeks"
+                </label>
+            </Show>
+            
+            <button
+                disabled=move || price.pending().get()
+                on:click=move |_| price.dispatch(form.with(build_pricing_request))
+            >
+                "Price"
+            </button>
+            
+            {move || price.value().get().map(|result| match result {
+                Ok(response) => view! {
+                    <div class="pricing-result">
+                        <p class="price">{format!("Price: {:.4}", response.price)}</p>
+                        {response.std_error.map(|std_error| view! {
+                            <p class="std-error">{format!("Std error: {:.4}", std_error)}</p>
+                        })}
+                        <p class="timing">{format!("Computed in {:.1} ms", response.computation_time_ms)}</p>
+                        <ul class="greeks">
+                            {greeks(&response)
+                                .into_iter()
+                                .map(|(name, value)| view! { <li>{format!("{}: {:.4}", name, value)}</li> })
+                                .collect_view()}
+                        </ul>
+                    </div>
+                }.into_view(),
+                Err(error) => view! { <p class="pricing-error">{error.to_string()}</p> }.into_view(),
+            })}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn form(style: OptionStyle, kind: OptionKind) -> PricingForm {
+        PricingForm {
+            style,
+            kind,
+            spot: 105.0,
+            strike: 100.0,
+            dividend_yield: 0.01,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn european_request_carries_the_common_inputs() {
+        let PricingRequest::European(kind, request) =
+            build_pricing_request(&form(OptionStyle::European, OptionKind::Put))
+        else {
+            panic!("expected a European request");
+        };
+        
+        assert_eq!(kind, OptionKind::Put);
+        assert_eq!(request.spot, 105.0);
+        assert_eq!(request.strike, 100.0);
+        assert_eq!(request.rate, 0.05);
+        assert_eq!(request.dividend_yield, 0.01);
+        assert_eq!(request.volatility, 0.2);
+        assert_eq!(request.time_to_maturity, 1.0);
+        assert!(request.compute_greeks);
+        // Left to the server's defaults
+        assert!(request.config.is_none());
+    }
+    
+    #[test]
+    fn each_style_gets_its_own_inputs() {
+        let american = PricingForm {
+            num_exercise_points: 50,
+            ..form(OptionStyle::American, OptionKind::Call)
+        };
+        match build_pricing_request(&american) {
+            PricingRequest::American(OptionKind::Call, request) => {
+                assert_eq!(request.num_exercise_points, 50);
+                assert_eq!(request.spot, 105.0);
+            }
+            other => panic!("expected an American call, got {:?}", other),
+        }
+        
+        let asian = PricingForm {
+            num_observations: 52,
+            ..form(OptionStyle::Asian, OptionKind::Put)
+        };
+        match build_pricing_request(&asian) {
+            PricingRequest::Asian(OptionKind::Put, request) => {
+                assert_eq!(request.num_observations, 52);
+            }
+            other => panic!("expected an Asian put, got {:?}", other),
+        }
+        
+        let barrier = PricingForm {
+            barrier_level: 90.0,
+            barrier_type: BarrierType::DownAndIn,
+            rebate: 1.5,
+            ..form(OptionStyle::Barrier, OptionKind::Call)
+        };
+        match build_pricing_request(&barrier) {
+            PricingRequest::Barrier(OptionKind::Call, request) => {
+                assert_eq!(request.barrier_level, 90.0);
+                assert_eq!(request.barrier_type(), BarrierType::DownAndIn);
+                assert_eq!(request.rebate, 1.5);
+            }
+            other => panic!("expected a barrier call, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn only_the_greeks_the_server_filled_in_are_shown() {
+        let response = PriceResponse {
+            price: 10.45,
+            delta: Some(0.64),
+            vega: Some(37.5),
+            ..Default::default()
+        };
+        assert_eq!(greeks(&response), vec![("Delta", 0.64), ("Vega", 37.5)]);
+        assert!(greeks(&PriceResponse::default()).is_empty());
+    }
+    
+    #[test]
+    fn each_style_is_found_by_its_label() {
+        // The style select's values are the labels
+        for style in OptionStyle::ALL {
+            let found = OptionStyle::ALL.into_iter().find(|other| other.label() == style.label());
+            assert_eq!(found, Some(style));
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// frontend/src/lib.rs
+pub mod api;
+pub mod components;
+pub mod proto;
@@ -0,0 +1,16 @@
+// Generated gRPC-Web clients, built from ../protos by build.rs
+
+// Common types
+pub mod common {
+    tonic::include_proto!("common");
+}
+
+// Trading service
+pub mod trading {
+    tonic::include_proto!("trading");
+}
+
+// Pricing service
+pub mod pricing {
+    tonic::include_proto!("pricing");
+}
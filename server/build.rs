@@ -1,8 +1,43 @@
+use std::path::PathBuf;
+
+/// Library directory used when MCOPTIONS_LIB_DIR is not set
+const DEFAULT_LIB_DIR: &str = "../../MonteCarloLib/lib/build";
+
+/// Library names to look for, in order, when MCOPTIONS_LIB_NAME is not set
+const LIB_NAMES: &[&str] = &["mcoptions", "MonteCarloLib"];
+
+/// Messages sent as JSON by the WebSocket bridge and pricing REST endpoint,
+/// with the types they contain
+const JSON_MESSAGES: &[&str] = &[
+    ".common.Timestamp",
+    ".trading.TradeReport",
+    ".trading.BookUpdate",
+    ".trading.OrderBookSnapshot",
+    ".trading.BookDelta",
+    ".trading.PriceLevel",
+    ".trading.SequenceGap",
+    ".pricing.PriceResponse",
+];
+
+/// Requests the pricing REST endpoint accepts as JSON. Omitted fields take
+/// their proto defaults, and misspelt ones are rejected rather than ignored.
+const JSON_REQUESTS: &[&str] = &[".pricing.EuropeanRequest", ".pricing.SimulationConfig"];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);  //
     
     // Compile all protobuf files
-    tonic_build::configure()
+    let mut builder = tonic_build::configure();
+    for message in JSON_MESSAGES {
+        builder = builder.type_attribute(message, "#[derive(serde::Serialize)]");
+    }
+    for message in JSON_REQUESTS {
+        builder = builder.type_attribute(
+            message,
+            "#[derive(serde::Deserialize)] #[serde(default, deny_unknown_fields)]",
+        );
+    }
+    builder
         .build_server(true)
         .build_client(true)
         .protoc_arg("--experimental_allow_proto3_optional")
@@ -12,6 +47,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "../protos/common.proto",
                 "../protos/trading.proto",
                 "../protos/pricing.proto",
+                "../protos/health.proto",
+                "../protos/admin.proto",
             ],
             &["../protos"],
         )?;
@@ -19,14 +56,62 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=../protos/common.proto");
     println!("cargo:rerun-if-changed=../protos/trading.proto");
     println!("cargo:rerun-if-changed=../protos/pricing.proto");
+    println!("cargo:rerun-if-changed=../protos/health.proto");
+    println!("cargo:rerun-if-changed=../protos/admin.proto");
     
-    // Link the Monte Carlo library using absolute path
-    let lib_dir = "/home/paullopez/Desktop/cpp-workspace/MonteCarloLib/lib/build";
+    link_monte_carlo_library()?;
     
-    println!("cargo:rustc-link-search=native={}", lib_dir);
-    println!("cargo:rustc-link-lib=dylib=mcoptions");
-    println!("cargo:rerun-if-changed={}/libmcoptions.so", lib_dir);
-    println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir);
+    Ok(())
+}
+
+/// Resolve the Monte Carlo library from MCOPTIONS_LIB_DIR / MCOPTIONS_LIB_NAME
+/// and emit link flags for it
+fn link_monte_carlo_library() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=MCOPTIONS_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=MCOPTIONS_LIB_NAME");
+    
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
+    let lib_dir = std::env::var("MCOPTIONS_LIB_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_LIB_DIR));
+    // Relative paths are taken from the server crate, not the caller's cwd
+    let lib_dir = manifest_dir.join(lib_dir);
+    let lib_dir = lib_dir.canonicalize().map_err(|e| {
+        format!(
+            "Monte Carlo library directory {} not found ({}); set MCOPTIONS_LIB_DIR",
+            lib_dir.display(),
+            e
+        )
+    })?;
+    
+    let candidates: Vec<String> = match std::env::var("MCOPTIONS_LIB_NAME") {
+        Ok(name) => vec![name],
+        Err(_) => LIB_NAMES.iter().map(|name| name.to_string()).collect(),
+    };
+    
+    let (name, lib_path) = candidates
+        .iter()
+        .find_map(|name| {
+            let path = lib_dir.join(format!("lib{}.so", name));
+            path.is_file().then_some((name, path))
+        })
+        .ok_or_else(|| {
+            format!(
+                "No Monte Carlo library ({}) found in {}; set MCOPTIONS_LIB_DIR or MCOPTIONS_LIB_NAME",
+                candidates
+                    .iter()
+                    .map(|name| format!("lib{}.so", name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                lib_dir.display()
+            )
+        })?;
+    
+    println!("cargo:warning=Linking Monte Carlo library {}", lib_path.display());
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib=dylib={}", name);
+    println!("cargo:rerun-if-changed={}", lib_path.display());
+    println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir.display());
     
     Ok(())
 }
@@ -0,0 +1,271 @@
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Token scope granting access to the admin service
+const ADMIN_SCOPE: &str = "admin";
+
+/// Caller identity taken from a verified bearer token, stored in the
+/// request extensions for handlers to check against request fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedUser {
+    pub user_id: u64,
+    /// Whether the token carries the admin scope
+    pub admin: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    /// Subject: the user id, as a decimal string
+    sub: String,
+    /// Space-separated scopes, as in OAuth 2.0
+    #[serde(default)]
+    scope: String,
+}
+
+/// Verifies HS256-signed JWTs
+struct TokenVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl TokenVerifier {
+    fn new(signing_key: &[u8]) -> Self {
+        // `exp` is checked when present, with no leeway; tokens without
+        // one don't expire
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims = HashSet::new();
+        validation.leeway = 0;
+        validation.validate_aud = false;
+        
+        Self {
+            key: DecodingKey::from_secret(signing_key),
+            validation,
+        }
+    }
+    
+    #[allow(clippy::result_large_err)]
+    fn verify(&self, token: &str) -> Result<AuthenticatedUser, Status> {
+        let invalid = |reason: &str| Status::unauthenticated(format!("Invalid token: {}", reason));
+        
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)
+            .map_err(|e| {
+                invalid(match e.kind() {
+                    ErrorKind::InvalidSignature => "bad signature",
+                    ErrorKind::InvalidAlgorithm => "unsupported algorithm",
+                    ErrorKind::ExpiredSignature => "expired",
+                    ErrorKind::ImmatureSignature => "not yet valid",
+                    ErrorKind::Base64(_) => "bad base64",
+                    ErrorKind::Json(_) | ErrorKind::Utf8(_) => "bad header or claims",
+                    _ => "expected header.payload.signature",
+                })
+            })?
+            .claims;
+        
+        let user_id = claims
+            .sub
+            .parse()
+            .map_err(|_| invalid("subject is not a user id"))?;
+        
+        let admin = claims.scope.split_whitespace().any(|scope| scope == ADMIN_SCOPE);
+        
+        Ok(AuthenticatedUser { user_id, admin })
+    }
+}
+
+/// Checks the `authorization: Bearer <jwt>` metadata on incoming RPCs.
+/// Valid tokens attach an `AuthenticatedUser` to the request; requests
+/// without a token are rejected only when auth is required.
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    verifier: Option<Arc<TokenVerifier>>,
+    required: bool,
+    /// Only let through tokens with the admin scope
+    admin: bool,
+}
+
+impl AuthInterceptor {
+    /// `signing_key` may only be `None` when auth is not required
+    pub fn new(signing_key: Option<&str>, required: bool) -> Self {
+        Self {
+            verifier: signing_key.map(|key| Arc::new(TokenVerifier::new(key.as_bytes()))),
+            required,
+            admin: false,
+        }
+    }
+    
+    /// Same verifier, with the token optional
+    pub fn optional(&self) -> Self {
+        Self {
+            verifier: self.verifier.clone(),
+            required: false,
+            admin: false,
+        }
+    }
+    
+    /// Same verifier, requiring a token with the admin scope whether or
+    /// not auth is otherwise required
+    pub fn admin(&self) -> Self {
+        Self {
+            verifier: self.verifier.clone(),
+            required: true,
+            admin: true,
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(value) = request.metadata().get("authorization") else {
+            return if self.required {
+                Err(Status::unauthenticated("Missing authorization token"))
+            } else {
+                Ok(request)
+            };
+        };
+        
+        let token = value
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Expected a Bearer token"))?;
+        
+        let verifier = self
+            .verifier
+            .as_ref()
+            .ok_or_else(|| Status::unauthenticated("Token authentication is not configured"))?;
+        
+        let user = verifier.verify(token)?;
+        if self.admin && !user.admin {
+            return Err(Status::permission_denied(format!(
+                "Token for user {} lacks the {} scope",
+                user.user_id, ADMIN_SCOPE
+            )));
+        }
+        request.extensions_mut().insert(user);
+        
+        Ok(request)
+    }
+}
+
+/// Reject requests acting for a user other than the authenticated one.
+/// Unauthenticated requests (auth not required) are let through.
+#[allow(clippy::result_large_err)]
+pub fn authorize_user<T>(request: &Request<T>, user_id: u64) -> Result<(), Status> {
+    match request.extensions().get::<AuthenticatedUser>() {
+        Some(user) if user.user_id != user_id => Err(Status::permission_denied(format!(
+            "Token for user {} cannot act for user {}",
+            user.user_id, user_id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+    
+    const KEY: &str = "test-signing-key";
+    
+    fn token(algorithm: Algorithm, key: &str, claims: serde_json::Value) -> String {
+        jsonwebtoken::encode(&Header::new(algorithm), &claims, &EncodingKey::from_secret(key.as_bytes()))
+            .unwrap()
+    }
+    
+    fn request(token: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+        request
+    }
+    
+    fn in_an_hour() -> i64 {
+        chrono::Utc::now().timestamp() + 3600
+    }
+    
+    #[test]
+    fn valid_token_attaches_the_user() {
+        let mut interceptor = AuthInterceptor::new(Some(KEY), true);
+        let token = token(Algorithm::HS256, KEY, json!({"sub": "42", "exp": in_an_hour()}));
+        
+        let request = interceptor.call(request(&token)).unwrap();
+        assert_eq!(
+            request.extensions().get::<AuthenticatedUser>(),
+            Some(&AuthenticatedUser {
+                user_id: 42,
+                admin: false
+            })
+        );
+        assert!(authorize_user(&request, 42).is_ok());
+        assert_eq!(
+            authorize_user(&request, 43).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+    
+    #[test]
+    fn token_signed_with_another_key_is_rejected() {
+        let mut interceptor = AuthInterceptor::new(Some(KEY), true);
+        let token = token(Algorithm::HS256, "other-key", json!({"sub": "42"}));
+        
+        let err = interceptor.call(request(&token)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        assert_eq!(err.message(), "Invalid token: bad signature");
+    }
+    
+    #[test]
+    fn expired_token_is_rejected() {
+        let mut interceptor = AuthInterceptor::new(Some(KEY), true);
+        let expired = chrono::Utc::now().timestamp() - 1;
+        let token = token(Algorithm::HS256, KEY, json!({"sub": "42", "exp": expired}));
+        
+        let err = interceptor.call(request(&token)).unwrap_err();
+        assert_eq!(err.message(), "Invalid token: expired");
+    }
+    
+    #[test]
+    fn other_algorithms_are_rejected() {
+        let mut interceptor = AuthInterceptor::new(Some(KEY), true);
+        let token = token(Algorithm::HS512, KEY, json!({"sub": "42"}));
+        
+        let err = interceptor.call(request(&token)).unwrap_err();
+        assert_eq!(err.message(), "Invalid token: unsupported algorithm");
+        
+        // An unsigned token claiming "alg": "none"
+        let unsigned = "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.eyJzdWIiOiI0MiJ9.";
+        let err = interceptor.call(request(unsigned)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+    
+    #[test]
+    fn admin_service_needs_the_admin_scope() {
+        let mut admin = AuthInterceptor::new(Some(KEY), false).admin();
+        
+        let user = token(Algorithm::HS256, KEY, json!({"sub": "42", "scope": "trade"}));
+        let err = admin.call(request(&user)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        
+        let operator = token(Algorithm::HS256, KEY, json!({"sub": "1", "scope": "trade admin"}));
+        let request = admin.call(request(&operator)).unwrap();
+        assert!(request.extensions().get::<AuthenticatedUser>().unwrap().admin);
+        
+        let err = admin.call(Request::new(())).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+    
+    #[test]
+    fn token_is_optional_unless_required() {
+        assert!(AuthInterceptor::new(Some(KEY), false).call(Request::new(())).is_ok());
+        let err = AuthInterceptor::new(Some(KEY), true)
+            .call(Request::new(()))
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+}
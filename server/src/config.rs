@@ -1,3 +1,4 @@
+use crate::matching::PriceScale;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -6,6 +7,22 @@ pub struct Config {
     pub server: ServerConfig,
     pub matching_engine: MatchingEngineConfig,
     pub monte_carlo: MonteCarloConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub order_limits: OrderLimitConfig,
+    #[serde(default)]
+    pub order_throttle: OrderThrottleConfig,
+    #[serde(default)]
+    pub symbols: SymbolConfig,
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    #[serde(default)]
+    pub market_data: MarketDataConfig,
+    #[serde(default)]
+    pub paper_trading: PaperTradingConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,11 +36,78 @@ pub struct ServerConfig {
     /// Enable CORS for browser clients
     pub enable_cors: bool,
     
-    /// Maximum concurrent connections
+    /// Maximum concurrent connections; also caps the REST endpoint's
     pub max_connections: usize,
     
     /// Request timeout in seconds
     pub request_timeout_secs: u64,
+    
+    /// Address of the Prometheus metrics endpoint (e.g., "0.0.0.0:9090")
+    #[serde(default = "default_metrics_address")]
+    pub metrics_address: String,
+    
+    /// Serve gRPC reflection for grpcurl and similar tools (on by default
+    /// in debug builds)
+    #[serde(default = "default_enable_reflection")]
+    pub enable_reflection: bool,
+    
+    /// TLS certificate and key; the server runs in plaintext when unset
+    #[serde(default)]
+    pub tls: TlsConfig,
+    
+    /// Address of the WebSocket bridge relaying market data streams as
+    /// JSON (e.g., "0.0.0.0:9091"); the bridge is off when unset
+    #[serde(default)]
+    pub websocket_address: Option<String>,
+    
+    /// Address of the HTTP+JSON pricing endpoint (e.g., "0.0.0.0:8081");
+    /// off when unset
+    #[serde(default)]
+    pub rest_address: Option<String>,
+    
+    /// Log line format: "text" for reading in a terminal, "json" for log
+    /// pipelines. The LOG_FORMAT environment variable takes precedence.
+    #[serde(default)]
+    pub log_format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the enclosing spans
+    /// (the request id among them)
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => anyhow::bail!("Unknown log format {:?}, expected \"text\" or \"json\"", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM-encoded certificate chain
+    pub cert_path: Option<String>,
+    
+    /// PEM-encoded private key
+    pub key_path: Option<String>,
+}
+
+impl TlsConfig {
+    /// Certificate and key paths, when TLS is enabled
+    pub fn paths(&self) -> Option<(&str, &str)> {
+        Some((self.cert_path.as_deref()?, self.key_path.as_deref()?))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,14 +118,273 @@ pub struct MatchingEngineConfig {
     /// Connection pool size
     pub pool_size: usize,
     
-    /// Connection timeout in milliseconds
+    /// Upper bound the pool may grow to when every connection is busy;
+    /// unset keeps the pool at `pool_size`
+    pub max_pool_size: Option<usize>,
+    
+    /// Connection timeout in milliseconds, per attempt
     pub connect_timeout_ms: u64,
     
+    /// Attempts made to open each connection before giving up, so a
+    /// gateway that is still starting doesn't fail startup
+    pub connect_attempts: u32,
+    
+    /// Delay before the second connect attempt in milliseconds, doubling
+    /// for each later one up to `reconnect_max_delay_ms`
+    pub connect_retry_delay_ms: u64,
+    
     /// Read timeout in milliseconds
     pub read_timeout_ms: u64,
     
-    /// Enable connection keep-alive
+    /// How long a write to the gateway may block in milliseconds before
+    /// the connection is treated as dead and reconnected
+    pub write_timeout_ms: u64,
+    
+    /// How long to wait for an OrderAck/OrderReject in milliseconds
+    pub order_ack_timeout_ms: u64,
+    
+    /// Initial delay before reconnecting a dropped connection in milliseconds
+    pub reconnect_base_delay_ms: u64,
+    
+    /// Upper bound for the exponential reconnect backoff in milliseconds
+    pub reconnect_max_delay_ms: u64,
+    
+    /// Enable connection keep-alive (periodic heartbeats, dead connection detection)
     pub keepalive: bool,
+    
+    /// Heartbeat interval in milliseconds when keepalive is enabled
+    pub heartbeat_interval_ms: u64,
+    
+    /// With keepalive, how long a connection may go without receiving
+    /// anything before it is unhealthy: no new requests are sent on it and
+    /// it is reconnected. 0 means 3 heartbeat intervals.
+    pub stale_threshold_ms: u64,
+    
+    /// Session identifier sent to the gateway on logon (max 15 bytes)
+    pub session_id: String,
+    
+    /// How long to wait for the gateway to confirm a logon in milliseconds
+    pub logon_timeout_ms: u64,
+    
+    /// Ask the gateway to protect frames with a CRC32 trailer. Used only
+    /// if the gateway agrees at logon.
+    pub checksums: bool,
+    
+    /// Start even when no gateway connection can be opened. Pricing serves
+    /// as usual while trading answers UNAVAILABLE, and the pool keeps
+    /// connecting in the background.
+    pub start_degraded: bool,
+    
+    /// Fixed-point units per dollar in gateway prices (100 = cents)
+    pub price_scale: u64,
+    
+    /// Record every frame sent to and received from the gateway in this
+    /// file, for `trading-server --replay <file>`; unset disables capture
+    pub capture_path: Option<String>,
+    
+    /// Largest frame accepted from the gateway in bytes. A header claiming
+    /// more drops the connection (which then reconnects) rather than
+    /// buffering for a body that may never come.
+    pub max_message_size: u32,
+    
+    /// Which connection carries an order's messages: "none" spreads all
+    /// of them round-robin; "order" sends an order's cancels and replaces
+    /// on the connection that submitted it; "user" does that too, and
+    /// submits each user's orders on one connection picked by hashing the
+    /// user id
+    #[serde(default)]
+    pub affinity: Affinity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Affinity {
+    #[default]
+    None,
+    Order,
+    User,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Reject RPCs without a valid bearer token
+    pub required: bool,
+    
+    /// HMAC key used to verify HS256 tokens
+    pub signing_key: Option<String>,
+    
+    /// Let PricingService calls through without a token even when auth is
+    /// required
+    pub allow_unauthenticated_pricing: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Orders and cancels each user may send per second; 0 disables limiting
+    pub orders_per_second: f64,
+    
+    /// Requests a user may send back to back (defaults to one second's worth)
+    pub burst: Option<u32>,
+    
+    /// Per-user limits replacing the global ones
+    #[serde(default)]
+    pub overrides: Vec<UserRateLimit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRateLimit {
+    pub user_id: u64,
+    
+    /// 0 exempts the user from limiting
+    pub orders_per_second: f64,
+    
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderLimitConfig {
+    /// Largest quantity a single order may have; 0 disables the check
+    pub max_order_quantity: u64,
+    
+    /// Largest price * quantity of a single order, in dollars; 0 disables
+    /// the check
+    pub max_order_notional: f64,
+    
+    /// Price in dollars used for the notional of market orders; 0 exempts
+    /// market orders from the notional check
+    pub market_reference_price: f64,
+    
+    /// Per-user limits; unset fields fall back to the global ones
+    #[serde(default)]
+    pub overrides: Vec<UserOrderLimit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserOrderLimit {
+    pub user_id: u64,
+    
+    /// 0 exempts the user from the quantity check
+    pub max_order_quantity: Option<u64>,
+    
+    /// 0 exempts the user from the notional check
+    pub max_order_notional: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderThrottleConfig {
+    /// Orders per symbol that may be awaiting the gateway's ack at once;
+    /// further orders for the symbol are refused. 0 disables the limit.
+    pub max_in_flight_per_symbol: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolConfig {
+    /// Symbols orders may be sent for, matched after trimming and
+    /// uppercasing; empty allows every symbol
+    pub allowed: Vec<String>,
+    
+    /// Price increment in dollars for symbols without their own
+    pub default_tick_size: f64,
+    
+    /// Per-symbol price increments replacing the default
+    pub tick_sizes: Vec<SymbolTickSize>,
+}
+
+impl Default for SymbolConfig {
+    fn default() -> Self {
+        Self {
+            allowed: Vec::new(),
+            default_tick_size: 0.01,
+            tick_sizes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolTickSize {
+    pub symbol: String,
+    
+    /// Price increment in dollars
+    pub tick_size: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdempotencyConfig {
+    /// How long a submitted order's idempotency key is remembered, in seconds
+    pub ttl_secs: u64,
+    
+    /// Maximum number of remembered keys across all users
+    pub max_keys: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 600,
+            max_keys: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketDataConfig {
+    /// How far back realized volatility looks, in seconds
+    pub volatility_window_secs: u64,
+    
+    /// Fewest trades in the window needed to estimate volatility
+    pub min_volatility_trades: usize,
+    
+    /// Trades kept per symbol
+    pub max_trades_per_symbol: usize,
+    
+    /// Shortest gap between quotes sent to a StreamQuotes client, in
+    /// milliseconds; quotes in between are replaced by the latest. 0 sends
+    /// every quote.
+    pub quote_interval_ms: u64,
+}
+
+impl Default for MarketDataConfig {
+    fn default() -> Self {
+        Self {
+            volatility_window_secs: 3600,
+            min_volatility_trades: 30,
+            max_trades_per_symbol: 10_000,
+            quote_interval_ms: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaperTradingConfig {
+    /// Match orders in memory against synthetic liquidity instead of
+    /// sending them to the gateway. Market data still comes from the
+    /// gateway, which may be down.
+    pub enabled: bool,
+    
+    /// Synthetic price levels seeded on each side of a symbol's book
+    pub levels: usize,
+    
+    /// Quantity resting at each synthetic level
+    pub level_quantity: u64,
+    
+    /// Gap between synthetic levels, in basis points of the last price
+    pub level_spacing_bps: u64,
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            levels: 5,
+            level_quantity: 100,
+            level_spacing_bps: 10,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +392,27 @@ pub struct MonteCarloConfig {
     /// Path to the Monte Carlo shared library
     pub library_path: String,
     
+    /// Number of Monte Carlo contexts available for concurrent pricing
+    pub context_pool_size: usize,
+    
+    /// Pricing requests allowed to simulate at once; the rest queue
+    pub max_in_flight_pricings: usize,
+    
+    /// How long a queued pricing request waits for a slot before it is
+    /// refused with RESOURCE_EXHAUSTED; 0 refuses it straight away
+    pub pricing_queue_timeout_ms: u64,
+    
+    /// Longest a simulation may run before the request fails with
+    /// DEADLINE_EXCEEDED; 0 is unlimited. A shorter client deadline wins.
+    pub pricing_timeout_ms: u64,
+    
+    /// Responses to seeded pricing requests kept for identical repeats,
+    /// least recently used dropped first; 0 turns the cache off
+    pub pricing_cache_size: usize,
+    
+    /// Most strike x maturity points one PriceSurface request may price
+    pub max_surface_points: usize,
+    
     /// Default number of simulations
     pub default_simulations: u64,
     
@@ -63,6 +427,53 @@ pub struct MonteCarloConfig {
     
     /// Enable stratified sampling by default
     pub default_stratified_sampling: bool,
+    
+    /// Per-option-type changes to the defaults above
+    #[serde(default)]
+    pub overrides: OptionTypeOverrides,
+}
+
+/// Simulation defaults for one option type; unset fields fall back to the
+/// `monte_carlo.default_*` values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_simulations: Option<u64>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_steps: Option<u64>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub antithetic: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_variates: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stratified_sampling: Option<bool>,
+}
+
+/// Overrides keyed by option type, e.g. `[monte_carlo.overrides.american]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OptionTypeOverrides {
+    pub european: SimulationOverrides,
+    pub american: SimulationOverrides,
+    pub bermudan: SimulationOverrides,
+    pub asian: SimulationOverrides,
+    pub barrier: SimulationOverrides,
+    pub lookback: SimulationOverrides,
+    pub digital: SimulationOverrides,
+    pub spread: SimulationOverrides,
+}
+
+fn default_metrics_address() -> String {
+    "0.0.0.0:9090".to_string()
+}
+
+fn default_enable_reflection() -> bool {
+    cfg!(debug_assertions)
 }
 
 impl Default for Config {
@@ -74,36 +485,278 @@ impl Default for Config {
                 enable_cors: true,
                 max_connections: 1000,
                 request_timeout_secs: 30,
+                metrics_address: default_metrics_address(),
+                enable_reflection: default_enable_reflection(),
+                tls: TlsConfig::default(),
+                websocket_address: None,
+                rest_address: None,
+                log_format: LogFormat::default(),
             },
             matching_engine: MatchingEngineConfig {
                 gateway_address: "127.0.0.1:8080".to_string(),
                 pool_size: 10,
+                max_pool_size: None,
                 connect_timeout_ms: 5000,
+                connect_attempts: 3,
+                connect_retry_delay_ms: 200,
                 read_timeout_ms: 10000,
+                write_timeout_ms: 5000,
+                order_ack_timeout_ms: 5000,
+                reconnect_base_delay_ms: 100,
+                reconnect_max_delay_ms: 30_000,
                 keepalive: true,
+                heartbeat_interval_ms: 1000,
+                stale_threshold_ms: 0,
+                session_id: "trading-ui".to_string(),
+                logon_timeout_ms: 5000,
+                checksums: false,
+                start_degraded: false,
+                price_scale: 100,
+                capture_path: None,
+                max_message_size: crate::matching::protocol::MAX_FRAME_LEN,
+                affinity: Affinity::default(),
             },
             monte_carlo: MonteCarloConfig {
                 library_path: "../MonteCarloLib/build/bin/release/libMonteCarloLib.so"
                     .to_string(),
+                context_pool_size: 4,
+                max_in_flight_pricings: 4,
+                pricing_queue_timeout_ms: 1000,
+                pricing_timeout_ms: 30_000,
+                pricing_cache_size: 1000,
+                max_surface_points: 400,
                 default_simulations: 10_000,
                 default_steps: 252,
                 default_antithetic: true,
                 default_control_variates: false,
                 default_stratified_sampling: false,
+                overrides: OptionTypeOverrides {
+                    european: SimulationOverrides {
+                        num_steps: Some(52),
+                        ..Default::default()
+                    },
+                    american: SimulationOverrides {
+                        num_steps: Some(504),
+                        ..Default::default()
+                    },
+                    bermudan: SimulationOverrides {
+                        num_steps: Some(504),
+                        ..Default::default()
+                    },
+                    asian: SimulationOverrides {
+                        num_steps: Some(504),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
             },
+            auth: AuthConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            order_limits: OrderLimitConfig::default(),
+            order_throttle: OrderThrottleConfig::default(),
+            symbols: SymbolConfig::default(),
+            idempotency: IdempotencyConfig::default(),
+            market_data: MarketDataConfig::default(),
+            paper_trading: PaperTradingConfig::default(),
         }
     }
 }
 
 impl Config {
-    /// Load configuration from file or environment
+    /// Load configuration from file or environment, layered over the
+    /// defaults. A missing config file is fine; a malformed one is an error.
     pub fn load() -> anyhow::Result<Self> {
+        Self::load_from("config")
+    }
+    
+    /// Load from the config file at `path`, which may leave out the
+    /// extension
+    fn load_from(path: &str) -> anyhow::Result<Self> {
         let config = config::Config::builder()
-            .add_source(config::File::with_name("config").required(false))
+            .add_source(config::Config::try_from(&Config::default())?)
+            .add_source(config::File::with_name(path).required(false))
             .add_source(config::Environment::with_prefix("TRADING"))
             .build()?;
         
-        Ok(config.try_deserialize().unwrap_or_default())
+        let config: Config = config.try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    /// Reject values the server can't start with
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let positive = [
+            ("server.max_connections", self.server.max_connections as u64),
+            ("server.request_timeout_secs", self.server.request_timeout_secs),
+            ("matching_engine.pool_size", self.matching_engine.pool_size as u64),
+            ("matching_engine.connect_timeout_ms", self.matching_engine.connect_timeout_ms),
+            ("matching_engine.connect_attempts", self.matching_engine.connect_attempts as u64),
+            ("matching_engine.read_timeout_ms", self.matching_engine.read_timeout_ms),
+            ("matching_engine.write_timeout_ms", self.matching_engine.write_timeout_ms),
+            ("matching_engine.order_ack_timeout_ms", self.matching_engine.order_ack_timeout_ms),
+            ("matching_engine.heartbeat_interval_ms", self.matching_engine.heartbeat_interval_ms),
+            ("matching_engine.logon_timeout_ms", self.matching_engine.logon_timeout_ms),
+            ("matching_engine.price_scale", self.matching_engine.price_scale),
+            ("monte_carlo.context_pool_size", self.monte_carlo.context_pool_size as u64),
+            ("monte_carlo.max_in_flight_pricings", self.monte_carlo.max_in_flight_pricings as u64),
+            ("monte_carlo.max_surface_points", self.monte_carlo.max_surface_points as u64),
+            ("idempotency.ttl_secs", self.idempotency.ttl_secs),
+            ("idempotency.max_keys", self.idempotency.max_keys as u64),
+            ("market_data.volatility_window_secs", self.market_data.volatility_window_secs),
+            ("market_data.max_trades_per_symbol", self.market_data.max_trades_per_symbol as u64),
+            ("paper_trading.levels", self.paper_trading.levels as u64),
+            ("paper_trading.level_quantity", self.paper_trading.level_quantity),
+            ("paper_trading.level_spacing_bps", self.paper_trading.level_spacing_bps),
+        ];
+        for (field, value) in positive {
+            anyhow::ensure!(value > 0, "{} must be greater than 0", field);
+        }
+        
+        anyhow::ensure!(
+            self.server.tls.cert_path.is_some() == self.server.tls.key_path.is_some(),
+            "server.tls.cert_path and server.tls.key_path must be set together"
+        );
+        
+        anyhow::ensure!(
+            !self.auth.required || self.auth.signing_key.as_deref().is_some_and(|key| !key.is_empty()),
+            "auth.signing_key must be set when auth.required is true"
+        );
+        
+        anyhow::ensure!(
+            self.matching_engine
+                .max_pool_size
+                .is_none_or(|max| max >= self.matching_engine.pool_size),
+            "matching_engine.max_pool_size must be at least matching_engine.pool_size"
+        );
+        
+        // A threshold inside one heartbeat interval would flag a quiet but
+        // healthy connection between echoes
+        anyhow::ensure!(
+            self.matching_engine.stale_threshold_ms == 0
+                || self.matching_engine.stale_threshold_ms > self.matching_engine.heartbeat_interval_ms,
+            "matching_engine.stale_threshold_ms must be 0 or more than matching_engine.heartbeat_interval_ms"
+        );
+        
+        anyhow::ensure!(
+            self.matching_engine.max_message_size >= crate::matching::protocol::MIN_MAX_FRAME_LEN,
+            "matching_engine.max_message_size must be at least {} bytes",
+            crate::matching::protocol::MIN_MAX_FRAME_LEN
+        );
+        
+        anyhow::ensure!(
+            self.market_data.min_volatility_trades >= 2,
+            "market_data.min_volatility_trades must be at least 2"
+        );
+        
+        let global_limit = (
+            "rate_limit",
+            self.rate_limit.orders_per_second,
+            self.rate_limit.burst,
+        );
+        let user_limits = self
+            .rate_limit
+            .overrides
+            .iter()
+            .map(|user| ("rate_limit.overrides", user.orders_per_second, user.burst));
+        for (field, orders_per_second, burst) in std::iter::once(global_limit).chain(user_limits) {
+            anyhow::ensure!(
+                orders_per_second.is_finite() && orders_per_second >= 0.0,
+                "{}.orders_per_second must be a non-negative number",
+                field
+            );
+            anyhow::ensure!(burst != Some(0), "{}.burst must be greater than 0", field);
+        }
+        
+        let global_amounts = [
+            ("order_limits.max_order_notional", self.order_limits.max_order_notional),
+            ("order_limits.market_reference_price", self.order_limits.market_reference_price),
+        ];
+        let user_amounts = self
+            .order_limits
+            .overrides
+            .iter()
+            .filter_map(|user| user.max_order_notional)
+            .map(|notional| ("order_limits.overrides.max_order_notional", notional));
+        for (field, value) in global_amounts.into_iter().chain(user_amounts) {
+            anyhow::ensure!(
+                value.is_finite() && value >= 0.0,
+                "{} must be a non-negative number",
+                field
+            );
+        }
+        
+        for symbol in &self.symbols.allowed {
+            let normalized = crate::symbols::normalize(symbol);
+            anyhow::ensure!(!normalized.is_empty(), "symbols.allowed cannot contain an empty symbol");
+            crate::matching::protocol::validate_symbol(&normalized)
+                .map_err(|e| anyhow::anyhow!("Invalid symbol in symbols.allowed: {}", e))?;
+        }
+        
+        // Ticks must land on the gateway's price grid
+        let price_scale = PriceScale::new(self.matching_engine.price_scale);
+        let on_grid = |tick_size: f64| price_scale.to_fixed(tick_size).is_some_and(|units| units > 0);
+        anyhow::ensure!(
+            on_grid(self.symbols.default_tick_size),
+            "symbols.default_tick_size must be a positive multiple of {}",
+            price_scale
+        );
+        for tick in &self.symbols.tick_sizes {
+            let normalized = crate::symbols::normalize(&tick.symbol);
+            anyhow::ensure!(!normalized.is_empty(), "symbols.tick_sizes cannot contain an empty symbol");
+            crate::matching::protocol::validate_symbol(&normalized)
+                .map_err(|e| anyhow::anyhow!("Invalid symbol in symbols.tick_sizes: {}", e))?;
+            anyhow::ensure!(
+                on_grid(tick.tick_size),
+                "symbols.tick_sizes tick size for {} must be a positive multiple of {}",
+                normalized,
+                price_scale
+            );
+        }
+        
+        self.server_addr()?;
+        self.metrics_addr()?;
+        self.websocket_addr()?;
+        self.rest_addr()?;
+        self.matching_engine
+            .gateway_address
+            .parse::<SocketAddr>()
+            .map_err(|e| anyhow::anyhow!("Invalid gateway address: {}", e))?;
+        
+        Ok(())
+    }
+    
+    /// Get the metrics endpoint socket address
+    pub fn metrics_addr(&self) -> anyhow::Result<SocketAddr> {
+        self.server
+            .metrics_address
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid metrics address: {}", e))
+    }
+    
+    /// Get the WebSocket bridge address, if the bridge is enabled
+    pub fn websocket_addr(&self) -> anyhow::Result<Option<SocketAddr>> {
+        self.server
+            .websocket_address
+            .as_deref()
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("Invalid WebSocket address: {}", e))
+            })
+            .transpose()
+    }
+    
+    /// Get the HTTP+JSON pricing address, if the endpoint is enabled
+    pub fn rest_addr(&self) -> anyhow::Result<Option<SocketAddr>> {
+        self.server
+            .rest_address
+            .as_deref()
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("Invalid REST address: {}", e))
+            })
+            .transpose()
     }
     
     /// Get the server socket address
@@ -114,3 +767,84 @@ impl Config {
             .map_err(|e| anyhow::anyhow!("Invalid bind address: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Write `contents` to a fresh TOML file, returning its path
+    fn config_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("config-{}-{}.toml", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn bad_toml_value_is_an_error() {
+        let path = config_file("bad-value", "[matching_engine]\npool_size = \"four\"\n");
+        let result = Config::load_from(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("pool_size"), "{}", err);
+    }
+    
+    #[test]
+    fn malformed_toml_is_an_error() {
+        let path = config_file("malformed", "[matching_engine\npool_size = 4\n");
+        let result = Config::load_from(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn invalid_value_fails_validation() {
+        let path = config_file("zero-pool", "[matching_engine]\npool_size = 0\n");
+        let result = Config::load_from(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("matching_engine.pool_size"), "{}", err);
+    }
+    
+    #[test]
+    fn missing_file_yields_the_defaults() {
+        let path = std::env::temp_dir().join(format!("config-{}-missing.toml", std::process::id()));
+        let config = Config::load_from(path.to_str().unwrap()).unwrap();
+        
+        let defaults = Config::default();
+        assert_eq!(config.server.bind_address, defaults.server.bind_address);
+        assert_eq!(config.matching_engine.gateway_address, defaults.matching_engine.gateway_address);
+        assert_eq!(config.matching_engine.pool_size, defaults.matching_engine.pool_size);
+    }
+    
+    #[test]
+    fn file_values_override_the_defaults() {
+        let path = config_file("override", "[matching_engine]\ngateway_address = \"10.0.0.5:9000\"\n");
+        let config = Config::load_from(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        
+        let config = config.unwrap();
+        assert_eq!(config.matching_engine.gateway_address, "10.0.0.5:9000");
+        assert_eq!(config.server.bind_address, Config::default().server.bind_address);
+    }
+    
+    #[test]
+    fn option_type_overrides_load_from_toml() {
+        let path = config_file(
+            "overrides",
+            "[monte_carlo.overrides.american]\nnum_simulations = 50000\nantithetic = false\n",
+        );
+        let config = Config::load_from(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        
+        let overrides = config.unwrap().monte_carlo.overrides;
+        assert_eq!(overrides.american.num_simulations, Some(50_000));
+        assert_eq!(overrides.american.antithetic, Some(false));
+        // Overrides left out of the file keep their defaults
+        let defaults = Config::default().monte_carlo.overrides;
+        assert_eq!(overrides.american.num_steps, defaults.american.num_steps);
+        assert_eq!(overrides.asian.num_steps, defaults.asian.num_steps);
+    }
+}
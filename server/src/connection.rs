@@ -0,0 +1,105 @@
+use futures::Stream;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tonic::transport::server::{Connected, TcpConnectInfo};
+
+/// Peer addresses of gRPC connections that have closed
+pub type ClosedConnections = mpsc::UnboundedReceiver<SocketAddr>;
+
+/// Accepts gRPC connections and reports each one's peer address once it
+/// closes, so per-connection state can be cleaned up. Handlers see the same
+/// address through `Request::remote_addr`.
+pub struct TrackedIncoming {
+    listener: TcpListener,
+    closed: mpsc::UnboundedSender<SocketAddr>,
+}
+
+impl TrackedIncoming {
+    pub async fn bind(addr: SocketAddr) -> io::Result<(Self, ClosedConnections)> {
+        let listener = TcpListener::bind(addr).await?;
+        let (closed, closed_rx) = mpsc::unbounded_channel();
+        Ok((Self { listener, closed }, closed_rx))
+    }
+}
+
+impl Stream for TrackedIncoming {
+    type Item = io::Result<TrackedStream>;
+    
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.listener.poll_accept(cx).map(|accepted| {
+            Some(accepted.map(|(stream, remote_addr)| TrackedStream {
+                stream,
+                remote_addr,
+                closed: this.closed.clone(),
+            }))
+        })
+    }
+}
+
+/// A gRPC connection that reports its peer address when dropped
+pub struct TrackedStream {
+    stream: TcpStream,
+    remote_addr: SocketAddr,
+    closed: mpsc::UnboundedSender<SocketAddr>,
+}
+
+impl Drop for TrackedStream {
+    fn drop(&mut self) {
+        // Nobody listening just means nothing needs cleaning up
+        let _ = self.closed.send(self.remote_addr);
+    }
+}
+
+impl Connected for TrackedStream {
+    type ConnectInfo = TcpConnectInfo;
+    
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.stream.connect_info()
+    }
+}
+
+impl AsyncRead for TrackedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TrackedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+    }
+    
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write_vectored(cx, bufs)
+    }
+    
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+    
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+    
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
+}
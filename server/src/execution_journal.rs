@@ -0,0 +1,213 @@
+use crate::matching::protocol::ExecutionMessage;
+use crate::metrics::METRICS;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use tokio::sync::{broadcast, watch};
+use tracing::warn;
+
+/// Capacity of the sequenced execution broadcast channel. Recording never
+/// waits on subscribers; one that falls this far behind loses the oldest
+/// executions and is told so by `JournalSubscription::recv`.
+const JOURNAL_CHANNEL_CAPACITY: usize = 1024;
+
+/// An execution numbered by its position in the user's journal
+#[derive(Debug, Clone)]
+pub struct SequencedExecution {
+    pub sequence: u64,
+    pub execution: ExecutionMessage,
+}
+
+/// Why a resume point can't be replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// Executions after the resume point have already been dropped
+    TooOld,
+    /// The resume point is past the user's latest execution, e.g. from
+    /// before a server restart
+    Unknown,
+}
+
+/// Why a subscription yielded no execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// The subscriber fell behind and this many executions were dropped
+    /// for it. Later ones still arrive, but the gap has to be filled by
+    /// replaying from the journal.
+    Lagged(u64),
+    /// The journal has closed
+    Closed,
+}
+
+#[derive(Debug, Default)]
+struct UserJournal {
+    /// Sequence of the user's latest execution; 0 before the first
+    last_sequence: u64,
+    recent: VecDeque<SequencedExecution>,
+}
+
+/// Recent executions per user, numbered 1, 2, 3... per user so a
+/// reconnecting stream can ask for everything after the last one it saw.
+/// Each user keeps the latest `capacity` executions.
+pub struct ExecutionJournal {
+    users: DashMap<u64, UserJournal>,
+    capacity: usize,
+    tx: broadcast::Sender<SequencedExecution>,
+    closed: watch::Sender<bool>,
+}
+
+/// Live sequenced executions
+pub struct JournalSubscription {
+    rx: broadcast::Receiver<SequencedExecution>,
+    closed: watch::Receiver<bool>,
+}
+
+impl JournalSubscription {
+    /// Wait for the next execution
+    pub async fn recv(&mut self) -> Result<SequencedExecution, RecvError> {
+        let received = tokio::select! {
+            received = self.rx.recv() => received,
+            _ = self.closed.wait_for(|closed| *closed) => return Err(RecvError::Closed),
+        };
+        match received {
+            Ok(sequenced) => Ok(sequenced),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Execution journal subscriber lagged, skipped {} messages", skipped);
+                METRICS.record_executions_lagged("stream", skipped);
+                Err(RecvError::Lagged(skipped))
+            }
+            Err(broadcast::error::RecvError::Closed) => Err(RecvError::Closed),
+        }
+    }
+}
+
+impl ExecutionJournal {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(JOURNAL_CHANNEL_CAPACITY);
+        Self {
+            users: DashMap::new(),
+            capacity,
+            tx,
+            closed: watch::Sender::new(false),
+        }
+    }
+    
+    /// Number and keep an execution, then publish it to subscribers
+    pub fn record(&self, execution: ExecutionMessage) {
+        // Sequencing and publishing under the user's entry lock keeps
+        // subscribers seeing each user's executions in sequence order
+        let mut journal = self.users.entry(execution.user_id).or_default();
+        journal.last_sequence += 1;
+        let sequenced = SequencedExecution {
+            sequence: journal.last_sequence,
+            execution,
+        };
+        
+        journal.recent.push_back(sequenced.clone());
+        while journal.recent.len() > self.capacity {
+            journal.recent.pop_front();
+        }
+        
+        // No subscribers is not an error
+        let _ = self.tx.send(sequenced);
+    }
+    
+    /// Live executions from now on. Subscribe before calling `replay` so
+    /// nothing falls between the two.
+    pub fn subscribe(&self) -> JournalSubscription {
+        JournalSubscription {
+            rx: self.tx.subscribe(),
+            closed: self.closed.subscribe(),
+        }
+    }
+    
+    /// End every subscription; nothing more will be recorded
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+    
+    /// A user's executions after `after_sequence`, oldest first
+    pub fn replay(
+        &self,
+        user_id: u64,
+        after_sequence: u64,
+    ) -> Result<Vec<SequencedExecution>, ReplayError> {
+        let Some(journal) = self.users.get(&user_id) else {
+            return Err(ReplayError::Unknown);
+        };
+        
+        if after_sequence > journal.last_sequence {
+            return Err(ReplayError::Unknown);
+        }
+        // The next execution the caller needs must still be kept
+        let oldest = journal
+            .recent
+            .front()
+            .map_or(journal.last_sequence + 1, |oldest| oldest.sequence);
+        if after_sequence + 1 < oldest {
+            return Err(ReplayError::TooOld);
+        }
+        
+        Ok(journal
+            .recent
+            .iter()
+            .filter(|sequenced| sequenced.sequence > after_sequence)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::protocol::Side;
+    
+    fn execution(user_id: u64, execution_id: u64) -> ExecutionMessage {
+        ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: 1,
+            exchange_order_id: 1,
+            execution_id,
+            user_id,
+            side: Side::Buy,
+            fill_price: 10_000,
+            fill_quantity: 10,
+            leaves_quantity: 0,
+            timestamp: 0,
+        }
+    }
+    
+    fn sequences(replay: Vec<SequencedExecution>) -> Vec<(u64, u64)> {
+        replay
+            .into_iter()
+            .map(|sequenced| (sequenced.sequence, sequenced.execution.execution_id))
+            .collect()
+    }
+    
+    #[test]
+    fn replay_from_mid_buffer_returns_the_gap() {
+        let journal = ExecutionJournal::new(3);
+        for execution_id in 1..=5 {
+            journal.record(execution(7, execution_id));
+        }
+        // Another user's executions are numbered on their own
+        journal.record(execution(8, 6));
+        
+        assert_eq!(sequences(journal.replay(7, 3).unwrap()), vec![(4, 4), (5, 5)]);
+        assert_eq!(sequences(journal.replay(7, 5).unwrap()), vec![]);
+        assert_eq!(sequences(journal.replay(8, 0).unwrap()), vec![(1, 6)]);
+    }
+    
+    #[test]
+    fn replay_past_the_buffer_is_too_old() {
+        let journal = ExecutionJournal::new(3);
+        for execution_id in 1..=5 {
+            journal.record(execution(7, execution_id));
+        }
+        
+        // 3, 4 and 5 are kept, so resuming after 2 is the oldest possible
+        assert_eq!(sequences(journal.replay(7, 2).unwrap()), vec![(3, 3), (4, 4), (5, 5)]);
+        assert_eq!(journal.replay(7, 1).unwrap_err(), ReplayError::TooOld);
+        assert_eq!(journal.replay(7, 6).unwrap_err(), ReplayError::Unknown);
+        assert_eq!(journal.replay(9, 1).unwrap_err(), ReplayError::Unknown);
+    }
+}
@@ -0,0 +1,30 @@
+use tokio::sync::watch;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Fails RPCs with UNAVAILABLE while no gateway connection is up, instead
+/// of letting orders time out and streams sit silent, then passes the rest
+/// on to `inner`
+#[derive(Clone)]
+pub struct GatewayGate<I> {
+    liveness: watch::Receiver<bool>,
+    inner: I,
+}
+
+impl<I> GatewayGate<I> {
+    /// `liveness` is the pool's, from `MatchingClient::watch_liveness`
+    pub fn new(liveness: watch::Receiver<bool>, inner: I) -> Self {
+        Self { liveness, inner }
+    }
+}
+
+impl<I: Interceptor> Interceptor for GatewayGate<I> {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if !*self.liveness.borrow() {
+            return Err(Status::unavailable(
+                "Matching engine gateway is unavailable, try again later",
+            ));
+        }
+        self.inner.call(request)
+    }
+}
@@ -0,0 +1,316 @@
+use crate::config::IdempotencyConfig;
+use crate::proto::trading::OrderResponse;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tonic::Status;
+
+/// Longest idempotency key accepted
+pub const MAX_KEY_LEN: usize = 128;
+
+/// The order parameters a key is bound to. Reusing a key for a different
+/// order is an error rather than a dedup hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderFingerprint {
+    pub symbol: String,
+    pub side: i32,
+    pub order_type: i32,
+    pub price: u64,
+    pub stop_price: u64,
+    pub quantity: u64,
+    pub client_order_id: u64,
+}
+
+#[derive(Debug)]
+enum KeyState {
+    /// The first request with this key is still talking to the gateway
+    Pending(watch::Receiver<Option<OrderResponse>>),
+    Done(OrderResponse),
+}
+
+#[derive(Debug)]
+struct KeyEntry {
+    fingerprint: OrderFingerprint,
+    state: KeyState,
+    created_at: Instant,
+}
+
+/// Result of claiming a key
+pub enum Claim<'a> {
+    /// First use: submit the order, then `complete` the guard
+    New(ClaimGuard<'a>),
+    /// Seen before: return the original response
+    Existing(OrderResponse),
+}
+
+/// Remembers recent (user_id, idempotency_key) pairs and the response each
+/// produced, so retried submissions aren't sent to the gateway twice.
+///
+/// Completed keys are kept for `ttl_secs`. The store holds at most
+/// `max_keys` completed keys; the oldest are dropped first.
+pub struct IdempotencyStore {
+    entries: DashMap<(u64, String), KeyEntry>,
+    ttl: Duration,
+    max_keys: usize,
+}
+
+impl IdempotencyStore {
+    pub fn new(config: &IdempotencyConfig) -> Self {
+        Self {
+            entries: DashMap::new(),
+            ttl: Duration::from_secs(config.ttl_secs),
+            max_keys: config.max_keys,
+        }
+    }
+    
+    /// Claim `key` for `user_id`. A concurrent request holding the same key
+    /// is waited on; if it fails without a response, the key is claimed anew.
+    #[allow(clippy::result_large_err)]
+    pub async fn claim(
+        &self,
+        user_id: u64,
+        key: &str,
+        fingerprint: OrderFingerprint,
+    ) -> Result<Claim<'_>, Status> {
+        if key.len() > MAX_KEY_LEN {
+            return Err(Status::invalid_argument(format!(
+                "Idempotency key longer than {} bytes",
+                MAX_KEY_LEN
+            )));
+        }
+        
+        loop {
+            if self.entries.len() >= self.max_keys {
+                self.evict();
+            }
+            
+            let mut pending = match self.entries.entry((user_id, key.to_string())) {
+                Entry::Occupied(mut occupied) => {
+                    let entry = occupied.get();
+                    let expired = matches!(entry.state, KeyState::Done(_))
+                        && entry.created_at.elapsed() >= self.ttl;
+                    
+                    if expired {
+                        let (guard, receiver) = self.guard(user_id, key);
+                        occupied.insert(Self::pending(fingerprint, receiver));
+                        return Ok(Claim::New(guard));
+                    }
+                    
+                    if entry.fingerprint != fingerprint {
+                        return Err(Status::already_exists(format!(
+                            "Idempotency key {:?} was already used for a different order",
+                            key
+                        )));
+                    }
+                    
+                    match &entry.state {
+                        KeyState::Done(response) => return Ok(Claim::Existing(response.clone())),
+                        KeyState::Pending(receiver) => receiver.clone(),
+                    }
+                }
+                Entry::Vacant(vacant) => {
+                    let (guard, receiver) = self.guard(user_id, key);
+                    vacant.insert(Self::pending(fingerprint, receiver));
+                    return Ok(Claim::New(guard));
+                }
+            };
+            
+            // The shard lock is released before waiting
+            let response = pending
+                .wait_for(Option::is_some)
+                .await
+                .ok()
+                .and_then(|response| response.clone());
+            if let Some(response) = response {
+                return Ok(Claim::Existing(response));
+            }
+        }
+    }
+    
+    fn guard(
+        &self,
+        user_id: u64,
+        key: &str,
+    ) -> (ClaimGuard<'_>, watch::Receiver<Option<OrderResponse>>) {
+        let (sender, receiver) = watch::channel(None);
+        let guard = ClaimGuard {
+            store: self,
+            key: (user_id, key.to_string()),
+            sender: Some(sender),
+        };
+        (guard, receiver)
+    }
+    
+    fn pending(
+        fingerprint: OrderFingerprint,
+        receiver: watch::Receiver<Option<OrderResponse>>,
+    ) -> KeyEntry {
+        KeyEntry {
+            fingerprint,
+            state: KeyState::Pending(receiver),
+            created_at: Instant::now(),
+        }
+    }
+    
+    /// Drop expired keys, then the oldest completed ones if still full
+    fn evict(&self) {
+        self.entries.retain(|_, entry| {
+            !matches!(entry.state, KeyState::Done(_)) || entry.created_at.elapsed() < self.ttl
+        });
+        
+        let excess = (self.entries.len() + 1).saturating_sub(self.max_keys);
+        if excess == 0 {
+            return;
+        }
+        
+        let mut completed: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| matches!(entry.state, KeyState::Done(_)))
+            .map(|entry| (entry.created_at, entry.key().clone()))
+            .collect();
+        completed.sort_unstable_by_key(|(created_at, _)| *created_at);
+        
+        for (_, key) in completed.into_iter().take(excess) {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Holds a claimed key until the order's response is known. Dropping it
+/// without `complete` (the request failed) releases the key for a retry.
+pub struct ClaimGuard<'a> {
+    store: &'a IdempotencyStore,
+    key: (u64, String),
+    sender: Option<watch::Sender<Option<OrderResponse>>>,
+}
+
+impl ClaimGuard<'_> {
+    /// Record the response and hand it to any request waiting on the key
+    pub fn complete(mut self, response: &OrderResponse) {
+        if let Some(mut entry) = self.store.entries.get_mut(&self.key) {
+            entry.state = KeyState::Done(response.clone());
+            entry.created_at = Instant::now();
+        }
+        if let Some(sender) = self.sender.take() {
+            sender.send_replace(Some(response.clone()));
+        }
+    }
+}
+
+impl Drop for ClaimGuard<'_> {
+    fn drop(&mut self) {
+        if self.sender.is_some() {
+            self.store
+                .entries
+                .remove_if(&self.key, |_, entry| matches!(entry.state, KeyState::Pending(_)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{sleep, timeout};
+    
+    fn store(ttl_secs: u64) -> IdempotencyStore {
+        IdempotencyStore::new(&IdempotencyConfig {
+            ttl_secs,
+            ..IdempotencyConfig::default()
+        })
+    }
+    
+    fn fingerprint(quantity: u64) -> OrderFingerprint {
+        OrderFingerprint {
+            symbol: "AAPL".to_string(),
+            side: 1,
+            order_type: 1,
+            price: 10_000,
+            stop_price: 0,
+            quantity,
+            client_order_id: 0,
+        }
+    }
+    
+    fn response(client_order_id: u64) -> OrderResponse {
+        OrderResponse {
+            client_order_id,
+            ..OrderResponse::default()
+        }
+    }
+    
+    async fn claim_new<'a>(store: &'a IdempotencyStore, key: &str) -> ClaimGuard<'a> {
+        match store.claim(7, key, fingerprint(100)).await.unwrap() {
+            Claim::New(guard) => guard,
+            Claim::Existing(response) => panic!("expected a new claim, got {:?}", response),
+        }
+    }
+    
+    async fn claim_existing(store: &IdempotencyStore, key: &str) -> OrderResponse {
+        match store.claim(7, key, fingerprint(100)).await.unwrap() {
+            Claim::Existing(response) => response,
+            Claim::New(_) => panic!("expected the existing response"),
+        }
+    }
+    
+    #[tokio::test]
+    async fn repeated_key_returns_the_first_response() {
+        let store = store(600);
+        claim_new(&store, "key").await.complete(&response(1));
+        
+        assert_eq!(claim_existing(&store, "key").await, response(1));
+        
+        // Keys are per user
+        let claim = store.claim(8, "key", fingerprint(100)).await.unwrap();
+        assert!(matches!(claim, Claim::New(_)));
+    }
+    
+    #[tokio::test]
+    async fn key_reused_for_another_order_is_rejected() {
+        let store = store(600);
+        claim_new(&store, "key").await.complete(&response(1));
+        
+        let err = store.claim(7, "key", fingerprint(200)).await.err().unwrap();
+        assert_eq!(err.code(), tonic::Code::AlreadyExists);
+    }
+    
+    #[tokio::test]
+    async fn concurrent_request_waits_for_the_first_response() {
+        let store = store(600);
+        let guard = claim_new(&store, "key").await;
+        
+        let (waited, ()) = tokio::join!(claim_existing(&store, "key"), async {
+            sleep(Duration::from_millis(50)).await;
+            guard.complete(&response(1));
+        });
+        assert_eq!(waited, response(1));
+    }
+    
+    #[tokio::test]
+    async fn dropped_guard_releases_the_key() {
+        let store = store(600);
+        let guard = claim_new(&store, "key").await;
+        
+        // A waiting request claims the key once the first one gives up
+        let (retry, ()) = tokio::join!(
+            async { timeout(Duration::from_secs(1), claim_new(&store, "key")).await.unwrap() },
+            async {
+                sleep(Duration::from_millis(50)).await;
+                drop(guard);
+            }
+        );
+        retry.complete(&response(2));
+        assert_eq!(claim_existing(&store, "key").await, response(2));
+    }
+    
+    #[tokio::test]
+    async fn expired_key_can_be_reused() {
+        let store = store(0);
+        claim_new(&store, "key").await.complete(&response(1));
+        
+        // Past its TTL the key is free, even for a different order
+        let claim = store.claim(7, "key", fingerprint(200)).await.unwrap();
+        assert!(matches!(claim, Claim::New(_)));
+    }
+}
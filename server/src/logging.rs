@@ -0,0 +1,134 @@
+use crate::config::LogFormat;
+use anyhow::Context;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+/// Filter applied when RUST_LOG is unset
+const DEFAULT_FILTER: &str = "trading_server=debug,order_events=info,tower_http=debug";
+
+/// Environment variable overriding `server.log_format`
+const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// The format to log in: LOG_FORMAT when set, else the configured one
+pub fn format(configured: LogFormat) -> anyhow::Result<LogFormat> {
+    match std::env::var(LOG_FORMAT_ENV) {
+        Ok(value) => value.parse().with_context(|| format!("Invalid {}", LOG_FORMAT_ENV)),
+        Err(_) => Ok(configured),
+    }
+}
+
+/// Install the global subscriber, writing to stdout in `format` whatever
+/// RUST_LOG (or the default filter) lets through
+pub fn init(format: LogFormat) {
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into()))
+        .with(fmt_layer(format, std::io::stdout))
+        .init();
+}
+
+/// The formatting layer for `format`. JSON lines carry the current span
+/// and the list of enclosing spans, so each line from an RPC has its
+/// `rpc` span's request id and method.
+fn fmt_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).with_span_list(true).boxed(),
+    }
+}
+
+/// Log output kept in memory, for tests of what gets logged
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct CapturedLogs(std::sync::Arc<parking_lot::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl CapturedLogs {
+    /// A subscriber writing every event here in `format`. Install it with
+    /// `tracing::subscriber::set_default` for the current thread.
+    pub(crate) fn subscriber(&self, format: LogFormat) -> impl Subscriber + Send + Sync {
+        tracing_subscriber::registry().with(fmt_layer(format, self.clone()))
+    }
+    
+    /// Lines written so far
+    pub(crate) fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.lock()).lines().map(str::to_string).collect()
+    }
+    
+    /// Lines written so far, each parsed as JSON
+    pub(crate) fn json_lines(&self) -> Vec<serde_json::Value> {
+        self.lines()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {:?}", e, line)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl<'w> MakeWriter<'w> for CapturedLogs {
+    type Writer = CapturedLogs;
+    
+    fn make_writer(&'w self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Log two events, one inside an `rpc` span, in `format`
+    fn log_in(format: LogFormat) -> CapturedLogs {
+        let logs = CapturedLogs::default();
+        tracing::subscriber::with_default(logs.subscriber(format), || {
+            tracing::info!(user_id = 7, "Order accepted");
+            tracing::info_span!("rpc", request_id = "abc-123").in_scope(|| {
+                tracing::warn!("Quote \"stale\"\nretrying");
+            });
+        });
+        logs
+    }
+    
+    #[test]
+    fn json_logs_one_object_per_line() {
+        let logs = log_in(LogFormat::Json);
+        
+        let lines = logs.json_lines();
+        assert_eq!(lines.len(), 2, "{:?}", logs.lines());
+        assert!(lines.iter().all(serde_json::Value::is_object));
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "Order accepted");
+        assert_eq!(lines[0]["fields"]["user_id"], 7);
+        // A newline in a message stays escaped within its line
+        assert_eq!(lines[1]["fields"]["message"], "Quote \"stale\"\nretrying");
+        assert_eq!(lines[1]["span"]["request_id"], "abc-123");
+        assert_eq!(lines[1]["spans"][0]["name"], "rpc");
+    }
+    
+    #[test]
+    fn text_logs_are_not_json() {
+        let logs = log_in(LogFormat::Text);
+        
+        let lines = logs.lines();
+        assert!(lines[0].contains("Order accepted"), "{:?}", lines);
+        assert!(lines.iter().all(|line| serde_json::from_str::<serde_json::Value>(line).is_err()));
+    }
+}
@@ -1,39 +1,77 @@
+mod auth;
 mod config;
+mod connection;
+mod execution_journal;
+mod gateway_gate;
+mod idempotency;
+mod logging;
 mod matching;
+mod metrics;
+mod order_limits;
+mod order_throttle;
 mod pricing;
+mod pricing_cache;
 mod proto;
+mod rate_limit;
+mod request_id;
+mod rest;
 mod services;
+mod stop_orders;
+mod symbols;
+mod ws;
 
+use crate::auth::AuthInterceptor;
 use crate::config::Config;
-use crate::matching::MatchingClient;
+use crate::connection::TrackedIncoming;
+use crate::gateway_gate::GatewayGate;
+use crate::idempotency::IdempotencyStore;
+use crate::matching::capture::WireCapture;
+use crate::matching::{
+    ConnectionOptions, MatchingBackend, MatchingClient, OrderStore, PaperMatchingBackend,
+    TradeHistory,
+};
+use crate::order_limits::OrderLimits;
+use crate::order_throttle::OrderThrottle;
 use crate::pricing::MonteCarloEngine;
+use crate::pricing_cache::PricingCache;
+use crate::proto::admin::admin_service_server::AdminServiceServer;
+use crate::proto::health::{health_check_response::ServingStatus, health_server::HealthServer};
 use crate::proto::pricing::pricing_service_server::PricingServiceServer;
 use crate::proto::trading::trading_service_server::TradingServiceServer;
-use crate::services::{PricingServiceImpl, TradingServiceImpl};
+use crate::rate_limit::RateLimiter;
+use crate::request_id::RequestIdLayer;
+use crate::services::pricing::SimulationDefaults;
+use crate::services::{AdminServiceImpl, HealthServiceImpl, PricingServiceImpl, TradingServiceImpl};
+use crate::symbols::SymbolRegistry;
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
+use std::time::Duration;
+use tonic::server::NamedService;
 use tonic::transport::Server;
 use tonic_web::GrpcWebLayer;
-use tonic_reflection::server::Builder as ReflectionBuilder;
+use tonic_reflection::server::{
+    Builder as ReflectionBuilder, ServerReflection, ServerReflectionServer,
+};
 use tracing::{error, info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // `trading-server --replay <file>` prints a gateway capture and exits
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("--replay") {
+        let path = args.next().context("--replay needs a capture file")?;
+        return matching::capture::replay_file(&path)
+            .with_context(|| format!("Failed to replay {}", path));
+    }
+
+    // Load configuration first: it picks the log format
+    let config = Config::load().context("Failed to load configuration")?;
+
     // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "trading_server=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    logging::init(logging::format(config.server.log_format)?);
 
     info!("Starting Trading Platform gRPC Server");
-
-    // Load configuration
-    let config = Config::load().context("Failed to load configuration")?;
     info!("Configuration loaded: {:#?}", config);
 
     // Initialize Monte Carlo engine
@@ -42,37 +80,176 @@ async fn main() -> Result<()> {
         config.monte_carlo.library_path
     );
     let monte_carlo_engine = Arc::new(
-        MonteCarloEngine::new().context("Failed to initialize Monte Carlo engine")?,
+        MonteCarloEngine::new(config.monte_carlo.context_pool_size)
+            .context("Failed to initialize Monte Carlo engine")?,
+    );
+    info!(
+        "Monte Carlo engine initialized with {} contexts",
+        monte_carlo_engine.pool_size()
     );
-    info!("Monte Carlo engine initialized");
 
     // Initialize matching engine client
     info!(
         "Connecting to matching engine at: {}",
         config.matching_engine.gateway_address
     );
+    let mut connection_options = ConnectionOptions::from(&config.matching_engine);
+    if let Some(path) = &config.matching_engine.capture_path {
+        let capture = WireCapture::create(path)
+            .with_context(|| format!("Failed to create capture file {}", path))?;
+        connection_options.capture = Some(Arc::new(capture));
+    }
+    let order_store = Arc::new(OrderStore::new());
+    let trade_history = Arc::new(TradeHistory::new(&config.market_data));
+    // Paper trading only needs the gateway for market data
+    let paper_trading = config.paper_trading.enabled;
     let matching_client = Arc::new(
         MatchingClient::new(
             config.matching_engine.gateway_address.clone(),
             config.matching_engine.pool_size,
-            config.matching_engine.connect_timeout_ms,
+            config
+                .matching_engine
+                .max_pool_size
+                .unwrap_or(config.matching_engine.pool_size),
+            connection_options,
+            Arc::clone(&order_store),
+            trade_history,
+            config.matching_engine.start_degraded || paper_trading,
         )
         .await
         .context("Failed to connect to matching engine")?,
     );
-    info!("Connected to matching engine");
+    if matching_client.active_connections() == 0 && !paper_trading {
+        warn!("Matching engine unreachable; trading is unavailable until it connects");
+    } else {
+        info!(
+            "Connected to matching engine ({} connections up)",
+            matching_client.active_connections()
+        );
+    }
+
+    let backend: Arc<dyn MatchingBackend> = if paper_trading {
+        warn!("Paper trading enabled: orders are matched in memory and never reach the gateway");
+        Arc::new(PaperMatchingBackend::new(
+            Arc::clone(&matching_client),
+            Arc::clone(&order_store),
+            &config.paper_trading,
+        ))
+    } else {
+        matching_client.clone()
+    };
 
     // Create gRPC services
-    let pricing_service = PricingServiceImpl::new(Arc::clone(&monte_carlo_engine));
-    let trading_service = TradingServiceImpl::new(Arc::clone(&matching_client));
+    let order_throttle = Arc::new(OrderThrottle::new(&config.order_throttle));
+    let simulation_defaults = SimulationDefaults::new(&config.monte_carlo);
+    simulation_defaults.log();
+    let pricing_service = PricingServiceImpl::new(
+        Arc::clone(&monte_carlo_engine),
+        Arc::clone(&matching_client),
+        simulation_defaults,
+        config.monte_carlo.max_in_flight_pricings,
+        Duration::from_millis(config.monte_carlo.pricing_queue_timeout_ms),
+        (config.monte_carlo.pricing_timeout_ms > 0)
+            .then(|| Duration::from_millis(config.monte_carlo.pricing_timeout_ms)),
+        PricingCache::new(config.monte_carlo.pricing_cache_size),
+        config.monte_carlo.max_surface_points,
+    );
+    let trading_service = TradingServiceImpl::new(
+        Arc::clone(&matching_client),
+        Arc::clone(&backend),
+        Arc::clone(&order_store),
+        Arc::new(RateLimiter::new(&config.rate_limit)),
+        Arc::new(OrderLimits::new(&config.order_limits)),
+        Arc::clone(&order_throttle),
+        Arc::new(SymbolRegistry::new(&config.symbols, matching_client.price_scale())),
+        Arc::new(IdempotencyStore::new(&config.idempotency)),
+        Duration::from_millis(config.market_data.quote_interval_ms),
+    );
+    let admin_service = AdminServiceImpl::new(
+        Arc::clone(&matching_client),
+        Arc::clone(&order_store),
+        Arc::clone(&monte_carlo_engine),
+        order_throttle,
+    );
+
+    // Pricing is ready now that the engine is up. Trading follows the
+    // gateway: NOT_SERVING while every connection is down, including when
+    // started degraded before the first one comes up. Paper trading is
+    // always up.
+    let health_service = HealthServiceImpl::new();
+    health_service.set_status("", ServingStatus::Serving);
+    health_service.set_status(
+        PricingServiceServer::<PricingServiceImpl>::NAME,
+        ServingStatus::Serving,
+    );
+    health_service.set_status(
+        AdminServiceServer::<AdminServiceImpl>::NAME,
+        ServingStatus::Serving,
+    );
+    health_service.follow(
+        TradingServiceServer::<TradingServiceImpl>::NAME,
+        backend.watch_liveness(),
+    );
+
+    // Trading always goes through the interceptor; pricing can be left
+    // open to anonymous callers while still honouring tokens that are sent.
+    // Admin always needs a token with the admin scope.
+    let auth = AuthInterceptor::new(config.auth.signing_key.as_deref(), config.auth.required);
+    let pricing_auth = if config.auth.allow_unauthenticated_pricing {
+        auth.optional()
+    } else {
+        auth.clone()
+    };
+    let trading_auth = GatewayGate::new(backend.watch_liveness(), auth.clone());
+    let admin_auth = auth.admin();
+    if config.auth.required {
+        info!("Bearer token authentication required");
+    }
 
     // Get server address
     let addr = config
         .server_addr()
         .context("Failed to parse server address")?;
 
+    // Connections are tracked so orders sent with cancel_on_disconnect can
+    // be cancelled when theirs closes
+    let (incoming, closed_connections) = TrackedIncoming::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind gRPC server to {}", addr))?;
+    tokio::spawn(trading_service.clone().cancel_on_disconnect(closed_connections));
+
     info!("gRPC server listening on {}", addr);
 
+    // Metrics get their own port so scrapes never touch the gRPC listener
+    let metrics_addr = config.metrics_addr()?;
+    let metrics_listener = tokio::net::TcpListener::bind(metrics_addr)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint {}", metrics_addr))?;
+    tokio::spawn(metrics::serve(metrics_listener, Arc::clone(&matching_client)));
+
+    // The WebSocket bridge is opt-in, for browsers that can't stream over
+    // gRPC-Web; it authorizes like the trading service
+    if let Some(websocket_addr) = config.websocket_addr()? {
+        let websocket_listener = tokio::net::TcpListener::bind(websocket_addr)
+            .await
+            .with_context(|| format!("Failed to bind WebSocket bridge {}", websocket_addr))?;
+        tokio::spawn(ws::serve(websocket_listener, trading_service.clone(), trading_auth.clone()));
+    }
+
+    // Likewise the HTTP+JSON pricing endpoint, for scripts without gRPC
+    // tooling; it authorizes like the pricing service
+    if let Some(rest_addr) = config.rest_addr()? {
+        let rest_listener = tokio::net::TcpListener::bind(rest_addr)
+            .await
+            .with_context(|| format!("Failed to bind REST endpoint {}", rest_addr))?;
+        tokio::spawn(rest::serve(
+            rest_listener,
+            pricing_service.clone(),
+            pricing_auth.clone(),
+            config.server.max_connections,
+        ));
+    }
+
     // Build server - only gRPC-Web for now (tower-http CORS has compatibility issues)
     if config.server.enable_cors {
         warn!("CORS via tower-http has compatibility issues - skipping for now");
@@ -80,40 +257,66 @@ async fn main() -> Result<()> {
     }
 
     // Build reflection service for grpcurl support
-    let reflection_service = ReflectionBuilder::configure()
-        .register_encoded_file_descriptor_set(tonic::include_file_descriptor_set!("proto_descriptor"))
-        .build()
-        .context("Failed to build reflection service")?;
+    let reflection_service = if config.server.enable_reflection {
+        Some(reflection_service()?)
+    } else {
+        None
+    };
 
     info!("Server started successfully!");
     info!("");
     info!("Available services:");
     info!("  - pricing.PricingService (Monte Carlo options pricing)");
     info!("  - trading.TradingService (Order submission and market data)");
-    info!("  - grpc.reflection.v1alpha.ServerReflection");
+    info!("  - admin.AdminService (diagnostics and connection pool refresh)");
+    info!("  - grpc.health.v1.Health (health checks)");
+    if reflection_service.is_some() {
+        info!("  - grpc.reflection.v1alpha.ServerReflection");
+    }
     info!("");
     info!("Server is ready to accept connections");
 
+    let shutdown = drain_on(
+        shutdown_signal(),
+        health_service.clone(),
+        Arc::clone(&matching_client),
+    );
+
+    let mut builder = Server::builder();
+    if let Some((cert_path, key_path)) = config.server.tls.paths() {
+        builder = configure_tls(builder, cert_path, key_path)?;
+        info!("TLS enabled with certificate {}", cert_path);
+    }
+
     let result = if config.server.enable_grpc_web {
         info!("Enabling gRPC-Web for browser support");
-        Server::builder()
+        // Under TLS, browsers still reach gRPC-Web over HTTP/2 via ALPN
+        builder
             .accept_http1(true)
+            .layer(RequestIdLayer)
             .layer(GrpcWebLayer::new())
-            .add_service(reflection_service)
-            .add_service(PricingServiceServer::new(pricing_service))
-            .add_service(TradingServiceServer::new(trading_service))
-            .serve(addr)
+            .add_service(HealthServer::new(health_service))
+            .add_optional_service(reflection_service)
+            .add_service(PricingServiceServer::with_interceptor(pricing_service, pricing_auth))
+            .add_service(TradingServiceServer::with_interceptor(trading_service, trading_auth))
+            .add_service(AdminServiceServer::with_interceptor(admin_service, admin_auth))
+            .serve_with_incoming_shutdown(incoming, shutdown)
             .await
     } else {
         info!("Running in gRPC-only mode (no browser support)");
-        Server::builder()
-            .add_service(reflection_service)
-            .add_service(PricingServiceServer::new(pricing_service))
-            .add_service(TradingServiceServer::new(trading_service))
-            .serve(addr)
+        builder
+            .layer(RequestIdLayer)
+            .add_service(HealthServer::new(health_service))
+            .add_optional_service(reflection_service)
+            .add_service(PricingServiceServer::with_interceptor(pricing_service, pricing_auth))
+            .add_service(TradingServiceServer::with_interceptor(trading_service, trading_auth))
+            .add_service(AdminServiceServer::with_interceptor(admin_service, admin_auth))
+            .serve_with_incoming_shutdown(incoming, shutdown)
             .await
     };
 
+    info!("Server stopped");
+
     // Handle result
     if let Err(e) = result {
         error!("Server error: {}", e);
@@ -122,3 +325,184 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Reflection over every service in the descriptor set `build.rs` writes
+fn reflection_service() -> Result<ServerReflectionServer<impl ServerReflection>> {
+    ReflectionBuilder::configure()
+        .register_encoded_file_descriptor_set(tonic::include_file_descriptor_set!("proto_descriptor"))
+        .build()
+        .context("Failed to build reflection service")
+}
+
+/// Load the certificate and key and enable TLS. Failing here stops startup
+/// rather than falling back to plaintext.
+#[cfg(feature = "tls")]
+fn configure_tls(builder: Server, cert_path: &str, key_path: &str) -> Result<Server> {
+    let cert = std::fs::read(cert_path)
+        .with_context(|| format!("Failed to read TLS certificate {}", cert_path))?;
+    let key = std::fs::read(key_path)
+        .with_context(|| format!("Failed to read TLS private key {}", key_path))?;
+    let identity = tonic::transport::Identity::from_pem(cert, key);
+
+    builder
+        .tls_config(tonic::transport::ServerTlsConfig::new().identity(identity))
+        .with_context(|| format!("Invalid TLS certificate {} or key {}", cert_path, key_path))
+}
+
+#[cfg(not(feature = "tls"))]
+fn configure_tls(_builder: Server, _cert_path: &str, _key_path: &str) -> Result<Server> {
+    anyhow::bail!("TLS is configured but the server was built without the `tls` feature")
+}
+
+/// Waits for `signal`, then ends the streaming RPCs, which graceful shutdown
+/// would otherwise wait on forever: health Watch streams directly, trading
+/// streams by shutting down the matching client
+async fn drain_on(
+    signal: impl std::future::Future<Output = ()>,
+    health_service: HealthServiceImpl,
+    matching_client: Arc<MatchingClient>,
+) {
+    signal.await;
+    health_service.shutdown();
+    matching_client.shutdown().await;
+}
+
+/// Resolves when the process receives Ctrl-C or SIGTERM. A signal that
+/// can't be listened for never fires, rather than shutting down immediately.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    info!("Shutdown signal received");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::health::health_client::HealthClient;
+    use crate::proto::health::HealthCheckRequest;
+    use tokio::sync::oneshot;
+    use tokio::time::timeout;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tokio_stream::StreamExt;
+    use tonic::transport::Channel;
+    use tonic_reflection::pb::server_reflection_client::ServerReflectionClient;
+    use tonic_reflection::pb::server_reflection_request::MessageRequest;
+    use tonic_reflection::pb::server_reflection_response::MessageResponse;
+    use tonic_reflection::pb::ServerReflectionRequest;
+
+    #[tokio::test]
+    async fn shutdown_signal_ends_streams_and_the_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let health_service = HealthServiceImpl::new();
+        health_service.set_status("", ServingStatus::Serving);
+        let matching_client = Arc::new(MatchingClient::without_gateway(100).await);
+        let mut executions = matching_client.subscribe_executions(None, None);
+        let (trigger, signal) = oneshot::channel::<()>();
+        let server = tokio::spawn(
+            Server::builder()
+                .add_service(HealthServer::new(health_service.clone()))
+                .serve_with_incoming_shutdown(
+                    TcpListenerStream::new(listener),
+                    drain_on(
+                        async {
+                            let _ = signal.await;
+                        },
+                        health_service,
+                        matching_client,
+                    ),
+                ),
+        );
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut watch = HealthClient::new(channel)
+            .watch(HealthCheckRequest { service: String::new() })
+            .await
+            .unwrap()
+            .into_inner();
+        let first = watch.next().await.unwrap().unwrap();
+        assert_eq!(first.status, ServingStatus::Serving as i32);
+
+        trigger.send(()).unwrap();
+
+        // The open stream reports NOT_SERVING and then ends without an error
+        let last = timeout(Duration::from_secs(5), watch.next()).await.unwrap();
+        assert_eq!(last.unwrap().unwrap().status, ServingStatus::NotServing as i32);
+        let end = timeout(Duration::from_secs(5), watch.next()).await.unwrap();
+        assert!(end.is_none(), "{:?}", end);
+        assert!(timeout(Duration::from_secs(5), executions.recv()).await.unwrap().is_none());
+
+        let served = timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+        assert!(served.is_ok(), "{:?}", served);
+    }
+
+    #[tokio::test]
+    async fn reflection_lists_the_services() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(reflection_service().unwrap())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        let mut responses = ServerReflectionClient::new(channel)
+            .server_reflection_info(tokio_stream::iter([request]))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let response = responses.next().await.unwrap().unwrap();
+        let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+            panic!("Expected a service list, got {:?}", response.message_response);
+        };
+        let services: Vec<_> = list.service.into_iter().map(|service| service.name).collect();
+        for name in [
+            "pricing.PricingService",
+            "trading.TradingService",
+            "admin.AdminService",
+            "grpc.health.v1.Health",
+        ] {
+            assert!(services.contains(&name.to_string()), "{} not in {:?}", name, services);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::client::{MatchingClient, OrderAckResult, OrderReplaceResult};
+use super::error::MatchingError;
+use super::protocol::{OrderType, Side};
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+/// Where the trading service sends orders: the gateway, through
+/// `MatchingClient`, or the simulated matcher of `PaperMatchingBackend`.
+/// Executions come back through the client's subscriptions either way.
+#[tonic::async_trait]
+pub trait MatchingBackend: Send + Sync {
+    /// Submit an order, waiting for the ack until the caller's `deadline`.
+    /// `tag` is kept with the order in the store. `client_order_id` is one
+    /// already handed out by the store, as for a triggered stop; `None`
+    /// allocates a new one.
+    #[allow(clippy::too_many_arguments)]
+    async fn submit_order(
+        &self,
+        symbol: String,
+        user_id: u64,
+        side: Side,
+        order_type: OrderType,
+        price: u64,
+        quantity: u64,
+        tag: String,
+        client_order_id: Option<u64>,
+        deadline: Option<Duration>,
+    ) -> Result<OrderAckResult, MatchingError>;
+    
+    /// Cancel an order
+    async fn cancel_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        deadline: Option<Duration>,
+    ) -> Result<(), MatchingError>;
+    
+    /// Replace an order's price and quantity
+    async fn replace_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+        deadline: Option<Duration>,
+    ) -> Result<OrderReplaceResult, MatchingError>;
+    
+    /// Whether orders can be accepted, updated as that changes
+    fn watch_liveness(&self) -> watch::Receiver<bool>;
+}
+
+#[tonic::async_trait]
+impl MatchingBackend for MatchingClient {
+    #[allow(clippy::too_many_arguments)]
+    async fn submit_order(
+        &self,
+        symbol: String,
+        user_id: u64,
+        side: Side,
+        order_type: OrderType,
+        price: u64,
+        quantity: u64,
+        tag: String,
+        client_order_id: Option<u64>,
+        deadline: Option<Duration>,
+    ) -> Result<OrderAckResult, MatchingError> {
+        MatchingClient::submit_order(
+            self,
+            symbol,
+            user_id,
+            side,
+            order_type,
+            price,
+            quantity,
+            tag,
+            client_order_id,
+            deadline,
+        )
+        .await
+    }
+    
+    async fn cancel_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        deadline: Option<Duration>,
+    ) -> Result<(), MatchingError> {
+        MatchingClient::cancel_order(self, symbol, client_order_id, user_id, deadline).await
+    }
+    
+    async fn replace_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+        deadline: Option<Duration>,
+    ) -> Result<OrderReplaceResult, MatchingError> {
+        MatchingClient::replace_order(
+            self,
+            symbol,
+            client_order_id,
+            user_id,
+            new_price,
+            new_quantity,
+            deadline,
+        )
+        .await
+    }
+    
+    fn watch_liveness(&self) -> watch::Receiver<bool> {
+        MatchingClient::watch_liveness(self)
+    }
+}
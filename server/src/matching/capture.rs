@@ -0,0 +1,321 @@
+use super::client::IncomingMessage;
+use super::protocol::*;
+use bytes::{Buf, BytesMut};
+use parking_lot::Mutex;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// First bytes of every capture file
+const CAPTURE_MAGIC: &[u8; 8] = b"TPCAP001";
+
+/// Bytes before each captured frame: timestamp (8), direction (1) and
+/// frame length (4), big-endian like the wire protocol
+const RECORD_HEADER_LEN: usize = 13;
+
+/// Which way a captured frame went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent = 0,
+    Received = 1,
+}
+
+/// One frame read back from a capture file
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    /// When the frame was recorded, in nanoseconds since the Unix epoch
+    pub timestamp: u64,
+    pub direction: Direction,
+    /// The frame as it was on the wire, header and checksum included
+    pub bytes: Vec<u8>,
+}
+
+/// Records every frame sent to or received from the gateway, for replay
+/// against the decoders offline (`replay_file`). One capture is shared by
+/// all of a pool's connections. Recording is best effort: if the file
+/// can't be written, capture stops and the connections carry on.
+pub struct WireCapture {
+    path: PathBuf,
+    file: Mutex<Option<BufWriter<File>>>,
+}
+
+impl WireCapture {
+    /// Create the capture file at `path`, replacing any earlier one
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = BufWriter::new(File::create(&path)?);
+        file.write_all(CAPTURE_MAGIC)?;
+        file.flush()?;
+        
+        info!("Capturing gateway traffic to {}", path.display());
+        Ok(Self {
+            path,
+            file: Mutex::new(Some(file)),
+        })
+    }
+    
+    /// Append a frame. Each one is flushed, so a capture taken up to a
+    /// crash still holds the frame that caused it.
+    pub fn record(&self, direction: Direction, frame: &[u8]) {
+        let mut file = self.file.lock();
+        let Some(writer) = file.as_mut() else {
+            return;
+        };
+        
+        let timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        header[..8].copy_from_slice(&timestamp.to_be_bytes());
+        header[8] = direction as u8;
+        header[9..].copy_from_slice(&(frame.len() as u32).to_be_bytes());
+        
+        let written = writer
+            .write_all(&header)
+            .and_then(|()| writer.write_all(frame))
+            .and_then(|()| writer.flush());
+        if let Err(e) = written {
+            warn!("Failed to write capture file {}, stopping capture: {}", self.path.display(), e);
+            *file = None;
+        }
+    }
+}
+
+impl fmt::Debug for WireCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WireCapture").field("path", &self.path).finish()
+    }
+}
+
+/// Read every frame in a capture file. A record cut short, as the last
+/// one can be if the process died mid-write, ends the capture.
+pub fn read_capture(path: impl AsRef<Path>) -> io::Result<Vec<CapturedFrame>> {
+    let data = std::fs::read(path)?;
+    let Some(mut buf) = data.strip_prefix(CAPTURE_MAGIC.as_slice()) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a wire capture file"));
+    };
+    
+    let mut frames = Vec::new();
+    while buf.has_remaining() {
+        if buf.remaining() < RECORD_HEADER_LEN {
+            warn!("Capture ends in a truncated record header");
+            break;
+        }
+        
+        let timestamp = buf.get_u64();
+        let direction = match buf.get_u8() {
+            0 => Direction::Sent,
+            1 => Direction::Received,
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid direction {} in capture record {}", other, frames.len()),
+                ))
+            }
+        };
+        let len = buf.get_u32() as usize;
+        if buf.remaining() < len {
+            warn!("Capture ends in a truncated frame: have {} bytes, need {}", buf.remaining(), len);
+            break;
+        }
+        
+        frames.push(CapturedFrame {
+            timestamp,
+            direction,
+            bytes: buf[..len].to_vec(),
+        });
+        buf.advance(len);
+    }
+    
+    Ok(frames)
+}
+
+/// A captured frame as the protocol's decoders read it
+#[derive(Debug)]
+pub enum ReplayedMessage {
+    NewOrder(NewOrderMessage),
+    CancelOrder(CancelOrderMessage),
+    ReplaceOrder(ReplaceOrderMessage),
+    LogonResponse(LogonResponseMessage),
+    /// Anything the receiver hands on, executions and market data included
+    Incoming(IncomingMessage),
+    /// A frame with nothing to decode past its header, e.g. a heartbeat
+    /// or a request the client sent
+    HeaderOnly(MessageType),
+}
+
+impl fmt::Display for ReplayedMessage {
+    /// The decoded message, without the replay wrapper
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayedMessage::NewOrder(msg) => write!(f, "{:?}", msg),
+            ReplayedMessage::CancelOrder(msg) => write!(f, "{:?}", msg),
+            ReplayedMessage::ReplaceOrder(msg) => write!(f, "{:?}", msg),
+            ReplayedMessage::LogonResponse(msg) => write!(f, "{:?}", msg),
+            ReplayedMessage::Incoming(msg) => write!(f, "{:?}", msg),
+            ReplayedMessage::HeaderOnly(msg_type) => write!(f, "{:?}", msg_type),
+        }
+    }
+}
+
+/// Decode one captured frame the way the client does: header, checksum,
+/// then the body for its type. Logon frames are told apart by direction.
+pub fn decode_frame(direction: Direction, frame: &[u8]) -> io::Result<(MessageHeader, ReplayedMessage)> {
+    let header = MessageHeader::decode(&mut BytesMut::from(&frame[..frame.len().min(16)]))?;
+    let mut frame = BytesMut::from(frame);
+    header.verify_checksum(&mut frame)?;
+    let mut body = frame.split_off(16);
+    
+    let message = match (header.msg_type, direction) {
+        (MessageType::NewOrder, _) => {
+            ReplayedMessage::NewOrder(NewOrderMessage::decode(header.clone(), &mut body)?)
+        }
+        (MessageType::CancelOrder, _) => {
+            ReplayedMessage::CancelOrder(CancelOrderMessage::decode(header.clone(), &mut body)?)
+        }
+        (MessageType::ReplaceOrder, _) => {
+            ReplayedMessage::ReplaceOrder(ReplaceOrderMessage::decode(header.clone(), &mut body)?)
+        }
+        (MessageType::Logon, Direction::Received) => {
+            ReplayedMessage::LogonResponse(LogonResponseMessage::decode(&mut body)?)
+        }
+        (MessageType::OrderAck, _) => {
+            ReplayedMessage::Incoming(IncomingMessage::OrderAck(OrderAckMessage::decode(&mut body)?))
+        }
+        (MessageType::OrderReject, _) => ReplayedMessage::Incoming(IncomingMessage::OrderReject(
+            OrderRejectMessage::decode(&mut body)?,
+        )),
+        (MessageType::OrderCancelled, _) => ReplayedMessage::Incoming(
+            IncomingMessage::OrderCancelled(OrderCancelledMessage::decode(&mut body)?),
+        ),
+        (MessageType::OrderReplaced, _) => ReplayedMessage::Incoming(
+            IncomingMessage::OrderReplaced(OrderReplacedMessage::decode(&mut body)?),
+        ),
+        (MessageType::Execution, _) => ReplayedMessage::Incoming(IncomingMessage::Execution(
+            ExecutionMessage::decode(&mut body)?,
+        )),
+        (MessageType::Trade, _) => {
+            ReplayedMessage::Incoming(IncomingMessage::Trade(TradeMessage::decode(&mut body)?))
+        }
+        (MessageType::Quote, _) => {
+            ReplayedMessage::Incoming(IncomingMessage::Quote(QuoteMessage::decode(&mut body)?))
+        }
+        (MessageType::BookSnapshot, _) => ReplayedMessage::Incoming(IncomingMessage::BookSnapshot(
+            BookSnapshotMessage::decode(&mut body)?,
+        )),
+        (MessageType::BookDelta, _) => ReplayedMessage::Incoming(IncomingMessage::BookDelta(
+            BookDeltaMessage::decode(&mut body)?,
+        )),
+        (msg_type, _) => ReplayedMessage::HeaderOnly(msg_type),
+    };
+    
+    Ok((header, message))
+}
+
+/// Decode a capture file and print each frame on its own line. A frame
+/// that fails to decode is printed as hex alongside the error.
+pub fn replay_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let frames = read_capture(path)?;
+    
+    for (i, frame) in frames.iter().enumerate() {
+        let arrow = match frame.direction {
+            Direction::Sent => "->",
+            Direction::Received => "<-",
+        };
+        match decode_frame(frame.direction, &frame.bytes) {
+            Ok((header, message)) => println!(
+                "#{} {} {} seq={} {}",
+                i, frame.timestamp, arrow, header.sequence, message
+            ),
+            Err(e) => {
+                let hex: String = frame.bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                println!(
+                    "#{} {} {} failed to decode {} bytes: {} [{}]",
+                    i,
+                    frame.timestamp,
+                    arrow,
+                    frame.bytes.len(),
+                    e,
+                    hex
+                );
+            }
+        }
+    }
+    
+    println!("{} frames", frames.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+    
+    /// Record `frames` to a fresh capture file and read them back
+    fn round_trip(name: &str, frames: &[(Direction, &[u8])]) -> Vec<CapturedFrame> {
+        let path = std::env::temp_dir().join(format!("capture-{}-{}.bin", std::process::id(), name));
+        let capture = WireCapture::create(&path).unwrap();
+        for (direction, frame) in frames {
+            capture.record(*direction, frame);
+        }
+        drop(capture);
+        
+        let captured = read_capture(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        captured
+    }
+    
+    #[test]
+    fn sent_new_order_replays() {
+        let order =
+            NewOrderMessage::new("AAPL".to_string(), 42, 7, Side::Buy, OrderType::Limit, 10_025, 300).unwrap();
+        let frame = order.encode();
+        
+        let captured = round_trip("new-order", &[(Direction::Sent, &frame)]);
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0].direction, Direction::Sent);
+        assert_eq!(captured[0].bytes, frame[..]);
+        
+        let (header, message) = decode_frame(Direction::Sent, &captured[0].bytes).unwrap();
+        assert_eq!(header.length as usize, frame.len());
+        let ReplayedMessage::NewOrder(replayed) = message else {
+            panic!("expected a new order, got {}", message);
+        };
+        assert_eq!(replayed.symbol, "AAPL");
+        assert_eq!(replayed.client_order_id, 42);
+        assert_eq!(replayed.side, Side::Buy);
+        assert_eq!(replayed.order_type, OrderType::Limit);
+        assert_eq!((replayed.price, replayed.quantity), (10_025, 300));
+        assert_eq!(replayed.timestamp, order.timestamp);
+    }
+    
+    #[test]
+    fn received_execution_replays() {
+        let mut frame = BytesMut::new();
+        MessageHeader::new(MessageType::Execution, 16 + 88).encode(&mut frame);
+        frame.put_slice(b"MSFT\0\0\0\0\0\0\0\0\0\0\0\0");
+        frame.put_u64(42); // client_order_id
+        frame.put_u64(1_001); // exchange_order_id
+        frame.put_u64(5); // execution_id
+        frame.put_u64(7); // user_id
+        frame.put_u8(Side::Sell as u8);
+        frame.put_bytes(0, 7);
+        frame.put_u64(40_050); // fill_price
+        frame.put_u64(100); // fill_quantity
+        frame.put_u64(200); // leaves_quantity
+        frame.put_u64(1_700_000_000_000_000_000); // timestamp
+        MessageHeader::append_checksum(&mut frame);
+        
+        let captured = round_trip("execution", &[(Direction::Received, &frame)]);
+        let (header, message) = decode_frame(captured[0].direction, &captured[0].bytes).unwrap();
+        assert_eq!(header.length as usize, frame.len());
+        assert!(header.has_checksum());
+        let ReplayedMessage::Incoming(IncomingMessage::Execution(execution)) = message else {
+            panic!("expected an execution, got {}", message);
+        };
+        assert_eq!(execution.symbol, "MSFT");
+        assert_eq!((execution.client_order_id, execution.execution_id), (42, 5));
+        assert_eq!(execution.side, Side::Sell);
+        assert_eq!((execution.fill_price, execution.fill_quantity, execution.leaves_quantity), (40_050, 100, 200));
+    }
+}
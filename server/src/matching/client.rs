@@ -1,18 +1,209 @@
+use super::capture::{Direction, WireCapture};
+use super::error::MatchingError;
+use super::order_store::OrderStore;
 use super::protocol::*;
-use anyhow::{Context, Result};
+use super::stats::{ConnectionCounters, ConnectionStats};
+use super::trade_history::TradeHistory;
+use crate::config::{Affinity, MatchingEngineConfig};
+use shared::PriceScale;
 use bytes::{Buf, BytesMut};
+use dashmap::DashMap;
+use futures::FutureExt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, Notify, RwLock};
 use tokio::time::{timeout, Duration};
 use tracing::{debug, error, info, warn};
 
+/// Gateway response to a submitted order
+pub type OrderAckResult = Result<OrderAckMessage, OrderRejectMessage>;
+
+/// Gateway response to a replace request
+pub type OrderReplaceResult = Result<OrderReplacedMessage, OrderRejectMessage>;
+
+/// Timeouts and reconnect policy for gateway connections
+#[derive(Debug, Clone)]
+pub struct ConnectionOptions {
+    /// Timeout of each connect attempt
+    pub connect_timeout: Duration,
+    /// Attempts at opening a connection, at least 1
+    pub connect_attempts: u32,
+    /// Delay before the second connect attempt, doubling after that
+    pub connect_retry_delay: Duration,
+    pub ack_timeout: Duration,
+    pub read_timeout: Duration,
+    /// Longest a single frame write may block before the connection is
+    /// treated as dead
+    pub write_timeout: Duration,
+    pub reconnect_base_delay: Duration,
+    pub reconnect_max_delay: Duration,
+    /// Heartbeat period, or `None` when keepalive is disabled
+    pub heartbeat_interval: Option<Duration>,
+    /// How long a connection may go without inbound data before it is
+    /// unhealthy and reopened; `None` when keepalive is disabled, as a
+    /// quiet gateway is then normal
+    pub stale_threshold: Option<Duration>,
+    /// Session identifier sent with Logon/Logout
+    pub session_id: String,
+    pub logon_timeout: Duration,
+    /// Ask the gateway for CRC32 frame trailers at logon
+    pub checksums: bool,
+    /// Fixed-point scale of prices on the wire
+    pub price_scale: PriceScale,
+    /// Where every frame sent and received is recorded, if anywhere
+    pub capture: Option<Arc<WireCapture>>,
+    /// Largest frame accepted from the gateway
+    pub max_message_size: u32,
+    /// Which pooled connection carries an order's messages
+    pub affinity: Affinity,
+}
+
+impl From<&MatchingEngineConfig> for ConnectionOptions {
+    fn from(config: &MatchingEngineConfig) -> Self {
+        Self {
+            connect_timeout: Duration::from_millis(config.connect_timeout_ms),
+            connect_attempts: config.connect_attempts,
+            connect_retry_delay: Duration::from_millis(config.connect_retry_delay_ms),
+            ack_timeout: Duration::from_millis(config.order_ack_timeout_ms),
+            read_timeout: Duration::from_millis(config.read_timeout_ms),
+            write_timeout: Duration::from_millis(config.write_timeout_ms),
+            reconnect_base_delay: Duration::from_millis(config.reconnect_base_delay_ms),
+            reconnect_max_delay: Duration::from_millis(config.reconnect_max_delay_ms),
+            heartbeat_interval: config
+                .keepalive
+                .then(|| Duration::from_millis(config.heartbeat_interval_ms)),
+            stale_threshold: config.keepalive.then(|| match config.stale_threshold_ms {
+                0 => Duration::from_millis(config.heartbeat_interval_ms) * MISSED_HEARTBEATS_BEFORE_DEAD,
+                ms => Duration::from_millis(ms),
+            }),
+            session_id: config.session_id.clone(),
+            logon_timeout: Duration::from_millis(config.logon_timeout_ms),
+            checksums: config.checksums,
+            price_scale: PriceScale::new(config.price_scale),
+            // Opened by the caller, as it creates a file
+            capture: None,
+            max_message_size: config.max_message_size,
+            affinity: config.affinity,
+        }
+    }
+}
+
+/// Heartbeat intervals without inbound data before a connection is
+/// considered dead, unless `stale_threshold_ms` says otherwise
+const MISSED_HEARTBEATS_BEFORE_DEAD: u32 = 3;
+
+/// Bytes skipped looking for a valid frame header before giving up on the
+/// connection and reconnecting
+const MAX_RESYNC_BYTES: usize = 64 * 1024;
+
+/// Requests awaiting a reply from the gateway
+#[derive(Default)]
+struct PendingRequests {
+    /// Orders awaiting an OrderAck/OrderReject, keyed by client_order_id
+    orders: DashMap<u64, oneshot::Sender<OrderAckResult>>,
+    /// Replaces awaiting an OrderReplaced/OrderReject, keyed by client_order_id
+    replaces: DashMap<u64, oneshot::Sender<OrderReplaceResult>>,
+    /// Book snapshot requests awaiting a reply, keyed by request_id
+    books: DashMap<u64, oneshot::Sender<BookSnapshotMessage>>,
+}
+
+impl PendingRequests {
+    /// Drop every waiter, failing the requests still in flight
+    fn clear(&self) {
+        self.orders.clear();
+        self.replaces.clear();
+        self.books.clear();
+    }
+    
+    /// Number of requests awaiting a reply
+    fn len(&self) -> usize {
+        self.orders.len() + self.replaces.len() + self.books.len()
+    }
+}
+
+/// A gateway session that has completed logon
+struct Session {
+    stream: TcpStream,
+    /// Sequence number of the last frame read during logon
+    inbound_sequence: u64,
+    /// Whether the gateway agreed to CRC32 frame trailers
+    checksums: bool,
+}
+
+/// Per-session message sequence numbers and checksum setting. Both restart
+/// at logon.
+#[derive(Default)]
+struct SessionSequences {
+    /// Last sequence stamped on an outbound message
+    outbound: AtomicU64,
+    /// Last sequence received from the gateway (0 if it doesn't sequence)
+    inbound: AtomicU64,
+    /// Append CRC32 trailers to outbound frames
+    checksums: AtomicBool,
+}
+
+impl SessionSequences {
+    /// Reset for a freshly logged-on session
+    fn reset(&self, inbound: u64, checksums: bool) {
+        // The logon itself went out as sequence 1
+        self.outbound.store(1, Ordering::Release);
+        self.inbound.store(inbound, Ordering::Release);
+        self.checksums.store(checksums, Ordering::Release);
+    }
+    
+    /// Record an inbound sequence number, logging gaps and replays
+    fn check_inbound(&self, sequence: u64) {
+        // Gateways that don't sequence send 0
+        if sequence == 0 {
+            return;
+        }
+        
+        let last = self.inbound.swap(sequence, Ordering::AcqRel);
+        if last != 0 && sequence != last + 1 {
+            if sequence > last {
+                warn!(
+                    "Inbound sequence gap: expected {}, received {} ({} missed)",
+                    last + 1,
+                    sequence,
+                    sequence - last - 1
+                );
+            } else {
+                warn!(
+                    "Inbound sequence went backwards: last {}, received {}",
+                    last, sequence
+                );
+            }
+        }
+    }
+}
+
 /// Connection to the matching engine gateway
 pub struct MatchingConnection {
-    stream: Arc<Mutex<TcpStream>>,
+    /// Unique among the pool's connections, past and present, so an
+    /// order's connection can be found again
+    id: u64,
+    address: String,
+    options: ConnectionOptions,
+    /// Write half of the gateway socket. The receiver task owns the read
+    /// half, so writers never wait on a blocked read.
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    /// Signalled when a write times out, so the receiver task drops the
+    /// stalled connection and reconnects
+    write_stalled: Arc<Notify>,
+    /// False while the receiver task is re-establishing the connection
+    connected: Arc<AtomicBool>,
+    /// Set once we've logged out; stops reconnection and heartbeats
+    closing: Arc<AtomicBool>,
     message_tx: mpsc::UnboundedSender<IncomingMessage>,
-    sequence: Arc<RwLock<u64>>,
+    sequences: Arc<SessionSequences>,
+    /// Source of book snapshot request ids
+    next_request_id: AtomicU64,
+    pending: Arc<PendingRequests>,
+    orders: Arc<OrderStore>,
+    stats: Arc<ConnectionCounters>,
 }
 
 /// Incoming message types
@@ -21,42 +212,303 @@ pub struct MatchingConnection {
 pub enum IncomingMessage {
     OrderAck(OrderAckMessage),
     OrderReject(OrderRejectMessage),
+    OrderReplaced(OrderReplacedMessage),
+    OrderCancelled(OrderCancelledMessage),
     Execution(ExecutionMessage),
+    Trade(TradeMessage),
+    Quote(QuoteMessage),
+    BookSnapshot(BookSnapshotMessage),
+    BookDelta(BookDeltaMessage),
+    /// The connection to the gateway dropped; a reconnect is under way
+    Disconnected,
+    /// The connection was re-established after `Disconnected`
+    Reconnected,
 }
 
 impl MatchingConnection {
-    /// Connect to the matching engine gateway
+    /// Connect to the matching engine gateway and log on. The connection
+    /// is only returned once the gateway has confirmed the logon.
     pub async fn connect(
+        id: u64,
         address: &str,
-        connect_timeout: Duration,
-    ) -> Result<(Self, mpsc::UnboundedReceiver<IncomingMessage>)> {
+        options: ConnectionOptions,
+        orders: Arc<OrderStore>,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<IncomingMessage>), MatchingError> {
         info!("Connecting to matching engine gateway at {}", address);
         
-        let stream = timeout(connect_timeout, TcpStream::connect(address))
-            .await
-            .context("Connection timeout")?
-            .context("Failed to connect to gateway")?;
-        
-        // Disable Nagle's algorithm for low latency
-        stream.set_nodelay(true)?;
+        let session = Self::open_session_with_retry(address, &options).await?;
         
-        info!("Connected to matching engine gateway");
+        info!(
+            "Connected to matching engine gateway (session {})",
+            options.session_id
+        );
         
         let (message_tx, message_rx) = mpsc::unbounded_channel();
+        let (reader, writer) = session.stream.into_split();
         
         let conn = Self {
-            stream: Arc::new(Mutex::new(stream)),
+            id,
+            address: address.to_string(),
+            options,
+            writer: Arc::new(Mutex::new(writer)),
+            write_stalled: Arc::new(Notify::new()),
+            connected: Arc::new(AtomicBool::new(true)),
+            closing: Arc::new(AtomicBool::new(false)),
             message_tx,
-            sequence: Arc::new(RwLock::new(0)),
+            sequences: Arc::new(SessionSequences::default()),
+            next_request_id: AtomicU64::new(0),
+            pending: Arc::new(PendingRequests::default()),
+            orders,
+            stats: Arc::new(ConnectionCounters::default()),
         };
+        conn.sequences.reset(session.inbound_sequence, session.checksums);
+        conn.stats.record_connected(false);
         
         // Start message receiver task
-        conn.start_receiver();
+        conn.start_receiver(reader);
+        
+        if let Some(interval) = conn.options.heartbeat_interval {
+            conn.start_heartbeat(interval);
+        }
         
         Ok((conn, message_rx))
     }
     
-    /// Submit a new order
+    /// Open a session, retrying with exponential backoff up to
+    /// `connect_attempts` attempts in all. Each attempt gets the full
+    /// connect and logon timeouts. Returns the last attempt's error.
+    async fn open_session_with_retry(
+        address: &str,
+        options: &ConnectionOptions,
+    ) -> Result<Session, MatchingError> {
+        let mut delay = options.connect_retry_delay;
+        let mut attempt = 1u32;
+        
+        loop {
+            match Self::open_session(address, options).await {
+                Ok(session) => return Ok(session),
+                Err(e) if attempt < options.connect_attempts => {
+                    warn!(
+                        "Connect attempt {} of {} to {} failed, retrying in {:?}: {:#}",
+                        attempt, options.connect_attempts, address, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(options.reconnect_max_delay);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    
+    /// Open a TCP stream to the gateway
+    async fn open_stream(address: &str, connect_timeout: Duration) -> Result<TcpStream, MatchingError> {
+        let stream = timeout(connect_timeout, TcpStream::connect(address))
+            .await
+            .map_err(|_| MatchingError::Timeout(format!("Timed out connecting to gateway at {}", address)))?
+            .map_err(|e| MatchingError::io("Failed to connect to gateway", e))?;
+        
+        // Disable Nagle's algorithm for low latency
+        stream.set_nodelay(true)?;
+        
+        Ok(stream)
+    }
+    
+    /// Open a TCP stream to the gateway and complete the logon handshake
+    async fn open_session(address: &str, options: &ConnectionOptions) -> Result<Session, MatchingError> {
+        let mut stream = Self::open_stream(address, options.connect_timeout).await?;
+        
+        let (inbound_sequence, checksums) = timeout(options.logon_timeout, Self::logon(&mut stream, options))
+            .await
+            .map_err(|_| {
+                MatchingError::Timeout(format!(
+                    "Timed out waiting for logon confirmation for session {}",
+                    options.session_id
+                ))
+            })??;
+        
+        Ok(Session {
+            stream,
+            inbound_sequence,
+            checksums,
+        })
+    }
+    
+    /// Send Logon and wait for the gateway's reply. Frames are read exactly,
+    /// so nothing after the reply is consumed before the receiver starts.
+    /// Returns the sequence number of the last frame read and whether the
+    /// gateway agreed to checksums.
+    async fn logon(stream: &mut TcpStream, options: &ConnectionOptions) -> Result<(u64, bool), MatchingError> {
+        let heartbeat_ms = options
+            .heartbeat_interval
+            .map_or(0, |interval| interval.as_millis() as u32);
+        let mut msg = LogonMessage::new(options.session_id.clone(), heartbeat_ms);
+        msg.header.sequence = 1;
+        
+        debug!("Logging on: session={}", msg.session_id);
+        
+        // A checksummed Logon asks the gateway to checksum the session
+        let mut logon = msg.encode();
+        if options.checksums {
+            MessageHeader::append_checksum(&mut logon);
+        }
+        if let Some(capture) = &options.capture {
+            capture.record(Direction::Sent, &logon);
+        }
+        
+        stream
+            .write_all(&logon)
+            .await
+            .map_err(|e| MatchingError::io("Failed to send logon", e))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| MatchingError::io("Failed to flush", e))?;
+        
+        loop {
+            let mut header_bytes = [0u8; 16];
+            stream
+                .read_exact(&mut header_bytes)
+                .await
+                .map_err(|e| MatchingError::io("Connection closed during logon", e))?;
+            let header = MessageHeader::decode(&mut BytesMut::from(&header_bytes[..]))?;
+            if header.length > options.max_message_size {
+                return Err(MatchingError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Gateway sent a {} byte frame during logon, over max_message_size {}",
+                        header.length, options.max_message_size
+                    ),
+                )));
+            }
+            
+            let mut frame = BytesMut::zeroed((header.length as usize).max(16));
+            frame[..16].copy_from_slice(&header_bytes);
+            stream
+                .read_exact(&mut frame[16..])
+                .await
+                .map_err(|e| MatchingError::io("Connection closed during logon", e))?;
+            if let Some(capture) = &options.capture {
+                capture.record(Direction::Received, &frame);
+            }
+            header.verify_checksum(&mut frame)?;
+            
+            if header.msg_type != MessageType::Logon {
+                debug!("Ignoring {:?} while logging on", header.msg_type);
+                continue;
+            }
+            
+            let mut body = frame.split_off(16);
+            let response = LogonResponseMessage::decode(&mut body)?;
+            
+            if !response.accepted {
+                warn!(
+                    "Gateway rejected logon for session {} (reason {}): {}",
+                    options.session_id, response.reason, response.text
+                );
+                return Err(MatchingError::Rejected {
+                    reason: response.reason,
+                    text: response.text,
+                });
+            }
+            
+            let checksums = options.checksums && header.has_checksum();
+            if checksums {
+                debug!("Gateway agreed to checksums for session {}", options.session_id);
+            }
+            
+            return Ok((header.sequence, checksums));
+        }
+    }
+    
+    /// Log out of the gateway session. The connection is not re-established
+    /// afterwards, and requests still awaiting a reply fail.
+    pub async fn logout(&self) -> Result<(), MatchingError> {
+        self.closing.store(true, Ordering::Release);
+        
+        if !self.is_connected() {
+            self.pending.clear();
+            return Ok(());
+        }
+        
+        let msg = LogoutMessage::new(self.options.session_id.clone());
+        
+        debug!("Logging out: session={}", msg.session_id);
+        
+        self.connected.store(false, Ordering::Release);
+        let sent = self.write(msg.encode()).await;
+        self.pending.clear();
+        sent?;
+        
+        info!(
+            "Logged out of session {} (last sequence out={}, in={})",
+            self.options.session_id,
+            self.outbound_sequence(),
+            self.inbound_sequence()
+        );
+        
+        Ok(())
+    }
+    
+    /// Sequence number of the last message sent in this session
+    pub fn outbound_sequence(&self) -> u64 {
+        self.sequences.outbound.load(Ordering::Acquire)
+    }
+    
+    /// Sequence number of the last message received in this session
+    pub fn inbound_sequence(&self) -> u64 {
+        self.sequences.inbound.load(Ordering::Acquire)
+    }
+    
+    /// Whether the connection is currently usable
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Acquire)
+    }
+    
+    /// Whether the connection is up and has heard from the gateway, if
+    /// only a heartbeat, within `stale_threshold`
+    pub fn is_healthy(&self, stale_threshold: Duration) -> bool {
+        self.is_connected() && self.stats.since_last_received() <= stale_threshold
+    }
+    
+    /// Whether requests should be sent on this connection: it is up and,
+    /// with keepalive, not stale
+    fn is_usable(&self) -> bool {
+        match self.options.stale_threshold {
+            Some(stale_threshold) => self.is_healthy(stale_threshold),
+            None => self.is_connected(),
+        }
+    }
+    
+    /// Number of requests on this connection awaiting a reply
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+    
+    /// Traffic, reconnect and error counters, reported as pool `slot`
+    pub fn stats(&self, slot: usize) -> ConnectionStats {
+        ConnectionStats::new(
+            slot,
+            self.address.clone(),
+            self.is_connected(),
+            self.in_flight(),
+            &self.stats,
+        )
+    }
+    
+    /// Error for a request whose waiter was dropped before `awaited` happened
+    fn reply_dropped(&self, awaited: &str) -> MatchingError {
+        if self.closing.load(Ordering::Acquire) {
+            MatchingError::Closed(format!("Server shutting down before {}", awaited))
+        } else {
+            MatchingError::Closed(format!("Connection closed before {}", awaited))
+        }
+    }
+    
+    /// Submit a new order and wait up to `ack_timeout` for the gateway to
+    /// acknowledge or reject it. A new client_order_id is allocated unless
+    /// one is given.
+    #[allow(clippy::too_many_arguments)]
     pub async fn submit_order(
         &self,
         symbol: String,
@@ -65,8 +517,12 @@ impl MatchingConnection {
         order_type: OrderType,
         price: u64,
         quantity: u64,
-    ) -> Result<u64> {
-        let client_order_id = self.next_sequence().await;
+        tag: String,
+        client_order_id: Option<u64>,
+        ack_timeout: Duration,
+    ) -> Result<OrderAckResult, MatchingError> {
+        let client_order_id =
+            client_order_id.unwrap_or_else(|| self.orders.next_client_order_id());
         
         let msg = NewOrderMessage::new(
             symbol,
@@ -76,222 +532,1383 @@ impl MatchingConnection {
             order_type,
             price,
             quantity,
-        );
+        )?;
         
         debug!(
             "Submitting order: id={}, symbol={}, side={:?}, price={}, qty={}",
             client_order_id, msg.symbol, side, price, quantity
         );
         
-        self.send_message(msg.encode()).await?;
+        // Register before sending so a fast ack can't race past us
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.pending.orders.insert(client_order_id, ack_tx);
+        self.orders.insert_new(
+            client_order_id,
+            user_id,
+            msg.symbol.clone(),
+            side,
+            price,
+            quantity,
+            tag,
+            Some(self.id),
+        );
+        
+        let response = timeout(ack_timeout, async {
+            if let Err(e) = self.send_message(msg.encode()).await {
+                self.orders.remove(client_order_id);
+                return Err(e);
+            }
+            ack_rx
+                .await
+                .map_err(|_| self.reply_dropped("order was acknowledged"))
+        })
+        .await;
+        
+        if !matches!(response, Ok(Ok(_))) {
+            self.pending.orders.remove(&client_order_id);
+        }
         
-        Ok(client_order_id)
+        response.map_err(|_| MatchingError::AckTimeout { client_order_id })?
     }
     
-    /// Cancel an existing order
+    /// Cancel an existing order, giving up if it can't be sent within
+    /// `send_timeout`
     pub async fn cancel_order(
         &self,
         symbol: String,
         client_order_id: u64,
         user_id: u64,
-    ) -> Result<()> {
-        let msg = CancelOrderMessage::new(symbol, client_order_id, user_id);
+        send_timeout: Duration,
+    ) -> Result<(), MatchingError> {
+        let msg = CancelOrderMessage::new(symbol, client_order_id, user_id)?;
         
         debug!("Cancelling order: id={}", client_order_id);
         
-        self.send_message(msg.encode()).await?;
+        timeout(send_timeout, self.send_message(msg.encode()))
+            .await
+            .map_err(|_| {
+                MatchingError::Timeout(format!("Timed out sending cancel for order {}", client_order_id))
+            })??;
         
         Ok(())
     }
     
-    /// Send a raw message
-    async fn send_message(&self, data: BytesMut) -> Result<()> {
-        let mut stream = self.stream.lock().await;
+    /// Replace the price/quantity of a resting order and wait up to
+    /// `ack_timeout` for the gateway to confirm or reject the change
+    pub async fn replace_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+        ack_timeout: Duration,
+    ) -> Result<OrderReplaceResult, MatchingError> {
+        let msg = ReplaceOrderMessage::new(
+            symbol,
+            client_order_id,
+            user_id,
+            new_price,
+            new_quantity,
+        )?;
         
-        stream
-            .write_all(&data)
-            .await
-            .context("Failed to send message")?;
+        debug!(
+            "Replacing order: id={}, symbol={}, price={}, qty={}",
+            client_order_id, msg.symbol, new_price, new_quantity
+        );
         
-        stream.flush().await.context("Failed to flush")?;
+        let (replace_tx, replace_rx) = oneshot::channel();
+        self.pending.replaces.insert(client_order_id, replace_tx);
         
-        Ok(())
+        let response = timeout(ack_timeout, async {
+            self.send_message(msg.encode()).await?;
+            replace_rx
+                .await
+                .map_err(|_| self.reply_dropped("replace was acknowledged"))
+        })
+        .await;
+        
+        if !matches!(response, Ok(Ok(_))) {
+            self.pending.replaces.remove(&client_order_id);
+        }
+        
+        response.map_err(|_| {
+            MatchingError::Timeout(format!(
+                "Timed out waiting for acknowledgement of replace for order {}",
+                client_order_id
+            ))
+        })?
+    }
+    
+    /// Request an order book snapshot and wait for the gateway's reply
+    pub async fn request_order_book(
+        &self,
+        symbol: String,
+        depth: u32,
+    ) -> Result<BookSnapshotMessage, MatchingError> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let msg = OrderBookRequestMessage::new(symbol, request_id, depth)?;
+        
+        debug!(
+            "Requesting order book: id={}, symbol={}, depth={}",
+            request_id, msg.symbol, depth
+        );
+        
+        self.await_book_snapshot(request_id, msg.encode()).await
+    }
+    
+    /// Ask the gateway to recover a symbol's book after deltas
+    /// `from_sequence` up to `to_sequence` went missing, and wait for the
+    /// snapshot it answers with
+    pub async fn request_resend(
+        &self,
+        symbol: String,
+        from_sequence: u32,
+        to_sequence: u32,
+    ) -> Result<BookSnapshotMessage, MatchingError> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let msg = ResendRequestMessage::new(symbol, request_id, from_sequence, to_sequence)?;
+        
+        debug!(
+            "Requesting resend: id={}, symbol={}, sequences {}..{}",
+            request_id, msg.symbol, from_sequence, to_sequence
+        );
+        
+        self.await_book_snapshot(request_id, msg.encode()).await
     }
     
-    /// Get next sequence number
-    async fn next_sequence(&self) -> u64 {
-        let mut seq = self.sequence.write().await;
-        *seq += 1;
-        *seq
+    /// Send a request the gateway answers with a BookSnapshot carrying
+    /// `request_id`, and wait for it
+    async fn await_book_snapshot(
+        &self,
+        request_id: u64,
+        request: BytesMut,
+    ) -> Result<BookSnapshotMessage, MatchingError> {
+        let (book_tx, book_rx) = oneshot::channel();
+        self.pending.books.insert(request_id, book_tx);
+        
+        let response = timeout(self.options.read_timeout, async {
+            self.send_message(request).await?;
+            book_rx
+                .await
+                .map_err(|_| self.reply_dropped("book snapshot arrived"))
+        })
+        .await;
+        
+        if !matches!(response, Ok(Ok(_))) {
+            self.pending.books.remove(&request_id);
+        }
+        
+        response.map_err(|_| {
+            MatchingError::Timeout(format!("Timed out waiting for book snapshot {}", request_id))
+        })?
+    }
+    
+    /// Send a raw message. Fails fast while reconnecting so callers can retry.
+    async fn send_message(&self, data: BytesMut) -> Result<(), MatchingError> {
+        if !self.is_connected() {
+            return Err(MatchingError::NotConnected(
+                "Not connected to gateway (reconnecting)".to_string(),
+            ));
+        }
+        
+        self.write(data).await
+    }
+    
+    /// `write_message` on this connection's writer
+    async fn write(&self, data: BytesMut) -> Result<(), MatchingError> {
+        Self::write_message(
+            &self.writer,
+            &self.sequences,
+            &self.stats,
+            &self.options,
+            &self.write_stalled,
+            data,
+        )
+        .await
+    }
+    
+    /// Stamp the next outbound sequence number on a frame and write it to
+    /// the gateway socket. Stamping under the writer lock keeps sequence
+    /// order identical to wire order.
+    ///
+    /// A write blocked for longer than the write timeout (the gateway isn't
+    /// draining its socket) fails and signals `write_stalled`. It fails as
+    /// `Io` rather than `Timeout`, so callers report it as retriable
+    /// rather than as an expired deadline.
+    async fn write_message(
+        writer: &Mutex<OwnedWriteHalf>,
+        sequences: &SessionSequences,
+        stats: &ConnectionCounters,
+        options: &ConnectionOptions,
+        write_stalled: &Notify,
+        mut data: BytesMut,
+    ) -> Result<(), MatchingError> {
+        let write_timeout = options.write_timeout;
+        let mut writer = writer.lock().await;
+        
+        let sequence = sequences.outbound.fetch_add(1, Ordering::AcqRel) + 1;
+        MessageHeader::set_sequence(&mut data, sequence);
+        if sequences.checksums.load(Ordering::Acquire) {
+            MessageHeader::append_checksum(&mut data);
+        }
+        
+        let written = timeout(write_timeout, async {
+            writer
+                .write_all(&data)
+                .await
+                .map_err(|e| MatchingError::io("Failed to send message", e))?;
+            writer
+                .flush()
+                .await
+                .map_err(|e| MatchingError::io("Failed to flush", e))
+        })
+        .await
+        .unwrap_or_else(|_| {
+            // Part of the frame may be on the wire, so the session can't be reused
+            write_stalled.notify_one();
+            Err(MatchingError::Io(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Timed out writing to gateway after {:?}", write_timeout),
+            )))
+        });
+        
+        match &written {
+            // The type is the header's second byte
+            Ok(()) => {
+                stats.record_sent(data[1], data.len());
+                if let Some(capture) = &options.capture {
+                    capture.record(Direction::Sent, &data);
+                }
+            }
+            Err(e) => stats.record_error(format!("{:#}", e)),
+        }
+        written
     }
     
-    /// Start the message receiver task
-    fn start_receiver(&self) {
-        let stream = Arc::clone(&self.stream);
+    /// Start the message receiver task. The task also supervises the
+    /// connection: when the gateway drops it, it reconnects with backoff.
+    fn start_receiver(&self, mut reader: OwnedReadHalf) {
+        let address = self.address.clone();
+        let options = self.options.clone();
+        let writer = Arc::clone(&self.writer);
+        let connected = Arc::clone(&self.connected);
+        let closing = Arc::clone(&self.closing);
         let message_tx = self.message_tx.clone();
+        let pending = Arc::clone(&self.pending);
+        let orders = Arc::clone(&self.orders);
+        let sequences = Arc::clone(&self.sequences);
+        let stats = Arc::clone(&self.stats);
+        let write_stalled = Arc::clone(&self.write_stalled);
+        let idle_timeout = self.options.stale_threshold;
         
         tokio::spawn(async move {
-            let mut buf = BytesMut::with_capacity(4096);
-            
             loop {
-                let mut stream = stream.lock().await;
+                Self::receive_messages(
+                    &mut reader,
+                    &message_tx,
+                    &pending,
+                    &orders,
+                    &sequences,
+                    &stats,
+                    options.capture.as_deref(),
+                    &write_stalled,
+                    idle_timeout,
+                    options.max_message_size,
+                )
+                .await;
                 
-                // Read data into buffer
-                match stream.read_buf(&mut buf).await {
-                    Ok(0) => {
-                        warn!("Gateway connection closed");
-                        break;
-                    }
-                    Ok(n) => {
-                        debug!("Received {} bytes from gateway", n);
+                connected.store(false, Ordering::Release);
+                let _ = message_tx.send(IncomingMessage::Disconnected);
+                
+                // Fail any requests still waiting on this connection
+                pending.clear();
+                
+                if message_tx.is_closed() || closing.load(Ordering::Acquire) {
+                    break;
+                }
+                
+                let session = Self::reconnect(&address, &options, &stats).await;
+                let (new_reader, new_writer) = session.stream.into_split();
+                reader = new_reader;
+                
+                let mut writer = writer.lock().await;
+                *writer = new_writer;
+                // A stall signalled by a write on the old socket no longer applies
+                let _ = write_stalled.notified().now_or_never();
+                sequences.reset(session.inbound_sequence, session.checksums);
+                stats.record_connected(true);
+                drop(writer);
+                
+                connected.store(true, Ordering::Release);
+                let _ = message_tx.send(IncomingMessage::Reconnected);
+                
+                info!("Reconnected to matching engine gateway at {}", address);
+            }
+            
+            warn!("Message receiver task terminated");
+        });
+    }
+    
+    /// Start the heartbeat task, which keeps the gateway session alive
+    fn start_heartbeat(&self, interval: Duration) {
+        let writer = Arc::clone(&self.writer);
+        let connected = Arc::clone(&self.connected);
+        let closing = Arc::clone(&self.closing);
+        let message_tx = self.message_tx.clone();
+        let sequences = Arc::clone(&self.sequences);
+        let stats = Arc::clone(&self.stats);
+        let options = self.options.clone();
+        let write_stalled = Arc::clone(&self.write_stalled);
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            
+            while !message_tx.is_closed() && !closing.load(Ordering::Acquire) {
+                ticker.tick().await;
+                
+                // The receiver task owns reconnection; just skip beats meanwhile
+                if !connected.load(Ordering::Acquire) {
+                    continue;
+                }
+                
+                let heartbeat = HeartbeatMessage::new().encode();
+                if let Err(e) =
+                    Self::write_message(&writer, &sequences, &stats, &options, &write_stalled, heartbeat).await
+                {
+                    warn!("Failed to send heartbeat: {:#}", e);
+                }
+            }
+            
+            debug!("Heartbeat task terminated");
+        });
+    }
+    
+    /// Reconnect and log on again with exponential backoff, retrying until it succeeds
+    async fn reconnect(
+        address: &str,
+        options: &ConnectionOptions,
+        stats: &ConnectionCounters,
+    ) -> Session {
+        let mut delay = options.reconnect_base_delay;
+        let mut attempt = 1u32;
+        
+        loop {
+            info!(
+                "Reconnecting to gateway at {} in {:?} (attempt {})",
+                address, delay, attempt
+            );
+            tokio::time::sleep(delay).await;
+            
+            match Self::open_session(address, options).await {
+                Ok(session) => return session,
+                Err(e) => {
+                    warn!("Reconnect attempt {} failed: {:#}", attempt, e);
+                    stats.record_error(format!("{:#}", e));
+                }
+            }
+            
+            delay = (delay * 2).min(options.reconnect_max_delay);
+            attempt += 1;
+        }
+    }
+    
+    /// Read and dispatch messages until the gateway connection drops, goes
+    /// silent for longer than `idle_timeout`, sends more than
+    /// `MAX_RESYNC_BYTES` without a valid frame header or a header longer
+    /// than `max_message_size`, or a write to it times out
+    #[allow(clippy::too_many_arguments)]
+    async fn receive_messages(
+        reader: &mut OwnedReadHalf,
+        message_tx: &mpsc::UnboundedSender<IncomingMessage>,
+        pending: &PendingRequests,
+        orders: &OrderStore,
+        sequences: &SessionSequences,
+        stats: &ConnectionCounters,
+        capture: Option<&WireCapture>,
+        write_stalled: &Notify,
+        idle_timeout: Option<Duration>,
+        max_message_size: u32,
+    ) {
+        let mut buf = BytesMut::with_capacity(4096);
+        // Bytes dropped since the last valid header
+        let mut skipped = 0usize;
+        
+        loop {
+            // Read data into buffer (read_buf is cancel-safe)
+            let read = tokio::select! {
+                read = async {
+                    match idle_timeout {
+                        Some(idle) => timeout(idle, reader.read_buf(&mut buf)).await,
+                        None => Ok(reader.read_buf(&mut buf).await),
                     }
+                } => read,
+                () = write_stalled.notified() => {
+                    // The writer already recorded the timeout
+                    warn!("Write to gateway timed out, treating connection as dead");
+                    return;
+                }
+            };
+            let Ok(read) = read else {
+                let idle = idle_timeout.unwrap_or_default();
+                warn!("No data from gateway for {:?}, treating connection as dead", idle);
+                stats.record_error(format!("No data from gateway for {:?}", idle));
+                return;
+            };
+            
+            match read {
+                Ok(0) => {
+                    warn!("Gateway connection closed");
+                    stats.record_error("Gateway closed the connection");
+                    return;
+                }
+                Ok(n) => {
+                    debug!("Received {} bytes from gateway", n);
+                    stats.record_bytes_received(n);
+                }
+                Err(e) => {
+                    error!("Error reading from gateway: {}", e);
+                    stats.record_error(format!("Error reading from gateway: {}", e));
+                    return;
+                }
+            }
+            
+            // Process messages in buffer
+            while buf.len() >= 16 {
+                // Peek at header
+                let header = match MessageHeader::decode(&mut BytesMut::from(&buf[..16])) {
+                    Ok(h) => h,
                     Err(e) => {
-                        error!("Error reading from gateway: {}", e);
-                        break;
+                        // Slide forward a byte at a time to the next valid
+                        // header, keeping whatever follows it
+                        if skipped == 0 {
+                            warn!("Failed to decode header, resynchronizing: {}", e);
+                        }
+                        skipped += 1;
+                        if skipped > MAX_RESYNC_BYTES {
+                            error!(
+                                "No valid frame header in {} bytes, disconnecting",
+                                MAX_RESYNC_BYTES
+                            );
+                            stats.record_error(format!(
+                                "No valid frame header in {} bytes",
+                                MAX_RESYNC_BYTES
+                            ));
+                            return;
+                        }
+                        buf.advance(1);
+                        continue;
                     }
+                };
+                
+                if skipped > 0 {
+                    warn!("Resynchronized after skipping {} bytes", skipped);
+                    skipped = 0;
+                }
+                
+                // Checked before waiting for the body, so a corrupt length
+                // can't keep the buffer growing
+                if header.length > max_message_size {
+                    error!(
+                        "Frame header claims {} bytes, over max_message_size {}, disconnecting",
+                        header.length, max_message_size
+                    );
+                    stats.record_error(format!(
+                        "Frame of {} bytes over max_message_size {}",
+                        header.length, max_message_size
+                    ));
+                    return;
+                }
+                
+                // Check if we have full message
+                if buf.len() < header.length as usize {
+                    debug!(
+                        "Waiting for more data: have {}, need {}",
+                        buf.len(),
+                        header.length
+                    );
+                    break;
+                }
+                
+                let mut msg_buf = buf.split_to(header.length as usize);
+                if let Some(capture) = capture {
+                    capture.record(Direction::Received, &msg_buf);
+                }
+                
+                // The header's length still frames the message, so a bad
+                // checksum only costs this frame
+                if let Err(e) = header.verify_checksum(&mut msg_buf) {
+                    warn!("Skipping corrupted frame (sequence {}): {}", header.sequence, e);
+                    continue;
                 }
                 
-                // Release the lock while processing messages
-                drop(stream);
+                sequences.check_inbound(header.sequence);
+                stats.record_received(header.msg_type);
+                
+                msg_buf.advance(16); // Skip header
                 
-                // Process messages in buffer
-                while buf.len() >= 16 {
-                    // Peek at header
-                    let mut peek_buf = buf.clone();
-                    let header = match MessageHeader::decode(&mut peek_buf) {
-                        Ok(h) => h,
-                        Err(e) => {
-                            error!("Failed to decode header: {}", e);
-                            buf.clear();
-                            break;
+                // Process message based on type
+                match header.msg_type {
+                    MessageType::OrderAck => {
+                        match OrderAckMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received OrderAck: {:?}", msg);
+                                orders.on_ack(&msg);
+                                if let Some((_, tx)) = pending.orders.remove(&msg.client_order_id) {
+                                    let _ = tx.send(Ok(msg.clone()));
+                                }
+                                let _ = message_tx.send(IncomingMessage::OrderAck(msg));
+                            }
+                            Err(e) => error!("Failed to decode OrderAck: {}", e),
                         }
-                    };
-                    
-                    // Check if we have full message
-                    if buf.len() < header.length as usize {
-                        debug!(
-                            "Waiting for more data: have {}, need {}",
-                            buf.len(),
-                            header.length
-                        );
-                        break;
                     }
-                    
-                    // Remove header from buffer
-                    let mut msg_buf = buf.split_to(header.length as usize);
-                    msg_buf.advance(16); // Skip header
-                    
-                    // Process message based on type
-                    match header.msg_type {
-                        MessageType::OrderAck => {
-                            match OrderAckMessage::decode(&mut msg_buf) {
-                                Ok(msg) => {
-                                    debug!("Received OrderAck: {:?}", msg);
-                                    let _ = message_tx.send(IncomingMessage::OrderAck(msg));
+                    MessageType::OrderReject => {
+                        match OrderRejectMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received OrderReject: {:?}", msg);
+                                orders.on_reject(&msg);
+                                // A reject answers either a new order or a replace
+                                if let Some((_, tx)) = pending.orders.remove(&msg.client_order_id) {
+                                    let _ = tx.send(Err(msg.clone()));
+                                } else if let Some((_, tx)) =
+                                    pending.replaces.remove(&msg.client_order_id)
+                                {
+                                    let _ = tx.send(Err(msg.clone()));
                                 }
-                                Err(e) => error!("Failed to decode OrderAck: {}", e),
+                                let _ = message_tx.send(IncomingMessage::OrderReject(msg));
                             }
+                            Err(e) => error!("Failed to decode OrderReject: {}", e),
                         }
-                        MessageType::OrderReject => {
-                            match OrderRejectMessage::decode(&mut msg_buf) {
-                                Ok(msg) => {
-                                    debug!("Received OrderReject: {:?}", msg);
-                                    let _ = message_tx.send(IncomingMessage::OrderReject(msg));
+                    }
+                    MessageType::OrderReplaced => {
+                        match OrderReplacedMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received OrderReplaced: {:?}", msg);
+                                orders.on_replaced(&msg);
+                                if let Some((_, tx)) = pending.replaces.remove(&msg.client_order_id) {
+                                    let _ = tx.send(Ok(msg.clone()));
                                 }
-                                Err(e) => error!("Failed to decode OrderReject: {}", e),
+                                let _ = message_tx.send(IncomingMessage::OrderReplaced(msg));
                             }
+                            Err(e) => error!("Failed to decode OrderReplaced: {}", e),
                         }
-                        MessageType::Execution => {
-                            match ExecutionMessage::decode(&mut msg_buf) {
-                                Ok(msg) => {
-                                    debug!("Received Execution: {:?}", msg);
-                                    let _ = message_tx.send(IncomingMessage::Execution(msg));
-                                }
-                                Err(e) => error!("Failed to decode Execution: {}", e),
+                    }
+                    MessageType::OrderCancelled => {
+                        match OrderCancelledMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received OrderCancelled: {:?}", msg);
+                                orders.on_cancelled(&msg);
+                                let _ = message_tx.send(IncomingMessage::OrderCancelled(msg));
                             }
+                            Err(e) => error!("Failed to decode OrderCancelled: {}", e),
                         }
-                        _ => {
-                            debug!("Ignoring message type: {:?}", header.msg_type);
+                    }
+                    MessageType::Execution => {
+                        match ExecutionMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received Execution: {:?}", msg);
+                                orders.on_execution(&msg);
+                                let _ = message_tx.send(IncomingMessage::Execution(msg));
+                            }
+                            Err(e) => error!("Failed to decode Execution: {}", e),
                         }
                     }
-                }
-            }
-            
-            warn!("Message receiver task terminated");
+                    MessageType::Trade => {
+                        match TradeMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received Trade: {:?}", msg);
+                                let _ = message_tx.send(IncomingMessage::Trade(msg));
+                            }
+                            Err(e) => error!("Failed to decode Trade: {}", e),
+                        }
+                    }
+                    MessageType::Quote => {
+                        match QuoteMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received Quote: {:?}", msg);
+                                let _ = message_tx.send(IncomingMessage::Quote(msg));
+                            }
+                            Err(e) => error!("Failed to decode Quote: {}", e),
+                        }
+                    }
+                    MessageType::BookSnapshot => {
+                        match BookSnapshotMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received BookSnapshot: {:?}", msg);
+                                if let Some((_, tx)) = pending.books.remove(&msg.request_id) {
+                                    let _ = tx.send(msg.clone());
+                                }
+                                let _ = message_tx.send(IncomingMessage::BookSnapshot(msg));
+                            }
+                            Err(e) => error!("Failed to decode BookSnapshot: {}", e),
+                        }
+                    }
+                    MessageType::BookDelta => {
+                        match BookDeltaMessage::decode(&mut msg_buf) {
+                            Ok(msg) => {
+                                debug!("Received BookDelta: {:?}", msg);
+                                let _ = message_tx.send(IncomingMessage::BookDelta(msg));
+                            }
+                            Err(e) => error!("Failed to decode BookDelta: {}", e),
+                        }
+                    }
+                    _ => {
+                        debug!("Ignoring message type: {:?}", header.msg_type);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Capacity of the execution broadcast channel. The gateway reader never
+/// waits on subscribers: one that falls this far behind loses the oldest
+/// executions instead.
+const EXECUTION_CHANNEL_CAPACITY: usize = 1024;
+
+/// Filtered subscription to execution reports from the gateway
+pub struct ExecutionSubscription {
+    rx: broadcast::Receiver<ExecutionMessage>,
+    shutdown: watch::Receiver<bool>,
+    symbol: Option<String>,
+    user_id: Option<u64>,
+}
+
+impl ExecutionSubscription {
+    /// Wait for the next execution matching this subscription's filters.
+    /// Returns `None` once the client has shut down.
+    pub async fn recv(&mut self) -> Option<ExecutionMessage> {
+        loop {
+            let received = tokio::select! {
+                received = self.rx.recv() => received,
+                _ = self.shutdown.wait_for(|down| *down) => return None,
+            };
+            match received {
+                Ok(msg) => {
+                    if self.matches(&msg) {
+                        return Some(msg);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Execution subscriber lagged, skipped {} messages", skipped);
+                    crate::metrics::METRICS.record_executions_lagged("gateway", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+    
+    fn matches(&self, msg: &ExecutionMessage) -> bool {
+        self.symbol.as_ref().is_none_or(|s| *s == msg.symbol)
+            && self.user_id.is_none_or(|u| u == msg.user_id)
+    }
+}
+
+/// Capacity of the trade broadcast channel. Slow subscribers lose the
+/// oldest trades rather than buffering without bound.
+const TRADE_CHANNEL_CAPACITY: usize = 1024;
+
+/// Subscription to public trades from the gateway, optionally for one symbol
+pub struct TradeSubscription {
+    rx: broadcast::Receiver<TradeMessage>,
+    shutdown: watch::Receiver<bool>,
+    symbol: Option<String>,
+    /// Trades were dropped since the last one received
+    lagged: bool,
+}
+
+impl TradeSubscription {
+    /// Wait for the next trade matching this subscription's symbol, and
+    /// whether trades may have been dropped just before it because this
+    /// subscriber fell behind. The channel carries every symbol, so those
+    /// dropped weren't necessarily this symbol's. Returns `None` once the
+    /// client has shut down.
+    pub async fn recv(&mut self) -> Option<(TradeMessage, bool)> {
+        loop {
+            let received = tokio::select! {
+                received = self.rx.recv() => received,
+                _ = self.shutdown.wait_for(|down| *down) => return None,
+            };
+            match received {
+                Ok(msg) => {
+                    if self.symbol.as_ref().is_none_or(|s| *s == msg.symbol) {
+                        return Some((msg, std::mem::take(&mut self.lagged)));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Trade subscriber lagged, dropped {} oldest trades", skipped);
+                    self.lagged = true;
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Capacity of the quote broadcast channel. Quotes supersede each other, so
+/// a lagging subscriber only misses ones it would have skipped anyway.
+const QUOTE_CHANNEL_CAPACITY: usize = 256;
+
+/// Subscription to top-of-book quotes from the gateway for one symbol
+pub struct QuoteSubscription {
+    rx: broadcast::Receiver<QuoteMessage>,
+    shutdown: watch::Receiver<bool>,
+    symbol: String,
+}
+
+impl QuoteSubscription {
+    /// Wait for the next quote for this subscription's symbol.
+    /// Returns `None` once the client has shut down.
+    pub async fn recv(&mut self) -> Option<QuoteMessage> {
+        loop {
+            let received = tokio::select! {
+                received = self.rx.recv() => received,
+                _ = self.shutdown.wait_for(|down| *down) => return None,
+            };
+            match received {
+                Ok(msg) => {
+                    if msg.symbol == self.symbol {
+                        return Some(msg);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Quote subscriber lagged, skipped {} quotes", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Capacity of the book delta broadcast channel. A lagging subscriber sees
+/// a sequence gap and has to start again from a snapshot.
+const BOOK_DELTA_CHANNEL_CAPACITY: usize = 1024;
+
+/// Subscription to order book deltas from the gateway for one symbol
+pub struct BookDeltaSubscription {
+    rx: broadcast::Receiver<BookDeltaMessage>,
+    shutdown: watch::Receiver<bool>,
+    symbol: String,
+}
+
+impl BookDeltaSubscription {
+    /// Wait for the next delta for this subscription's symbol. Every pooled
+    /// connection carries the same deltas, so each may arrive more than
+    /// once; the sequence tells them apart. Returns `None` once the client
+    /// has shut down.
+    pub async fn recv(&mut self) -> Option<BookDeltaMessage> {
+        loop {
+            let received = tokio::select! {
+                received = self.rx.recv() => received,
+                _ = self.shutdown.wait_for(|down| *down) => return None,
+            };
+            match received {
+                Ok(msg) => {
+                    if msg.symbol == self.symbol {
+                        return Some(msg);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Book delta subscriber lagged, skipped {} deltas", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Connections added for load are only worth keeping while every pooled
+/// connection has at least this many requests awaiting a reply
+const BUSY_CONNECTION_IN_FLIGHT: usize = 32;
+
+/// How often an idle pool gives back a connection added for load
+const POOL_SHRINK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often a connection replaced by a pool refresh is checked for
+/// requests still using it
+const RETIRE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What the pool needs to open a connection and route its messages. Shared
+/// with the tasks that grow the pool in the background.
+#[derive(Clone)]
+struct PoolContext {
+    address: String,
+    options: ConnectionOptions,
+    orders: Arc<OrderStore>,
+    /// Recent trades per symbol
+    trades: Arc<TradeHistory>,
+    execution_tx: broadcast::Sender<ExecutionMessage>,
+    trade_tx: broadcast::Sender<TradeMessage>,
+    quote_tx: broadcast::Sender<QuoteMessage>,
+    /// Timestamp of the last quote published per symbol
+    last_quote_at: Arc<DashMap<String, Option<u64>>>,
+    book_delta_tx: broadcast::Sender<BookDeltaMessage>,
+    /// Pooled connections currently up
+    live_connections: Arc<AtomicUsize>,
+    /// Whether at least one pooled connection is up
+    liveness_tx: Arc<watch::Sender<bool>>,
+    /// Source of `MatchingConnection` ids
+    next_connection_id: Arc<AtomicU64>,
+}
+
+impl PoolContext {
+    /// Connect and log on, then spawn the task that dispatches the
+    /// connection's messages to subscribers
+    async fn open(&self, slot: usize) -> Result<Arc<MatchingConnection>, MatchingError> {
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let (conn, mut rx) = MatchingConnection::connect(
+            id,
+            &self.address,
+            self.options.clone(),
+            Arc::clone(&self.orders),
+        )
+        .await?;
+        self.report_liveness(true);
+        
+        let pool = self.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                debug!("Pool connection {} received: {:?}", slot, msg);
+                pool.publish(msg);
+            }
+        });
+        
+        Ok(Arc::new(conn))
+    }
+    
+    /// Hand a message to the trade history and subscribers, or count a
+    /// connection going down or up
+    fn publish(&self, msg: IncomingMessage) {
+        // No subscribers is not an error
+        match msg {
+            IncomingMessage::Execution(exec) => {
+                let _ = self.execution_tx.send(exec);
+            }
+            IncomingMessage::Trade(trade) => {
+                self.trades.record(&trade, || {
+                    let _ = self.trade_tx.send(trade.clone());
+                });
+            }
+            IncomingMessage::Quote(quote) => {
+                // As for trades, each pooled connection may deliver the same
+                // quote; only newer ones are passed on, in order
+                let mut last = self.last_quote_at.entry(quote.symbol.clone()).or_insert(None);
+                if last.is_none_or(|last| quote.timestamp > last) {
+                    *last = Some(quote.timestamp);
+                    let _ = self.quote_tx.send(quote);
+                }
+            }
+            IncomingMessage::BookDelta(delta) => {
+                let _ = self.book_delta_tx.send(delta);
+            }
+            IncomingMessage::Disconnected => self.report_liveness(false),
+            IncomingMessage::Reconnected => self.report_liveness(true),
+            _ => {}
+        }
+    }
+    
+    /// Count a pooled connection going up or down and publish the pool's
+    /// liveness if it changed
+    fn report_liveness(&self, up: bool) {
+        let live = if up {
+            self.live_connections.fetch_add(1, Ordering::AcqRel) + 1
+        } else {
+            self.live_connections.fetch_sub(1, Ordering::AcqRel) - 1
+        };
+        
+        crate::metrics::METRICS.set_matching_connections_active(live);
+        
+        self.liveness_tx.send_if_modified(|was_live| {
+            let is_live = live > 0;
+            std::mem::replace(was_live, is_live) != is_live
         });
     }
 }
 
-/// Connection pool for managing multiple connections
-#[allow(dead_code)]
+/// Connection pool for managing multiple connections.
+///
+/// Connections that fail to open at startup are retried in the background
+/// until the pool reaches `pool_size`; with `start_degraded` that includes
+/// starting with none at all. When every connection is busy the pool
+/// grows, one connection at a time, up to `max_pool_size`, and gives the
+/// extra connections back once it is idle again.
 pub struct MatchingClient {
-    address: String,
     pool_size: usize,
-    connect_timeout: Duration,
+    max_pool_size: usize,
+    pool: PoolContext,
     connections: Arc<RwLock<Vec<Arc<MatchingConnection>>>>,
+    /// Round-robin position for `get_connection`
+    next_connection: AtomicUsize,
+    /// Set while a connection is being added for load
+    growing: Arc<AtomicBool>,
+    /// Flipped to true by `shutdown`, ending every subscription
+    shutdown_tx: watch::Sender<bool>,
 }
 
 impl MatchingClient {
-    pub async fn new(address: String, pool_size: usize, connect_timeout_ms: u64) -> Result<Self> {
-        let connect_timeout = Duration::from_millis(connect_timeout_ms);
-        
+    pub async fn new(
+        address: String,
+        pool_size: usize,
+        max_pool_size: usize,
+        options: ConnectionOptions,
+        orders: Arc<OrderStore>,
+        trades: Arc<TradeHistory>,
+        start_degraded: bool,
+    ) -> Result<Self, MatchingError> {
+    
         info!(
-            "Creating matching client pool: address={}, size={}",
-            address, pool_size
+            "Creating matching client pool: address={}, size={}, max size={}",
+            address, pool_size, max_pool_size
         );
         
-        let mut connections = Vec::with_capacity(pool_size);
+        let (execution_tx, _) = broadcast::channel(EXECUTION_CHANNEL_CAPACITY);
+        let (trade_tx, _) = broadcast::channel(TRADE_CHANNEL_CAPACITY);
+        let (quote_tx, _) = broadcast::channel(QUOTE_CHANNEL_CAPACITY);
+        let (book_delta_tx, _) = broadcast::channel(BOOK_DELTA_CHANNEL_CAPACITY);
+        let pool = PoolContext {
+            address,
+            options,
+            orders,
+            trades,
+            execution_tx,
+            trade_tx,
+            quote_tx,
+            last_quote_at: Arc::new(DashMap::new()),
+            book_delta_tx,
+            live_connections: Arc::new(AtomicUsize::new(0)),
+            liveness_tx: Arc::new(watch::Sender::new(false)),
+            next_connection_id: Arc::new(AtomicU64::new(0)),
+        };
         
         // Create initial connections
-        for i in 0..pool_size {
-            match MatchingConnection::connect(&address, connect_timeout).await {
-                Ok((conn, mut rx)) => {
-                    // Spawn task to handle incoming messages
-                    tokio::spawn(async move {
-                        while let Some(msg) = rx.recv().await {
-                            // Here we could dispatch to subscribers
-                            debug!("Pool connection {} received: {:?}", i, msg);
-                        }
-                    });
-                    
-                    connections.push(Arc::new(conn));
-                }
+        let mut connections = Vec::with_capacity(max_pool_size);
+        let mut failed = Vec::new();
+        for slot in 0..pool_size {
+            match pool.open(slot).await {
+                Ok(conn) => connections.push(conn),
                 Err(e) => {
-                    error!("Failed to create connection {}: {}", i, e);
+                    error!("Failed to create connection {}: {:#}", slot, e);
+                    failed.push(slot);
                 }
             }
         }
         
         if connections.is_empty() {
-            anyhow::bail!("Failed to create any connections to gateway");
+            if !start_degraded {
+                return Err(MatchingError::NotConnected(
+                    "Failed to create any connections to gateway".to_string(),
+                ));
+            }
+            warn!("No connections to gateway, starting degraded and retrying in the background");
         }
         
-        info!("Created {} connections to gateway", connections.len());
+        info!("Created {} of {} connections to gateway", connections.len(), pool_size);
         
-        Ok(Self {
-            address,
+        let client = Self {
             pool_size,
-            connect_timeout,
+            max_pool_size: max_pool_size.max(pool_size),
+            pool,
             connections: Arc::new(RwLock::new(connections)),
-        })
+            next_connection: AtomicUsize::new(0),
+            growing: Arc::new(AtomicBool::new(false)),
+            shutdown_tx: watch::Sender::new(false),
+        };
+        
+        for slot in failed {
+            client.spawn_refill(slot);
+        }
+        if client.max_pool_size > client.pool_size {
+            client.spawn_shrinker();
+        }
+        
+        Ok(client)
+    }
+    
+    /// Retry a connection that failed to open, backing off between attempts,
+    /// until it joins the pool or the client shuts down
+    fn spawn_refill(&self, slot: usize) {
+        let pool = self.pool.clone();
+        let connections = Arc::clone(&self.connections);
+        let max_pool_size = self.max_pool_size;
+        let mut shutdown = self.shutdown_tx.subscribe();
+        
+        tokio::spawn(async move {
+            let mut delay = pool.options.reconnect_base_delay;
+            
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown.wait_for(|down| *down) => return,
+                }
+                
+                match pool.open(slot).await {
+                    Ok(conn) => {
+                        if Self::add_connection(&connections, &shutdown, max_pool_size, conn).await {
+                            info!("Connection {} to gateway established", slot);
+                        }
+                        return;
+                    }
+                    Err(e) => {
+                        delay = (delay * 2).min(pool.options.reconnect_max_delay);
+                        warn!(
+                            "Failed to create connection {}, retrying in {:?}: {:#}",
+                            slot, delay, e
+                        );
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Add a connection for load. Only one is added at a time.
+    fn spawn_grow(&self, slot: usize) {
+        if self.growing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        
+        let pool = self.pool.clone();
+        let connections = Arc::clone(&self.connections);
+        let max_pool_size = self.max_pool_size;
+        let shutdown = self.shutdown_tx.subscribe();
+        let growing = Arc::clone(&self.growing);
+        
+        tokio::spawn(async move {
+            match pool.open(slot).await {
+                Ok(conn) => {
+                    if Self::add_connection(&connections, &shutdown, max_pool_size, conn).await {
+                        info!("Added connection {} to gateway for load", slot);
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to add connection {} to gateway: {:#}", slot, e);
+                    // Busy requests would otherwise retry against a down
+                    // gateway on every call
+                    tokio::time::sleep(pool.options.reconnect_max_delay).await;
+                }
+            }
+            growing.store(false, Ordering::Release);
+        });
+    }
+    
+    /// Periodically log out one connection beyond `pool_size` that no
+    /// request is using, as long as no connection is busy
+    fn spawn_shrinker(&self) {
+        let connections = Arc::clone(&self.connections);
+        let pool_size = self.pool_size;
+        let mut shutdown = self.shutdown_tx.subscribe();
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(POOL_SHRINK_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.wait_for(|down| *down) => return,
+                }
+                
+                let mut pooled = connections.write().await;
+                if pooled.len() <= pool_size
+                    || pooled.iter().any(|conn| conn.in_flight() >= BUSY_CONNECTION_IN_FLIGHT / 2)
+                {
+                    continue;
+                }
+                
+                // A request holds its connection until it completes, so a
+                // connection only the pool references is unused
+                let Some(idle) = pooled.iter().rposition(|conn| Arc::strong_count(conn) == 1) else {
+                    continue;
+                };
+                let conn = pooled.remove(idle);
+                let remaining = pooled.len();
+                drop(pooled);
+                
+                if let Err(e) = conn.logout().await {
+                    warn!("Failed to log out idle connection: {:#}", e);
+                }
+                info!("Removed idle connection from pool ({} remaining)", remaining);
+            }
+        });
+    }
+    
+    /// Add a newly opened connection, or log it out if the pool is full or
+    /// shutting down. Returns whether it was added.
+    async fn add_connection(
+        connections: &RwLock<Vec<Arc<MatchingConnection>>>,
+        shutdown: &watch::Receiver<bool>,
+        max_pool_size: usize,
+        conn: Arc<MatchingConnection>,
+    ) -> bool {
+        let mut pooled = connections.write().await;
+        
+        // Checked under the lock, so `logout` either sees the connection or
+        // it is logged out here
+        if *shutdown.borrow() || pooled.len() >= max_pool_size {
+            drop(pooled);
+            if let Err(e) = conn.logout().await {
+                warn!("Failed to log out surplus connection: {:#}", e);
+            }
+            return false;
+        }
+        
+        pooled.push(conn);
+        true
+    }
+    
+    /// Subscribe to execution reports, optionally filtered by symbol and user.
+    /// Dropping the subscription unsubscribes.
+    pub fn subscribe_executions(
+        &self,
+        symbol: Option<String>,
+        user_id: Option<u64>,
+    ) -> ExecutionSubscription {
+        ExecutionSubscription {
+            rx: self.pool.execution_tx.subscribe(),
+            shutdown: self.shutdown_tx.subscribe(),
+            symbol,
+            user_id,
+        }
+    }
+    
+    /// Subscribe to public trades, optionally for a single symbol.
+    /// Dropping the subscription unsubscribes.
+    pub fn subscribe_trades(&self, symbol: Option<String>) -> TradeSubscription {
+        TradeSubscription {
+            rx: self.pool.trade_tx.subscribe(),
+            shutdown: self.shutdown_tx.subscribe(),
+            symbol,
+            lagged: false,
+        }
+    }
+    
+    /// Subscribe to top-of-book quotes for a symbol. Dropping the
+    /// subscription unsubscribes.
+    pub fn subscribe_quotes(&self, symbol: String) -> QuoteSubscription {
+        QuoteSubscription {
+            rx: self.pool.quote_tx.subscribe(),
+            shutdown: self.shutdown_tx.subscribe(),
+            symbol,
+        }
+    }
+    
+    /// Subscribe to order book deltas for a symbol. Subscribe before
+    /// fetching the snapshot to build on, so no delta falls between the
+    /// two. Dropping the subscription unsubscribes.
+    pub fn subscribe_book_deltas(&self, symbol: String) -> BookDeltaSubscription {
+        BookDeltaSubscription {
+            rx: self.pool.book_delta_tx.subscribe(),
+            shutdown: self.shutdown_tx.subscribe(),
+            symbol,
+        }
+    }
+    
+    /// Watch pool liveness; the value changes when the last live connection
+    /// drops or the first one comes back
+    pub fn watch_liveness(&self) -> watch::Receiver<bool> {
+        self.pool.liveness_tx.subscribe()
+    }
+    
+    /// Number of pooled connections currently up
+    pub fn active_connections(&self) -> usize {
+        self.pool.live_connections.load(Ordering::Acquire)
+    }
+    
+    /// Traffic, reconnect and error counters for each pooled connection
+    pub async fn connection_stats(&self) -> Vec<ConnectionStats> {
+        self.connections
+            .read()
+            .await
+            .iter()
+            .enumerate()
+            .map(|(slot, conn)| conn.stats(slot))
+            .collect()
+    }
+    
+    /// Replace every pooled connection with a freshly opened one, e.g. to
+    /// pick up a gateway that was restarted behind the same address. All
+    /// the new connections are opened before any old one is touched, so if
+    /// one fails to open the pool is left as it was. The old connections
+    /// are logged out in the background once the requests already on them
+    /// complete. Returns how many connections were replaced.
+    pub async fn refresh_pool(&self) -> Result<usize, MatchingError> {
+        let count = self.connections.read().await.len().max(self.pool_size);
+        
+        let mut fresh = Vec::with_capacity(count);
+        for slot in 0..count {
+            match self.pool.open(slot).await {
+                Ok(conn) => fresh.push(conn),
+                Err(e) => {
+                    warn!("Failed to open replacement connection {}: {}", slot, e);
+                    Self::log_out_unused(&fresh).await;
+                    return Err(e);
+                }
+            }
+        }
+        
+        let mut pooled = self.connections.write().await;
+        
+        // Checked under the lock, as in `add_connection`
+        if *self.shutdown_tx.borrow() {
+            drop(pooled);
+            Self::log_out_unused(&fresh).await;
+            return Err(MatchingError::Closed("Matching client is shutting down".to_string()));
+        }
+        
+        let replaced = std::mem::replace(&mut *pooled, fresh);
+        drop(pooled);
+        
+        info!("Refreshed connection pool, replacing {} connections", replaced.len());
+        let count = replaced.len();
+        self.spawn_retire(replaced);
+        Ok(count)
+    }
+    
+    /// Log out connections that never joined the pool
+    async fn log_out_unused(connections: &[Arc<MatchingConnection>]) {
+        for conn in connections {
+            if let Err(e) = conn.logout().await {
+                warn!("Failed to log out unused connection: {:#}", e);
+            }
+        }
+    }
+    
+    /// Log out connections taken out of the pool once no request holds
+    /// them, or once a reply to the last request can no longer arrive
+    fn spawn_retire(&self, retired: Vec<Arc<MatchingConnection>>) {
+        let drain_timeout = self.pool.options.ack_timeout.max(self.pool.options.read_timeout);
+        
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + drain_timeout;
+            
+            for conn in retired {
+                // A request holds its connection until it completes
+                while Arc::strong_count(&conn) > 1 && tokio::time::Instant::now() < deadline {
+                    tokio::time::sleep(RETIRE_POLL_INTERVAL).await;
+                }
+                if let Err(e) = conn.logout().await {
+                    warn!("Failed to log out replaced connection: {:#}", e);
+                }
+            }
+        });
+    }
+    
+    /// Fixed-point scale of the prices this client sends and receives
+    pub fn price_scale(&self) -> PriceScale {
+        self.pool.options.price_scale
+    }
+    
+    /// Deliver a message to subscribers as if a pooled connection had
+    /// received it. The paper-trading backend reports its simulated
+    /// executions and trades this way.
+    pub fn publish(&self, msg: IncomingMessage) {
+        self.pool.publish(msg);
+    }
+    
+    /// Price of the most recent trade seen for a symbol, in fixed-point
+    /// units
+    pub fn last_trade_price(&self, symbol: &str) -> Option<u64> {
+        self.pool.trades.last_price(symbol)
+    }
+    
+    /// Annualized realized volatility from a symbol's recent trades, when
+    /// there are enough of them
+    pub fn realized_volatility(&self, symbol: &str) -> Option<f64> {
+        self.pool.trades.realized_volatility(symbol)
     }
     
     /// Get a connection from the pool (round-robin)
-    async fn get_connection(&self) -> Result<Arc<MatchingConnection>> {
+    async fn get_connection(&self) -> Result<Arc<MatchingConnection>, MatchingError> {
+        self.pick_connection(None).await
+    }
+    
+    /// Get the connection to submit a user's new order on: under user
+    /// affinity the one the user id hashes to, else round-robin
+    async fn submit_connection(&self, user_id: u64) -> Result<Arc<MatchingConnection>, MatchingError> {
+        match self.pool.options.affinity {
+            Affinity::User => self.pick_connection(Some(user_id)).await,
+            Affinity::None | Affinity::Order => self.get_connection().await,
+        }
+    }
+    
+    /// Get the connection to cancel or replace an order on. With affinity
+    /// that is the one the order was submitted on, for as long as it is in
+    /// the pool and up; after that, or without affinity, it is picked as
+    /// for a new order from the user.
+    async fn order_connection(
+        &self,
+        client_order_id: u64,
+        user_id: u64,
+    ) -> Result<Arc<MatchingConnection>, MatchingError> {
+        if self.pool.options.affinity != Affinity::None {
+            if let Some(id) = self.pool.orders.connection(client_order_id) {
+                let connections = self.connections.read().await;
+                if let Some(conn) = connections.iter().find(|conn| conn.id == id && conn.is_usable()) {
+                    return Ok(Arc::clone(conn));
+                }
+                debug!(
+                    "Connection {} that submitted order {} is down, stale or gone, using another",
+                    id, client_order_id
+                );
+            }
+        }
+        
+        self.submit_connection(user_id).await
+    }
+    
+    /// Get a connection from the pool: the one `key` hashes to, or the
+    /// next round-robin without a key. Connections that are down,
+    /// reconnecting or stale are passed over for the next healthy one.
+    async fn pick_connection(&self, key: Option<u64>) -> Result<Arc<MatchingConnection>, MatchingError> {
         let connections = self.connections.read().await;
         
         if connections.is_empty() {
-            anyhow::bail!("No connections available");
+            return Err(MatchingError::NotConnected("No connections available".to_string()));
+        }
+        
+        let start = match key {
+            Some(key) => jump_hash(key, connections.len()),
+            None => self.next_connection.fetch_add(1, Ordering::Relaxed),
+        };
+        let offset = (0..connections.len())
+            .find(|offset| connections[start.wrapping_add(*offset) % connections.len()].is_usable())
+            .ok_or_else(|| {
+                MatchingError::NotConnected(format!(
+                    "No healthy connections to gateway (all {} reconnecting or stale)",
+                    connections.len()
+                ))
+            })?;
+        
+        // Move past the skipped ones so the next healthy connection doesn't
+        // take their share too
+        if key.is_none() && offset > 0 {
+            self.next_connection.fetch_add(offset, Ordering::Relaxed);
         }
         
-        // Simple round-robin
-        let idx = (chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as usize)
-            % connections.len();
+        // Grow when every connection already has a queue of requests
+        if connections.len() < self.max_pool_size
+            && connections
+                .iter()
+                .all(|conn| !conn.is_usable() || conn.in_flight() >= BUSY_CONNECTION_IN_FLIGHT)
+        {
+            self.spawn_grow(connections.len());
+        }
         
-        Ok(Arc::clone(&connections[idx]))
+        Ok(Arc::clone(&connections[start.wrapping_add(offset) % connections.len()]))
     }
     
-    /// Submit an order through the pool
+    /// How long to wait on the gateway for a request with the caller's
+    /// remaining `deadline`. A deadline can shorten the configured ack
+    /// timeout but not extend it.
+    pub fn request_timeout(&self, deadline: Option<Duration>) -> Duration {
+        let ack_timeout = self.pool.options.ack_timeout;
+        deadline.map_or(ack_timeout, |deadline| deadline.min(ack_timeout))
+    }
+    
+    /// Submit an order through the pool, waiting for the ack until the
+    /// caller's `deadline` or the configured ack timeout
+    #[allow(clippy::too_many_arguments)]
     pub async fn submit_order(
         &self,
         symbol: String,
@@ -300,10 +1917,23 @@ impl MatchingClient {
         order_type: OrderType,
         price: u64,
         quantity: u64,
-    ) -> Result<u64> {
-        let conn = self.get_connection().await?;
-        conn.submit_order(symbol, user_id, side, order_type, price, quantity)
-            .await
+        tag: String,
+        client_order_id: Option<u64>,
+        deadline: Option<Duration>,
+    ) -> Result<OrderAckResult, MatchingError> {
+        let conn = self.submit_connection(user_id).await?;
+        conn.submit_order(
+            symbol,
+            user_id,
+            side,
+            order_type,
+            price,
+            quantity,
+            tag,
+            client_order_id,
+            self.request_timeout(deadline),
+        )
+        .await
     }
     
     /// Cancel an order through the pool
@@ -312,8 +1942,868 @@ impl MatchingClient {
         symbol: String,
         client_order_id: u64,
         user_id: u64,
-    ) -> Result<()> {
+        deadline: Option<Duration>,
+    ) -> Result<(), MatchingError> {
+        let conn = self.order_connection(client_order_id, user_id).await?;
+        conn.cancel_order(symbol, client_order_id, user_id, self.request_timeout(deadline))
+            .await
+    }
+    
+    /// Replace an order's price and quantity through the pool
+    pub async fn replace_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+        deadline: Option<Duration>,
+    ) -> Result<OrderReplaceResult, MatchingError> {
+        let conn = self.order_connection(client_order_id, user_id).await?;
+        conn.replace_order(
+            symbol,
+            client_order_id,
+            user_id,
+            new_price,
+            new_quantity,
+            self.request_timeout(deadline),
+        )
+        .await
+    }
+    
+    /// Request an order book snapshot through the pool, with same-price
+    /// entries merged into at most `depth` price levels per side, best
+    /// first
+    pub async fn get_order_book(
+        &self,
+        symbol: String,
+        depth: u32,
+    ) -> Result<BookSnapshotMessage, MatchingError> {
+        let conn = self.get_connection().await?;
+        let snapshot = conn.request_order_book(symbol, depth).await?;
+        Ok(snapshot.aggregated(depth as usize))
+    }
+    
+    /// Recover a symbol's book after a gap in its delta sequence: the
+    /// gateway is told which deltas were missed and answers with a fresh
+    /// snapshot, aggregated like `get_order_book`'s
+    pub async fn resend_order_book(
+        &self,
+        symbol: String,
+        depth: u32,
+        from_sequence: u32,
+        to_sequence: u32,
+    ) -> Result<BookSnapshotMessage, MatchingError> {
         let conn = self.get_connection().await?;
-        conn.cancel_order(symbol, client_order_id, user_id).await
+        let snapshot = conn.request_resend(symbol, from_sequence, to_sequence).await?;
+        Ok(snapshot.aggregated(depth as usize))
+    }
+    
+    /// Log every pooled connection out of the gateway
+    pub async fn logout(&self) {
+        let connections = self.connections.read().await;
+        
+        for (i, conn) in connections.iter().enumerate() {
+            if let Err(e) = conn.logout().await {
+                warn!("Failed to log out connection {}: {:#}", i, e);
+            }
+        }
+        
+        info!("Logged out {} gateway connections", connections.len());
+    }
+    
+    /// Stop the client for server shutdown: end every subscription, log out
+    /// of the gateway and fail requests still awaiting a reply
+    pub async fn shutdown(&self) {
+        self.shutdown_tx.send_replace(true);
+        self.logout().await;
+    }
+}
+
+#[cfg(test)]
+impl MatchingClient {
+    /// A client whose gateway never answers, started degraded, for tests
+    /// of the services on top of it. Messages are fed in with `publish`.
+    pub(crate) async fn without_gateway(price_scale: u64) -> Self {
+        let mut config = crate::config::Config::default();
+        config.matching_engine.gateway_address = "127.0.0.1:1".to_string();
+        config.matching_engine.connect_timeout_ms = 50;
+        config.matching_engine.connect_attempts = 1;
+        config.matching_engine.reconnect_base_delay_ms = 60_000;
+        config.matching_engine.reconnect_max_delay_ms = 60_000;
+        config.matching_engine.price_scale = price_scale;
+        
+        Self::new(
+            config.matching_engine.gateway_address.clone(),
+            1,
+            1,
+            ConnectionOptions::from(&config.matching_engine),
+            Arc::new(OrderStore::new()),
+            Arc::new(TradeHistory::new(&config.market_data)),
+            true,
+        )
+        .await
+        .expect("a degraded client starts without a gateway")
+    }
+}
+
+/// Jump consistent hash (Lamping and Veach): which of `buckets` `key`
+/// belongs to. Growing the pool by one moves only the keys that land on
+/// the new connection.
+fn jump_hash(mut key: u64, buckets: usize) -> usize {
+    let mut bucket = 0;
+    let mut next = 0u64;
+    while next < buckets as u64 {
+        bucket = next;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as u64;
+    }
+    bucket as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+    use shared::OrderStatus;
+    use tokio::net::TcpListener;
+    
+    fn frame(msg_type: MessageType, body: &[u8]) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        MessageHeader::new(msg_type, (16 + body.len()) as u32).encode(&mut buf);
+        buf.extend_from_slice(body);
+        buf.to_vec()
+    }
+    
+    fn symbol(buf: &mut BytesMut, symbol: &str) {
+        let mut bytes = [0u8; 16];
+        bytes[..symbol.len()].copy_from_slice(symbol.as_bytes());
+        buf.extend_from_slice(&bytes);
+    }
+    
+    fn trade_frame(trade_id: u64, price: u64, timestamp: u64) -> Vec<u8> {
+        let mut body = BytesMut::new();
+        symbol(&mut body, "AAPL");
+        body.put_u64(trade_id);
+        body.put_u64(price);
+        body.put_u64(100);
+        body.put_u64(timestamp);
+        frame(MessageType::Trade, &body)
+    }
+    
+    fn quote_frame(bid_price: u64, timestamp: u64) -> Vec<u8> {
+        let mut body = BytesMut::new();
+        symbol(&mut body, "AAPL");
+        body.put_u64(bid_price);
+        body.put_u64(100);
+        body.put_u64(bid_price + 1);
+        body.put_u64(100);
+        body.put_u64(timestamp);
+        frame(MessageType::Quote, &body)
+    }
+    
+    fn execution_frame(execution_id: u64, user_id: u64) -> Vec<u8> {
+        let mut body = BytesMut::new();
+        symbol(&mut body, "AAPL");
+        body.put_u64(execution_id);
+        body.put_u64(99);
+        body.put_u64(execution_id);
+        body.put_u64(user_id);
+        body.put_u8(Side::Buy as u8);
+        body.put_bytes(0, 7);
+        body.put_u64(10_000);
+        body.put_u64(10);
+        body.put_u64(0);
+        body.put_u64(execution_id * 10);
+        frame(MessageType::Execution, &body)
+    }
+    
+    /// Read one frame, returning its type byte
+    async fn read_frame(stream: &mut TcpStream) -> Option<u8> {
+        let mut header = [0u8; 16];
+        stream.read_exact(&mut header).await.ok()?;
+        let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let mut body = vec![0u8; length.saturating_sub(16)];
+        stream.read_exact(&mut body).await.ok()?;
+        Some(header[1])
+    }
+    
+    /// A gateway that accepts every logon. Once `go` turns true, each
+    /// connection is sent `script(n)`, `n` counting connections from 0;
+    /// after that it answers whatever it is sent with a heartbeat while
+    /// `answer(n)` holds.
+    async fn fake_gateway<S, A>(go: watch::Receiver<bool>, script: S, answer: A) -> String
+    where
+        S: Fn(usize) -> Vec<Vec<u8>> + Send + Sync + 'static,
+        A: Fn(usize) -> bool + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let script = Arc::new(script);
+        let answer = Arc::new(answer);
+        tokio::spawn(async move {
+            for n in 0.. {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let (mut go, script, answer) = (go.clone(), Arc::clone(&script), Arc::clone(&answer));
+                tokio::spawn(async move {
+                    read_frame(&mut stream).await;
+                    stream.write_all(&frame(MessageType::Logon, &[0u8; 96])).await.unwrap();
+                    
+                    let _ = go.wait_for(|go| *go).await;
+                    for frame in script(n) {
+                        stream.write_all(&frame).await.unwrap();
+                    }
+                    while read_frame(&mut stream).await.is_some() {
+                        if answer(n) {
+                            let _ = stream.write_all(&frame(MessageType::Heartbeat, &[])).await;
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+    
+    fn options() -> ConnectionOptions {
+        let mut config = crate::config::Config::default();
+        config.matching_engine.connect_attempts = 1;
+        config.matching_engine.connect_timeout_ms = 1000;
+        config.matching_engine.keepalive = false;
+        ConnectionOptions::from(&config.matching_engine)
+    }
+    
+    async fn client(address: String, pool_size: usize, options: ConnectionOptions) -> MatchingClient {
+        client_with_orders(address, pool_size, options, Arc::new(OrderStore::new())).await
+    }
+    
+    async fn client_with_orders(
+        address: String,
+        pool_size: usize,
+        options: ConnectionOptions,
+        orders: Arc<OrderStore>,
+    ) -> MatchingClient {
+        let config = crate::config::Config::default();
+        MatchingClient::new(
+            address,
+            pool_size,
+            pool_size,
+            options,
+            orders,
+            Arc::new(TradeHistory::new(&config.market_data)),
+            false,
+        )
+        .await
+        .unwrap()
+    }
+    
+    #[tokio::test]
+    async fn trades_from_every_connection_are_published_once_in_order() {
+        let (go, ready) = watch::channel(false);
+        // Both connections deliver the same feed, the second with a repeat
+        // of trade 3 and a straggler older than the latest trade
+        let address = fake_gateway(
+            ready,
+            |n| {
+                let mut frames: Vec<_> = (1..=5).map(|id| trade_frame(id, 10_000 + id, id * 10)).collect();
+                frames.extend((1..=3).map(|t| quote_frame(9_000 + t, t * 10)));
+                if n == 1 {
+                    frames.push(trade_frame(3, 10_003, 30));
+                    frames.push(trade_frame(9, 10_009, 5));
+                    frames.push(quote_frame(9_002, 20));
+                }
+                frames
+            },
+            |_| true,
+        )
+        .await;
+        let client = client(address, 2, options()).await;
+        let mut trades = client.subscribe_trades(None);
+        let mut quotes = client.subscribe_quotes("AAPL".to_string());
+        go.send_replace(true);
+        
+        for id in 1..=5 {
+            let (trade, _) = timeout(Duration::from_secs(1), trades.recv()).await.unwrap().unwrap();
+            assert_eq!((trade.trade_id, trade.price), (id, 10_000 + id));
+        }
+        assert!(timeout(Duration::from_millis(200), trades.recv()).await.is_err());
+        assert_eq!(client.last_trade_price("AAPL"), Some(10_005));
+        
+        for t in 1..=3 {
+            let quote = timeout(Duration::from_secs(1), quotes.recv()).await.unwrap().unwrap();
+            assert_eq!(quote.timestamp, t * 10);
+        }
+        assert!(timeout(Duration::from_millis(200), quotes.recv()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn executions_reach_each_matching_subscriber() {
+        let (go, ready) = watch::channel(false);
+        let address = fake_gateway(
+            ready,
+            |_| vec![execution_frame(1, 7), execution_frame(2, 8), execution_frame(3, 7)],
+            |_| true,
+        )
+        .await;
+        let client = client(address, 1, options()).await;
+        let mut everyone = client.subscribe_executions(None, None);
+        let mut user = client.subscribe_executions(Some("AAPL".to_string()), Some(7));
+        let mut other_symbol = client.subscribe_executions(Some("MSFT".to_string()), None);
+        go.send_replace(true);
+        
+        for execution_id in 1..=3 {
+            let msg = timeout(Duration::from_secs(1), everyone.recv()).await.unwrap().unwrap();
+            assert_eq!(msg.execution_id, execution_id);
+        }
+        for execution_id in [1, 3] {
+            let msg = timeout(Duration::from_secs(1), user.recv()).await.unwrap().unwrap();
+            assert_eq!((msg.execution_id, msg.user_id, msg.fill_price), (execution_id, 7, 10_000));
+        }
+        assert!(timeout(Duration::from_millis(100), user.recv()).await.is_err());
+        assert!(timeout(Duration::from_millis(100), other_symbol.recv()).await.is_err());
+        
+        // Dropped subscriptions unsubscribe
+        drop((everyone, user, other_symbol));
+        assert_eq!(client.pool.execution_tx.receiver_count(), 0);
+    }
+    
+    /// Accept a connection and confirm its logon
+    async fn accept_logon(listener: &TcpListener) -> TcpStream {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_frame(&mut stream).await;
+        stream.write_all(&frame(MessageType::Logon, &[0u8; 96])).await.unwrap();
+        stream
+    }
+    
+    #[tokio::test]
+    async fn dropped_connection_reconnects_and_resumes_sending() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (received_tx, mut received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            // The first session is dropped straight after logon
+            drop(accept_logon(&listener).await);
+            let mut stream = accept_logon(&listener).await;
+            while let Some(msg_type) = read_frame(&mut stream).await {
+                let _ = received_tx.send(msg_type);
+            }
+        });
+        let mut options = options();
+        options.reconnect_base_delay = Duration::from_millis(10);
+        let orders = Arc::new(OrderStore::new());
+        let (conn, mut messages) = MatchingConnection::connect(0, &address, options, orders).await.unwrap();
+        
+        let next = timeout(Duration::from_secs(1), messages.recv()).await.unwrap();
+        assert!(matches!(next, Some(IncomingMessage::Disconnected)), "{:?}", next);
+        let next = timeout(Duration::from_secs(1), messages.recv()).await.unwrap();
+        assert!(matches!(next, Some(IncomingMessage::Reconnected)), "{:?}", next);
+        assert!(conn.is_connected());
+        assert_eq!(conn.stats(0).reconnects, 1);
+        
+        conn.cancel_order("AAPL".to_string(), 42, 7, Duration::from_secs(1))
+            .await
+            .unwrap();
+        let sent = timeout(Duration::from_secs(1), received.recv()).await.unwrap();
+        assert_eq!(sent, Some(MessageType::CancelOrder as u8));
+    }
+    
+    #[tokio::test]
+    async fn connect_retries_a_refused_logon() {
+        // Each gateway refuses its first logon, as if still starting up,
+        // and accepts the next
+        let refusing_gateway = || async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                read_frame(&mut stream).await;
+                let mut refusal = [0u8; 96];
+                refusal[16] = 1;
+                refusal[24..35].copy_from_slice(b"starting up");
+                stream.write_all(&frame(MessageType::Logon, &refusal)).await.unwrap();
+                
+                let _session = accept_logon(&listener).await;
+                std::future::pending::<()>().await;
+            });
+            address
+        };
+        let mut options = options();
+        options.connect_retry_delay = Duration::from_millis(10);
+        
+        options.connect_attempts = 1;
+        let address = refusing_gateway().await;
+        let orders = Arc::new(OrderStore::new());
+        match MatchingConnection::connect(0, &address, options.clone(), Arc::clone(&orders)).await {
+            Err(MatchingError::Rejected { reason, text }) => {
+                assert_eq!((reason, text.as_str()), (1, "starting up"));
+            }
+            Err(e) => panic!("expected a logon reject, got {:?}", e),
+            Ok(_) => panic!("expected a logon reject"),
+        }
+        
+        options.connect_attempts = 2;
+        let address = refusing_gateway().await;
+        let (conn, _messages) = MatchingConnection::connect(0, &address, options, orders).await.unwrap();
+        assert!(conn.is_connected());
+    }
+    
+    #[tokio::test]
+    async fn write_goes_out_while_the_receiver_is_idle() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (received_tx, mut received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            // Never sends anything after the logon, so the receiver sits
+            // in a read
+            let mut stream = accept_logon(&listener).await;
+            while let Some(msg_type) = read_frame(&mut stream).await {
+                let _ = received_tx.send(msg_type);
+            }
+        });
+        let orders = Arc::new(OrderStore::new());
+        let (conn, _messages) = MatchingConnection::connect(0, &address, options(), orders).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        
+        timeout(
+            Duration::from_millis(200),
+            conn.cancel_order("AAPL".to_string(), 42, 7, Duration::from_millis(100)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let sent = timeout(Duration::from_millis(200), received.recv()).await.unwrap();
+        assert_eq!(sent, Some(MessageType::CancelOrder as u8));
+    }
+    
+    #[tokio::test]
+    async fn consecutive_sends_carry_consecutive_sequences() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (received_tx, mut received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut stream = accept_logon(&listener).await;
+            loop {
+                let mut header = [0u8; 16];
+                if stream.read_exact(&mut header).await.is_err() {
+                    return;
+                }
+                let header = MessageHeader::decode(&mut BytesMut::from(&header[..])).unwrap();
+                let mut body = vec![0u8; header.length as usize - 16];
+                stream.read_exact(&mut body).await.unwrap();
+                let _ = received_tx.send(header.sequence);
+            }
+        });
+        let orders = Arc::new(OrderStore::new());
+        let (conn, _messages) = MatchingConnection::connect(0, &address, options(), orders).await.unwrap();
+        
+        // The logon went out as sequence 1
+        for client_order_id in [42, 43] {
+            conn.cancel_order("AAPL".to_string(), client_order_id, 7, Duration::from_secs(1))
+                .await
+                .unwrap();
+        }
+        for sequence in [2, 3] {
+            assert_eq!(timeout(Duration::from_secs(1), received.recv()).await.unwrap(), Some(sequence));
+        }
+        assert_eq!(conn.outbound_sequence(), 3);
+    }
+    
+    #[test]
+    fn inbound_sequences_follow_the_gateway() {
+        let sequences = SessionSequences::default();
+        sequences.reset(5, false);
+        
+        sequences.check_inbound(6);
+        assert_eq!(sequences.inbound.load(Ordering::Acquire), 6);
+        
+        // A gap or a replay is logged, and the count carries on from
+        // what was received
+        sequences.check_inbound(9);
+        assert_eq!(sequences.inbound.load(Ordering::Acquire), 9);
+        sequences.check_inbound(4);
+        assert_eq!(sequences.inbound.load(Ordering::Acquire), 4);
+        
+        // Gateways that don't sequence send 0
+        sequences.check_inbound(0);
+        assert_eq!(sequences.inbound.load(Ordering::Acquire), 4);
+        
+        // Logon starts the count again
+        sequences.reset(0, false);
+        sequences.check_inbound(1);
+        assert_eq!(sequences.inbound.load(Ordering::Acquire), 1);
+        assert_eq!(sequences.outbound.load(Ordering::Acquire), 1);
+    }
+    
+    #[tokio::test]
+    async fn stalled_write_times_out_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            // The first session is never read from, so its socket fills up
+            let _stalled = accept_logon(&listener).await;
+            let mut stream = accept_logon(&listener).await;
+            while read_frame(&mut stream).await.is_some() {}
+        });
+        let mut options = options();
+        options.write_timeout = Duration::from_millis(100);
+        options.reconnect_base_delay = Duration::from_millis(10);
+        let orders = Arc::new(OrderStore::new());
+        let (conn, mut messages) = MatchingConnection::connect(0, &address, options, orders).await.unwrap();
+        
+        let err = timeout(Duration::from_secs(10), async {
+            loop {
+                let sent = conn.cancel_order("AAPL".to_string(), 42, 7, Duration::from_secs(1));
+                if let Err(e) = sent.await {
+                    return e;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(
+            matches!(&err, MatchingError::Io(e) if e.kind() == std::io::ErrorKind::TimedOut),
+            "{:?}",
+            err
+        );
+        
+        let next = timeout(Duration::from_secs(1), messages.recv()).await.unwrap();
+        assert!(matches!(next, Some(IncomingMessage::Disconnected)), "{:?}", next);
+        let next = timeout(Duration::from_secs(1), messages.recv()).await.unwrap();
+        assert!(matches!(next, Some(IncomingMessage::Reconnected)), "{:?}", next);
+        let stats = conn.stats(0);
+        assert!(stats.last_error.unwrap().contains("Timed out writing"));
+        conn.cancel_order("AAPL".to_string(), 42, 7, Duration::from_secs(1))
+            .await
+            .unwrap();
+    }
+    
+    #[tokio::test]
+    async fn heartbeats_go_out_every_interval() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (received_tx, mut received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut stream = accept_logon(&listener).await;
+            while let Some(msg_type) = read_frame(&mut stream).await {
+                let _ = received_tx.send(msg_type);
+            }
+        });
+        let mut options = options();
+        options.heartbeat_interval = Some(Duration::from_secs(30));
+        let orders = Arc::new(OrderStore::new());
+        let (conn, _messages) = MatchingConnection::connect(0, &address, options, orders).await.unwrap();
+        let heartbeats_sent = || {
+            conn.stats(0)
+                .messages_sent
+                .iter()
+                .find(|(msg_type, _)| *msg_type == MessageType::Heartbeat)
+                .map_or(0, |(_, count)| *count)
+        };
+        
+        // The first beat goes out at once, then one every interval
+        tokio::time::pause();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(heartbeats_sent(), 1);
+        tokio::time::sleep(Duration::from_secs(28)).await;
+        assert_eq!(heartbeats_sent(), 1);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(heartbeats_sent(), 2);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(heartbeats_sent(), 4);
+        
+        for _ in 0..4 {
+            assert_eq!(received.recv().await, Some(MessageType::Heartbeat as u8));
+        }
+    }
+    
+    #[tokio::test]
+    async fn frames_after_garbage_are_still_decoded() {
+        let (go, ready) = watch::channel(false);
+        let address = fake_gateway(
+            ready,
+            |_| {
+                // Garbage with no valid header in it, sent in one write with
+                // the frames behind it
+                let mut bytes = vec![0xFF; 21];
+                bytes.extend(trade_frame(1, 10_001, 10));
+                bytes.extend(trade_frame(2, 10_002, 20));
+                vec![bytes]
+            },
+            |_| true,
+        )
+        .await;
+        let client = client(address, 1, options()).await;
+        let mut trades = client.subscribe_trades(None);
+        go.send_replace(true);
+        
+        for id in 1..=2 {
+            let (trade, _) = timeout(Duration::from_secs(1), trades.recv()).await.unwrap().unwrap();
+            assert_eq!((trade.trade_id, trade.price), (id, 10_000 + id));
+        }
+        let stats = client.connection_stats().await.remove(0);
+        assert_eq!((stats.connected, stats.reconnects), (true, 0));
+    }
+    
+    #[tokio::test]
+    async fn garbage_past_the_resync_limit_disconnects() {
+        let (_go, ready) = watch::channel(true);
+        let address = fake_gateway(
+            ready,
+            |n| (n == 0).then(|| vec![0xFF; MAX_RESYNC_BYTES + 32]).into_iter().collect(),
+            |_| true,
+        )
+        .await;
+        let mut options = options();
+        options.reconnect_base_delay = Duration::from_millis(10);
+        let client = client(address, 1, options).await;
+        
+        let stats = timeout(Duration::from_secs(2), async {
+            loop {
+                let stats = client.connection_stats().await.remove(0);
+                if stats.reconnects > 0 {
+                    return stats;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let last_error = stats.last_error.unwrap();
+        assert!(last_error.contains("No valid frame header"), "{}", last_error);
+    }
+    
+    #[tokio::test]
+    async fn unacknowledged_order_times_out_with_its_id() {
+        let (_go, ready) = watch::channel(true);
+        let address = fake_gateway(ready, |_| Vec::new(), |_| false).await;
+        let orders = Arc::new(OrderStore::new());
+        let client = client_with_orders(address, 1, options(), Arc::clone(&orders)).await;
+        
+        let err = client
+            .submit_order(
+                "AAPL".to_string(),
+                7,
+                Side::Buy,
+                OrderType::Limit,
+                10_000,
+                100,
+                String::new(),
+                Some(42),
+                Some(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MatchingError::AckTimeout { client_order_id: 42 }), "{:?}", err);
+        
+        // The gateway may still take it, so the order stays pending
+        assert_eq!(orders.get(42, 7).unwrap().status, OrderStatus::PendingNew);
+    }
+    
+    #[tokio::test]
+    async fn sent_orders_are_counted_on_their_connection() {
+        let (_go, ready) = watch::channel(true);
+        let address = fake_gateway(ready, |_| Vec::new(), |_| false).await;
+        let client = client(address, 2, options()).await;
+        
+        // Unacknowledged, but sent all the same; round robin puts two on
+        // the first connection and one on the second
+        for client_order_id in 1..=3 {
+            let sent = client
+                .submit_order(
+                    "AAPL".to_string(),
+                    7,
+                    Side::Buy,
+                    OrderType::Limit,
+                    10_000,
+                    100,
+                    String::new(),
+                    Some(client_order_id),
+                    Some(Duration::from_millis(10)),
+                )
+                .await;
+            assert!(matches!(sent, Err(MatchingError::AckTimeout { .. })));
+        }
+        
+        let sent: Vec<_> = client
+            .connection_stats()
+            .await
+            .into_iter()
+            .map(|stats| {
+                let new_orders = stats
+                    .messages_sent
+                    .iter()
+                    .find(|(msg_type, _)| *msg_type == MessageType::NewOrder)
+                    .map_or(0, |(_, count)| *count);
+                (stats.slot, new_orders)
+            })
+            .collect();
+        assert_eq!(sent, vec![(0, 2), (1, 1)]);
+    }
+    
+    #[tokio::test]
+    async fn frame_over_max_message_size_disconnects() {
+        let (_go, ready) = watch::channel(true);
+        let address = fake_gateway(
+            ready,
+            |n| {
+                // Only the header, claiming a body far past the limit
+                let mut header = BytesMut::new();
+                MessageHeader::new(MessageType::Trade, 1_025).encode(&mut header);
+                (n == 0).then(|| header.to_vec()).into_iter().collect()
+            },
+            |_| true,
+        )
+        .await;
+        let mut options = options();
+        options.max_message_size = 1_024;
+        options.reconnect_base_delay = Duration::from_millis(10);
+        let client = client(address, 1, options).await;
+        
+        let stats = timeout(Duration::from_secs(2), async {
+            loop {
+                let stats = client.connection_stats().await.remove(0);
+                if stats.reconnects > 0 {
+                    return stats;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let last_error = stats.last_error.unwrap();
+        assert!(last_error.contains("over max_message_size 1024"), "{}", last_error);
+    }
+    
+    #[tokio::test]
+    async fn quiet_connection_is_passed_over() {
+        let (_go, ready) = watch::channel(true);
+        // Only the second connection answers heartbeats
+        let address = fake_gateway(ready, |_| Vec::new(), |n| n == 1).await;
+        let mut options = options();
+        options.heartbeat_interval = Some(Duration::from_millis(50));
+        options.stale_threshold = Some(Duration::from_millis(300));
+        options.reconnect_base_delay = Duration::from_secs(60);
+        options.reconnect_max_delay = Duration::from_secs(60);
+        let client = client(address, 2, options).await;
+        let (first, second) = {
+            let connections = client.connections.read().await;
+            (Arc::clone(&connections[0]), Arc::clone(&connections[1]))
+        };
+        
+        // Key 0 hashes to the first connection
+        assert!(Arc::ptr_eq(&client.pick_connection(Some(0)).await.unwrap(), &first));
+        
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(!first.is_usable());
+        assert!(Arc::ptr_eq(&client.pick_connection(Some(0)).await.unwrap(), &second));
+        assert!(Arc::ptr_eq(&client.pick_connection(None).await.unwrap(), &second));
+        assert!(Arc::ptr_eq(&client.pick_connection(None).await.unwrap(), &second));
+    }
+    
+    #[tokio::test]
+    async fn round_robin_rotates_over_healthy_connections() {
+        let (_go, ready) = watch::channel(true);
+        let address = fake_gateway(ready, |_| Vec::new(), |_| true).await;
+        let client = client(address, 3, options()).await;
+        let connections = client.connections.read().await.clone();
+        let picked = || async {
+            let conn = client.pick_connection(None).await.unwrap();
+            connections.iter().position(|pooled| Arc::ptr_eq(pooled, &conn)).unwrap()
+        };
+        
+        let mut order = Vec::new();
+        for _ in 0..6 {
+            order.push(picked().await);
+        }
+        assert_eq!(order, vec![0, 1, 2, 0, 1, 2]);
+        
+        // A connection that is down is skipped, without the next one
+        // taking its turns as well
+        connections[1].connected.store(false, Ordering::Release);
+        let mut order = Vec::new();
+        for _ in 0..6 {
+            order.push(picked().await);
+        }
+        assert_eq!(order, vec![0, 2, 0, 2, 0, 2]);
+        
+        for conn in &connections {
+            conn.connected.store(false, Ordering::Release);
+        }
+        let picked = client.pick_connection(None).await;
+        assert!(matches!(picked, Err(MatchingError::NotConnected(_))));
+    }
+    
+    #[tokio::test]
+    async fn pool_refills_a_connection_that_failed_to_open() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (accept_all, accepting) = watch::channel(false);
+        tokio::spawn(async move {
+            let mut sessions = Vec::new();
+            // The first connection logs on; the rest are hung up on until
+            // `accept_all`
+            sessions.push(accept_logon(&listener).await);
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                if !*accepting.borrow() {
+                    continue;
+                }
+                read_frame(&mut stream).await;
+                stream.write_all(&frame(MessageType::Logon, &[0u8; 96])).await.unwrap();
+                sessions.push(stream);
+            }
+        });
+        let mut options = options();
+        options.reconnect_base_delay = Duration::from_millis(10);
+        options.reconnect_max_delay = Duration::from_millis(20);
+        let client = client(address, 2, options).await;
+        assert_eq!(client.active_connections(), 1);
+        
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(client.active_connections(), 1);
+        
+        accept_all.send_replace(true);
+        timeout(Duration::from_secs(2), async {
+            while client.connections.read().await.len() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(client.active_connections(), 2);
+    }
+    
+    #[test]
+    fn jump_hash_is_stable() {
+        for buckets in 1..=16 {
+            for key in 0..1_000u64 {
+                let bucket = jump_hash(key, buckets);
+                assert!(bucket < buckets);
+                assert_eq!(jump_hash(key, buckets), bucket);
+            }
+        }
+        assert_eq!(jump_hash(0, 8), 0);
+        assert_eq!(jump_hash(u64::MAX, 1), 0);
+    }
+    
+    #[test]
+    fn growing_the_pool_moves_only_keys_for_the_new_connection() {
+        const KEYS: u64 = 10_000;
+        for buckets in 1..16 {
+            let mut moved = 0;
+            for key in 0..KEYS {
+                let before = jump_hash(key, buckets);
+                let after = jump_hash(key, buckets + 1);
+                if after != before {
+                    assert_eq!(after, buckets, "key {} moved between old connections", key);
+                    moved += 1;
+                }
+            }
+            
+            // About 1/(n + 1) of the keys, give or take
+            let expected = KEYS as f64 / (buckets + 1) as f64;
+            assert!((moved as f64 - expected).abs() < expected * 0.2, "{} of {} moved", moved, KEYS);
+        }
     }
 }
@@ -0,0 +1,39 @@
+use std::io;
+
+/// Why a request to the matching engine gateway failed. A gateway reject
+/// of an order or replace is an answer, not a failure, and comes back as
+/// `OrderAckResult`/`OrderReplaceResult` instead.
+#[derive(Debug, thiserror::Error)]
+pub enum MatchingError {
+    /// No usable connection to the gateway; nothing was sent
+    #[error("{0}")]
+    NotConnected(String),
+    
+    /// No reply in time. The request may still have reached the gateway.
+    #[error("{0}")]
+    Timeout(String),
+    
+    /// No ack or reject for an order in time. The order stays pending under
+    /// `client_order_id` and may still be accepted.
+    #[error("Timed out waiting for acknowledgement of order {client_order_id}")]
+    AckTimeout { client_order_id: u64 },
+    
+    /// The gateway refused the request, e.g. a logon
+    #[error("Gateway rejected the request (reason {reason}): {text}")]
+    Rejected { reason: u8, text: String },
+    
+    /// Encoding, sending or reading a message failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    
+    /// The connection or the client closed before the request completed
+    #[error("{0}")]
+    Closed(String),
+}
+
+impl MatchingError {
+    /// An I/O error prefixed with what was being done
+    pub(crate) fn io(context: &str, e: io::Error) -> Self {
+        MatchingError::Io(io::Error::new(e.kind(), format!("{}: {}", context, e)))
+    }
+}
@@ -1,5 +1,19 @@
+pub mod backend;
+pub mod capture;
 pub mod client;
+pub mod error;
+pub mod order_store;
+pub mod paper;
 pub mod protocol;
+pub mod stats;
+pub mod trade_history;
 
-pub use client::MatchingClient;
+pub use backend::MatchingBackend;
+pub use client::{ConnectionOptions, MatchingClient};
+pub use error::MatchingError;
+pub use order_store::{OrderState, OrderStore};
+pub use paper::PaperMatchingBackend;
 pub use protocol::{OrderType, Side};
+pub use shared::PriceScale;
+pub use stats::ConnectionStats;
+pub use trade_history::TradeHistory;
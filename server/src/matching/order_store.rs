@@ -0,0 +1,521 @@
+use super::protocol::{
+    ExecutionMessage, OrderAckMessage, OrderCancelledMessage, OrderRejectMessage,
+    OrderReplacedMessage, Side,
+};
+use dashmap::DashMap;
+use shared::OrderStatus;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, info};
+
+/// Target of the order lifecycle events, for filtering them out of (or
+/// into) their own log stream
+const ORDER_EVENTS_TARGET: &str = "order_events";
+
+/// Latest known state of an order
+#[derive(Debug, Clone)]
+pub struct OrderState {
+    pub client_order_id: u64,
+    pub exchange_order_id: u64,
+    pub user_id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub price: u64,              // Price in fixed-point units
+    pub original_quantity: u64,
+    pub filled_quantity: u64,
+    pub leaves_quantity: u64,
+    pub average_fill_price: f64, // Volume-weighted, in fixed-point units
+    pub status: OrderStatus,
+    pub timestamp: u64,          // Last update, nanoseconds
+    /// Client-supplied label; kept here only, never sent to the gateway
+    pub tag: String,
+    /// Id of the gateway connection the order was submitted on; `None` for
+    /// paper orders
+    pub connection: Option<u64>,
+}
+
+/// A user's net holding in one symbol, built up from executions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    /// Positive when long, negative when short
+    pub quantity: i64,
+    /// Volume-weighted price of the open quantity, in fixed-point units;
+    /// 0 when flat
+    pub average_cost: f64,
+}
+
+impl Position {
+    /// Apply a fill. Adding to the position (or opening one) folds the fill
+    /// into the average cost; reducing it leaves the cost of what's left
+    /// unchanged. A fill larger than the position closes it and opens the
+    /// opposite one with the remainder, at the fill price.
+    fn apply(&mut self, side: Side, price: u64, quantity: u64) {
+        let fill = match side {
+            Side::Buy => quantity as i64,
+            Side::Sell => -(quantity as i64),
+        };
+        let held = self.quantity.unsigned_abs() as f64;
+
+        if self.quantity == 0 || self.quantity.signum() == fill.signum() {
+            self.average_cost =
+                (self.average_cost * held + price as f64 * quantity as f64) / (held + quantity as f64);
+        } else if fill.unsigned_abs() > self.quantity.unsigned_abs() {
+            self.average_cost = price as f64;
+        } else if fill.unsigned_abs() == self.quantity.unsigned_abs() {
+            self.average_cost = 0.0;
+        }
+        self.quantity += fill;
+    }
+}
+
+/// In-memory order state, keyed by client_order_id.
+///
+/// The store also hands out client_order_ids so they stay unique across
+/// every connection in the pool, and keeps each user's net position per
+/// symbol from the executions it sees, tracked orders or not.
+///
+/// Each state transition is logged as a structured event under
+/// `ORDER_EVENTS_TARGET`: `order_held` (stops), `order_submitted`, `order_acked`,
+/// `order_rejected`, `order_filled` (once per fill) and `order_cancelled`,
+/// all with the same fields (see `log_event`).
+#[derive(Debug, Default)]
+pub struct OrderStore {
+    orders: DashMap<u64, OrderState>,
+    next_id: AtomicU64,
+    /// Keyed by user and symbol
+    positions: DashMap<(u64, String), Position>,
+}
+
+impl OrderStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate a client_order_id for a new order
+    pub fn next_client_order_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Record an order that is about to be sent to the gateway
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_new(
+        &self,
+        client_order_id: u64,
+        user_id: u64,
+        symbol: String,
+        side: Side,
+        price: u64,
+        quantity: u64,
+        tag: String,
+        connection: Option<u64>,
+    ) {
+        let order = OrderState {
+            client_order_id,
+            exchange_order_id: 0,
+            user_id,
+            symbol,
+            side,
+            price,
+            original_quantity: quantity,
+            filled_quantity: 0,
+            leaves_quantity: quantity,
+            average_fill_price: 0.0,
+            status: OrderStatus::PendingNew,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+            tag,
+            connection,
+        };
+        log_event("order_submitted", &order, order.price, quantity);
+        self.orders.insert(client_order_id, order);
+    }
+
+    /// Record a stop order held until it triggers. `price` is the limit
+    /// price of a stop-limit, 0 for a stop. Once triggered, the order sent
+    /// to the gateway takes over the entry under the same id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_stop(
+        &self,
+        client_order_id: u64,
+        user_id: u64,
+        symbol: String,
+        side: Side,
+        price: u64,
+        quantity: u64,
+        tag: String,
+    ) {
+        let order = OrderState {
+            client_order_id,
+            exchange_order_id: 0,
+            user_id,
+            symbol,
+            side,
+            price,
+            original_quantity: quantity,
+            filled_quantity: 0,
+            leaves_quantity: quantity,
+            average_fill_price: 0.0,
+            status: OrderStatus::PendingTrigger,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+            tag,
+            connection: None,
+        };
+        log_event("order_held", &order, order.price, quantity);
+        self.orders.insert(client_order_id, order);
+    }
+
+    /// Mark a held stop cancelled before it triggered
+    pub fn cancel_stop(&self, client_order_id: u64) {
+        if let Some(mut order) = self.orders.get_mut(&client_order_id) {
+            if order.status != OrderStatus::PendingTrigger {
+                return;
+            }
+            let cancelled_quantity = order.leaves_quantity;
+            order.status = OrderStatus::Cancelled;
+            order.leaves_quantity = 0;
+            order.timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+            log_event("order_cancelled", &order, order.price, cancelled_quantity);
+        }
+    }
+
+    /// Record an order that never reached the gateway as rejected, e.g. a
+    /// triggered stop that couldn't be sent. `order` is its last known
+    /// state, as the entry may already be gone.
+    pub fn reject_unsent(&self, mut order: OrderState) {
+        order.status = OrderStatus::Rejected;
+        order.leaves_quantity = 0;
+        order.timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+        log_event("order_rejected", &order, order.price, order.original_quantity);
+        self.orders.insert(order.client_order_id, order);
+    }
+
+    /// Forget an order that never reached the gateway
+    pub fn remove(&self, client_order_id: u64) {
+        self.orders.remove(&client_order_id);
+    }
+
+    /// Look up an order. Orders belonging to another user are not visible.
+    pub fn get(&self, client_order_id: u64, user_id: u64) -> Option<OrderState> {
+        self.orders
+            .get(&client_order_id)
+            .filter(|order| order.user_id == user_id)
+            .map(|order| order.clone())
+    }
+
+    /// A user's orders that can still trade, optionally for one symbol,
+    /// oldest first
+    pub fn live_orders(&self, user_id: u64, symbol: Option<&str>) -> Vec<OrderState> {
+        self.live_orders_matching(|order| order.user_id == user_id, symbol)
+    }
+
+    /// Every user's orders that can still trade, optionally for one
+    /// symbol, oldest first
+    pub fn all_live_orders(&self, symbol: Option<&str>) -> Vec<OrderState> {
+        self.live_orders_matching(|_| true, symbol)
+    }
+
+    fn live_orders_matching<F>(&self, owned: F, symbol: Option<&str>) -> Vec<OrderState>
+    where
+        F: Fn(&OrderState) -> bool,
+    {
+        let mut orders: Vec<OrderState> = self
+            .orders
+            .iter()
+            .filter(|order| {
+                owned(order)
+                    && !order.status.is_terminal()
+                    && symbol.is_none_or(|symbol| order.symbol == symbol)
+            })
+            .map(|order| order.clone())
+            .collect();
+        orders.sort_by_key(|order| order.client_order_id);
+        orders
+    }
+
+    /// Current status of an order, if tracked
+    pub fn status(&self, client_order_id: u64) -> Option<OrderStatus> {
+        self.orders.get(&client_order_id).map(|order| order.status)
+    }
+
+    /// Tag an order was submitted with, if tracked
+    pub fn tag(&self, client_order_id: u64) -> Option<String> {
+        self.orders.get(&client_order_id).map(|order| order.tag.clone())
+    }
+
+    /// Id of the gateway connection an order was submitted on, if tracked
+    /// and sent to the gateway
+    pub fn connection(&self, client_order_id: u64) -> Option<u64> {
+        self.orders.get(&client_order_id).and_then(|order| order.connection)
+    }
+
+    pub fn on_ack(&self, msg: &OrderAckMessage) {
+        if let Some(mut order) = self.orders.get_mut(&msg.client_order_id) {
+            order.exchange_order_id = msg.exchange_order_id;
+            // An execution may have overtaken the ack
+            if order.status == OrderStatus::PendingNew {
+                order.status = OrderStatus::New;
+            }
+            order.timestamp = msg.timestamp;
+            log_event("order_acked", &order, order.price, order.original_quantity);
+        }
+    }
+
+    pub fn on_reject(&self, msg: &OrderRejectMessage) {
+        if let Some(mut order) = self.orders.get_mut(&msg.client_order_id) {
+            // Rejected replaces leave the original order working
+            if order.status == OrderStatus::PendingNew {
+                order.status = OrderStatus::Rejected;
+                order.leaves_quantity = 0;
+                log_event("order_rejected", &order, order.price, order.original_quantity);
+            }
+            order.timestamp = msg.timestamp;
+        }
+    }
+
+    pub fn on_cancelled(&self, msg: &OrderCancelledMessage) {
+        if let Some(mut order) = self.orders.get_mut(&msg.client_order_id) {
+            // A fill may have won the race with the cancel
+            if order.status.is_terminal() {
+                return;
+            }
+            let cancelled_quantity = order.leaves_quantity;
+            order.status = OrderStatus::Cancelled;
+            order.leaves_quantity = 0;
+            order.timestamp = msg.timestamp;
+            log_event("order_cancelled", &order, order.price, cancelled_quantity);
+        }
+    }
+
+    pub fn on_replaced(&self, msg: &OrderReplacedMessage) {
+        if let Some(mut order) = self.orders.get_mut(&msg.client_order_id) {
+            order.price = msg.new_price;
+            order.original_quantity = msg.new_quantity;
+            order.leaves_quantity = msg.new_quantity.saturating_sub(order.filled_quantity);
+            order.timestamp = msg.timestamp;
+        }
+    }
+
+    /// A user's open positions by symbol, sorted by symbol. Symbols the
+    /// user has traded back to flat are left out.
+    pub fn positions(&self, user_id: u64) -> Vec<(String, Position)> {
+        let mut positions: Vec<(String, Position)> = self
+            .positions
+            .iter()
+            .filter(|entry| entry.key().0 == user_id && entry.value().quantity != 0)
+            .map(|entry| (entry.key().1.clone(), *entry.value()))
+            .collect();
+        positions.sort_by(|a, b| a.0.cmp(&b.0));
+        positions
+    }
+
+    pub fn on_execution(&self, msg: &ExecutionMessage) {
+        self.positions
+            .entry((msg.user_id, msg.symbol.clone()))
+            .or_default()
+            .apply(msg.side, msg.fill_price, msg.fill_quantity);
+
+        let Some(mut order) = self.orders.get_mut(&msg.client_order_id) else {
+            debug!(
+                "Execution for untracked order {}, not recording",
+                msg.client_order_id
+            );
+            return;
+        };
+
+        let filled = order.filled_quantity + msg.fill_quantity;
+        if filled > 0 {
+            order.average_fill_price = (order.average_fill_price
+                * order.filled_quantity as f64
+                + msg.fill_price as f64 * msg.fill_quantity as f64)
+                / filled as f64;
+        }
+
+        order.filled_quantity = filled;
+        order.leaves_quantity = msg.leaves_quantity;
+        order.exchange_order_id = msg.exchange_order_id;
+        order.status = if msg.leaves_quantity == 0 {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        order.timestamp = msg.timestamp;
+        log_event("order_filled", &order, msg.fill_price, msg.fill_quantity);
+    }
+}
+
+/// Log an order lifecycle event with tracing fields rather than message
+/// text. `price` and `qty` are the event's own: the limit price and
+/// quantity for most events, the fill for `order_filled`, the quantity
+/// left to cancel for `order_cancelled`. Prices are in fixed-point units.
+fn log_event(event: &'static str, order: &OrderState, price: u64, qty: u64) {
+    info!(
+        target: ORDER_EVENTS_TARGET,
+        event,
+        client_order_id = order.client_order_id,
+        symbol = %order.symbol,
+        user_id = order.user_id,
+        side = ?order.side,
+        price,
+        qty,
+        status = ?order.status,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fills: &[(Side, u64, u64)]) -> Position {
+        let mut position = Position::default();
+        for &(side, price, quantity) in fills {
+            position.apply(side, price, quantity);
+        }
+        position
+    }
+
+    #[test]
+    fn adding_averages_the_cost() {
+        let long = position(&[(Side::Buy, 10_000, 100), (Side::Buy, 10_300, 200)]);
+        assert_eq!(long, Position { quantity: 300, average_cost: 10_200.0 });
+
+        let short = position(&[(Side::Sell, 10_000, 100), (Side::Sell, 9_700, 200)]);
+        assert_eq!(short, Position { quantity: -300, average_cost: 9_800.0 });
+    }
+
+    #[test]
+    fn reducing_keeps_the_cost() {
+        let long = position(&[(Side::Buy, 10_000, 300), (Side::Sell, 12_000, 100)]);
+        assert_eq!(long, Position { quantity: 200, average_cost: 10_000.0 });
+
+        let short = position(&[(Side::Sell, 10_000, 300), (Side::Buy, 8_000, 100)]);
+        assert_eq!(short, Position { quantity: -200, average_cost: 10_000.0 });
+    }
+
+    #[test]
+    fn closing_goes_flat() {
+        let flat = position(&[(Side::Buy, 10_000, 100), (Side::Buy, 10_200, 100), (Side::Sell, 11_000, 200)]);
+        assert_eq!(flat, Position::default());
+
+        // A flat position reopens at the next fill's price
+        let reopened = position(&[(Side::Sell, 10_000, 100), (Side::Buy, 9_000, 100), (Side::Buy, 9_500, 50)]);
+        assert_eq!(reopened, Position { quantity: 50, average_cost: 9_500.0 });
+    }
+
+    #[test]
+    fn fill_past_zero_flips_at_the_fill_price() {
+        let short = position(&[(Side::Buy, 10_000, 100), (Side::Sell, 10_500, 250)]);
+        assert_eq!(short, Position { quantity: -150, average_cost: 10_500.0 });
+
+        let long = position(&[(Side::Sell, 10_000, 100), (Side::Buy, 9_800, 130)]);
+        assert_eq!(long, Position { quantity: 30, average_cost: 9_800.0 });
+    }
+
+    fn execution(execution_id: u64, fill_price: u64, fill_quantity: u64, leaves_quantity: u64) -> ExecutionMessage {
+        ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: 42,
+            exchange_order_id: 99,
+            execution_id,
+            user_id: 7,
+            side: Side::Buy,
+            fill_price,
+            fill_quantity,
+            leaves_quantity,
+            timestamp: execution_id,
+        }
+    }
+
+    #[test]
+    fn partial_fills_accumulate() {
+        let store = OrderStore::new();
+        store.insert_new(42, 7, "AAPL".to_string(), Side::Buy, 10_100, 300, String::new(), None);
+
+        store.on_execution(&execution(1, 10_000, 100, 200));
+        let order = store.get(42, 7).unwrap();
+        assert_eq!((order.filled_quantity, order.leaves_quantity), (100, 200));
+        assert_eq!(order.average_fill_price, 10_000.0);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+
+        store.on_execution(&execution(2, 10_100, 100, 100));
+        let order = store.get(42, 7).unwrap();
+        assert_eq!((order.filled_quantity, order.leaves_quantity), (200, 100));
+        assert_eq!(order.average_fill_price, 10_050.0);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.exchange_order_id, 99);
+
+        store.on_execution(&execution(3, 10_100, 100, 0));
+        let order = store.get(42, 7).unwrap();
+        assert_eq!((order.filled_quantity, order.leaves_quantity), (300, 0));
+        assert!((order.average_fill_price - 30_200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(order.status, OrderStatus::Filled);
+    }
+    
+    #[test]
+    fn submit_and_ack_log_order_events() {
+        let logs = crate::logging::CapturedLogs::default();
+        let subscriber = logs.subscriber(crate::config::LogFormat::Json);
+        tracing::subscriber::with_default(subscriber, || {
+            let store = OrderStore::new();
+            store.insert_new(42, 7, "AAPL".to_string(), Side::Buy, 10_100, 300, String::new(), None);
+            store.on_ack(&OrderAckMessage {
+                client_order_id: 42,
+                exchange_order_id: 99,
+                user_id: 7,
+                timestamp: 1,
+            });
+        });
+        
+        let events: Vec<_> = logs
+            .json_lines()
+            .into_iter()
+            .filter(|line| line["target"] == ORDER_EVENTS_TARGET)
+            .map(|line| line["fields"].clone())
+            .collect();
+        assert_eq!(events.len(), 2, "{:?}", events);
+        assert_eq!(events[0]["event"], "order_submitted");
+        assert_eq!(events[0]["status"], "PendingNew");
+        assert_eq!(events[1]["event"], "order_acked");
+        assert_eq!(events[1]["status"], "New");
+        for event in &events {
+            assert_eq!(event["client_order_id"], 42);
+            assert_eq!(event["symbol"], "AAPL");
+            assert_eq!(event["user_id"], 7);
+            assert_eq!(event["side"], "Buy");
+            assert_eq!(event["price"], 10_100);
+            assert_eq!(event["qty"], 300);
+        }
+    }
+    
+    #[test]
+    fn cancel_after_a_fill_leaves_the_order_filled() {
+        let store = OrderStore::new();
+        store.insert_new(42, 7, "AAPL".to_string(), Side::Buy, 10_100, 100, String::new(), None);
+        store.on_execution(&execution(1, 10_000, 100, 0));
+        
+        store.on_cancelled(&OrderCancelledMessage {
+            client_order_id: 42,
+            exchange_order_id: 99,
+            user_id: 7,
+            timestamp: 2,
+        });
+        let order = store.get(42, 7).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!((order.filled_quantity, order.leaves_quantity), (100, 0));
+    }
+    
+    #[test]
+    fn reject_of_a_replace_leaves_the_order_working() {
+        let store = OrderStore::new();
+        store.insert_new(42, 7, "AAPL".to_string(), Side::Buy, 10_100, 100, String::new(), None);
+        store.on_execution(&execution(1, 10_000, 40, 60));
+        
+        store.on_reject(&OrderRejectMessage {
+            client_order_id: 42,
+            user_id: 7,
+            reason: 1,
+            text: "replace rejected".to_string(),
+            timestamp: 2,
+        });
+        let order = store.get(42, 7).unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.leaves_quantity, 60);
+    }
+}
@@ -0,0 +1,521 @@
+use super::backend::MatchingBackend;
+use super::client::{IncomingMessage, MatchingClient, OrderAckResult, OrderReplaceResult};
+use super::error::MatchingError;
+use super::order_store::OrderStore;
+use super::protocol::{
+    ExecutionMessage, OrderAckMessage, OrderCancelledMessage, OrderRejectMessage,
+    OrderReplacedMessage, OrderType, RejectCode, Side, TradeMessage,
+};
+use crate::config::PaperTradingConfig;
+use dashmap::mapref::one::RefMut;
+use dashmap::DashMap;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::Duration;
+use tracing::{debug, info};
+
+/// Basis points in a whole
+const BPS_PER_UNIT: u64 = 10_000;
+
+/// Who an order resting in a paper book belongs to
+#[derive(Debug, Clone, Copy)]
+struct Owner {
+    client_order_id: u64,
+    exchange_order_id: u64,
+    user_id: u64,
+}
+
+/// Quantity resting at a price. Synthetic liquidity has no owner.
+#[derive(Debug, Clone)]
+struct Resting {
+    owner: Option<Owner>,
+    quantity: u64,
+}
+
+/// An incoming order trading against a resting one
+#[derive(Debug, Clone, Copy)]
+struct Fill {
+    price: u64,
+    quantity: u64,
+    /// The resting order, when it isn't synthetic, and what it has left
+    maker: Option<(Owner, u64)>,
+    /// What the incoming order has left
+    taker_leaves: u64,
+}
+
+/// One symbol's simulated book, prices in fixed-point units
+#[derive(Debug)]
+struct PaperBook {
+    bids: BTreeMap<u64, VecDeque<Resting>>,
+    asks: BTreeMap<u64, VecDeque<Resting>>,
+    /// Price of the last fill, or the one the book was seeded around
+    last_price: u64,
+}
+
+impl PaperBook {
+    fn new(reference_price: u64) -> Self {
+        Self {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_price: reference_price,
+        }
+    }
+    
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<u64, VecDeque<Resting>> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+    
+    /// Seed synthetic levels on each side that has run dry, stepping away
+    /// from the last price
+    fn replenish(&mut self, config: &PaperTradingConfig) {
+        let step = (self.last_price.saturating_mul(config.level_spacing_bps) / BPS_PER_UNIT).max(1);
+        let last_price = self.last_price;
+        let synthetic = || {
+            VecDeque::from([Resting {
+                owner: None,
+                quantity: config.level_quantity,
+            }])
+        };
+        
+        if self.bids.is_empty() {
+            for level in 1..=config.levels as u64 {
+                let price = last_price.saturating_sub(step.saturating_mul(level));
+                if price == 0 {
+                    break;
+                }
+                self.bids.insert(price, synthetic());
+            }
+        }
+        if self.asks.is_empty() {
+            for level in 1..=config.levels as u64 {
+                let price = last_price.saturating_add(step.saturating_mul(level));
+                self.asks.insert(price, synthetic());
+            }
+        }
+    }
+    
+    /// Trade `quantity` of an incoming order against the other side, best
+    /// price first and oldest first within a price, going no further than
+    /// `limit` (none for a market order)
+    fn take(&mut self, side: Side, limit: Option<u64>, mut quantity: u64) -> Vec<Fill> {
+        let mut fills = Vec::new();
+        
+        while quantity > 0 {
+            let best = match side {
+                Side::Buy => self.asks.first_entry(),
+                Side::Sell => self.bids.last_entry(),
+            };
+            let Some(mut level) = best else {
+                break;
+            };
+            let price = *level.key();
+            let crosses = limit.is_none_or(|limit| match side {
+                Side::Buy => price <= limit,
+                Side::Sell => price >= limit,
+            });
+            if !crosses {
+                break;
+            }
+            
+            let queue = level.get_mut();
+            let Some(resting) = queue.front_mut() else {
+                level.remove();
+                continue;
+            };
+            let traded = resting.quantity.min(quantity);
+            resting.quantity -= traded;
+            quantity -= traded;
+            let maker = resting.owner.map(|owner| (owner, resting.quantity));
+            if resting.quantity == 0 {
+                queue.pop_front();
+            }
+            if queue.is_empty() {
+                level.remove();
+            }
+            
+            self.last_price = price;
+            fills.push(Fill {
+                price,
+                quantity: traded,
+                maker,
+                taker_leaves: quantity,
+            });
+        }
+        
+        fills
+    }
+    
+    /// Rest an order behind the others at its price
+    fn rest(&mut self, side: Side, price: u64, owner: Owner, quantity: u64) {
+        self.levels_mut(side).entry(price).or_default().push_back(Resting {
+            owner: Some(owner),
+            quantity,
+        });
+    }
+    
+    /// Take a user's resting order out of the book, returning its side,
+    /// owner and remaining quantity
+    fn remove(&mut self, client_order_id: u64, user_id: u64) -> Option<(Side, Owner, u64)> {
+        for side in [Side::Buy, Side::Sell] {
+            let levels = self.levels_mut(side);
+            let found = levels.iter().find_map(|(&price, queue)| {
+                queue
+                    .iter()
+                    .position(|resting| {
+                        resting.owner.is_some_and(|owner| {
+                            owner.client_order_id == client_order_id && owner.user_id == user_id
+                        })
+                    })
+                    .map(|index| (price, index))
+            });
+            let Some((price, index)) = found else {
+                continue;
+            };
+            
+            let queue = levels.get_mut(&price)?;
+            let resting = queue.remove(index)?;
+            if queue.is_empty() {
+                levels.remove(&price);
+            }
+            return Some((side, resting.owner?, resting.quantity));
+        }
+        None
+    }
+}
+
+/// Paper trading: orders are matched in memory and never reach the
+/// gateway.
+///
+/// Each symbol's book is seeded with synthetic liquidity around its last
+/// traded price (or, before any trade, the first limit order's price) and
+/// reseeded whenever a side runs dry. Limit orders that don't fully cross
+/// rest and can trade with later orders; a market order's unfilled
+/// remainder is cancelled. Acks, executions and trades go through the
+/// order store and the client's subscriptions just as the gateway's do.
+pub struct PaperMatchingBackend {
+    client: Arc<MatchingClient>,
+    orders: Arc<OrderStore>,
+    config: PaperTradingConfig,
+    books: DashMap<String, PaperBook>,
+    /// Source of exchange order, execution and trade ids
+    next_id: AtomicU64,
+    /// Always up: there's no connection to lose
+    liveness: watch::Sender<bool>,
+}
+
+impl PaperMatchingBackend {
+    pub fn new(client: Arc<MatchingClient>, orders: Arc<OrderStore>, config: &PaperTradingConfig) -> Self {
+        Self {
+            client,
+            orders,
+            config: config.clone(),
+            books: DashMap::new(),
+            next_id: AtomicU64::new(0),
+            liveness: watch::Sender::new(true),
+        }
+    }
+    
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+    
+    /// A symbol's book, seeded around its last traded price or else
+    /// `fallback_price`. `None` while neither is known.
+    fn book(&self, symbol: &str, fallback_price: Option<u64>) -> Option<RefMut<'_, String, PaperBook>> {
+        if let Some(book) = self.books.get_mut(symbol) {
+            return Some(book);
+        }
+        
+        let reference_price = self.client.last_trade_price(symbol).or(fallback_price)?;
+        info!("Seeding paper book for {} around {}", symbol, reference_price);
+        Some(
+            self.books
+                .entry(symbol.to_string())
+                .or_insert_with(|| PaperBook::new(reference_price)),
+        )
+    }
+    
+    /// Reject an order as the gateway would
+    fn reject(&self, client_order_id: u64, user_id: u64, reason: RejectCode, text: String) -> OrderRejectMessage {
+        let reject = OrderRejectMessage {
+            client_order_id,
+            user_id,
+            reason: reason as u8,
+            text,
+            timestamp: now(),
+        };
+        self.orders.on_reject(&reject);
+        reject
+    }
+    
+    /// Report each fill as an execution for the incoming order, another for
+    /// the resting order unless it was synthetic, and a public trade
+    fn report_fills(&self, symbol: &str, side: Side, taker: Owner, fills: &[Fill]) {
+        let maker_side = match side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        
+        for fill in fills {
+            self.execute(symbol, side, taker, fill, fill.taker_leaves);
+            if let Some((maker, leaves)) = fill.maker {
+                self.execute(symbol, maker_side, maker, fill, leaves);
+            }
+            self.client.publish(IncomingMessage::Trade(TradeMessage {
+                symbol: symbol.to_string(),
+                trade_id: self.next_id(),
+                price: fill.price,
+                quantity: fill.quantity,
+                timestamp: now(),
+            }));
+        }
+    }
+    
+    fn execute(&self, symbol: &str, side: Side, owner: Owner, fill: &Fill, leaves_quantity: u64) {
+        let execution = ExecutionMessage {
+            symbol: symbol.to_string(),
+            client_order_id: owner.client_order_id,
+            exchange_order_id: owner.exchange_order_id,
+            execution_id: self.next_id(),
+            user_id: owner.user_id,
+            side,
+            fill_price: fill.price,
+            fill_quantity: fill.quantity,
+            leaves_quantity,
+            timestamp: now(),
+        };
+        self.orders.on_execution(&execution);
+        self.client.publish(IncomingMessage::Execution(execution));
+    }
+    
+    fn cancelled(&self, owner: Owner) {
+        let cancelled = OrderCancelledMessage {
+            client_order_id: owner.client_order_id,
+            exchange_order_id: owner.exchange_order_id,
+            user_id: owner.user_id,
+            timestamp: now(),
+        };
+        self.orders.on_cancelled(&cancelled);
+        self.client.publish(IncomingMessage::OrderCancelled(cancelled));
+    }
+}
+
+#[tonic::async_trait]
+impl MatchingBackend for PaperMatchingBackend {
+    #[allow(clippy::too_many_arguments)]
+    async fn submit_order(
+        &self,
+        symbol: String,
+        user_id: u64,
+        side: Side,
+        order_type: OrderType,
+        price: u64,
+        quantity: u64,
+        tag: String,
+        client_order_id: Option<u64>,
+        _deadline: Option<Duration>,
+    ) -> Result<OrderAckResult, MatchingError> {
+        let client_order_id =
+            client_order_id.unwrap_or_else(|| self.orders.next_client_order_id());
+        self.orders
+            .insert_new(client_order_id, user_id, symbol.clone(), side, price, quantity, tag, None);
+        
+        let limit = (order_type == OrderType::Limit).then_some(price);
+        let Some(mut book) = self.book(&symbol, limit) else {
+            let text = format!("No reference price for {} yet; send a limit order first", symbol);
+            return Ok(Err(self.reject(client_order_id, user_id, RejectCode::MarketClosed, text)));
+        };
+        book.replenish(&self.config);
+        
+        let ack = OrderAckMessage {
+            client_order_id,
+            exchange_order_id: self.next_id(),
+            user_id,
+            timestamp: now(),
+        };
+        self.orders.on_ack(&ack);
+        let owner = Owner {
+            client_order_id,
+            exchange_order_id: ack.exchange_order_id,
+            user_id,
+        };
+        
+        let fills = book.take(side, limit, quantity);
+        let leaves = fills.last().map_or(quantity, |fill| fill.taker_leaves);
+        if leaves > 0 && limit.is_some() {
+            book.rest(side, price, owner, leaves);
+        }
+        drop(book);
+        
+        self.report_fills(&symbol, side, owner, &fills);
+        if leaves > 0 && limit.is_none() {
+            self.cancelled(owner);
+        }
+        
+        Ok(Ok(ack))
+    }
+    
+    async fn cancel_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        _deadline: Option<Duration>,
+    ) -> Result<(), MatchingError> {
+        let removed = self
+            .books
+            .get_mut(&symbol)
+            .and_then(|mut book| book.remove(client_order_id, user_id));
+        
+        // Like the gateway, a cancel for an order that isn't resting is a no-op
+        match removed {
+            Some((_, owner, _)) => self.cancelled(owner),
+            None => debug!("Order {} is not resting in the paper book", client_order_id),
+        }
+        Ok(())
+    }
+    
+    async fn replace_order(
+        &self,
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+        _deadline: Option<Duration>,
+    ) -> Result<OrderReplaceResult, MatchingError> {
+        let filled = self
+            .orders
+            .get(client_order_id, user_id)
+            .map_or(0, |order| order.filled_quantity);
+        if new_quantity <= filled {
+            let text = format!("New quantity must exceed the {} already filled", filled);
+            return Ok(Err(self.reject(client_order_id, user_id, RejectCode::InvalidQuantity, text)));
+        }
+        
+        let mut book = self.books.get_mut(&symbol);
+        let removed = book.as_mut().and_then(|book| book.remove(client_order_id, user_id));
+        let (Some(mut book), Some((side, owner, _))) = (book, removed) else {
+            let text = format!("Order {} is not resting", client_order_id);
+            return Ok(Err(self.reject(client_order_id, user_id, RejectCode::UnknownOrder, text)));
+        };
+        
+        // The replaced order loses its time priority and may now cross
+        book.replenish(&self.config);
+        let leaves = new_quantity - filled;
+        let fills = book.take(side, Some(new_price), leaves);
+        let remaining = fills.last().map_or(leaves, |fill| fill.taker_leaves);
+        if remaining > 0 {
+            book.rest(side, new_price, owner, remaining);
+        }
+        drop(book);
+        
+        let replaced = OrderReplacedMessage {
+            client_order_id,
+            exchange_order_id: owner.exchange_order_id,
+            new_price,
+            new_quantity,
+            timestamp: now(),
+        };
+        self.orders.on_replaced(&replaced);
+        self.report_fills(&symbol, side, owner, &fills);
+        
+        Ok(Ok(replaced))
+    }
+    
+    fn watch_liveness(&self) -> watch::Receiver<bool> {
+        self.liveness.subscribe()
+    }
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::matching::client::ConnectionOptions;
+    use crate::matching::trade_history::TradeHistory;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+    
+    /// A paper backend whose client has no gateway to talk to
+    async fn paper_backend() -> (Arc<MatchingClient>, PaperMatchingBackend) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        
+        let config = Config::default();
+        let orders = Arc::new(OrderStore::new());
+        let client = Arc::new(
+            MatchingClient::new(
+                address,
+                1,
+                1,
+                ConnectionOptions::from(&config.matching_engine),
+                Arc::clone(&orders),
+                Arc::new(TradeHistory::new(&config.market_data)),
+                true,
+            )
+            .await
+            .unwrap(),
+        );
+        let backend =
+            PaperMatchingBackend::new(Arc::clone(&client), orders, &PaperTradingConfig::default());
+        (client, backend)
+    }
+    
+    async fn buy(backend: &PaperMatchingBackend, price: u64, quantity: u64) -> OrderAckMessage {
+        backend
+            .submit_order(
+                "AAPL".to_string(),
+                7,
+                Side::Buy,
+                OrderType::Limit,
+                price,
+                quantity,
+                String::new(),
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap()
+    }
+    
+    #[tokio::test]
+    async fn marketable_order_fills_at_the_book_price() {
+        let (client, backend) = paper_backend().await;
+        let mut executions = client.subscribe_executions(Some("AAPL".to_string()), Some(7));
+        
+        // Seeds the book around 10_000: synthetic asks from 10_010 up, 10 apart
+        let resting = buy(&backend, 10_000, 50).await;
+        
+        // Crosses the best ask but not the next one
+        let ack = buy(&backend, 10_015, 60).await;
+        assert_ne!(ack.client_order_id, resting.client_order_id);
+        
+        let execution = timeout(Duration::from_secs(5), executions.recv()).await.unwrap().unwrap();
+        assert_eq!(execution.client_order_id, ack.client_order_id);
+        assert_eq!(execution.side, Side::Buy);
+        assert_eq!((execution.fill_price, execution.fill_quantity), (10_010, 60));
+        assert_eq!(execution.leaves_quantity, 0);
+        
+        let order = backend.orders.get(ack.client_order_id, 7).unwrap();
+        assert_eq!(order.status, shared::OrderStatus::Filled);
+        assert_eq!(order.average_fill_price, 10_010.0);
+        assert_eq!(client.last_trade_price("AAPL"), Some(10_010));
+        // The order that didn't cross is still resting
+        let status = backend.orders.status(resting.client_order_id);
+        assert_eq!(status, Some(shared::OrderStatus::New));
+        
+        client.shutdown().await;
+    }
+}
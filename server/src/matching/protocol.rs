@@ -1,9 +1,32 @@
 use bytes::{Buf, BufMut, BytesMut};
+use std::collections::BTreeMap;
 use std::io;
 
 /// Protocol version
 pub const PROTOCOL_VERSION: u8 = 1;
 
+/// Header flag (in `reserved`): the frame ends with a CRC32 trailer over
+/// everything before it, counted in the header length. A client asks for
+/// checksums by sending a checksummed Logon; the gateway agrees by
+/// checksumming its Logon reply and every frame after it.
+pub const FLAG_CRC32: u16 = 0x0001;
+
+/// Default for the largest frame accepted from the gateway
+/// (`matching_engine.max_message_size`), which bounds a full-depth book
+/// snapshot (65535 levels a side)
+pub const MAX_FRAME_LEN: u32 = 4 * 1024 * 1024;
+
+/// Smallest `max_message_size` allowed; every fixed-size message, the
+/// logon reply included, fits in it
+pub const MIN_MAX_FRAME_LEN: u32 = 1024;
+
+/// Size of the CRC32 trailer
+pub const CRC32_TRAILER_LEN: usize = 4;
+
+/// Versions accepted from the gateway. During an upgrade this can list both
+/// the old and new version while messages are still sent as `PROTOCOL_VERSION`.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[u8] = &[PROTOCOL_VERSION];
+
 /// Message types matching the C++ protocol
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -12,6 +35,8 @@ pub enum MessageType {
     NewOrder = 0x01,
     CancelOrder = 0x02,
     ReplaceOrder = 0x03,
+    BookSnapshotRequest = 0x04,
+    ResendRequest = 0x05,
     
     // Engine → Client
     OrderAck = 0x10,
@@ -25,6 +50,8 @@ pub enum MessageType {
     // Market Data
     Trade = 0x30,
     Quote = 0x31,
+    BookSnapshot = 0x32,
+    BookDelta = 0x33,
     
     // System
     Heartbeat = 0xF0,
@@ -40,6 +67,8 @@ impl TryFrom<u8> for MessageType {
             0x01 => Ok(MessageType::NewOrder),
             0x02 => Ok(MessageType::CancelOrder),
             0x03 => Ok(MessageType::ReplaceOrder),
+            0x04 => Ok(MessageType::BookSnapshotRequest),
+            0x05 => Ok(MessageType::ResendRequest),
             0x10 => Ok(MessageType::OrderAck),
             0x11 => Ok(MessageType::OrderReject),
             0x12 => Ok(MessageType::OrderCancelled),
@@ -47,6 +76,8 @@ impl TryFrom<u8> for MessageType {
             0x20 => Ok(MessageType::Execution),
             0x30 => Ok(MessageType::Trade),
             0x31 => Ok(MessageType::Quote),
+            0x32 => Ok(MessageType::BookSnapshot),
+            0x33 => Ok(MessageType::BookDelta),
             0xF0 => Ok(MessageType::Heartbeat),
             0xF1 => Ok(MessageType::Logon),
             0xF2 => Ok(MessageType::Logout),
@@ -60,18 +91,122 @@ impl TryFrom<u8> for MessageType {
 
 /// Order side
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Side {
     Buy = 0x01,
     Sell = 0x02,
 }
 
+impl TryFrom<u8> for Side {
+    type Error = io::Error;
+    
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(Side::Buy),
+            0x02 => Ok(Side::Sell),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown side: 0x{:02x}", value),
+            )),
+        }
+    }
+}
+
 /// Order type
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrderType {
     Limit = 0x01,
     Market = 0x02,
+    /// Market order once the market trades through the stop price
+    Stop = 0x03,
+    /// Limit order once the market trades through the stop price
+    StopLimit = 0x04,
+}
+
+impl TryFrom<u8> for OrderType {
+    type Error = io::Error;
+    
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(OrderType::Limit),
+            0x02 => Ok(OrderType::Market),
+            0x03 => Ok(OrderType::Stop),
+            0x04 => Ok(OrderType::StopLimit),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown order type: 0x{:02x}", value),
+            )),
+        }
+    }
+}
+
+/// How a book delta changes a price level
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookAction {
+    Add = 0x01,
+    Change = 0x02,
+    Delete = 0x03,
+}
+
+impl TryFrom<u8> for BookAction {
+    type Error = io::Error;
+    
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(BookAction::Add),
+            0x02 => Ok(BookAction::Change),
+            0x03 => Ok(BookAction::Delete),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown book action: 0x{:02x}", value),
+            )),
+        }
+    }
+}
+
+/// Order reject reason codes
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectCode {
+    UnknownSymbol = 0x01,
+    InvalidPrice = 0x02,
+    InvalidQuantity = 0x03,
+    DuplicateOrderId = 0x04,
+    UnknownOrder = 0x05,
+    InsufficientFunds = 0x06,
+    MarketClosed = 0x07,
+    SystemError = 0x08,
+    BelowMinimumSize = 0x09,
+    PriceOutOfBand = 0x0A,
+    SizeTooLarge = 0x0B,
+    InsufficientPermissions = 0x0C,
+}
+
+impl TryFrom<u8> for RejectCode {
+    type Error = io::Error;
+    
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(RejectCode::UnknownSymbol),
+            0x02 => Ok(RejectCode::InvalidPrice),
+            0x03 => Ok(RejectCode::InvalidQuantity),
+            0x04 => Ok(RejectCode::DuplicateOrderId),
+            0x05 => Ok(RejectCode::UnknownOrder),
+            0x06 => Ok(RejectCode::InsufficientFunds),
+            0x07 => Ok(RejectCode::MarketClosed),
+            0x08 => Ok(RejectCode::SystemError),
+            0x09 => Ok(RejectCode::BelowMinimumSize),
+            0x0A => Ok(RejectCode::PriceOutOfBand),
+            0x0B => Ok(RejectCode::SizeTooLarge),
+            0x0C => Ok(RejectCode::InsufficientPermissions),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown reject code: 0x{:02x}", value),
+            )),
+        }
+    }
 }
 
 /// Message header (16 bytes)
@@ -103,20 +238,87 @@ impl MessageHeader {
         buf.put_u64(self.sequence);
     }
     
-    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
-        if buf.len() < 16 {
+    /// Overwrite the sequence number of an already encoded message. Must
+    /// come before `append_checksum`.
+    pub fn set_sequence(buf: &mut BytesMut, sequence: u64) {
+        buf[8..16].copy_from_slice(&sequence.to_be_bytes());
+    }
+    
+    /// Whether the frame carries a CRC32 trailer
+    pub fn has_checksum(&self) -> bool {
+        self.reserved & FLAG_CRC32 != 0
+    }
+    
+    /// Append a CRC32 trailer to an encoded message, flagging it in the
+    /// header and counting it in the length
+    pub fn append_checksum(buf: &mut BytesMut) {
+        let flags = u16::from_be_bytes([buf[2], buf[3]]) | FLAG_CRC32;
+        buf[2..4].copy_from_slice(&flags.to_be_bytes());
+        let length = (buf.len() + CRC32_TRAILER_LEN) as u32;
+        buf[4..8].copy_from_slice(&length.to_be_bytes());
+        
+        let checksum = crc32(buf);
+        buf.put_u32(checksum);
+    }
+    
+    /// Verify and strip the CRC32 trailer of a complete frame (header
+    /// included). Frames without `FLAG_CRC32` are left as they are.
+    pub fn verify_checksum(&self, frame: &mut BytesMut) -> io::Result<()> {
+        if !self.has_checksum() {
+            return Ok(());
+        }
+        
+        if frame.len() < 16 + CRC32_TRAILER_LEN {
             return Err(io::Error::new(
                 io::ErrorKind::UnexpectedEof,
-                "Not enough data for header",
+                "Not enough data for checksum",
+            ));
+        }
+        
+        let end = frame.len() - CRC32_TRAILER_LEN;
+        let expected = u32::from_be_bytes([frame[end], frame[end + 1], frame[end + 2], frame[end + 3]]);
+        let actual = crc32(&frame[..end]);
+        if expected != actual {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Checksum mismatch on {:?} frame: expected {:08x}, computed {:08x}",
+                    self.msg_type, expected, actual
+                ),
             ));
         }
         
+        frame.truncate(end);
+        Ok(())
+    }
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, 16, "header")?;
+        
         let version = buf.get_u8();
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported protocol version {} (supported: {:?})",
+                    version, SUPPORTED_PROTOCOL_VERSIONS
+                ),
+            ));
+        }
+        
         let msg_type = MessageType::try_from(buf.get_u8())?;
         let reserved = buf.get_u16();
         let length = buf.get_u32();
         let sequence = buf.get_u64();
         
+        // The upper bound is the receiver's to enforce (max_message_size)
+        if length < 16 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid frame length {}", length),
+            ));
+        }
+        
         Ok(Self {
             version,
             msg_type,
@@ -127,6 +329,92 @@ impl MessageHeader {
     }
 }
 
+/// CRC-32 (IEEE 802.3, as used by zlib) lookup table
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE 802.3) of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Longest symbol the 16-byte, null-terminated symbol field can carry
+pub const MAX_SYMBOL_LEN: usize = 15;
+
+/// Check that a symbol fits the wire format: at most 15 bytes of printable
+/// ASCII. Longer symbols would otherwise be truncated into a different one.
+pub fn validate_symbol(symbol: &str) -> io::Result<()> {
+    if symbol.len() > MAX_SYMBOL_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Symbol {:?} is longer than {} bytes", symbol, MAX_SYMBOL_LEN),
+        ));
+    }
+    
+    if !symbol.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Symbol {:?} must be printable ASCII", symbol),
+        ));
+    }
+    
+    Ok(())
+}
+
+/// Write a symbol as 16 null-padded bytes. Constructors validate symbols,
+/// so the length cap only matters for fields changed afterwards.
+fn encode_symbol(buf: &mut BytesMut, symbol: &str) {
+    let mut symbol_bytes = [0u8; 16];
+    let symbol_len = symbol.len().min(MAX_SYMBOL_LEN);
+    symbol_bytes[..symbol_len].copy_from_slice(&symbol.as_bytes()[..symbol_len]);
+    buf.put_slice(&symbol_bytes);
+}
+
+/// Fail with `UnexpectedEof` unless `buf` holds at least `len` bytes.
+/// Decoders check their whole body up front, so a frame whose length field
+/// understates it is rejected instead of panicking mid-read.
+fn ensure_len(buf: &BytesMut, len: usize, what: &str) -> io::Result<()> {
+    if buf.len() < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Not enough data for {}: have {} bytes, need {}", what, buf.len(), len),
+        ));
+    }
+    
+    Ok(())
+}
+
+/// Read a 16-byte null-padded symbol, rejecting one that isn't valid UTF-8
+fn decode_symbol(buf: &mut BytesMut) -> io::Result<String> {
+    let mut symbol_bytes = [0u8; 16];
+    buf.copy_to_slice(&mut symbol_bytes);
+    let symbol_len = symbol_bytes.iter().position(|&b| b == 0).unwrap_or(symbol_bytes.len());
+    
+    std::str::from_utf8(&symbol_bytes[..symbol_len])
+        .map(str::to_string)
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Symbol is not valid UTF-8: {}", e),
+            )
+        })
+}
+
 /// New Order Message
 #[derive(Debug, Clone)]
 pub struct NewOrderMessage {
@@ -136,7 +424,7 @@ pub struct NewOrderMessage {
     pub user_id: u64,
     pub side: Side,
     pub order_type: OrderType,
-    pub price: u64,      // Price in cents (fixed-point)
+    pub price: u64,      // Price in fixed-point units
     pub quantity: u64,
     pub timestamp: u64,
 }
@@ -150,9 +438,11 @@ impl NewOrderMessage {
         order_type: OrderType,
         price: u64,
         quantity: u64,
-    ) -> Self {
-        Self {
-            header: MessageHeader::new(MessageType::NewOrder, 88), // Fixed size
+    ) -> io::Result<Self> {
+        validate_symbol(&symbol)?;
+        
+        Ok(Self {
+            header: MessageHeader::new(MessageType::NewOrder, 76), // Fixed size
             symbol,
             client_order_id,
             user_id,
@@ -161,20 +451,17 @@ impl NewOrderMessage {
             price,
             quantity,
             timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
-        }
+        })
     }
     
     pub fn encode(&self) -> BytesMut {
-        let mut buf = BytesMut::with_capacity(88);
+        let mut buf = BytesMut::with_capacity(76);
         
         // Header
         self.header.encode(&mut buf);
         
         // Symbol (16 bytes, null-padded)
-        let mut symbol_bytes = [0u8; 16];
-        let symbol_len = self.symbol.len().min(15);
-        symbol_bytes[..symbol_len].copy_from_slice(&self.symbol.as_bytes()[..symbol_len]);
-        buf.put_slice(&symbol_bytes);
+        encode_symbol(&mut buf, &self.symbol);
         
         // Fields
         buf.put_u64(self.client_order_id);
@@ -188,6 +475,33 @@ impl NewOrderMessage {
         
         buf
     }
+    
+    /// Decode the body of a sent order, e.g. from a capture file
+    pub fn decode(header: MessageHeader, buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, 60, "NewOrder")?;
+        
+        let symbol = decode_symbol(buf)?;
+        let client_order_id = buf.get_u64();
+        let user_id = buf.get_u64();
+        let side = Side::try_from(buf.get_u8())?;
+        let order_type = OrderType::try_from(buf.get_u8())?;
+        buf.advance(2); // reserved
+        let price = buf.get_u64();
+        let quantity = buf.get_u64();
+        let timestamp = buf.get_u64();
+        
+        Ok(Self {
+            header,
+            symbol,
+            client_order_id,
+            user_id,
+            side,
+            order_type,
+            price,
+            quantity,
+            timestamp,
+        })
+    }
 }
 
 /// Cancel Order Message
@@ -201,14 +515,16 @@ pub struct CancelOrderMessage {
 }
 
 impl CancelOrderMessage {
-    pub fn new(symbol: String, client_order_id: u64, user_id: u64) -> Self {
-        Self {
+    pub fn new(symbol: String, client_order_id: u64, user_id: u64) -> io::Result<Self> {
+        validate_symbol(&symbol)?;
+        
+        Ok(Self {
             header: MessageHeader::new(MessageType::CancelOrder, 56), // Fixed size
             symbol,
             client_order_id,
             user_id,
             timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
-        }
+        })
     }
     
     pub fn encode(&self) -> BytesMut {
@@ -218,10 +534,7 @@ impl CancelOrderMessage {
         self.header.encode(&mut buf);
         
         // Symbol (16 bytes, null-padded)
-        let mut symbol_bytes = [0u8; 16];
-        let symbol_len = self.symbol.len().min(15);
-        symbol_bytes[..symbol_len].copy_from_slice(&self.symbol.as_bytes()[..symbol_len]);
-        buf.put_slice(&symbol_bytes);
+        encode_symbol(&mut buf, &self.symbol);
         
         // Fields
         buf.put_u64(self.client_order_id);
@@ -230,123 +543,556 @@ impl CancelOrderMessage {
         
         buf
     }
+    
+    /// Decode the body of a sent cancel, e.g. from a capture file
+    pub fn decode(header: MessageHeader, buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, 40, "CancelOrder")?;
+        
+        Ok(Self {
+            header,
+            symbol: decode_symbol(buf)?,
+            client_order_id: buf.get_u64(),
+            user_id: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
 }
 
-/// Order Acknowledgement
+/// Replace Order Message
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct OrderAckMessage {
+pub struct ReplaceOrderMessage {
+    pub header: MessageHeader,
+    pub symbol: String,
     pub client_order_id: u64,
-    pub exchange_order_id: u64,
     pub user_id: u64,
+    pub new_price: u64,      // Price in fixed-point units
+    pub new_quantity: u64,
     pub timestamp: u64,
 }
 
-impl OrderAckMessage {
-    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
-        if buf.len() < 32 {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "Not enough data for OrderAck",
-            ));
-        }
+impl ReplaceOrderMessage {
+    pub fn new(
+        symbol: String,
+        client_order_id: u64,
+        user_id: u64,
+        new_price: u64,
+        new_quantity: u64,
+    ) -> io::Result<Self> {
+        validate_symbol(&symbol)?;
+        
+        Ok(Self {
+            header: MessageHeader::new(MessageType::ReplaceOrder, 72), // Fixed size
+            symbol,
+            client_order_id,
+            user_id,
+            new_price,
+            new_quantity,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        })
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(72);
+        
+        // Header
+        self.header.encode(&mut buf);
+        
+        // Symbol (16 bytes, null-padded)
+        encode_symbol(&mut buf, &self.symbol);
+        
+        // Fields
+        buf.put_u64(self.client_order_id);
+        buf.put_u64(self.user_id);
+        buf.put_u64(self.new_price);
+        buf.put_u64(self.new_quantity);
+        buf.put_u64(self.timestamp);
+        
+        buf
+    }
+    
+    /// Decode the body of a sent replace, e.g. from a capture file
+    pub fn decode(header: MessageHeader, buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, 56, "ReplaceOrder")?;
         
         Ok(Self {
+            header,
+            symbol: decode_symbol(buf)?,
             client_order_id: buf.get_u64(),
-            exchange_order_id: buf.get_u64(),
             user_id: buf.get_u64(),
+            new_price: buf.get_u64(),
+            new_quantity: buf.get_u64(),
             timestamp: buf.get_u64(),
         })
     }
 }
 
-/// Order Reject
+/// Heartbeat Message
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct OrderRejectMessage {
-    pub client_order_id: u64,
-    pub user_id: u64,
-    pub reason: u8,
-    pub text: String,
+pub struct HeartbeatMessage {
+    pub header: MessageHeader,
     pub timestamp: u64,
 }
 
-impl OrderRejectMessage {
-    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
-        if buf.len() < 88 {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "Not enough data for OrderReject",
-            ));
+impl HeartbeatMessage {
+    pub fn new() -> Self {
+        Self {
+            header: MessageHeader::new(MessageType::Heartbeat, 24), // Fixed size
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
         }
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(24);
         
-        let client_order_id = buf.get_u64();
-        let user_id = buf.get_u64();
-        let reason = buf.get_u8();
-        
-        // Skip reserved bytes
-        buf.advance(7);
-        
-        // Read text (64 bytes, null-terminated)
-        let mut text_bytes = [0u8; 64];
-        buf.copy_to_slice(&mut text_bytes);
-        let text = String::from_utf8_lossy(&text_bytes)
-            .trim_end_matches('\0')
-            .to_string();
+        // Header
+        self.header.encode(&mut buf);
         
-        let timestamp = buf.get_u64();
+        // Fields
+        buf.put_u64(self.timestamp);
         
-        Ok(Self {
-            client_order_id,
-            user_id,
-            reason,
-            text,
-            timestamp,
-        })
+        buf
     }
 }
 
-/// Execution Report
+impl Default for HeartbeatMessage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Logon Message
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct ExecutionMessage {
-    pub symbol: String,
-    pub client_order_id: u64,
-    pub exchange_order_id: u64,
-    pub execution_id: u64,
-    pub user_id: u64,
-    pub side: Side,
-    pub fill_price: u64,
-    pub fill_quantity: u64,
-    pub leaves_quantity: u64,
+pub struct LogonMessage {
+    pub header: MessageHeader,
+    pub session_id: String,
+    pub heartbeat_interval_ms: u32,
     pub timestamp: u64,
 }
 
-impl ExecutionMessage {
-    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
-        if buf.len() < 88 {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "Not enough data for Execution",
-            ));
+impl LogonMessage {
+    pub fn new(session_id: String, heartbeat_interval_ms: u32) -> Self {
+        Self {
+            header: MessageHeader::new(MessageType::Logon, 48), // Fixed size
+            session_id,
+            heartbeat_interval_ms,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
         }
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(48);
         
-        // Symbol (16 bytes)
-        let mut symbol_bytes = [0u8; 16];
-        buf.copy_to_slice(&mut symbol_bytes);
-        let symbol = String::from_utf8_lossy(&symbol_bytes)
+        // Header
+        self.header.encode(&mut buf);
+        
+        // Session ID (16 bytes, null-padded)
+        let mut session_bytes = [0u8; 16];
+        let session_len = self.session_id.len().min(15);
+        session_bytes[..session_len].copy_from_slice(&self.session_id.as_bytes()[..session_len]);
+        buf.put_slice(&session_bytes);
+        
+        // Fields
+        buf.put_u32(self.heartbeat_interval_ms);
+        buf.put_u32(0); // reserved
+        buf.put_u64(self.timestamp);
+        
+        buf
+    }
+}
+
+/// Logout Message
+#[derive(Debug, Clone)]
+pub struct LogoutMessage {
+    pub header: MessageHeader,
+    pub session_id: String,
+    pub timestamp: u64,
+}
+
+impl LogoutMessage {
+    pub fn new(session_id: String) -> Self {
+        Self {
+            header: MessageHeader::new(MessageType::Logout, 40), // Fixed size
+            session_id,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        }
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(40);
+        
+        // Header
+        self.header.encode(&mut buf);
+        
+        // Session ID (16 bytes, null-padded)
+        let mut session_bytes = [0u8; 16];
+        let session_len = self.session_id.len().min(15);
+        session_bytes[..session_len].copy_from_slice(&self.session_id.as_bytes()[..session_len]);
+        buf.put_slice(&session_bytes);
+        
+        // Fields
+        buf.put_u64(self.timestamp);
+        
+        buf
+    }
+}
+
+/// Order Book Snapshot Request
+#[derive(Debug, Clone)]
+pub struct OrderBookRequestMessage {
+    pub header: MessageHeader,
+    pub symbol: String,
+    pub request_id: u64,
+    pub depth: u32,
+    pub timestamp: u64,
+}
+
+impl OrderBookRequestMessage {
+    pub fn new(symbol: String, request_id: u64, depth: u32) -> io::Result<Self> {
+        validate_symbol(&symbol)?;
+        
+        Ok(Self {
+            header: MessageHeader::new(MessageType::BookSnapshotRequest, 56), // Fixed size
+            symbol,
+            request_id,
+            depth,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        })
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(56);
+        
+        // Header
+        self.header.encode(&mut buf);
+        
+        // Symbol (16 bytes, null-padded)
+        encode_symbol(&mut buf, &self.symbol);
+        
+        // Fields
+        buf.put_u64(self.request_id);
+        buf.put_u32(self.depth);
+        buf.put_u32(0); // reserved
+        buf.put_u64(self.timestamp);
+        
+        buf
+    }
+}
+
+/// Resend Request: a client missed a symbol's book deltas from
+/// `from_sequence` up to (not including) `to_sequence`. Deltas alone can't
+/// repair the book once one is lost, so the gateway answers with a full
+/// BookSnapshot carrying `request_id`, as for a BookSnapshotRequest.
+#[derive(Debug, Clone)]
+pub struct ResendRequestMessage {
+    pub header: MessageHeader,
+    pub symbol: String,
+    pub request_id: u64,
+    pub from_sequence: u32,
+    pub to_sequence: u32,
+    pub timestamp: u64,
+}
+
+impl ResendRequestMessage {
+    pub fn new(symbol: String, request_id: u64, from_sequence: u32, to_sequence: u32) -> io::Result<Self> {
+        validate_symbol(&symbol)?;
+        
+        Ok(Self {
+            header: MessageHeader::new(MessageType::ResendRequest, 56), // Fixed size
+            symbol,
+            request_id,
+            from_sequence,
+            to_sequence,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        })
+    }
+    
+    pub fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(56);
+        
+        // Header
+        self.header.encode(&mut buf);
+        
+        // Symbol (16 bytes, null-padded)
+        encode_symbol(&mut buf, &self.symbol);
+        
+        // Fields
+        buf.put_u64(self.request_id);
+        buf.put_u32(self.from_sequence);
+        buf.put_u32(self.to_sequence);
+        buf.put_u64(self.timestamp);
+        
+        buf
+    }
+}
+
+/// Order Acknowledgement
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct OrderAckMessage {
+    pub client_order_id: u64,
+    pub exchange_order_id: u64,
+    pub user_id: u64,
+    pub timestamp: u64,
+}
+
+impl OrderAckMessage {
+    /// Body size on the wire
+    const SIZE: usize = 32;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "OrderAck")?;
+        
+        Ok(Self {
+            client_order_id: buf.get_u64(),
+            exchange_order_id: buf.get_u64(),
+            user_id: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+/// Order Reject
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct OrderRejectMessage {
+    pub client_order_id: u64,
+    pub user_id: u64,
+    pub reason: u8,
+    pub text: String,
+    pub timestamp: u64,
+}
+
+impl OrderRejectMessage {
+    /// Body size on the wire
+    const SIZE: usize = 96;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "OrderReject")?;
+        
+        let client_order_id = buf.get_u64();
+        let user_id = buf.get_u64();
+        let reason = buf.get_u8();
+        
+        // Skip reserved bytes
+        buf.advance(7);
+        
+        // Read text (64 bytes, null-terminated)
+        let mut text_bytes = [0u8; 64];
+        buf.copy_to_slice(&mut text_bytes);
+        let text = String::from_utf8_lossy(&text_bytes)
             .trim_end_matches('\0')
             .to_string();
         
+        let timestamp = buf.get_u64();
+        
+        Ok(Self {
+            client_order_id,
+            user_id,
+            reason,
+            text,
+            timestamp,
+        })
+    }
+}
+
+/// Order Cancelled confirmation
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct OrderCancelledMessage {
+    pub client_order_id: u64,
+    pub exchange_order_id: u64,
+    pub user_id: u64,
+    pub timestamp: u64,
+}
+
+impl OrderCancelledMessage {
+    /// Body size on the wire
+    const SIZE: usize = 32;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "OrderCancelled")?;
+        
+        Ok(Self {
+            client_order_id: buf.get_u64(),
+            exchange_order_id: buf.get_u64(),
+            user_id: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+/// Order Replaced Acknowledgement
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct OrderReplacedMessage {
+    pub client_order_id: u64,
+    pub exchange_order_id: u64,
+    pub new_price: u64,
+    pub new_quantity: u64,
+    pub timestamp: u64,
+}
+
+impl OrderReplacedMessage {
+    /// Body size on the wire
+    const SIZE: usize = 40;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "OrderReplaced")?;
+        
+        Ok(Self {
+            client_order_id: buf.get_u64(),
+            exchange_order_id: buf.get_u64(),
+            new_price: buf.get_u64(),
+            new_quantity: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+/// Logon Response (gateway → client, sent with MessageType::Logon)
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct LogonResponseMessage {
+    pub session_id: String,
+    pub accepted: bool,
+    pub reason: u8,
+    pub text: String,
+    pub timestamp: u64,
+}
+
+impl LogonResponseMessage {
+    /// Body size on the wire
+    const SIZE: usize = 96;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "Logon response")?;
+        
+        // Read session ID (16 bytes, null-terminated)
+        let mut session_bytes = [0u8; 16];
+        buf.copy_to_slice(&mut session_bytes);
+        let session_id = String::from_utf8_lossy(&session_bytes)
+            .trim_end_matches('\0')
+            .to_string();
+        
+        // Status: 0 = accepted, anything else is a reject reason
+        let reason = buf.get_u8();
+        
+        // Skip reserved bytes
+        buf.advance(7);
+        
+        // Read text (64 bytes, null-terminated)
+        let mut text_bytes = [0u8; 64];
+        buf.copy_to_slice(&mut text_bytes);
+        let text = String::from_utf8_lossy(&text_bytes)
+            .trim_end_matches('\0')
+            .to_string();
+        
+        let timestamp = buf.get_u64();
+        
+        Ok(Self {
+            session_id,
+            accepted: reason == 0,
+            reason,
+            text,
+            timestamp,
+        })
+    }
+}
+
+/// Trade (public market data print)
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct TradeMessage {
+    pub symbol: String,
+    pub trade_id: u64,
+    pub price: u64,      // Price in fixed-point units
+    pub quantity: u64,
+    pub timestamp: u64,
+}
+
+impl TradeMessage {
+    /// Body size on the wire
+    const SIZE: usize = 48;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "Trade")?;
+        
+        // Symbol (16 bytes)
+        let symbol = decode_symbol(buf)?;
+        
+        Ok(Self {
+            symbol,
+            trade_id: buf.get_u64(),
+            price: buf.get_u64(),
+            quantity: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+/// Quote (top of book: best bid and offer)
+#[derive(Debug, Clone)]
+pub struct QuoteMessage {
+    pub symbol: String,
+    pub bid_price: u64,      // Price in fixed-point units; 0 with no bids
+    pub bid_quantity: u64,
+    pub ask_price: u64,      // Price in fixed-point units; 0 with no asks
+    pub ask_quantity: u64,
+    pub timestamp: u64,
+}
+
+impl QuoteMessage {
+    /// Body size on the wire
+    const SIZE: usize = 56;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "Quote")?;
+        
+        // Symbol (16 bytes)
+        let symbol = decode_symbol(buf)?;
+        
+        Ok(Self {
+            symbol,
+            bid_price: buf.get_u64(),
+            bid_quantity: buf.get_u64(),
+            ask_price: buf.get_u64(),
+            ask_quantity: buf.get_u64(),
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+/// Execution Report
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ExecutionMessage {
+    pub symbol: String,
+    pub client_order_id: u64,
+    pub exchange_order_id: u64,
+    pub execution_id: u64,
+    pub user_id: u64,
+    pub side: Side,
+    pub fill_price: u64,
+    pub fill_quantity: u64,
+    pub leaves_quantity: u64,
+    pub timestamp: u64,
+}
+
+impl ExecutionMessage {
+    /// Body size on the wire
+    const SIZE: usize = 88;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "Execution")?;
+        
+        // Symbol (16 bytes)
+        let symbol = decode_symbol(buf)?;
+        
         let client_order_id = buf.get_u64();
         let exchange_order_id = buf.get_u64();
         let execution_id = buf.get_u64();
         let user_id = buf.get_u64();
-        let side = if buf.get_u8() == 0x01 {
-            Side::Buy
-        } else {
-            Side::Sell
-        };
+        let side = Side::try_from(buf.get_u8())?;
         
         // Skip reserved bytes
         buf.advance(7);
@@ -370,3 +1116,419 @@ impl ExecutionMessage {
         })
     }
 }
+
+/// Aggregated price level in a book snapshot (24 bytes on the wire)
+#[derive(Debug, Clone)]
+pub struct BookLevel {
+    pub price: u64,      // Price in fixed-point units
+    pub quantity: u64,
+    pub order_count: u32,
+}
+
+/// Order Book Snapshot
+#[derive(Debug, Clone)]
+pub struct BookSnapshotMessage {
+    pub symbol: String,
+    pub request_id: u64,
+    pub timestamp: u64,
+    pub sequence: u32,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+impl BookSnapshotMessage {
+    /// Fixed part of the body, before the variable-length levels
+    const FIXED_SIZE: usize = 40;
+    const LEVEL_SIZE: usize = 24;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::FIXED_SIZE, "BookSnapshot")?;
+        
+        // Symbol (16 bytes)
+        let symbol = decode_symbol(buf)?;
+        
+        let request_id = buf.get_u64();
+        let timestamp = buf.get_u64();
+        let sequence = buf.get_u32();
+        let bid_count = buf.get_u16() as usize;
+        let ask_count = buf.get_u16() as usize;
+        
+        ensure_len(buf, (bid_count + ask_count) * Self::LEVEL_SIZE, "BookSnapshot levels")?;
+        
+        let mut decode_levels = |count: usize| -> Vec<BookLevel> {
+            (0..count)
+                .map(|_| {
+                    let level = BookLevel {
+                        price: buf.get_u64(),
+                        quantity: buf.get_u64(),
+                        order_count: buf.get_u32(),
+                    };
+                    buf.advance(4); // reserved
+                    level
+                })
+                .collect()
+        };
+        
+        let bids = decode_levels(bid_count);
+        let asks = decode_levels(ask_count);
+        
+        Ok(Self {
+            symbol,
+            request_id,
+            timestamp,
+            sequence,
+            bids,
+            asks,
+        })
+    }
+    
+    /// The same snapshot with each side merged into price levels by
+    /// `aggregate_levels`
+    pub fn aggregated(self, depth: usize) -> Self {
+        Self {
+            bids: aggregate_levels(Side::Buy, self.bids, depth),
+            asks: aggregate_levels(Side::Sell, self.asks, depth),
+            ..self
+        }
+    }
+}
+
+/// Merge one side's book entries at the same price into a single level
+/// with the summed quantity and order count. The gateway may report a
+/// price as several entries (one per resting order, say) and in any order;
+/// the result has one level per price, best first (highest bid, lowest
+/// ask), truncated to `depth`.
+pub fn aggregate_levels(side: Side, entries: Vec<BookLevel>, depth: usize) -> Vec<BookLevel> {
+    let mut levels: BTreeMap<u64, BookLevel> = BTreeMap::new();
+    for entry in entries {
+        let level = levels.entry(entry.price).or_insert(BookLevel {
+            price: entry.price,
+            quantity: 0,
+            order_count: 0,
+        });
+        level.quantity = level.quantity.saturating_add(entry.quantity);
+        level.order_count = level.order_count.saturating_add(entry.order_count);
+    }
+    
+    let levels = levels.into_values();
+    match side {
+        Side::Buy => levels.rev().take(depth).collect(),
+        Side::Sell => levels.take(depth).collect(),
+    }
+}
+
+/// One price level change, numbered in the same sequence as the symbol's
+/// book snapshots
+#[derive(Debug, Clone)]
+pub struct BookDeltaMessage {
+    pub symbol: String,
+    pub sequence: u32,
+    pub side: Side,
+    pub action: BookAction,
+    pub level: BookLevel,
+    pub timestamp: u64,
+}
+
+impl BookDeltaMessage {
+    /// Body size on the wire
+    const SIZE: usize = 56;
+    
+    pub fn decode(buf: &mut BytesMut) -> io::Result<Self> {
+        ensure_len(buf, Self::SIZE, "BookDelta")?;
+        
+        // Symbol (16 bytes)
+        let symbol = decode_symbol(buf)?;
+        
+        let sequence = buf.get_u32();
+        let side = Side::try_from(buf.get_u8())?;
+        let action = BookAction::try_from(buf.get_u8())?;
+        buf.advance(2); // reserved
+        
+        let level = BookLevel {
+            price: buf.get_u64(),
+            quantity: buf.get_u64(),
+            order_count: buf.get_u32(),
+        };
+        buf.advance(4); // reserved
+        
+        Ok(Self {
+            symbol,
+            sequence,
+            side,
+            action,
+            level,
+            timestamp: buf.get_u64(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn side_and_order_type_decode_strictly() {
+        for side in [Side::Buy, Side::Sell] {
+            assert_eq!(Side::try_from(side as u8).unwrap(), side);
+        }
+        for order_type in [OrderType::Limit, OrderType::Market, OrderType::Stop, OrderType::StopLimit] {
+            assert_eq!(OrderType::try_from(order_type as u8).unwrap(), order_type);
+        }
+        
+        for value in [0x00, 0x03, b'B', 0xFF] {
+            let err = Side::try_from(value).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        for value in [0x00, 0x05, b'L', 0xFF] {
+            let err = OrderType::try_from(value).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        assert_eq!(Side::try_from(0x03).unwrap_err().to_string(), "Unknown side: 0x03");
+    }
+    
+    /// Decode every truncation of a `len`-byte body, expecting each to fail
+    /// with `UnexpectedEof` rather than panic
+    fn assert_short_bodies_rejected<T: std::fmt::Debug>(len: usize, decode: impl Fn(&mut BytesMut) -> io::Result<T>) {
+        for short in 0..len {
+            let mut buf = BytesMut::from(&vec![0u8; short][..]);
+            let err = decode(&mut buf).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "{} of {} bytes: {}", short, len, err);
+        }
+    }
+    
+    fn header(msg_type: MessageType) -> MessageHeader {
+        MessageHeader::new(msg_type, 16)
+    }
+    
+    #[test]
+    fn short_header_is_rejected() {
+        assert_short_bodies_rejected(16, MessageHeader::decode);
+    }
+    
+    #[test]
+    fn unsupported_header_version_is_rejected() {
+        let mut buf = BytesMut::new();
+        MessageHeader::new(MessageType::Heartbeat, 16).encode(&mut buf);
+        assert_eq!(MessageHeader::decode(&mut buf.clone()).unwrap().version, PROTOCOL_VERSION);
+        
+        for version in [0, PROTOCOL_VERSION + 1, 0xFF] {
+            let mut frame = buf.clone();
+            frame[0] = version;
+            let err = MessageHeader::decode(&mut frame).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains("Unsupported protocol version"), "{}", err);
+        }
+    }
+    
+    #[test]
+    fn symbols_must_fit_the_wire_field() {
+        let longest = "ABCDEFGHIJKLMNO";
+        assert_eq!(longest.len(), MAX_SYMBOL_LEN);
+        let mut buf = BytesMut::new();
+        encode_symbol(&mut buf, longest);
+        assert_eq!(buf.len(), 16);
+        assert_eq!(decode_symbol(&mut buf).unwrap(), longest);
+        
+        // Truncating these would send an order for another symbol, and
+        // a multibyte character could be cut in half
+        for symbol in ["ABCDEFGHIJKLMNOP", "AAPL.EXTENDED.XNAS", "ÄPFEL", "株式", "€", "AA PL", "AAPL\0"] {
+            let err = validate_symbol(symbol).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{:?}", symbol);
+            let order = NewOrderMessage::new(symbol.to_string(), 1, 7, Side::Buy, OrderType::Limit, 100, 1);
+            assert_eq!(order.unwrap_err().kind(), io::ErrorKind::InvalidInput, "{:?}", symbol);
+        }
+        // Eight characters, but sixteen bytes
+        assert!(validate_symbol("ÄÄÄÄÄÄÄÄ").unwrap_err().to_string().contains("longer than 15 bytes"));
+        
+        // Nor is a symbol from the gateway that isn't UTF-8 passed on
+        let mut buf = BytesMut::from(&[0xC3, 0x28, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0][..]);
+        assert_eq!(decode_symbol(&mut buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn short_new_order_is_rejected() {
+        assert_short_bodies_rejected(60, |buf| NewOrderMessage::decode(header(MessageType::NewOrder), buf));
+    }
+    
+    #[test]
+    fn short_cancel_order_is_rejected() {
+        assert_short_bodies_rejected(40, |buf| CancelOrderMessage::decode(header(MessageType::CancelOrder), buf));
+    }
+    
+    #[test]
+    fn replace_order_round_trips() {
+        let sent = ReplaceOrderMessage::new("AAPL".to_string(), 42, 7, 10_150, 300).unwrap();
+        let mut buf = sent.encode();
+        assert_eq!(buf.len(), sent.header.length as usize);
+        
+        let header = MessageHeader::decode(&mut buf).unwrap();
+        assert_eq!(header.msg_type, MessageType::ReplaceOrder);
+        let decoded = ReplaceOrderMessage::decode(header, &mut buf).unwrap();
+        assert!(buf.is_empty());
+        assert_eq!(decoded.symbol, "AAPL");
+        assert_eq!(
+            (decoded.client_order_id, decoded.user_id, decoded.new_price, decoded.new_quantity),
+            (42, 7, 10_150, 300)
+        );
+        assert_eq!(decoded.timestamp, sent.timestamp);
+    }
+    
+    #[test]
+    fn order_replaced_decodes() {
+        let mut buf = BytesMut::new();
+        for field in [42, 99, 10_150, 300, 5] {
+            buf.put_u64(field);
+        }
+        
+        let replaced = OrderReplacedMessage::decode(&mut buf).unwrap();
+        assert!(buf.is_empty());
+        assert_eq!(
+            (replaced.client_order_id, replaced.exchange_order_id, replaced.new_price),
+            (42, 99, 10_150)
+        );
+        assert_eq!((replaced.new_quantity, replaced.timestamp), (300, 5));
+    }
+    
+    #[test]
+    fn short_replace_order_is_rejected() {
+        assert_short_bodies_rejected(56, |buf| ReplaceOrderMessage::decode(header(MessageType::ReplaceOrder), buf));
+    }
+    
+    #[test]
+    fn short_order_ack_is_rejected() {
+        assert_short_bodies_rejected(OrderAckMessage::SIZE, OrderAckMessage::decode);
+    }
+    
+    #[test]
+    fn short_order_reject_is_rejected() {
+        assert_short_bodies_rejected(OrderRejectMessage::SIZE, OrderRejectMessage::decode);
+    }
+    
+    #[test]
+    fn short_order_cancelled_is_rejected() {
+        assert_short_bodies_rejected(OrderCancelledMessage::SIZE, OrderCancelledMessage::decode);
+    }
+    
+    #[test]
+    fn short_order_replaced_is_rejected() {
+        assert_short_bodies_rejected(OrderReplacedMessage::SIZE, OrderReplacedMessage::decode);
+    }
+    
+    #[test]
+    fn short_logon_response_is_rejected() {
+        assert_short_bodies_rejected(LogonResponseMessage::SIZE, LogonResponseMessage::decode);
+    }
+    
+    #[test]
+    fn short_trade_is_rejected() {
+        assert_short_bodies_rejected(TradeMessage::SIZE, TradeMessage::decode);
+    }
+    
+    #[test]
+    fn short_quote_is_rejected() {
+        assert_short_bodies_rejected(QuoteMessage::SIZE, QuoteMessage::decode);
+    }
+    
+    #[test]
+    fn short_execution_is_rejected() {
+        assert_short_bodies_rejected(ExecutionMessage::SIZE, ExecutionMessage::decode);
+    }
+    
+    #[test]
+    fn short_book_delta_is_rejected() {
+        assert_short_bodies_rejected(BookDeltaMessage::SIZE, BookDeltaMessage::decode);
+    }
+    
+    #[test]
+    fn short_book_snapshot_is_rejected() {
+        assert_short_bodies_rejected(BookSnapshotMessage::FIXED_SIZE, BookSnapshotMessage::decode);
+        
+        // One bid and one ask claimed, but only the bid sent
+        let mut fixed = BytesMut::new();
+        encode_symbol(&mut fixed, "AAPL");
+        fixed.put_u64(1); // request_id
+        fixed.put_u64(2); // timestamp
+        fixed.put_u32(3); // sequence
+        fixed.put_u16(1); // bid_count
+        fixed.put_u16(1); // ask_count
+        fixed.put_bytes(0, BookSnapshotMessage::LEVEL_SIZE);
+        let err = BookSnapshotMessage::decode(&mut fixed).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+    
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+    
+    #[test]
+    fn checksummed_frame_round_trips() {
+        let plain = HeartbeatMessage::new().encode();
+        let mut frame = plain.clone();
+        MessageHeader::append_checksum(&mut frame);
+        
+        let header = MessageHeader::decode(&mut frame.clone()).unwrap();
+        assert!(header.has_checksum());
+        assert_eq!(header.length as usize, frame.len());
+        assert_eq!(frame.len(), plain.len() + CRC32_TRAILER_LEN);
+        
+        header.verify_checksum(&mut frame).unwrap();
+        assert_eq!(frame[16..], plain[16..]);
+    }
+    
+    #[test]
+    fn corrupted_frame_is_rejected() {
+        let mut frame = HeartbeatMessage::new().encode();
+        MessageHeader::append_checksum(&mut frame);
+        let header = MessageHeader::decode(&mut frame.clone()).unwrap();
+        
+        for index in [0, 16, frame.len() - 1] {
+            let mut corrupted = frame.clone();
+            corrupted[index] ^= 0x01;
+            let err = header.verify_checksum(&mut corrupted).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "byte {}: {}", index, err);
+        }
+    }
+    
+    fn level(price: u64, quantity: u64, order_count: u32) -> BookLevel {
+        BookLevel {
+            price,
+            quantity,
+            order_count,
+        }
+    }
+    
+    fn summary(levels: &[BookLevel]) -> Vec<(u64, u64, u32)> {
+        levels.iter().map(|l| (l.price, l.quantity, l.order_count)).collect()
+    }
+    
+    /// Entries as the gateway might send them: one per resting order, unsorted
+    fn entries() -> Vec<BookLevel> {
+        vec![
+            level(10_000, 100, 1),
+            level(10_050, 200, 1),
+            level(9_950, 300, 2),
+            level(10_000, 400, 1),
+            level(10_050, 50, 3),
+        ]
+    }
+    
+    #[test]
+    fn same_price_entries_merge_into_one_level() {
+        let asks = aggregate_levels(Side::Sell, entries(), 10);
+        assert_eq!(summary(&asks), [(9_950, 300, 2), (10_000, 500, 2), (10_050, 250, 4)]);
+    }
+    
+    #[test]
+    fn levels_are_sorted_best_first_and_cut_to_depth() {
+        let bids = aggregate_levels(Side::Buy, entries(), 10);
+        assert_eq!(summary(&bids), [(10_050, 250, 4), (10_000, 500, 2), (9_950, 300, 2)]);
+        
+        let bids = aggregate_levels(Side::Buy, entries(), 2);
+        assert_eq!(summary(&bids), [(10_050, 250, 4), (10_000, 500, 2)]);
+        let asks = aggregate_levels(Side::Sell, entries(), 1);
+        assert_eq!(summary(&asks), [(9_950, 300, 2)]);
+        assert!(aggregate_levels(Side::Sell, Vec::new(), 5).is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use super::protocol::MessageType;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// One counter per possible message type byte, so counting is a single
+/// atomic add indexed by the type
+struct MessageCounts([AtomicU64; 256]);
+
+impl Default for MessageCounts {
+    fn default() -> Self {
+        Self(std::array::from_fn(|_| AtomicU64::new(0)))
+    }
+}
+
+impl MessageCounts {
+    fn increment(&self, msg_type: u8) {
+        self.0[msg_type as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Non-zero counts by message type. Bytes that aren't a known type
+    /// are never counted, since frames with them aren't sent or accepted.
+    fn snapshot(&self) -> Vec<(MessageType, u64)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(msg_type, count)| {
+                let count = count.load(Ordering::Relaxed);
+                let msg_type = MessageType::try_from(msg_type as u8).ok()?;
+                (count > 0).then_some((msg_type, count))
+            })
+            .collect()
+    }
+}
+
+/// Live counters for one gateway connection, shared by its writers,
+/// receiver and heartbeat tasks. Everything on the send and receive paths
+/// is an atomic; only recording an error takes a lock.
+#[derive(Default)]
+pub struct ConnectionCounters {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: MessageCounts,
+    messages_received: MessageCounts,
+    /// When the current session logged on, in nanoseconds since the Unix
+    /// epoch
+    connected_at: AtomicU64,
+    /// When the gateway last sent anything, heartbeats included, in
+    /// nanoseconds since the Unix epoch
+    last_received_at: AtomicU64,
+    reconnects: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl ConnectionCounters {
+    /// Count a frame written to the gateway
+    pub fn record_sent(&self, msg_type: u8, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.messages_sent.increment(msg_type);
+    }
+    
+    /// Count bytes read from the gateway, whole frames or not
+    pub fn record_bytes_received(&self, bytes: usize) {
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.last_received_at.store(now_nanos(), Ordering::Relaxed);
+    }
+    
+    /// Count a frame received from the gateway
+    pub fn record_received(&self, msg_type: MessageType) {
+        self.messages_received.increment(msg_type as u8);
+    }
+    
+    /// A session has logged on; `reconnect` if it replaces a dropped one
+    pub fn record_connected(&self, reconnect: bool) {
+        // The logon reply counts as inbound traffic
+        let now = now_nanos();
+        self.connected_at.store(now, Ordering::Relaxed);
+        self.last_received_at.store(now, Ordering::Relaxed);
+        if reconnect {
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    
+    /// Time since the gateway last sent anything
+    pub fn since_last_received(&self) -> Duration {
+        let last = self.last_received_at.load(Ordering::Relaxed);
+        Duration::from_nanos(now_nanos().saturating_sub(last))
+    }
+    
+    /// Remember why the connection last failed
+    pub fn record_error(&self, error: impl ToString) {
+        *self.last_error.lock() = Some(error.to_string());
+    }
+}
+
+/// Wall-clock time in nanoseconds since the Unix epoch
+fn now_nanos() -> u64 {
+    chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0).max(0) as u64
+}
+
+/// Point-in-time view of one pooled gateway connection. Traffic is counted
+/// from the first logon on, so the logon exchange itself isn't included.
+#[derive(Debug, Clone)]
+pub struct ConnectionStats {
+    /// Position in the pool
+    pub slot: usize,
+    pub address: String,
+    pub connected: bool,
+    /// Requests awaiting a reply
+    pub in_flight: usize,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Frames sent and received, by message type; types never seen are
+    /// left out
+    pub messages_sent: Vec<(MessageType, u64)>,
+    pub messages_received: Vec<(MessageType, u64)>,
+    /// When the current (or last) session logged on, in nanoseconds since
+    /// the Unix epoch
+    pub connected_at: u64,
+    /// Sessions re-established after the gateway dropped the connection
+    pub reconnects: u64,
+    /// Why the connection last failed, if it ever has
+    pub last_error: Option<String>,
+}
+
+impl ConnectionStats {
+    pub(super) fn new(
+        slot: usize,
+        address: String,
+        connected: bool,
+        in_flight: usize,
+        counters: &ConnectionCounters,
+    ) -> Self {
+        Self {
+            slot,
+            address,
+            connected,
+            in_flight,
+            bytes_sent: counters.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: counters.bytes_received.load(Ordering::Relaxed),
+            messages_sent: counters.messages_sent.snapshot(),
+            messages_received: counters.messages_received.snapshot(),
+            connected_at: counters.connected_at.load(Ordering::Relaxed),
+            reconnects: counters.reconnects.load(Ordering::Relaxed),
+            last_error: counters.last_error.lock().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stats(counters: &ConnectionCounters) -> ConnectionStats {
+        ConnectionStats::new(0, "127.0.0.1:9000".to_string(), true, 0, counters)
+    }
+    
+    #[test]
+    fn each_sent_frame_is_counted_by_type() {
+        let counters = ConnectionCounters::default();
+        counters.record_sent(MessageType::NewOrder as u8, 64);
+        counters.record_sent(MessageType::NewOrder as u8, 64);
+        counters.record_sent(MessageType::CancelOrder as u8, 48);
+        
+        let stats = stats(&counters);
+        assert_eq!(stats.bytes_sent, 176);
+        assert_eq!(
+            stats.messages_sent,
+            vec![(MessageType::NewOrder, 2), (MessageType::CancelOrder, 1)]
+        );
+        assert!(stats.messages_received.is_empty());
+    }
+    
+    #[test]
+    fn reconnects_and_the_last_error_are_kept() {
+        let counters = ConnectionCounters::default();
+        counters.record_connected(false);
+        counters.record_error("connection reset");
+        counters.record_connected(true);
+        counters.record_error("write timed out");
+        
+        let stats = stats(&counters);
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.last_error.as_deref(), Some("write timed out"));
+        assert!(stats.connected_at > 0);
+    }
+}
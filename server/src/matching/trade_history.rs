@@ -0,0 +1,168 @@
+use super::protocol::TradeMessage;
+use crate::config::MarketDataConfig;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+
+/// Seconds in a (365.25 day) year, for annualizing volatility
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+const NANOS_PER_SECOND: f64 = 1e9;
+
+#[derive(Debug, Clone, Copy)]
+struct TradePoint {
+    trade_id: u64,
+    /// Nanoseconds since the Unix epoch
+    timestamp: u64,
+    /// Fixed-point units
+    price: u64,
+}
+
+/// Recent trades per symbol, fed from the gateway's trade stream. Used for
+/// the last traded price and realized volatility.
+#[derive(Debug)]
+pub struct TradeHistory {
+    trades: DashMap<String, VecDeque<TradePoint>>,
+    window_nanos: u64,
+    min_trades: usize,
+    max_trades: usize,
+}
+
+impl TradeHistory {
+    pub fn new(config: &MarketDataConfig) -> Self {
+        Self {
+            trades: DashMap::new(),
+            window_nanos: config.volatility_window_secs.saturating_mul(1_000_000_000),
+            min_trades: config.min_volatility_trades,
+            max_trades: config.max_trades_per_symbol,
+        }
+    }
+    
+    /// Record a trade and call `publish`, unless the trade repeats one
+    /// already recorded or is older than the latest. Every pooled
+    /// connection may deliver the same trade; `publish` runs under the
+    /// symbol's lock, so subscribers see each trade once and in order.
+    pub fn record(&self, trade: &TradeMessage, publish: impl FnOnce()) {
+        let mut trades = self.trades.entry(trade.symbol.clone()).or_default();
+        
+        // A repeat has the timestamp of a trade already at the back
+        if let Some(last) = trades.back() {
+            let repeated = trades
+                .iter()
+                .rev()
+                .take_while(|point| point.timestamp == trade.timestamp)
+                .any(|point| point.trade_id == trade.trade_id);
+            if repeated || trade.timestamp < last.timestamp {
+                return;
+            }
+        }
+        
+        publish();
+        trades.push_back(TradePoint {
+            trade_id: trade.trade_id,
+            timestamp: trade.timestamp,
+            price: trade.price,
+        });
+        while trades.len() > self.max_trades {
+            trades.pop_front();
+        }
+    }
+    
+    /// Last trade price for a symbol, in fixed-point units
+    pub fn last_price(&self, symbol: &str) -> Option<u64> {
+        self.trades
+            .get(symbol)
+            .and_then(|trades| trades.back().map(|trade| trade.price))
+    }
+    
+    /// Annualized realized volatility over the trades inside the window
+    /// ending now. `None` when fewer than `min_volatility_trades` trades
+    /// fall inside it.
+    pub fn realized_volatility(&self, symbol: &str) -> Option<f64> {
+        let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0).max(0) as u64;
+        let since = now.saturating_sub(self.window_nanos);
+        
+        let trades = self.trades.get(symbol)?;
+        let recent: Vec<(u64, u64)> = trades
+            .iter()
+            .filter(|trade| trade.timestamp >= since)
+            .map(|trade| (trade.timestamp, trade.price))
+            .collect();
+        drop(trades);
+        
+        if recent.len() < self.min_trades.max(2) {
+            return None;
+        }
+        annualized_volatility(&recent)
+    }
+}
+
+/// Annualized volatility of a (timestamp nanos, price) series in time order.
+///
+/// Trades arrive at irregular intervals, so rather than scaling the sample
+/// standard deviation by a fixed period, the variance rate is the sum of
+/// squared log returns divided by the elapsed time in years.
+fn annualized_volatility(series: &[(u64, u64)]) -> Option<f64> {
+    let (first, last) = (series.first()?, series.last()?);
+    let elapsed_years =
+        last.0.saturating_sub(first.0) as f64 / NANOS_PER_SECOND / SECONDS_PER_YEAR;
+    if elapsed_years <= 0.0 {
+        return None;
+    }
+    
+    let sum_squared_returns: f64 = series
+        .windows(2)
+        .filter(|pair| pair[0].1 > 0 && pair[1].1 > 0)
+        .map(|pair| (pair[1].1 as f64 / pair[0].1 as f64).ln().powi(2))
+        .sum();
+    
+    Some((sum_squared_returns / elapsed_years).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const NANOS_PER_YEAR: u64 = (SECONDS_PER_YEAR * NANOS_PER_SECOND) as u64;
+    
+    #[test]
+    fn volatility_of_a_known_series() {
+        // Two 10% moves, each way, over exactly a year
+        let series = [(0, 10_000), (NANOS_PER_YEAR / 2, 11_000), (NANOS_PER_YEAR, 10_000)];
+        let expected = (2.0 * 1.1f64.ln().powi(2)).sqrt();
+        assert!((annualized_volatility(&series).unwrap() - expected).abs() < 1e-9);
+        
+        // The same moves in a quarter of the time are twice as volatile
+        let series = [(0, 10_000), (NANOS_PER_YEAR / 8, 11_000), (NANOS_PER_YEAR / 4, 10_000)];
+        assert!((annualized_volatility(&series).unwrap() - 2.0 * expected).abs() < 1e-9);
+        
+        // A flat price has no volatility
+        assert_eq!(annualized_volatility(&[(0, 10_000), (1_000, 10_000)]), Some(0.0));
+    }
+    
+    #[test]
+    fn too_few_trades_have_no_volatility() {
+        assert_eq!(annualized_volatility(&[]), None);
+        assert_eq!(annualized_volatility(&[(1_000, 10_000)]), None);
+        assert_eq!(annualized_volatility(&[(1_000, 10_000), (1_000, 11_000)]), None);
+        
+        let history = TradeHistory::new(&MarketDataConfig {
+            min_volatility_trades: 3,
+            ..MarketDataConfig::default()
+        });
+        let now = chrono::Utc::now().timestamp_nanos_opt().unwrap() as u64;
+        let trade = |trade_id, timestamp, price| TradeMessage {
+            symbol: "AAPL".to_string(),
+            trade_id,
+            price,
+            quantity: 100,
+            timestamp,
+        };
+        history.record(&trade(1, now - 2_000_000, 10_000), || {});
+        history.record(&trade(2, now - 1_000_000, 10_100), || {});
+        assert_eq!(history.realized_volatility("AAPL"), None);
+        
+        history.record(&trade(3, now, 10_000), || {});
+        assert!(history.realized_volatility("AAPL").unwrap() > 0.0);
+        assert_eq!(history.realized_volatility("MSFT"), None);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::matching::{ConnectionStats, MatchingClient};
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Process-wide metrics registry
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+/// Upper bounds of the `pricing_compute_ms` buckets, in milliseconds
+const COMPUTE_MS_BUCKETS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// Most label values one family keeps; past this, counters add to
+/// `OVERFLOW_LABEL` and new gauge series are dropped, so label values that
+/// come from requests (symbols, say) can't grow the scrape without bound
+const MAX_LABEL_VALUES: usize = 1000;
+
+/// Label value of the series collecting counts past `MAX_LABEL_VALUES`
+const OVERFLOW_LABEL: &str = "other";
+
+/// Counter or gauge family keyed by a single label value
+#[derive(Default)]
+struct LabeledCounter {
+    values: DashMap<String, AtomicU64>,
+}
+
+impl LabeledCounter {
+    fn increment(&self, label: &str) {
+        self.add(label, 1);
+    }
+    
+    fn add(&self, label: &str, amount: u64) {
+        if let Some(counter) = self.values.get(label) {
+            counter.fetch_add(amount, Ordering::Relaxed);
+            return;
+        }
+        let label = if self.values.len() < MAX_LABEL_VALUES { label } else { OVERFLOW_LABEL };
+        self.values
+            .entry(label.to_string())
+            .or_default()
+            .fetch_add(amount, Ordering::Relaxed);
+    }
+    
+    /// Set a gauge. A zero gauge is removed rather than kept, so the family
+    /// only holds the labels currently in use.
+    fn set(&self, label: &str, value: u64) {
+        if value == 0 {
+            self.values.remove(label);
+            return;
+        }
+        if let Some(gauge) = self.values.get(label) {
+            gauge.store(value, Ordering::Relaxed);
+            return;
+        }
+        if self.values.len() >= MAX_LABEL_VALUES {
+            return;
+        }
+        self.values
+            .entry(label.to_string())
+            .or_default()
+            .store(value, Ordering::Relaxed);
+    }
+    
+    /// Values sorted by label, so scrapes are stable
+    fn snapshot(&self) -> Vec<(String, u64)> {
+        let mut values: Vec<_> = self
+            .values
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().load(Ordering::Relaxed)))
+            .collect();
+        values.sort();
+        values
+    }
+}
+
+#[derive(Default)]
+struct HistogramState {
+    /// Non-cumulative count per bucket in `COMPUTE_MS_BUCKETS`
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+/// Histogram over `COMPUTE_MS_BUCKETS`
+#[derive(Default)]
+struct Histogram {
+    state: Mutex<HistogramState>,
+}
+
+impl Histogram {
+    fn observe(&self, value: f64) {
+        let mut state = self.state.lock();
+        if state.buckets.is_empty() {
+            state.buckets = vec![0; COMPUTE_MS_BUCKETS.len()];
+        }
+        if let Some(i) = COMPUTE_MS_BUCKETS.iter().position(|bound| value <= *bound) {
+            state.buckets[i] += 1;
+        }
+        state.count += 1;
+        state.sum += value;
+    }
+}
+
+/// Counters, histograms and gauges exported to Prometheus
+#[derive(Default)]
+pub struct Metrics {
+    pricing_requests: LabeledCounter,
+    pricing_compute_ms: Histogram,
+    orders_submitted: LabeledCounter,
+    orders_rejected: LabeledCounter,
+    executions_lagged: LabeledCounter,
+    orders_in_flight: LabeledCounter,
+    matching_connections_active: AtomicU64,
+}
+
+impl Metrics {
+    /// Count a priced request and record how long the computation took
+    pub fn record_pricing(&self, option_type: &str, computation_time_ms: f64) {
+        self.pricing_requests.increment(option_type);
+        self.pricing_compute_ms.observe(computation_time_ms);
+    }
+    
+    /// Count an order sent to the gateway, by side ("buy"/"sell")
+    pub fn record_order_submitted(&self, side: &str) {
+        self.orders_submitted.increment(side);
+    }
+    
+    /// Count an order rejected by the gateway, by reason
+    pub fn record_order_rejected(&self, reason: &str) {
+        self.orders_rejected.increment(reason);
+    }
+    
+    /// Count executions dropped for a subscriber that fell behind, by
+    /// subscriber ("gateway" for the journal's own feed, "stream" for
+    /// StreamExecutions clients)
+    pub fn record_executions_lagged(&self, subscriber: &str, skipped: u64) {
+        self.executions_lagged.add(subscriber, skipped);
+    }
+    
+    /// Set the number of orders awaiting the gateway's ack for `symbol`
+    pub fn set_orders_in_flight(&self, symbol: &str, in_flight: u64) {
+        self.orders_in_flight.set(symbol, in_flight);
+    }
+    
+    /// Set the number of live gateway connections
+    pub fn set_matching_connections_active(&self, active: usize) {
+        self.matching_connections_active
+            .store(active as u64, Ordering::Relaxed);
+    }
+    
+    /// Render every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        
+        Self::render_counter(
+            &mut out,
+            "pricing_requests_total",
+            "Pricing requests served",
+            "option_type",
+            &self.pricing_requests,
+        );
+        
+        let state = self.pricing_compute_ms.state.lock();
+        let _ = writeln!(out, "# HELP pricing_compute_ms Pricing computation time in milliseconds");
+        let _ = writeln!(out, "# TYPE pricing_compute_ms histogram");
+        let mut cumulative = 0;
+        for (i, bound) in COMPUTE_MS_BUCKETS.iter().enumerate() {
+            cumulative += state.buckets.get(i).copied().unwrap_or(0);
+            let _ = writeln!(out, "pricing_compute_ms_bucket{{le=\"{}\"}} {}", bound, cumulative);
+        }
+        let _ = writeln!(out, "pricing_compute_ms_bucket{{le=\"+Inf\"}} {}", state.count);
+        let _ = writeln!(out, "pricing_compute_ms_sum {}", state.sum);
+        let _ = writeln!(out, "pricing_compute_ms_count {}", state.count);
+        drop(state);
+        
+        Self::render_counter(
+            &mut out,
+            "orders_submitted_total",
+            "Orders sent to the matching engine",
+            "side",
+            &self.orders_submitted,
+        );
+        Self::render_counter(
+            &mut out,
+            "orders_rejected_total",
+            "Orders rejected by the matching engine",
+            "reason",
+            &self.orders_rejected,
+        );
+        Self::render_counter(
+            &mut out,
+            "executions_lagged_total",
+            "Executions dropped for subscribers that fell behind",
+            "subscriber",
+            &self.executions_lagged,
+        );
+        Self::render_gauge(
+            &mut out,
+            "orders_in_flight",
+            "Orders awaiting the matching engine's ack",
+            "symbol",
+            &self.orders_in_flight,
+        );
+        
+        let _ = writeln!(out, "# HELP matching_connections_active Live matching engine connections");
+        let _ = writeln!(out, "# TYPE matching_connections_active gauge");
+        let _ = writeln!(
+            out,
+            "matching_connections_active {}",
+            self.matching_connections_active.load(Ordering::Relaxed)
+        );
+        
+        out
+    }
+    
+    /// Render per-connection gateway stats, labelled by pool slot
+    pub fn render_connection_stats(out: &mut String, stats: &[ConnectionStats]) {
+        let mut family = |name: &str, kind: &str, help: &str, value: &dyn Fn(&ConnectionStats) -> u64| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for conn in stats {
+                let _ = writeln!(out, "{}{{connection=\"{}\"}} {}", name, conn.slot, value(conn));
+            }
+        };
+        family(
+            "matching_connection_up",
+            "gauge",
+            "Whether the pooled gateway connection is connected",
+            &|conn| conn.connected as u64,
+        );
+        family(
+            "matching_connection_bytes_sent_total",
+            "counter",
+            "Bytes written to the gateway",
+            &|conn| conn.bytes_sent,
+        );
+        family(
+            "matching_connection_bytes_received_total",
+            "counter",
+            "Bytes read from the gateway",
+            &|conn| conn.bytes_received,
+        );
+        family(
+            "matching_connection_reconnects_total",
+            "counter",
+            "Sessions re-established after the gateway dropped the connection",
+            &|conn| conn.reconnects,
+        );
+        
+        for (name, help, sent) in [
+            ("matching_connection_messages_sent_total", "Frames sent to the gateway", true),
+            ("matching_connection_messages_received_total", "Frames received from the gateway", false),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for conn in stats {
+                let counts = if sent { &conn.messages_sent } else { &conn.messages_received };
+                for (msg_type, count) in counts {
+                    let _ = writeln!(
+                        out,
+                        "{}{{connection=\"{}\",type=\"{:?}\"}} {}",
+                        name, conn.slot, msg_type, count
+                    );
+                }
+            }
+        }
+    }
+    
+    fn render_counter(out: &mut String, name: &str, help: &str, label: &str, counter: &LabeledCounter) {
+        Self::render_labeled(out, name, "counter", help, label, counter);
+    }
+    
+    fn render_gauge(out: &mut String, name: &str, help: &str, label: &str, gauge: &LabeledCounter) {
+        Self::render_labeled(out, name, "gauge", help, label, gauge);
+    }
+    
+    fn render_labeled(
+        out: &mut String,
+        name: &str,
+        kind: &str,
+        help: &str,
+        label: &str,
+        counter: &LabeledCounter,
+    ) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (value, count) in counter.snapshot() {
+            let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, escape_label(&value), count);
+        }
+    }
+}
+
+/// Escape a label value for the text exposition format: backslash, double
+/// quote and newline
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serve `GET /metrics` on an already bound listener until the process
+/// exits, including `matching_client`'s per-connection stats
+pub async fn serve(listener: TcpListener, matching_client: Arc<MatchingClient>) {
+    if let Ok(addr) = listener.local_addr() {
+        info!("Metrics available at http://{}/metrics", addr);
+    }
+    
+    let app = Router::new()
+        .route("/metrics", get(scrape))
+        .with_state(matching_client);
+    if let Err(e) = axum::serve(listener, app).await {
+        warn!("Metrics endpoint stopped: {}", e);
+    }
+}
+
+async fn scrape(State(matching_client): State<Arc<MatchingClient>>) -> impl IntoResponse {
+    let mut body = METRICS.render();
+    Metrics::render_connection_stats(&mut body, &matching_client.connection_stats().await);
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::time::{timeout, Duration};
+    
+    #[tokio::test]
+    async fn scrape_reports_counters() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let matching_client = Arc::new(MatchingClient::without_gateway(100).await);
+        tokio::spawn(serve(listener, matching_client));
+        
+        METRICS.record_order_rejected("scrape_test");
+        METRICS.record_order_rejected("scrape_test");
+        
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("content-type: text/plain; version=0.0.4"), "{}", response);
+        assert!(response.contains("\norders_rejected_total{reason=\"scrape_test\"} 2\n"), "{}", response);
+        assert!(response.contains("# TYPE matching_connections_active gauge\n"), "{}", response);
+    }
+    
+    #[test]
+    fn label_values_are_escaped() {
+        let metrics = Metrics::default();
+        metrics.record_order_rejected("a \"quoted\"\\reason\nsplit");
+        assert!(metrics
+            .render()
+            .contains("orders_rejected_total{reason=\"a \\\"quoted\\\"\\\\reason\\nsplit\"} 1\n"));
+    }
+    
+    #[test]
+    fn label_values_are_bounded() {
+        let metrics = Metrics::default();
+        for i in 0..MAX_LABEL_VALUES + 10 {
+            metrics.record_order_rejected(&format!("reason{}", i));
+            metrics.set_orders_in_flight(&format!("SYM{}", i), 1);
+        }
+        assert_eq!(metrics.orders_rejected.snapshot().len(), MAX_LABEL_VALUES + 1);
+        assert!(metrics.render().contains("orders_rejected_total{reason=\"other\"} 10\n"));
+        assert_eq!(metrics.orders_in_flight.snapshot().len(), MAX_LABEL_VALUES);
+        
+        // Symbols with nothing in flight drop out, making room again
+        metrics.set_orders_in_flight("SYM0", 0);
+        metrics.set_orders_in_flight("NEW", 3);
+        let in_flight = metrics.orders_in_flight.snapshot();
+        assert!(!in_flight.iter().any(|(symbol, _)| symbol == "SYM0"));
+        assert!(in_flight.contains(&("NEW".to_string(), 3)));
+    }
+}
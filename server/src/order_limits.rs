@@ -0,0 +1,78 @@
+use crate::config::{OrderLimitConfig, UserOrderLimit};
+use std::collections::HashMap;
+use tonic::Status;
+
+/// Largest quantity and notional of one order; `None` is unlimited
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    max_quantity: Option<u64>,
+    max_notional: Option<f64>,
+}
+
+/// Fat-finger guard: rejects single orders whose quantity or notional
+/// (price * quantity, in dollars) exceeds the user's limits. Market orders
+/// are valued at the configured reference price, or skip the notional
+/// check without one.
+pub struct OrderLimits {
+    default_limits: Limits,
+    overrides: HashMap<u64, Limits>,
+    market_reference_price: Option<f64>,
+}
+
+impl OrderLimits {
+    pub fn new(config: &OrderLimitConfig) -> Self {
+        let default_limits = Limits {
+            max_quantity: (config.max_order_quantity > 0).then_some(config.max_order_quantity),
+            max_notional: (config.max_order_notional > 0.0).then_some(config.max_order_notional),
+        };
+        
+        Self {
+            default_limits,
+            overrides: config
+                .overrides
+                .iter()
+                .map(|UserOrderLimit { user_id, max_order_quantity, max_order_notional }| {
+                    let limits = Limits {
+                        max_quantity: max_order_quantity
+                            .map_or(default_limits.max_quantity, |max| (max > 0).then_some(max)),
+                        max_notional: max_order_notional
+                            .map_or(default_limits.max_notional, |max| (max > 0.0).then_some(max)),
+                    };
+                    (*user_id, limits)
+                })
+                .collect(),
+            market_reference_price: (config.market_reference_price > 0.0)
+                .then_some(config.market_reference_price),
+        }
+    }
+    
+    /// Check an order for `user_id`. `price` is in dollars, or `None` for a
+    /// market order.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, user_id: u64, price: Option<f64>, quantity: u64) -> Result<(), Status> {
+        let limits = self
+            .overrides
+            .get(&user_id)
+            .copied()
+            .unwrap_or(self.default_limits);
+        
+        if let Some(max) = limits.max_quantity.filter(|max| quantity > *max) {
+            return Err(Status::failed_precondition(format!(
+                "Order quantity {} exceeds max_order_quantity {}",
+                quantity, max
+            )));
+        }
+        
+        let notional = price.or(self.market_reference_price).map(|price| price * quantity as f64);
+        if let (Some(max), Some(notional)) = (limits.max_notional, notional) {
+            if notional > max {
+                return Err(Status::failed_precondition(format!(
+                    "Order notional ${:.2} exceeds max_order_notional ${:.2}",
+                    notional, max
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config::OrderThrottleConfig;
+use crate::metrics::METRICS;
+use dashmap::DashMap;
+use tonic::Status;
+
+/// Counts the orders awaiting a gateway ack per symbol and, with a limit
+/// configured, refuses new ones for a symbol that already has too many,
+/// so a backlog on a busy symbol is turned away instead of queueing.
+pub struct OrderThrottle {
+    max_in_flight: Option<u64>,
+    in_flight: DashMap<String, u64>,
+}
+
+/// An order counted as in flight for its symbol. Dropping it, once the
+/// order is acked, rejected or failed, takes it off the count.
+pub struct InFlightOrder<'a> {
+    throttle: &'a OrderThrottle,
+    symbol: String,
+}
+
+impl OrderThrottle {
+    pub fn new(config: &OrderThrottleConfig) -> Self {
+        Self {
+            max_in_flight: (config.max_in_flight_per_symbol > 0)
+                .then_some(config.max_in_flight_per_symbol),
+            in_flight: DashMap::new(),
+        }
+    }
+    
+    /// Count an order for `symbol` as in flight until the returned guard
+    /// is dropped, unless the symbol is at its limit
+    #[allow(clippy::result_large_err)]
+    pub fn acquire(&self, symbol: &str) -> Result<InFlightOrder<'_>, Status> {
+        // The entry guard holds the shard lock, so check-and-increment is atomic
+        let mut count = self.in_flight.entry(symbol.to_string()).or_insert(0);
+        if let Some(max) = self.max_in_flight.filter(|max| *count >= *max) {
+            return Err(Status::resource_exhausted(format!(
+                "{} already has {} orders awaiting the gateway, the most allowed",
+                symbol, max
+            )));
+        }
+        
+        *count += 1;
+        METRICS.set_orders_in_flight(symbol, *count);
+        Ok(InFlightOrder {
+            throttle: self,
+            symbol: symbol.to_string(),
+        })
+    }
+    
+    /// Orders in flight per symbol, sorted by symbol. Symbols seen before
+    /// but idle now are listed with zero.
+    pub fn in_flight(&self) -> Vec<(String, u64)> {
+        let mut counts: Vec<_> = self
+            .in_flight
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        counts.sort();
+        counts
+    }
+    
+    /// Per-symbol limit; `None` is unlimited
+    pub fn max_in_flight(&self) -> Option<u64> {
+        self.max_in_flight
+    }
+}
+
+impl Drop for InFlightOrder<'_> {
+    fn drop(&mut self) {
+        if let Some(mut count) = self.throttle.in_flight.get_mut(&self.symbol) {
+            *count = count.saturating_sub(1);
+            METRICS.set_orders_in_flight(&self.symbol, *count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn throttle(max_in_flight_per_symbol: u64) -> OrderThrottle {
+        OrderThrottle::new(&OrderThrottleConfig {
+            max_in_flight_per_symbol,
+        })
+    }
+    
+    #[test]
+    fn ack_takes_the_order_off_the_count() {
+        let throttle = throttle(0);
+        let first = throttle.acquire("AAPL").unwrap();
+        let second = throttle.acquire("AAPL").unwrap();
+        let other = throttle.acquire("MSFT").unwrap();
+        assert_eq!(
+            throttle.in_flight(),
+            vec![("AAPL".to_string(), 2), ("MSFT".to_string(), 1)]
+        );
+        
+        drop(first);
+        assert_eq!(
+            throttle.in_flight(),
+            vec![("AAPL".to_string(), 1), ("MSFT".to_string(), 1)]
+        );
+        
+        drop(second);
+        drop(other);
+        assert_eq!(
+            throttle.in_flight(),
+            vec![("AAPL".to_string(), 0), ("MSFT".to_string(), 0)]
+        );
+    }
+    
+    #[test]
+    fn symbol_at_its_limit_is_throttled() {
+        let throttle = throttle(2);
+        assert_eq!(throttle.max_in_flight(), Some(2));
+        let held = [throttle.acquire("AAPL").unwrap(), throttle.acquire("AAPL").unwrap()];
+        
+        let status = throttle.acquire("AAPL").err().unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(status.message().contains("AAPL already has 2 orders"), "{}", status.message());
+        // A refused order isn't counted, and other symbols aren't held back
+        assert_eq!(throttle.in_flight()[0], ("AAPL".to_string(), 2));
+        drop(throttle.acquire("MSFT").unwrap());
+        
+        drop(held);
+        let _room = throttle.acquire("AAPL").unwrap();
+        assert_eq!(throttle.in_flight()[0], ("AAPL".to_string(), 1));
+    }
+    
+    #[test]
+    fn zero_limit_is_unlimited() {
+        let throttle = throttle(0);
+        assert_eq!(throttle.max_in_flight(), None);
+        let held: Vec<_> = (0..1000).map(|_| throttle.acquire("AAPL").unwrap()).collect();
+        assert_eq!(throttle.in_flight(), vec![("AAPL".to_string(), 1000)]);
+        drop(held);
+    }
+}
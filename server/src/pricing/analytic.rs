@@ -0,0 +1,183 @@
+use std::f64::consts::SQRT_2;
+
+/// Black-Scholes price of a European call on an asset paying a continuous
+/// dividend yield, for checking the Monte Carlo library against
+pub fn black_scholes_call(
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+    time_to_maturity: f64,
+) -> f64 {
+    let (d1, d2) = d1_d2(spot, strike, rate, dividend_yield, volatility, time_to_maturity);
+    
+    spot * (-dividend_yield * time_to_maturity).exp() * norm_cdf(d1)
+        - strike * (-rate * time_to_maturity).exp() * norm_cdf(d2)
+}
+
+/// Black-Scholes price of a cash-or-nothing digital call paying `payout`:
+/// payout·N(d2)·e^(-rT)
+#[cfg(test)]
+pub fn cash_or_nothing_call(
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+    time_to_maturity: f64,
+    payout: f64,
+) -> f64 {
+    let (_, d2) = d1_d2(spot, strike, rate, dividend_yield, volatility, time_to_maturity);
+    payout * norm_cdf(d2) * (-rate * time_to_maturity).exp()
+}
+
+/// Kirk's approximation to the price of a call on the spread between two
+/// assets, S1 - S2 - K, both paying `dividend_yield`. Exact (Margrabe's
+/// formula) when the strike is zero.
+#[cfg(test)]
+#[allow(clippy::too_many_arguments)]
+pub fn kirk_spread_call(
+    spot1: f64,
+    spot2: f64,
+    strike: f64,
+    rate: f64,
+    dividend_yield: f64,
+    volatility1: f64,
+    volatility2: f64,
+    correlation: f64,
+    time_to_maturity: f64,
+) -> f64 {
+    let growth = ((rate - dividend_yield) * time_to_maturity).exp();
+    let (forward1, forward2) = (spot1 * growth, spot2 * growth);
+    let weight = forward2 / (forward2 + strike);
+    let volatility = (volatility1 * volatility1 - 2.0 * correlation * volatility1 * volatility2 * weight
+        + (volatility2 * weight).powi(2))
+    .sqrt();
+    
+    // A Black call on F1 struck at F2 + K, with the blended volatility
+    let vol_sqrt_t = volatility * time_to_maturity.sqrt();
+    let d1 = ((forward1 / (forward2 + strike)).ln() + 0.5 * vol_sqrt_t * vol_sqrt_t) / vol_sqrt_t;
+    let d2 = d1 - vol_sqrt_t;
+    (-rate * time_to_maturity).exp() * (forward1 * norm_cdf(d1) - (forward2 + strike) * norm_cdf(d2))
+}
+
+/// The Black-Scholes d1 and d2 terms
+fn d1_d2(
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+    time_to_maturity: f64,
+) -> (f64, f64) {
+    let vol_sqrt_t = volatility * time_to_maturity.sqrt();
+    let d1 = ((spot / strike).ln()
+        + (rate - dividend_yield + 0.5 * volatility * volatility) * time_to_maturity)
+        / vol_sqrt_t;
+    (d1, d1 - vol_sqrt_t)
+}
+
+/// Standard normal cumulative distribution function
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// Complementary error function, to a fractional error below 1.2e-7
+/// (the Chebyshev fit from Numerical Recipes)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let r = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::MonteCarloEngine;
+    use crate::proto::pricing::{DigitalType, SimulationConfig};
+    
+    #[test]
+    fn monte_carlo_digital_call_matches_the_closed_form() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = SimulationConfig {
+            num_simulations: 200_000,
+            seed: 7,
+            ..Default::default()
+        };
+        
+        for (strike, dividend_yield) in [(90.0, 0.0), (100.0, 0.02), (115.0, 0.0)] {
+            let expected = cash_or_nothing_call(100.0, strike, 0.05, dividend_yield, 0.2, 1.0, 10.0);
+            let priced = engine.price_digital_call(
+                100.0,
+                strike,
+                0.05,
+                dividend_yield,
+                0.2,
+                1.0,
+                10.0,
+                DigitalType::CashOrNothing,
+                &config,
+            );
+            // Within about four standard errors of the Monte Carlo price
+            assert!((priced - expected).abs() < 0.04, "strike {}: {} vs {}", strike, priced, expected);
+        }
+    }
+    
+    #[test]
+    fn closed_form_digital_call_is_the_discounted_probability() {
+        // At-the-money with zero rates and vanishing volatility, d2 → 0
+        let price = cash_or_nothing_call(100.0, 100.0, 0.0, 0.0, 1e-9, 1.0, 1.0);
+        assert!((price - 0.5).abs() < 1e-6, "{}", price);
+        
+        let deep = cash_or_nothing_call(100.0, 10.0, 0.05, 0.0, 0.2, 2.0, 1.0);
+        assert!((deep - (-0.1f64).exp()).abs() < 1e-6, "{}", deep);
+    }
+    
+    #[test]
+    fn monte_carlo_spread_call_matches_kirk() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = SimulationConfig {
+            num_simulations: 200_000,
+            seed: 7,
+            ..Default::default()
+        };
+        
+        for (strike, correlation) in [(0.0, 0.5), (5.0, 0.5), (5.0, -0.3)] {
+            let expected = kirk_spread_call(100.0, 96.0, strike, 0.05, 0.01, 0.25, 0.2, correlation, 1.0);
+            let priced = engine.price_spread_call(
+                100.0,
+                96.0,
+                strike,
+                0.05,
+                0.01,
+                0.25,
+                0.2,
+                correlation,
+                1.0,
+                &config,
+            );
+            assert!(
+                (priced - expected).abs() < 0.15,
+                "strike {}, correlation {}: {} vs {}",
+                strike,
+                correlation,
+                priced,
+                expected
+            );
+        }
+    }
+}
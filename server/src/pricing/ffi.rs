@@ -19,12 +19,12 @@ extern "C" {
     pub fn mco_context_set_antithetic(ctx: *mut mco_context_t, enabled: c_int);
     pub fn mco_context_set_control_variates(ctx: *mut mco_context_t, enabled: c_int);
     pub fn mco_context_set_stratified_sampling(ctx: *mut mco_context_t, enabled: c_int);
-    #[allow(dead_code)]
     pub fn mco_context_set_importance_sampling(
         ctx: *mut mco_context_t,
         enabled: c_int,
         drift_shift: c_double,
     );
+    pub fn mco_context_set_dividend_yield(ctx: *mut mco_context_t, dividend_yield: c_double);
     
     // European options
     pub fn mco_european_call(
@@ -153,4 +153,52 @@ extern "C" {
         time_to_maturity: c_double,
         fixed_strike: c_int,
     ) -> c_double;
+    
+    // Digital options
+    pub fn mco_digital_call(
+        ctx: *mut mco_context_t,
+        spot: c_double,
+        strike: c_double,
+        rate: c_double,
+        volatility: c_double,
+        time_to_maturity: c_double,
+        payout: c_double,
+        digital_type: c_int,
+    ) -> c_double;
+    
+    pub fn mco_digital_put(
+        ctx: *mut mco_context_t,
+        spot: c_double,
+        strike: c_double,
+        rate: c_double,
+        volatility: c_double,
+        time_to_maturity: c_double,
+        payout: c_double,
+        digital_type: c_int,
+    ) -> c_double;
+    
+    // Spread options
+    pub fn mco_spread_call(
+        ctx: *mut mco_context_t,
+        spot1: c_double,
+        spot2: c_double,
+        strike: c_double,
+        rate: c_double,
+        volatility1: c_double,
+        volatility2: c_double,
+        correlation: c_double,
+        time_to_maturity: c_double,
+    ) -> c_double;
+    
+    pub fn mco_spread_put(
+        ctx: *mut mco_context_t,
+        spot1: c_double,
+        spot2: c_double,
+        strike: c_double,
+        rate: c_double,
+        volatility1: c_double,
+        volatility2: c_double,
+        correlation: c_double,
+        time_to_maturity: c_double,
+    ) -> c_double;
 }
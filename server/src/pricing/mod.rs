@@ -1,4 +1,6 @@
+mod analytic;
 mod ffi;
 mod wrapper;
 
-pub use wrapper::MonteCarloEngine;
+pub use analytic::black_scholes_call;
+pub use wrapper::{GreekSelection, MonteCarloEngine};
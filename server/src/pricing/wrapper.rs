@@ -1,14 +1,127 @@
 use super::ffi;
-use crate::proto::pricing::{BarrierType, SimulationConfig};
+use crate::proto::pricing::{BarrierType, DigitalType, SimulationConfig};
 use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 
-/// Thread-safe wrapper around the Monte Carlo context
+/// Thread-safe wrapper around a pool of Monte Carlo contexts.
+/// Each pricing call checks out one context, so independent requests
+/// can run in parallel up to the pool size.
 pub struct MonteCarloEngine {
-    ctx: Arc<Mutex<MonteCarloContext>>,
+    contexts: Arc<Vec<Mutex<MonteCarloContext>>>,
+    next: Arc<AtomicUsize>,
 }
 
+/// Option sensitivities computed by bump-and-reprice; those not selected
+/// are left unset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Greeks {
+    pub delta: Option<f64>,
+    pub gamma: Option<f64>,
+    /// Per unit of volatility (1.0 = 100 vol points)
+    pub vega: Option<f64>,
+    /// Per year of calendar time
+    pub theta: Option<f64>,
+    /// Per unit of rate (1.0 = 10,000 bp)
+    pub rho: Option<f64>,
+}
+
+/// Which Greeks to compute. Delta and gamma share two repricings, vega
+/// and rho take two each and theta one, plus one shared by gamma and theta.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GreekSelection {
+    pub delta: bool,
+    pub gamma: bool,
+    pub vega: bool,
+    pub theta: bool,
+    pub rho: bool,
+}
+
+impl GreekSelection {
+    pub const ALL: Self = Self {
+        delta: true,
+        gamma: true,
+        vega: true,
+        theta: true,
+        rho: true,
+    };
+    
+    pub fn any(self) -> bool {
+        self.delta || self.gamma || self.vega || self.theta || self.rho
+    }
+}
+
+/// Monte Carlo price together with its sampling error
+#[derive(Debug, Clone, Copy)]
+pub struct PriceEstimate {
+    pub price: f64,
+    pub std_error: f64,
+    /// Paths simulated across all the sub-runs
+    pub simulations: u64,
+}
+
+impl PriceEstimate {
+    /// Half-width of the 95% confidence interval: price ± 1.96·std_error
+    pub fn confidence_95(&self) -> f64 {
+        1.96 * self.std_error
+    }
+}
+
+/// Running estimate reported by progressive pricing
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    /// Running estimate over the paths simulated so far
+    pub estimate: PriceEstimate,
+    /// Whether this is the final estimate
+    pub complete: bool,
+}
+
+/// Variance of an estimate with a technique switched on, relative to the
+/// same estimate with it off. Below 1 means the technique helps.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VarianceReduction {
+    pub antithetic: Option<f64>,
+    pub control_variates: Option<f64>,
+}
+
+/// Independent sub-runs used to estimate the standard error. The library
+/// only returns a point estimate, so the error comes from their spread.
+const STD_ERROR_BATCHES: u64 = 10;
+
+/// Estimates reported by progressive pricing, each after another equal
+/// share of the simulation budget
+const PROGRESS_ROUNDS: u64 = 10;
+
+/// Outcome of an implied volatility solve
+#[derive(Debug, Clone, Copy)]
+pub struct ImpliedVol {
+    pub volatility: f64,
+    pub iterations: u32,
+    pub converged: bool,
+    /// Modeled price minus target price at `volatility`
+    pub price_error: f64,
+}
+
+/// Volatility bracket searched by the implied volatility solver
+const IMPLIED_VOL_MIN: f64 = 1e-4;
+const IMPLIED_VOL_MAX: f64 = 5.0;
+
+/// Seed used for bumped repricings when the caller didn't fix one
+const GREEKS_DEFAULT_SEED: u64 = 0x5EED_6EE1;
+
+/// Relative spot bump for delta/gamma
+const SPOT_BUMP: f64 = 0.01;
+
+/// Absolute volatility bump for vega
+const VOL_BUMP: f64 = 0.01;
+
+/// Absolute rate bump for rho (1bp)
+const RATE_BUMP: f64 = 0.0001;
+
+/// Time bump for theta (one day)
+const TIME_BUMP: f64 = 1.0 / 365.0;
+
 struct MonteCarloContext {
     ptr: *mut ffi::mco_context_t,
 }
@@ -22,12 +135,18 @@ impl MonteCarloContext {
         Ok(Self { ptr })
     }
     
-    fn configure(&mut self, config: &SimulationConfig) {
+    fn configure(&mut self, config: &SimulationConfig, dividend_yield: f64) {
+        // Always seeded, so a pooled context never carries on from the
+        // previous request's random state
+        let seed = if config.seed > 0 {
+            config.seed
+        } else {
+            MonteCarloEngine::random_seed()
+        };
+        
         unsafe {
-            if config.seed > 0 {
-                ffi::mco_context_set_seed(self.ptr, config.seed);
-            }
-            ffi::mco_context_set_num_simulations(self.ptr, config.num_simulations);
+            ffi::mco_context_set_seed(self.ptr, seed);
+            ffi::mco_context_set_num_simulations(self.ptr, MonteCarloEngine::simulations_run(config));
             ffi::mco_context_set_num_steps(self.ptr, config.num_steps);
             ffi::mco_context_set_antithetic(self.ptr, config.antithetic_enabled as i32);
             ffi::mco_context_set_control_variates(
@@ -38,6 +157,13 @@ impl MonteCarloContext {
                 self.ptr,
                 config.stratified_sampling_enabled as i32,
             );
+            ffi::mco_context_set_importance_sampling(
+                self.ptr,
+                config.importance_sampling_enabled as i32,
+                config.importance_drift_shift,
+            );
+            // Always set, so a pooled context never keeps a previous yield
+            ffi::mco_context_set_dividend_yield(self.ptr, dividend_yield);
         }
     }
 }
@@ -55,59 +181,453 @@ impl Drop for MonteCarloContext {
 unsafe impl Send for MonteCarloContext {}
 
 impl MonteCarloEngine {
-    pub fn new() -> Result<Self> {
-        let ctx = MonteCarloContext::new()?;
+    pub fn new(pool_size: usize) -> Result<Self> {
+        if pool_size == 0 {
+            anyhow::bail!("Monte Carlo context pool size must be greater than 0");
+        }
+        
+        let contexts = (0..pool_size)
+            .map(|_| MonteCarloContext::new().map(Mutex::new))
+            .collect::<Result<Vec<_>>>()?;
+        
         Ok(Self {
-            ctx: Arc::new(Mutex::new(ctx)),
+            contexts: Arc::new(contexts),
+            next: Arc::new(AtomicUsize::new(0)),
         })
     }
     
+    /// Fill in a random seed when `config` has none, and return the seed
+    /// the run will use. Passing it back as `config.seed` reproduces the run.
+    pub fn resolve_seed(config: &mut SimulationConfig) -> u64 {
+        if config.seed == 0 {
+            config.seed = Self::random_seed();
+        }
+        config.seed
+    }
+    
+    /// Paths the library runs for `config`. Antithetic variates pair each
+    /// path with its mirror, so an odd count is rounded up to the next even
+    /// one rather than leaving it to the library to drop or skew a path.
+    pub fn simulations_run(config: &SimulationConfig) -> u64 {
+        if config.antithetic_enabled {
+            config.num_simulations.next_multiple_of(2)
+        } else {
+            config.num_simulations
+        }
+    }
+    
+    /// A nonzero seed; zero means "unseeded" to the library
+    fn random_seed() -> u64 {
+        rand::random::<u64>().max(1)
+    }
+    
+    /// Number of contexts in the pool
+    pub fn pool_size(&self) -> usize {
+        self.contexts.len()
+    }
+    
+    /// Number of contexts currently checked out by a pricing call
+    pub fn contexts_in_use(&self) -> usize {
+        self.contexts.iter().filter(|ctx| ctx.is_locked()).count()
+    }
+    
+    /// Check out a free context, starting from the next round-robin slot.
+    /// If every context is busy, block on that slot.
+    fn acquire(&self) -> MutexGuard<'_, MonteCarloContext> {
+        let len = self.contexts.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        
+        for i in 0..len {
+            if let Some(ctx) = self.contexts[(start + i) % len].try_lock() {
+                return ctx;
+            }
+        }
+        
+        self.contexts[start % len].lock()
+    }
+    
     // European options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_european_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_european_call(ctx.ptr, spot, strike, rate, volatility, time_to_maturity)
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_european_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_european_put(ctx.ptr, spot, strike, rate, volatility, time_to_maturity)
         }
     }
     
+    /// European call price with its standard error
+    #[allow(clippy::too_many_arguments)]
+    pub fn estimate_european_call(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+    ) -> PriceEstimate {
+        Self::estimate_with_std_error(config, |c| {
+            self.price_european_call(spot, strike, rate, dividend_yield, volatility, time_to_maturity, c)
+        })
+    }
+    
+    /// European put price with its standard error
+    #[allow(clippy::too_many_arguments)]
+    pub fn estimate_european_put(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+    ) -> PriceEstimate {
+        Self::estimate_with_std_error(config, |c| {
+            self.price_european_put(spot, strike, rate, dividend_yield, volatility, time_to_maturity, c)
+        })
+    }
+    
+    /// European call priced in rounds, calling `on_progress` with the
+    /// running estimate after each. Stops early if `on_progress` returns
+    /// false.
+    #[allow(clippy::too_many_arguments)]
+    pub fn estimate_european_call_progressive<P>(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+        on_progress: P,
+    ) where
+        P: FnMut(Progress) -> bool,
+    {
+        Self::estimate_progressively(
+            config,
+            |c| self.price_european_call(spot, strike, rate, dividend_yield, volatility, time_to_maturity, c),
+            on_progress,
+        )
+    }
+    
+    /// Run `PROGRESS_ROUNDS` rounds of `STD_ERROR_BATCHES` independent
+    /// sub-runs, folding each sub-run's price into a running mean and
+    /// variance, so every reported standard error uses all the paths so far
+    fn estimate_progressively<F, P>(config: &SimulationConfig, price: F, mut on_progress: P)
+    where
+        F: Fn(&SimulationConfig) -> f64,
+        P: FnMut(Progress) -> bool,
+    {
+        let mut batch_config = config.clone();
+        batch_config.num_simulations =
+            (config.num_simulations / (PROGRESS_ROUNDS * STD_ERROR_BATCHES)).max(1);
+        
+        // Welford's running mean and sum of squared deviations
+        let (mut count, mut mean, mut m2) = (0u64, 0.0, 0.0);
+        
+        for round in 0..PROGRESS_ROUNDS {
+            for _ in 0..STD_ERROR_BATCHES {
+                if config.seed != 0 {
+                    batch_config.seed = config.seed.wrapping_add(count).max(1);
+                }
+                let sample = price(&batch_config);
+                
+                count += 1;
+                let delta = sample - mean;
+                mean += delta / count as f64;
+                m2 += delta * (sample - mean);
+            }
+            
+            let n = count as f64;
+            let progress = Progress {
+                estimate: PriceEstimate {
+                    price: mean,
+                    std_error: (m2 / (n - 1.0) / n).sqrt(),
+                    simulations: count * Self::simulations_run(&batch_config),
+                },
+                complete: round + 1 == PROGRESS_ROUNDS,
+            };
+            if !on_progress(progress) {
+                break;
+            }
+        }
+    }
+    
+    /// Compare the standard error of `price` with each of antithetic and
+    /// control variates switched on and off, all other settings (and the
+    /// seed) held fixed. A ratio is `None` when the run without the
+    /// technique shows no variance to compare against.
+    pub fn variance_reduction<F>(config: &SimulationConfig, price: F) -> VarianceReduction
+    where
+        F: Fn(&SimulationConfig) -> f64,
+    {
+        let variance = |config: &SimulationConfig| {
+            Self::estimate_with_std_error(config, &price).std_error.powi(2)
+        };
+        let ratio = |toggle: fn(&mut SimulationConfig, bool)| {
+            let (mut on, mut off) = (config.clone(), config.clone());
+            toggle(&mut on, true);
+            toggle(&mut off, false);
+            
+            let baseline = variance(&off);
+            (baseline > 0.0).then(|| variance(&on) / baseline)
+        };
+        
+        VarianceReduction {
+            antithetic: ratio(|config, enabled| config.antithetic_enabled = enabled),
+            control_variates: ratio(|config, enabled| config.control_variates_enabled = enabled),
+        }
+    }
+    
+    /// Split the simulation budget into independent sub-runs and use the
+    /// spread of their prices to estimate the standard error of the mean.
+    /// The sub-runs share the budget evenly, so the paths run (reported in
+    /// the estimate) can differ from `num_simulations`.
+    fn estimate_with_std_error<F>(config: &SimulationConfig, price: F) -> PriceEstimate
+    where
+        F: Fn(&SimulationConfig) -> f64,
+    {
+        let batches = STD_ERROR_BATCHES.min(config.num_simulations).max(2);
+        let mut batch_config = config.clone();
+        batch_config.num_simulations = (config.num_simulations / batches).max(1);
+        
+        let prices: Vec<f64> = (0..batches)
+            .map(|i| {
+                // Distinct seeds keep the sub-runs independent but reproducible
+                if config.seed != 0 {
+                    batch_config.seed = config.seed.wrapping_add(i).max(1);
+                }
+                price(&batch_config)
+            })
+            .collect();
+        
+        let n = prices.len() as f64;
+        let mean = prices.iter().sum::<f64>() / n;
+        let variance = prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        
+        PriceEstimate {
+            price: mean,
+            std_error: (variance / n).sqrt(),
+            simulations: batches * Self::simulations_run(&batch_config),
+        }
+    }
+    
+    // Greeks
+    #[allow(clippy::too_many_arguments)]
+    pub fn greeks_european_call(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+        selection: GreekSelection,
+    ) -> Greeks {
+        self.finite_difference_greeks(spot, rate, volatility, time_to_maturity, config, selection, |s, r, v, t, c| {
+            self.price_european_call(s, strike, r, dividend_yield, v, t, c)
+        })
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn greeks_european_put(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+        selection: GreekSelection,
+    ) -> Greeks {
+        self.finite_difference_greeks(spot, rate, volatility, time_to_maturity, config, selection, |s, r, v, t, c| {
+            self.price_european_put(s, strike, r, dividend_yield, v, t, c)
+        })
+    }
+    
+    /// Bump-and-reprice Greeks using central differences where possible.
+    /// Every repricing runs with the same seed (common random numbers),
+    /// otherwise the differences are dominated by Monte Carlo noise. Only
+    /// the repricings the selected Greeks need are run.
+    #[allow(clippy::too_many_arguments)]
+    fn finite_difference_greeks<F>(
+        &self,
+        spot: f64,
+        rate: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+        selection: GreekSelection,
+        price: F,
+    ) -> Greeks
+    where
+        F: Fn(f64, f64, f64, f64, &SimulationConfig) -> f64,
+    {
+        let mut config = config.clone();
+        if config.seed == 0 {
+            config.seed = GREEKS_DEFAULT_SEED;
+        }
+        
+        let base = (selection.gamma || selection.theta)
+            .then(|| price(spot, rate, volatility, time_to_maturity, &config));
+        
+        let ds = spot * SPOT_BUMP;
+        let spot_bumps = (selection.delta || selection.gamma).then(|| {
+            let up = price(spot + ds, rate, volatility, time_to_maturity, &config);
+            let down = price(spot - ds, rate, volatility, time_to_maturity, &config);
+            (up, down)
+        });
+        let delta = spot_bumps
+            .filter(|_| selection.delta)
+            .map(|(up, down)| (up - down) / (2.0 * ds));
+        let gamma = spot_bumps
+            .zip(base)
+            .filter(|_| selection.gamma)
+            .map(|((up, down), base)| (up - 2.0 * base + down) / (ds * ds));
+        
+        // Keep the down-bumped volatility positive
+        let dv = VOL_BUMP.min(volatility / 2.0);
+        let vega = selection.vega.then(|| {
+            if dv > 0.0 {
+                let up = price(spot, rate, volatility + dv, time_to_maturity, &config);
+                let down = price(spot, rate, volatility - dv, time_to_maturity, &config);
+                (up - down) / (2.0 * dv)
+            } else {
+                0.0
+            }
+        });
+        
+        let rho = selection.rho.then(|| {
+            let up = price(spot, rate + RATE_BUMP, volatility, time_to_maturity, &config);
+            let down = price(spot, rate - RATE_BUMP, volatility, time_to_maturity, &config);
+            (up - down) / (2.0 * RATE_BUMP)
+        });
+        
+        // Theta looks forward in calendar time, so shorten the maturity
+        let dt = TIME_BUMP.min(time_to_maturity);
+        let theta = base.filter(|_| selection.theta).map(|base| {
+            if dt > 0.0 {
+                (price(spot, rate, volatility, time_to_maturity - dt, &config) - base) / dt
+            } else {
+                0.0
+            }
+        });
+        
+        Greeks {
+            delta,
+            gamma,
+            vega,
+            theta,
+            rho,
+        }
+    }
+    
+    /// Solve for the volatility at which the European price matches
+    /// `target_price` within `tolerance`, by bisection. Every repricing uses
+    /// the same seed, so the simulated price is monotonic in volatility.
+    #[allow(clippy::too_many_arguments)]
+    pub fn implied_vol_european(
+        &self,
+        is_call: bool,
+        target_price: f64,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        time_to_maturity: f64,
+        tolerance: f64,
+        max_iterations: u32,
+        config: &SimulationConfig,
+    ) -> ImpliedVol {
+        let mut config = config.clone();
+        if config.seed == 0 {
+            config.seed = GREEKS_DEFAULT_SEED;
+        }
+        
+        let price = |volatility: f64| {
+            if is_call {
+                self.price_european_call(spot, strike, rate, dividend_yield, volatility, time_to_maturity, &config)
+            } else {
+                self.price_european_put(spot, strike, rate, dividend_yield, volatility, time_to_maturity, &config)
+            }
+        };
+        
+        let (mut low, mut high) = (IMPLIED_VOL_MIN, IMPLIED_VOL_MAX);
+        let mut result = ImpliedVol {
+            volatility: (low + high) / 2.0,
+            iterations: 0,
+            converged: false,
+            price_error: f64::NAN,
+        };
+        
+        while result.iterations < max_iterations {
+            result.iterations += 1;
+            result.volatility = (low + high) / 2.0;
+            result.price_error = price(result.volatility) - target_price;
+            
+            if result.price_error.abs() <= tolerance {
+                result.converged = true;
+                break;
+            }
+            
+            if result.price_error > 0.0 {
+                high = result.volatility;
+            } else {
+                low = result.volatility;
+            }
+        }
+        
+        result
+    }
+    
     // Asian options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_asian_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         num_observations: u32,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_asian_arithmetic_call(
                 ctx.ptr,
@@ -121,18 +641,20 @@ impl MonteCarloEngine {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_asian_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         num_observations: u32,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_asian_arithmetic_put(
                 ctx.ptr,
@@ -147,18 +669,20 @@ impl MonteCarloEngine {
     }
     
     // American options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_american_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         num_exercise_points: u32,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_american_call(
                 ctx.ptr,
@@ -172,18 +696,20 @@ impl MonteCarloEngine {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_american_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         num_exercise_points: u32,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_american_put(
                 ctx.ptr,
@@ -197,17 +723,19 @@ impl MonteCarloEngine {
         }
     }
     // Bermudan options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_bermudan_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         exercise_dates: &[f64],
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_bermudan_call(
                 ctx.ptr,
@@ -221,17 +749,19 @@ impl MonteCarloEngine {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_bermudan_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         exercise_dates: &[f64],
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_bermudan_put(
                 ctx.ptr,
@@ -246,11 +776,13 @@ impl MonteCarloEngine {
     }
     
     // Barrier options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_barrier_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         barrier_level: f64,
@@ -258,8 +790,8 @@ impl MonteCarloEngine {
         rebate: f64,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_barrier_call(
                 ctx.ptr,
@@ -275,11 +807,13 @@ impl MonteCarloEngine {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_barrier_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         barrier_level: f64,
@@ -287,8 +821,8 @@ impl MonteCarloEngine {
         rebate: f64,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_barrier_put(
                 ctx.ptr,
@@ -305,18 +839,20 @@ impl MonteCarloEngine {
     }
     
     // Lookback options
+    #[allow(clippy::too_many_arguments)]
     pub fn price_lookback_call(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         fixed_strike: bool,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_lookback_call(
                 ctx.ptr,
@@ -330,18 +866,20 @@ impl MonteCarloEngine {
         }
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn price_lookback_put(
         &self,
         spot: f64,
         strike: f64,
         rate: f64,
+        dividend_yield: f64,
         volatility: f64,
         time_to_maturity: f64,
         fixed_strike: bool,
         config: &SimulationConfig,
     ) -> f64 {
-        let mut ctx = self.ctx.lock();
-        ctx.configure(config);
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
         unsafe {
             ffi::mco_lookback_put(
                 ctx.ptr,
@@ -354,12 +892,425 @@ impl MonteCarloEngine {
             )
         }
     }
+    
+    // Digital options
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_digital_call(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        payout: f64,
+        digital_type: DigitalType,
+        config: &SimulationConfig,
+    ) -> f64 {
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
+        unsafe {
+            ffi::mco_digital_call(
+                ctx.ptr,
+                spot,
+                strike,
+                rate,
+                volatility,
+                time_to_maturity,
+                payout,
+                digital_type as i32,
+            )
+        }
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_digital_put(
+        &self,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+        payout: f64,
+        digital_type: DigitalType,
+        config: &SimulationConfig,
+    ) -> f64 {
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
+        unsafe {
+            ffi::mco_digital_put(
+                ctx.ptr,
+                spot,
+                strike,
+                rate,
+                volatility,
+                time_to_maturity,
+                payout,
+                digital_type as i32,
+            )
+        }
+    }
+    
+    // Spread options. Both assets are simulated on the same context, so
+    // the path settings (num_steps, antithetic, ...) apply to each leg.
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_spread_call(
+        &self,
+        spot1: f64,
+        spot2: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility1: f64,
+        volatility2: f64,
+        correlation: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+    ) -> f64 {
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
+        unsafe {
+            ffi::mco_spread_call(
+                ctx.ptr,
+                spot1,
+                spot2,
+                strike,
+                rate,
+                volatility1,
+                volatility2,
+                correlation,
+                time_to_maturity,
+            )
+        }
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_spread_put(
+        &self,
+        spot1: f64,
+        spot2: f64,
+        strike: f64,
+        rate: f64,
+        dividend_yield: f64,
+        volatility1: f64,
+        volatility2: f64,
+        correlation: f64,
+        time_to_maturity: f64,
+        config: &SimulationConfig,
+    ) -> f64 {
+        let mut ctx = self.acquire();
+        ctx.configure(config, dividend_yield);
+        unsafe {
+            ffi::mco_spread_put(
+                ctx.ptr,
+                spot1,
+                spot2,
+                strike,
+                rate,
+                volatility1,
+                volatility2,
+                correlation,
+                time_to_maturity,
+            )
+        }
+    }
 }
 
 impl Clone for MonteCarloEngine {
     fn clone(&self) -> Self {
         Self {
-            ctx: Arc::clone(&self.ctx),
+            contexts: Arc::clone(&self.contexts),
+            next: Arc::clone(&self.next),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+    use std::time::Instant;
+    
+    fn config(num_simulations: u64, antithetic_enabled: bool) -> SimulationConfig {
+        SimulationConfig {
+            num_simulations,
+            seed: 7,
+            antithetic_enabled,
+            ..Default::default()
         }
     }
+    
+    #[test]
+    fn std_error_estimate_reports_the_paths_it_ran() {
+        let paths = AtomicU64::new(0);
+        let estimate = MonteCarloEngine::estimate_with_std_error(&config(1005, false), |config| {
+            paths.fetch_add(config.num_simulations, Ordering::Relaxed);
+            config.seed as f64
+        });
+        
+        // Ten sub-runs of 100 paths leave the last 5 unsimulated
+        assert_eq!(paths.load(Ordering::Relaxed), 1000);
+        assert_eq!(estimate.simulations, 1000);
+    }
+    
+    #[test]
+    fn odd_antithetic_count_is_bumped_and_reported() {
+        let paths = AtomicU64::new(0);
+        let estimate = MonteCarloEngine::estimate_with_std_error(&config(15, true), |config| {
+            paths.fetch_add(MonteCarloEngine::simulations_run(config), Ordering::Relaxed);
+            config.seed as f64
+        });
+        
+        // Ten sub-runs of one path each, every one rounded up to a pair
+        assert_eq!(paths.load(Ordering::Relaxed), 20);
+        assert_eq!(estimate.simulations, 20);
+    }
+    
+    #[test]
+    fn only_the_selected_greeks_are_repriced() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = config(1000, false);
+        // Pricing at the spot makes delta exactly one
+        let repricings = |selection: GreekSelection| {
+            let calls = AtomicU64::new(0);
+            let greeks =
+                engine.finite_difference_greeks(100.0, 0.05, 0.2, 1.0, &config, selection, |s, _, _, _, _| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    s
+                });
+            (greeks, calls.load(Ordering::Relaxed))
+        };
+        
+        let delta_only = GreekSelection { delta: true, ..Default::default() };
+        let (greeks, calls) = repricings(delta_only);
+        assert_eq!(calls, 2);
+        assert_eq!(greeks.delta, Some(1.0));
+        assert!(greeks.gamma.is_none() && greeks.vega.is_none());
+        assert!(greeks.theta.is_none() && greeks.rho.is_none());
+        
+        // Gamma reuses delta's spot bumps and adds the base price
+        let (_, calls) = repricings(GreekSelection { gamma: true, ..delta_only });
+        assert_eq!(calls, 3);
+        assert_eq!(repricings(GreekSelection::ALL).1, 8);
+        assert_eq!(repricings(GreekSelection::default()).1, 0);
+    }
+    
+    #[test]
+    fn throughput_scales_with_the_pool_size() {
+        const THREADS: usize = 4;
+        // Wall time to price a batch across THREADS threads, and the most
+        // contexts seen checked out at once
+        let run = |pool_size| {
+            let engine = MonteCarloEngine::new(pool_size).unwrap();
+            let config = config(200_000, false);
+            let done = AtomicUsize::new(0);
+            let mut peak = 0;
+            let started = Instant::now();
+            std::thread::scope(|scope| {
+                for _ in 0..THREADS {
+                    scope.spawn(|| {
+                        for _ in 0..4 {
+                            engine.price_european_call(100.0, 100.0, 0.05, 0.0, 0.2, 1.0, &config);
+                        }
+                        done.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+                while done.load(Ordering::Relaxed) < THREADS {
+                    peak = peak.max(engine.contexts_in_use());
+                    std::thread::yield_now();
+                }
+            });
+            (started.elapsed(), peak)
+        };
+        
+        let (serial, serial_peak) = run(1);
+        let (pooled, pooled_peak) = run(THREADS);
+        // One context prices one option at a time; a pool prices on every thread
+        assert_eq!(serial_peak, 1);
+        assert_eq!(pooled_peak, THREADS);
+        // Turning that into wall-clock speedup takes a core per thread
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        if cores >= THREADS {
+            assert!(pooled < serial / 2, "{:?} pooled, {:?} on one context", pooled, serial);
+        }
+    }
+    
+    #[test]
+    fn implied_vol_recovers_the_pricing_vol() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = config(20_000, false);
+        
+        for (is_call, volatility) in [(true, 0.25), (false, 0.4)] {
+            let price = if is_call {
+                engine.price_european_call(100.0, 105.0, 0.03, 0.01, volatility, 0.5, &config)
+            } else {
+                engine.price_european_put(100.0, 105.0, 0.03, 0.01, volatility, 0.5, &config)
+            };
+            
+            let solved =
+                engine.implied_vol_european(is_call, price, 100.0, 105.0, 0.03, 0.01, 0.5, 1e-6, 200, &config);
+            assert!(solved.converged, "{:?}", solved);
+            assert!((solved.volatility - volatility).abs() < 1e-3, "{:?}", solved);
+        }
+    }
+    
+    #[test]
+    fn implied_vol_out_of_the_bracket_does_not_converge() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = config(20_000, false);
+        
+        // Below the discounted intrinsic value of a deep in-the-money call,
+        // which even the lowest volatility in the bracket prices above
+        let solved = engine.implied_vol_european(true, 40.0, 100.0, 50.0, 0.03, 0.01, 0.5, 1e-6, 50, &config);
+        assert!(!solved.converged);
+        assert_eq!(solved.iterations, 50);
+        assert!(solved.volatility < IMPLIED_VOL_MIN + 1e-3, "{:?}", solved);
+        assert!(solved.price_error > 0.0);
+    }
+    
+    #[test]
+    fn put_call_parity_holds_with_a_dividend_yield() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let config = config(100_000, true);
+        let (spot, strike, rate, dividend_yield, time) = (100.0, 95.0, 0.04, 0.03, 1.0);
+        
+        let call = engine.price_european_call(spot, strike, rate, dividend_yield, 0.2, time, &config);
+        let put = engine.price_european_put(spot, strike, rate, dividend_yield, 0.2, time, &config);
+        
+        // C - P = S·e^(-qT) - K·e^(-rT), well away from the no-yield forward
+        let forward = spot * (-dividend_yield * time).exp() - strike * (-rate * time).exp();
+        assert!((call - put - forward).abs() < 0.05, "C - P = {}, expected {}", call - put, forward);
+        let without_yield = spot - strike * (-rate * time).exp();
+        assert!((call - put - without_yield).abs() > 1.0);
+    }
+    
+    #[test]
+    fn same_seed_reproduces_the_price() {
+        let engine = MonteCarloEngine::new(2).unwrap();
+        let price = |config: &SimulationConfig| {
+            engine.price_european_call(100.0, 100.0, 0.05, 0.0, 0.2, 1.0, config)
+        };
+        
+        let seeded = config(10_000, false);
+        assert_eq!(price(&seeded), price(&seeded));
+        let mut reseeded = seeded.clone();
+        reseeded.seed += 1;
+        assert_ne!(price(&seeded), price(&reseeded));
+        
+        // An unseeded run gets a random seed, which replays the run
+        let mut unseeded = config(10_000, false);
+        unseeded.seed = 0;
+        let seed = MonteCarloEngine::resolve_seed(&mut unseeded);
+        assert_ne!(seed, 0);
+        assert_eq!(unseeded.seed, seed);
+        assert_eq!(MonteCarloEngine::resolve_seed(&mut unseeded), seed);
+        assert_eq!(price(&unseeded), price(&unseeded));
+    }
+    
+    #[test]
+    fn variance_reduction_ratios_are_below_one_for_a_european_call() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let reduction = MonteCarloEngine::variance_reduction(&config(20_000, false), |config| {
+            engine.price_european_call(100.0, 100.0, 0.05, 0.0, 0.2, 1.0, config)
+        });
+        
+        let control_variates = reduction.control_variates.unwrap();
+        assert!(control_variates < 1.0, "control variates ratio {}", control_variates);
+        let antithetic = reduction.antithetic.unwrap();
+        assert!(antithetic < 1.0, "antithetic ratio {}", antithetic);
+    }
+    
+    #[test]
+    fn more_simulations_shrink_the_interval() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let interval = |num_simulations| {
+            engine
+                .estimate_european_call(100.0, 100.0, 0.05, 0.0, 0.2, 1.0, &config(num_simulations, false))
+                .confidence_95()
+        };
+        
+        // A hundred times the paths should cut the interval about tenfold
+        let (narrow, wide) = (interval(100_000), interval(1_000));
+        assert!(narrow > 0.0);
+        assert!(narrow < wide / 3.0, "±{} with more paths, ±{} with fewer", narrow, wide);
+    }
+    
+    #[test]
+    fn importance_sampling_shrinks_the_std_error_far_out_of_the_money() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let estimate = |config: &SimulationConfig| {
+            engine.estimate_european_call(100.0, 200.0, 0.05, 0.0, 0.2, 1.0, config)
+        };
+        
+        // Few paths finish past a strike twice the spot, so plain Monte Carlo
+        // prices off a handful of them; shifting the draws towards the strike
+        // puts most paths there
+        let plain = estimate(&config(20_000, false));
+        let sampled = estimate(&SimulationConfig {
+            importance_sampling_enabled: true,
+            importance_drift_shift: 3.0,
+            ..config(20_000, false)
+        });
+        
+        assert!(plain.std_error > 0.0);
+        assert!(
+            sampled.std_error < plain.std_error / 4.0,
+            "±{} with importance sampling, ±{} without",
+            sampled.std_error,
+            plain.std_error
+        );
+        // Reweighting keeps the estimate on the Black-Scholes price
+        let black_scholes = 0.004_798_8;
+        let gap = (sampled.price - black_scholes).abs();
+        assert!(gap < 3.0 * sampled.std_error, "{} ± {}", sampled.price, sampled.std_error);
+    }
+    
+    #[test]
+    fn progressive_intervals_narrow() {
+        // Sub-run prices alternating between 9 and 11 keep the spread
+        // steady, so only the growing path count moves the interval
+        let mut reported = Vec::new();
+        MonteCarloEngine::estimate_progressively(
+            &config(10_000, false),
+            |config| if config.seed % 2 == 0 { 9.0 } else { 11.0 },
+            |progress| {
+                reported.push(progress);
+                true
+            },
+        );
+        
+        assert_eq!(reported.len() as u64, PROGRESS_ROUNDS);
+        for pair in reported.windows(2) {
+            assert!(pair[1].estimate.confidence_95() < pair[0].estimate.confidence_95(), "{:?}", pair);
+            assert!(pair[1].estimate.simulations > pair[0].estimate.simulations);
+            assert!(!pair[0].complete);
+        }
+        let last = reported.last().unwrap();
+        assert!(last.complete);
+        assert_eq!(last.estimate.simulations, 10_000);
+        assert_eq!(last.estimate.price, 10.0);
+    }
+    
+    #[test]
+    fn progressive_pricing_stops_when_asked() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let mut reported = Vec::new();
+        engine.estimate_european_call_progressive(
+            100.0,
+            100.0,
+            0.05,
+            0.0,
+            0.2,
+            1.0,
+            &config(20_000, false),
+            |progress| {
+                reported.push(progress);
+                reported.len() < 3
+            },
+        );
+        
+        assert_eq!(reported.len(), 3);
+        assert!(reported[2].estimate.confidence_95() < reported[0].estimate.confidence_95());
+    }
 }
@@ -0,0 +1,212 @@
+use crate::proto::pricing::{PriceResponse, SimulationConfig};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use tonic::metadata::MetadataMap;
+use tonic::Response;
+
+/// A pricing request as the cache tells them apart: the RPC and the
+/// request's encoding, which covers every parameter and the whole
+/// simulation config
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    method: &'static str,
+    request: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct CachedPrice {
+    response: PriceResponse,
+    metadata: MetadataMap,
+    /// Position in `Entries::by_use`
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    prices: HashMap<CacheKey, CachedPrice>,
+    /// Keys by when they were last used, least recent first
+    by_use: BTreeMap<u64, CacheKey>,
+    next_use: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, key: &CacheKey) -> Option<&CachedPrice> {
+        let use_id = self.next_use;
+        let price = self.prices.get_mut(key)?;
+        self.by_use.remove(&price.last_used);
+        self.by_use.insert(use_id, key.clone());
+        price.last_used = use_id;
+        self.next_use += 1;
+        Some(price)
+    }
+}
+
+/// Remembers the responses to recent seeded pricing requests, so a client
+/// asking the same thing again (a UI re-rendering, say) gets the answer
+/// without another simulation. A seeded request always prices the same,
+/// so its response can be reused; unseeded ones get a fresh random seed
+/// each time and are never cached.
+///
+/// Holds at most `capacity` responses, dropping the least recently used
+/// first. A capacity of 0 turns the cache off.
+#[derive(Debug)]
+pub struct PricingCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl PricingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+    
+    /// The key for `request` to `method`, or `None` when it mustn't be
+    /// cached: the cache is off or the request has no seed
+    pub fn key(
+        &self,
+        method: &'static str,
+        request: &impl prost::Message,
+        config: Option<&SimulationConfig>,
+    ) -> Option<CacheKey> {
+        let seeded = config.is_some_and(|config| config.seed != 0);
+        (self.capacity > 0 && seeded).then(|| CacheKey {
+            method,
+            request: request.encode_to_vec(),
+        })
+    }
+    
+    /// The cached response for `key`, marked as a cache hit, with the
+    /// metadata it was first sent with
+    pub fn get(&self, key: &CacheKey) -> Option<Response<PriceResponse>> {
+        let mut entries = self.entries.lock();
+        let price = entries.touch(key)?;
+        
+        let response = PriceResponse {
+            cache_hit: true,
+            ..price.response.clone()
+        };
+        Some(Response::from_parts(price.metadata.clone(), response, Default::default()))
+    }
+    
+    /// Remember `response` for `key`, evicting the least recently used
+    /// response if the cache is full
+    pub fn insert(&self, key: CacheKey, response: &Response<PriceResponse>) {
+        let mut entries = self.entries.lock();
+        if let Some(previous) = entries.prices.remove(&key) {
+            entries.by_use.remove(&previous.last_used);
+        } else if entries.prices.len() >= self.capacity {
+            if let Some((_, oldest)) = entries.by_use.pop_first() {
+                entries.prices.remove(&oldest);
+            }
+        }
+        
+        let use_id = entries.next_use;
+        entries.next_use += 1;
+        entries.by_use.insert(use_id, key.clone());
+        entries.prices.insert(
+            key,
+            CachedPrice {
+                response: response.get_ref().clone(),
+                metadata: response.metadata().clone(),
+                last_used: use_id,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::pricing::EuropeanRequest;
+    
+    fn request(seed: u64) -> EuropeanRequest {
+        EuropeanRequest {
+            spot: 100.0,
+            strike: 100.0,
+            volatility: 0.2,
+            time_to_maturity: 1.0,
+            config: Some(SimulationConfig {
+                num_simulations: 1000,
+                seed,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+    
+    fn priced(price: f64) -> Response<PriceResponse> {
+        let mut response = Response::new(PriceResponse {
+            price,
+            ..Default::default()
+        });
+        response.metadata_mut().insert("x-test", "kept".parse().unwrap());
+        response
+    }
+    
+    #[test]
+    fn only_a_repeated_seeded_request_is_a_hit() {
+        let cache = PricingCache::new(4);
+        let req = request(7);
+        let key = cache.key("european_call", &req, req.config.as_ref()).unwrap();
+        
+        assert!(cache.get(&key).is_none());
+        let first = priced(10.45);
+        assert!(!first.get_ref().cache_hit);
+        cache.insert(key.clone(), &first);
+        
+        let second = cache.get(&key).unwrap();
+        assert!(second.get_ref().cache_hit);
+        assert_eq!(second.get_ref().price, 10.45);
+        assert_eq!(second.metadata().get("x-test").unwrap(), "kept");
+        
+        // Same parameters to another RPC, or another seed, miss
+        let put = cache.key("european_put", &req, req.config.as_ref()).unwrap();
+        assert!(cache.get(&put).is_none());
+        let reseeded = request(8);
+        let reseeded = cache.key("european_call", &reseeded, reseeded.config.as_ref()).unwrap();
+        assert!(cache.get(&reseeded).is_none());
+    }
+    
+    #[test]
+    fn unseeded_requests_are_never_cached() {
+        let cache = PricingCache::new(4);
+        let req = request(0);
+        assert!(cache.key("european_call", &req, req.config.as_ref()).is_none());
+        
+        let defaults = EuropeanRequest {
+            config: None,
+            ..request(7)
+        };
+        assert!(cache.key("european_call", &defaults, None).is_none());
+    }
+    
+    #[test]
+    fn zero_capacity_turns_the_cache_off() {
+        let cache = PricingCache::new(0);
+        let req = request(7);
+        assert!(cache.key("european_call", &req, req.config.as_ref()).is_none());
+    }
+    
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let cache = PricingCache::new(2);
+        let keys: Vec<CacheKey> = (1..=3)
+            .map(|seed| {
+                let req = request(seed);
+                cache.key("european_call", &req, req.config.as_ref()).unwrap()
+            })
+            .collect();
+        
+        cache.insert(keys[0].clone(), &priced(1.0));
+        cache.insert(keys[1].clone(), &priced(2.0));
+        assert!(cache.get(&keys[0]).is_some());
+        cache.insert(keys[2].clone(), &priced(3.0));
+        
+        assert!(cache.get(&keys[0]).is_some());
+        assert!(cache.get(&keys[1]).is_none());
+        assert!(cache.get(&keys[2]).is_some());
+    }
+}
@@ -1,5 +1,6 @@
 // This file is @generated by prost-build.
 /// Timestamp message
+#[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Timestamp {
@@ -39,6 +40,10 @@ impl Side {
 pub enum OrderType {
     Limit = 0,
     Market = 1,
+    /// Market order once a trade reaches stop_price
+    Stop = 2,
+    /// Limit order at price once a trade reaches stop_price
+    StopLimit = 3,
 }
 impl OrderType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -49,6 +54,8 @@ impl OrderType {
         match self {
             OrderType::Limit => "LIMIT",
             OrderType::Market => "MARKET",
+            OrderType::Stop => "STOP",
+            OrderType::StopLimit => "STOP_LIMIT",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -56,6 +63,8 @@ impl OrderType {
         match value {
             "LIMIT" => Some(Self::Limit),
             "MARKET" => Some(Self::Market),
+            "STOP" => Some(Self::Stop),
+            "STOP_LIMIT" => Some(Self::StopLimit),
             _ => None,
         }
     }
@@ -73,6 +82,14 @@ pub enum RejectReason {
     InsufficientFunds = 6,
     MarketClosed = 7,
     SystemError = 8,
+    /// Quantity under the symbol's minimum/lot size
+    BelowMinimumSize = 9,
+    /// Price outside the symbol's allowed band
+    PriceOutOfBand = 10,
+    /// Quantity over the symbol's maximum order size
+    SizeTooLarge = 11,
+    /// User may not trade the symbol
+    InsufficientPermissions = 12,
 }
 impl RejectReason {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -90,6 +107,10 @@ impl RejectReason {
             RejectReason::InsufficientFunds => "INSUFFICIENT_FUNDS",
             RejectReason::MarketClosed => "MARKET_CLOSED",
             RejectReason::SystemError => "SYSTEM_ERROR",
+            RejectReason::BelowMinimumSize => "BELOW_MINIMUM_SIZE",
+            RejectReason::PriceOutOfBand => "PRICE_OUT_OF_BAND",
+            RejectReason::SizeTooLarge => "SIZE_TOO_LARGE",
+            RejectReason::InsufficientPermissions => "INSUFFICIENT_PERMISSIONS",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -104,6 +125,10 @@ impl RejectReason {
             "INSUFFICIENT_FUNDS" => Some(Self::InsufficientFunds),
             "MARKET_CLOSED" => Some(Self::MarketClosed),
             "SYSTEM_ERROR" => Some(Self::SystemError),
+            "BELOW_MINIMUM_SIZE" => Some(Self::BelowMinimumSize),
+            "PRICE_OUT_OF_BAND" => Some(Self::PriceOutOfBand),
+            "SIZE_TOO_LARGE" => Some(Self::SizeTooLarge),
+            "INSUFFICIENT_PERMISSIONS" => Some(Self::InsufficientPermissions),
             _ => None,
         }
     }
@@ -16,5 +16,15 @@ pub mod pricing {
     tonic::include_proto!("pricing");
 }
 
+// Standard gRPC health checking
+pub mod health {
+    tonic::include_proto!("grpc.health.v1");
+}
+
+// Operational diagnostics
+pub mod admin {
+    tonic::include_proto!("admin");
+}
+
 // Re-export commonly used types
 pub use common::Timestamp;
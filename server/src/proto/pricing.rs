@@ -1,4 +1,6 @@
 // This file is @generated by prost-build.
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimulationConfig {
@@ -14,7 +16,19 @@ pub struct SimulationConfig {
     pub control_variates_enabled: bool,
     #[prost(bool, tag = "6")]
     pub stratified_sampling_enabled: bool,
+    #[prost(bool, tag = "7")]
+    pub importance_sampling_enabled: bool,
+    /// Drift shift applied when importance sampling is enabled
+    #[prost(double, tag = "8")]
+    pub importance_drift_shift: f64,
+    /// Also price with each of antithetic and control variates switched on
+    /// and off, and report the variance ratios in the response metadata.
+    /// Roughly quadruples the compute.
+    #[prost(bool, tag = "9")]
+    pub report_variance_reduction: bool,
 }
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EuropeanRequest {
@@ -30,6 +44,12 @@ pub struct EuropeanRequest {
     pub time_to_maturity: f64,
     #[prost(message, optional, tag = "6")]
     pub config: ::core::option::Option<SimulationConfig>,
+    /// Populate Greeks in the response (bump-and-reprice)
+    #[prost(bool, tag = "7")]
+    pub compute_greeks: bool,
+    /// Continuous yield; 0 = no dividends
+    #[prost(double, tag = "8")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -48,6 +68,8 @@ pub struct AmericanRequest {
     pub num_exercise_points: u32,
     #[prost(message, optional, tag = "7")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "8")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -66,6 +88,8 @@ pub struct AsianRequest {
     pub num_observations: u32,
     #[prost(message, optional, tag = "7")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "8")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -88,6 +112,8 @@ pub struct BarrierRequest {
     pub rebate: f64,
     #[prost(message, optional, tag = "9")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "10")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -106,6 +132,8 @@ pub struct LookbackRequest {
     pub fixed_strike: bool,
     #[prost(message, optional, tag = "7")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "8")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -122,6 +150,61 @@ pub struct BermudanRequest {
     pub exercise_dates: ::prost::alloc::vec::Vec<f64>,
     #[prost(message, optional, tag = "6")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "7")]
+    pub dividend_yield: f64,
+}
+/// Pays `payout` if the option finishes in the money. The payoff jumps at
+/// the strike, so prices are noisy; importance sampling helps.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DigitalRequest {
+    #[prost(double, tag = "1")]
+    pub spot: f64,
+    #[prost(double, tag = "2")]
+    pub strike: f64,
+    #[prost(double, tag = "3")]
+    pub rate: f64,
+    #[prost(double, tag = "4")]
+    pub volatility: f64,
+    #[prost(double, tag = "5")]
+    pub time_to_maturity: f64,
+    /// Cash amount, or units of the asset for ASSET_OR_NOTHING
+    #[prost(double, tag = "6")]
+    pub payout: f64,
+    #[prost(enumeration = "DigitalType", tag = "7")]
+    pub digital_type: i32,
+    #[prost(message, optional, tag = "8")]
+    pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "9")]
+    pub dividend_yield: f64,
+}
+/// Option on spot1 - spot2: the call pays max(S1 - S2 - K, 0) at maturity
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpreadRequest {
+    #[prost(double, tag = "1")]
+    pub spot1: f64,
+    #[prost(double, tag = "2")]
+    pub spot2: f64,
+    /// 0 = exchange option
+    #[prost(double, tag = "3")]
+    pub strike: f64,
+    #[prost(double, tag = "4")]
+    pub rate: f64,
+    #[prost(double, tag = "5")]
+    pub volatility1: f64,
+    #[prost(double, tag = "6")]
+    pub volatility2: f64,
+    /// Between the two assets' returns, in \[-1, 1\]
+    #[prost(double, tag = "7")]
+    pub correlation: f64,
+    #[prost(double, tag = "8")]
+    pub time_to_maturity: f64,
+    #[prost(message, optional, tag = "9")]
+    pub config: ::core::option::Option<SimulationConfig>,
+    /// Applied to both assets
+    #[prost(double, tag = "10")]
+    pub dividend_yield: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -142,7 +225,7 @@ pub struct MarketPriceRequest {
     /// Market data will be fetched from order book
     /// Volatility can be provided or implied
     ///
-    /// Optional - will estimate if 0
+    /// Optional - estimated from recent trades if 0
     #[prost(double, tag = "6")]
     pub volatility: f64,
     /// Risk-free rate
@@ -150,7 +233,57 @@ pub struct MarketPriceRequest {
     pub rate: f64,
     #[prost(message, optional, tag = "8")]
     pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "9")]
+    pub dividend_yield: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImpliedVolRequest {
+    /// "CALL" or "PUT" (European)
+    #[prost(string, tag = "1")]
+    pub option_type: ::prost::alloc::string::String,
+    /// Observed option price
+    #[prost(double, tag = "2")]
+    pub target_price: f64,
+    #[prost(double, tag = "3")]
+    pub spot: f64,
+    #[prost(double, tag = "4")]
+    pub strike: f64,
+    #[prost(double, tag = "5")]
+    pub rate: f64,
+    #[prost(double, tag = "6")]
+    pub time_to_maturity: f64,
+    /// 0 = server default; capped by the server
+    #[prost(uint32, tag = "7")]
+    pub max_iterations: u32,
+    /// Absolute price tolerance; 0 = server default
+    #[prost(double, tag = "8")]
+    pub tolerance: f64,
+    #[prost(message, optional, tag = "9")]
+    pub config: ::core::option::Option<SimulationConfig>,
+    #[prost(double, tag = "10")]
+    pub dividend_yield: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImpliedVolResponse {
+    #[prost(double, tag = "1")]
+    pub implied_volatility: f64,
+    #[prost(uint32, tag = "2")]
+    pub iterations: u32,
+    /// False if max_iterations ran out first
+    #[prost(bool, tag = "3")]
+    pub converged: bool,
+    /// Modeled price minus target at the solution
+    #[prost(double, tag = "4")]
+    pub price_error: f64,
+    #[prost(double, tag = "5")]
+    pub computation_time_ms: f64,
+    /// Pass back as config.seed to reproduce this solve
+    #[prost(uint64, tag = "6")]
+    pub seed_used: u64,
 }
+#[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PriceResponse {
@@ -171,6 +304,28 @@ pub struct PriceResponse {
     pub theta: ::core::option::Option<f64>,
     #[prost(double, optional, tag = "8")]
     pub rho: ::core::option::Option<f64>,
+    /// Spot used, when derived from market data
+    #[prost(double, optional, tag = "9")]
+    pub spot: ::core::option::Option<f64>,
+    /// Monte Carlo sampling error (European options)
+    #[prost(double, optional, tag = "10")]
+    pub std_error: ::core::option::Option<f64>,
+    /// 95% interval is price ± confidence_95 (1.96·std_error)
+    #[prost(double, optional, tag = "11")]
+    pub confidence_95: ::core::option::Option<f64>,
+    /// Pass back as config.seed to reproduce this price
+    #[prost(uint64, tag = "12")]
+    pub seed_used: u64,
+    /// Progressive pricing only
+    #[prost(uint64, tag = "13")]
+    pub simulations_completed: u64,
+    /// Final estimate; no more messages follow
+    #[prost(bool, tag = "14")]
+    pub complete: bool,
+    /// Served from the pricing cache (seeded requests only); computation_time_ms
+    /// is that of the run that filled it
+    #[prost(bool, tag = "15")]
+    pub cache_hit: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -181,6 +336,89 @@ pub struct BatchRequest {
     pub european_puts: ::prost::alloc::vec::Vec<EuropeanRequest>,
     #[prost(message, optional, tag = "3")]
     pub config: ::core::option::Option<SimulationConfig>,
+    /// Mixed option types, priced concurrently
+    #[prost(message, repeated, tag = "4")]
+    pub legs: ::prost::alloc::vec::Vec<BatchLeg>,
+    /// Greeks to compute for each leg, by bump-and-reprice with the batch
+    /// seed. Each costs one or two extra pricings per leg, so ask only for
+    /// those needed; none means prices only. European legs only for now.
+    #[prost(enumeration = "Greek", repeated, tag = "5")]
+    pub greeks: ::prost::alloc::vec::Vec<i32>,
+}
+/// One option in a mixed batch. Per-leg configs are ignored in favour of
+/// the batch config.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchLeg {
+    #[prost(oneof = "batch_leg::Option", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12")]
+    pub option: ::core::option::Option<batch_leg::Option>,
+}
+/// Nested message and enum types in `BatchLeg`.
+pub mod batch_leg {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Option {
+        #[prost(message, tag = "1")]
+        EuropeanCall(super::EuropeanRequest),
+        #[prost(message, tag = "2")]
+        EuropeanPut(super::EuropeanRequest),
+        #[prost(message, tag = "3")]
+        AmericanCall(super::AmericanRequest),
+        #[prost(message, tag = "4")]
+        AmericanPut(super::AmericanRequest),
+        #[prost(message, tag = "5")]
+        AsianCall(super::AsianRequest),
+        #[prost(message, tag = "6")]
+        AsianPut(super::AsianRequest),
+        #[prost(message, tag = "7")]
+        BarrierCall(super::BarrierRequest),
+        #[prost(message, tag = "8")]
+        BarrierPut(super::BarrierRequest),
+        #[prost(message, tag = "9")]
+        LookbackCall(super::LookbackRequest),
+        #[prost(message, tag = "10")]
+        LookbackPut(super::LookbackRequest),
+        #[prost(message, tag = "11")]
+        BermudanCall(super::BermudanRequest),
+        #[prost(message, tag = "12")]
+        BermudanPut(super::BermudanRequest),
+    }
+}
+/// Outcome of one leg; a bad leg reports an error without failing the batch
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchLegResult {
+    /// Set when BatchRequest.greeks is and the leg priced
+    #[prost(message, optional, tag = "3")]
+    pub greeks: ::core::option::Option<LegGreeks>,
+    #[prost(oneof = "batch_leg_result::Outcome", tags = "1, 2")]
+    pub outcome: ::core::option::Option<batch_leg_result::Outcome>,
+}
+/// Nested message and enum types in `BatchLegResult`.
+pub mod batch_leg_result {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Outcome {
+        #[prost(double, tag = "1")]
+        Price(f64),
+        #[prost(string, tag = "2")]
+        Error(::prost::alloc::string::String),
+    }
+}
+/// Greeks of one batch leg; only those requested are set
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LegGreeks {
+    #[prost(double, optional, tag = "1")]
+    pub delta: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "2")]
+    pub gamma: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "3")]
+    pub vega: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "4")]
+    pub theta: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "5")]
+    pub rho: ::core::option::Option<f64>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -191,6 +429,59 @@ pub struct BatchResponse {
     pub european_put_prices: ::prost::alloc::vec::Vec<f64>,
     #[prost(double, tag = "3")]
     pub total_computation_time_ms: f64,
+    /// Parallel to BatchRequest.legs
+    #[prost(message, repeated, tag = "4")]
+    pub leg_results: ::prost::alloc::vec::Vec<BatchLegResult>,
+    /// Pass back as config.seed to reproduce these prices
+    #[prost(uint64, tag = "5")]
+    pub seed_used: u64,
+}
+/// A European option priced at every (strike, maturity) pair. The grid is
+/// capped at monte_carlo.max_surface_points points.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SurfaceRequest {
+    #[prost(double, tag = "1")]
+    pub spot: f64,
+    #[prost(double, tag = "2")]
+    pub rate: f64,
+    #[prost(double, tag = "3")]
+    pub volatility: f64,
+    #[prost(double, tag = "4")]
+    pub dividend_yield: f64,
+    #[prost(double, repeated, tag = "5")]
+    pub strikes: ::prost::alloc::vec::Vec<f64>,
+    /// Times to maturity in years
+    #[prost(double, repeated, tag = "6")]
+    pub maturities: ::prost::alloc::vec::Vec<f64>,
+    /// "CALL" or "PUT"
+    #[prost(string, tag = "7")]
+    pub option_type: ::prost::alloc::string::String,
+    /// As in BatchRequest; none means prices only
+    #[prost(enumeration = "Greek", repeated, tag = "8")]
+    pub greeks: ::prost::alloc::vec::Vec<i32>,
+    /// One seed is shared by the whole grid
+    #[prost(message, optional, tag = "9")]
+    pub config: ::core::option::Option<SimulationConfig>,
+}
+/// Row-major: the point for strikes\[i\] and maturities\[j\] is at index
+/// i * num_maturities + j
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SurfaceResponse {
+    #[prost(uint32, tag = "1")]
+    pub num_strikes: u32,
+    #[prost(uint32, tag = "2")]
+    pub num_maturities: u32,
+    #[prost(double, repeated, tag = "3")]
+    pub prices: ::prost::alloc::vec::Vec<f64>,
+    /// Parallel to prices; empty when no greeks were requested
+    #[prost(message, repeated, tag = "4")]
+    pub greeks: ::prost::alloc::vec::Vec<LegGreeks>,
+    #[prost(double, tag = "5")]
+    pub total_computation_time_ms: f64,
+    #[prost(uint64, tag = "6")]
+    pub seed_used: u64,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -224,6 +515,67 @@ impl BarrierType {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DigitalType {
+    CashOrNothing = 0,
+    AssetOrNothing = 1,
+}
+impl DigitalType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DigitalType::CashOrNothing => "CASH_OR_NOTHING",
+            DigitalType::AssetOrNothing => "ASSET_OR_NOTHING",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CASH_OR_NOTHING" => Some(Self::CashOrNothing),
+            "ASSET_OR_NOTHING" => Some(Self::AssetOrNothing),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Greek {
+    Delta = 0,
+    Gamma = 1,
+    Vega = 2,
+    Theta = 3,
+    Rho = 4,
+}
+impl Greek {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Greek::Delta => "DELTA",
+            Greek::Gamma => "GAMMA",
+            Greek::Vega => "VEGA",
+            Greek::Theta => "THETA",
+            Greek::Rho => "RHO",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DELTA" => Some(Self::Delta),
+            "GAMMA" => Some(Self::Gamma),
+            "VEGA" => Some(Self::Vega),
+            "THETA" => Some(Self::Theta),
+            "RHO" => Some(Self::Rho),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod pricing_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -355,6 +707,38 @@ pub mod pricing_service_client {
                 .insert(GrpcMethod::new("pricing.PricingService", "PriceEuropeanPut"));
             self.inner.unary(req, path, codec).await
         }
+        /// Running estimates as the simulation progresses; the last one has
+        /// `complete` set. Cancel the stream to stop early.
+        pub async fn price_european_call_progressive(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EuropeanRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::PriceResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceEuropeanCallProgressive",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "pricing.PricingService",
+                        "PriceEuropeanCallProgressive",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
         /// American Options
         pub async fn price_american_call(
             &mut self,
@@ -580,6 +964,96 @@ pub mod pricing_service_client {
                 .insert(GrpcMethod::new("pricing.PricingService", "PriceBermudanPut"));
             self.inner.unary(req, path, codec).await
         }
+        /// Digital (binary) Options
+        pub async fn price_digital_call(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DigitalRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceDigitalCall",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "PriceDigitalCall"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn price_digital_put(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DigitalRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceDigitalPut",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "PriceDigitalPut"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Spread Options (two correlated assets)
+        pub async fn price_spread_call(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SpreadRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceSpreadCall",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "PriceSpreadCall"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn price_spread_put(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SpreadRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceSpreadPut",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "PriceSpreadPut"));
+            self.inner.unary(req, path, codec).await
+        }
         /// Batch pricing for portfolios
         pub async fn price_batch(
             &mut self,
@@ -603,6 +1077,32 @@ pub mod pricing_service_client {
                 .insert(GrpcMethod::new("pricing.PricingService", "PriceBatch"));
             self.inner.unary(req, path, codec).await
         }
+        /// European prices and Greeks over a grid of strikes and maturities
+        pub async fn price_surface(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SurfaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SurfaceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/PriceSurface",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "PriceSurface"));
+            self.inner.unary(req, path, codec).await
+        }
         /// NEW: Price an option based on current market data
         pub async fn price_from_market(
             &mut self,
@@ -626,6 +1126,32 @@ pub mod pricing_service_client {
                 .insert(GrpcMethod::new("pricing.PricingService", "PriceFromMarket"));
             self.inner.unary(req, path, codec).await
         }
+        /// Solve for the volatility that reproduces a European option price
+        pub async fn implied_volatility(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImpliedVolRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImpliedVolResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/pricing.PricingService/ImpliedVolatility",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("pricing.PricingService", "ImpliedVolatility"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -644,6 +1170,21 @@ pub mod pricing_service_server {
             &self,
             request: tonic::Request<super::EuropeanRequest>,
         ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        /// Server streaming response type for the PriceEuropeanCallProgressive method.
+        type PriceEuropeanCallProgressiveStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::PriceResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Running estimates as the simulation progresses; the last one has
+        /// `complete` set. Cancel the stream to stop early.
+        async fn price_european_call_progressive(
+            &self,
+            request: tonic::Request<super::EuropeanRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::PriceEuropeanCallProgressiveStream>,
+            tonic::Status,
+        >;
         /// American Options
         async fn price_american_call(
             &self,
@@ -689,16 +1230,47 @@ pub mod pricing_service_server {
             &self,
             request: tonic::Request<super::BermudanRequest>,
         ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        /// Digital (binary) Options
+        async fn price_digital_call(
+            &self,
+            request: tonic::Request<super::DigitalRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        async fn price_digital_put(
+            &self,
+            request: tonic::Request<super::DigitalRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        /// Spread Options (two correlated assets)
+        async fn price_spread_call(
+            &self,
+            request: tonic::Request<super::SpreadRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        async fn price_spread_put(
+            &self,
+            request: tonic::Request<super::SpreadRequest>,
+        ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
         /// Batch pricing for portfolios
         async fn price_batch(
             &self,
             request: tonic::Request<super::BatchRequest>,
         ) -> std::result::Result<tonic::Response<super::BatchResponse>, tonic::Status>;
+        /// European prices and Greeks over a grid of strikes and maturities
+        async fn price_surface(
+            &self,
+            request: tonic::Request<super::SurfaceRequest>,
+        ) -> std::result::Result<tonic::Response<super::SurfaceResponse>, tonic::Status>;
         /// NEW: Price an option based on current market data
         async fn price_from_market(
             &self,
             request: tonic::Request<super::MarketPriceRequest>,
         ) -> std::result::Result<tonic::Response<super::PriceResponse>, tonic::Status>;
+        /// Solve for the volatility that reproduces a European option price
+        async fn implied_volatility(
+            &self,
+            request: tonic::Request<super::ImpliedVolRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImpliedVolResponse>,
+            tonic::Status,
+        >;
     }
     /// Pricing Service - Monte Carlo options pricing via FFI to C library
     #[derive(Debug)]
@@ -782,11 +1354,58 @@ pub mod pricing_service_server {
             match req.uri().path() {
                 "/pricing.PricingService/PriceEuropeanCall" => {
                     #[allow(non_camel_case_types)]
-                    struct PriceEuropeanCallSvc<T: PricingService>(pub Arc<T>);
+                    struct PriceEuropeanCallSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::EuropeanRequest>
+                    for PriceEuropeanCallSvc<T> {
+                        type Response = super::PriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EuropeanRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_european_call(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceEuropeanCallSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/pricing.PricingService/PriceEuropeanPut" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceEuropeanPutSvc<T: PricingService>(pub Arc<T>);
                     impl<
                         T: PricingService,
                     > tonic::server::UnaryService<super::EuropeanRequest>
-                    for PriceEuropeanCallSvc<T> {
+                    for PriceEuropeanPutSvc<T> {
                         type Response = super::PriceResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
@@ -798,7 +1417,7 @@ pub mod pricing_service_server {
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PricingService>::price_european_call(&inner, request)
+                                <T as PricingService>::price_european_put(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -811,7 +1430,7 @@ pub mod pricing_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = PriceEuropeanCallSvc(inner);
+                        let method = PriceEuropeanPutSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -827,16 +1446,19 @@ pub mod pricing_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/pricing.PricingService/PriceEuropeanPut" => {
+                "/pricing.PricingService/PriceEuropeanCallProgressive" => {
                     #[allow(non_camel_case_types)]
-                    struct PriceEuropeanPutSvc<T: PricingService>(pub Arc<T>);
+                    struct PriceEuropeanCallProgressiveSvc<T: PricingService>(
+                        pub Arc<T>,
+                    );
                     impl<
                         T: PricingService,
-                    > tonic::server::UnaryService<super::EuropeanRequest>
-                    for PriceEuropeanPutSvc<T> {
+                    > tonic::server::ServerStreamingService<super::EuropeanRequest>
+                    for PriceEuropeanCallProgressiveSvc<T> {
                         type Response = super::PriceResponse;
+                        type ResponseStream = T::PriceEuropeanCallProgressiveStream;
                         type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
+                            tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
@@ -845,7 +1467,10 @@ pub mod pricing_service_server {
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as PricingService>::price_european_put(&inner, request)
+                                <T as PricingService>::price_european_call_progressive(
+                                        &inner,
+                                        request,
+                                    )
                                     .await
                             };
                             Box::pin(fut)
@@ -858,7 +1483,7 @@ pub mod pricing_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = PriceEuropeanPutSvc(inner);
+                        let method = PriceEuropeanCallProgressiveSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -869,7 +1494,7 @@ pub mod pricing_service_server {
                                 max_decoding_message_size,
                                 max_encoding_message_size,
                             );
-                        let res = grpc.unary(method, req).await;
+                        let res = grpc.server_streaming(method, req).await;
                         Ok(res)
                     };
                     Box::pin(fut)
@@ -1344,6 +1969,194 @@ pub mod pricing_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/pricing.PricingService/PriceDigitalCall" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceDigitalCallSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::DigitalRequest>
+                    for PriceDigitalCallSvc<T> {
+                        type Response = super::PriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DigitalRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_digital_call(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceDigitalCallSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/pricing.PricingService/PriceDigitalPut" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceDigitalPutSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::DigitalRequest>
+                    for PriceDigitalPutSvc<T> {
+                        type Response = super::PriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DigitalRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_digital_put(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceDigitalPutSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/pricing.PricingService/PriceSpreadCall" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceSpreadCallSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::SpreadRequest>
+                    for PriceSpreadCallSvc<T> {
+                        type Response = super::PriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SpreadRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_spread_call(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceSpreadCallSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/pricing.PricingService/PriceSpreadPut" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceSpreadPutSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::SpreadRequest>
+                    for PriceSpreadPutSvc<T> {
+                        type Response = super::PriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SpreadRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_spread_put(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceSpreadPutSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/pricing.PricingService/PriceBatch" => {
                     #[allow(non_camel_case_types)]
                     struct PriceBatchSvc<T: PricingService>(pub Arc<T>);
@@ -1390,6 +2203,52 @@ pub mod pricing_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/pricing.PricingService/PriceSurface" => {
+                    #[allow(non_camel_case_types)]
+                    struct PriceSurfaceSvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::SurfaceRequest>
+                    for PriceSurfaceSvc<T> {
+                        type Response = super::SurfaceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SurfaceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::price_surface(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PriceSurfaceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/pricing.PricingService/PriceFromMarket" => {
                     #[allow(non_camel_case_types)]
                     struct PriceFromMarketSvc<T: PricingService>(pub Arc<T>);
@@ -1437,6 +2296,53 @@ pub mod pricing_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/pricing.PricingService/ImpliedVolatility" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImpliedVolatilitySvc<T: PricingService>(pub Arc<T>);
+                    impl<
+                        T: PricingService,
+                    > tonic::server::UnaryService<super::ImpliedVolRequest>
+                    for ImpliedVolatilitySvc<T> {
+                        type Response = super::ImpliedVolResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ImpliedVolRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as PricingService>::implied_volatility(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ImpliedVolatilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -18,6 +18,22 @@ pub struct OrderRequest {
     /// Optional - will be generated if not provided
     #[prost(uint64, tag = "7")]
     pub client_order_id: u64,
+    /// Optional - retries with the same key return the original response
+    #[prost(string, tag = "8")]
+    pub idempotency_key: ::prost::alloc::string::String,
+    /// Cancel the order if the connection it was sent on closes
+    #[prost(bool, tag = "9")]
+    pub cancel_on_disconnect: bool,
+    /// Optional - free-form label (strategy, basket id) echoed on status and executions
+    #[prost(string, tag = "10")]
+    pub tag: ::prost::alloc::string::String,
+    /// Stop and stop-limit orders only: the trade price, in dollars, that
+    /// triggers the order. A buy stop must be above the last trade and a sell
+    /// stop below it. The server holds the order until then, reporting it as
+    /// PENDING_TRIGGER; the order it sends on trigger keeps the same
+    /// client_order_id and tag.
+    #[prost(double, tag = "11")]
+    pub stop_price: f64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -59,12 +75,131 @@ pub struct CancelResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelAllRequest {
+    #[prost(uint64, tag = "1")]
+    pub user_id: u64,
+    /// Optional - all symbols if empty
+    #[prost(string, tag = "2")]
+    pub symbol: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelAllResponse {
+    #[prost(uint32, tag = "1")]
+    pub cancelled_count: u32,
+    #[prost(uint32, tag = "2")]
+    pub failed_count: u32,
+    /// One per live order found
+    #[prost(message, repeated, tag = "3")]
+    pub results: ::prost::alloc::vec::Vec<CancelResponse>,
+    #[prost(message, optional, tag = "4")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplaceRequest {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub user_id: u64,
+    #[prost(uint64, tag = "3")]
+    pub client_order_id: u64,
+    /// Price in dollars (will be converted to cents)
+    #[prost(double, tag = "4")]
+    pub new_price: f64,
+    /// Total quantity; must exceed what has already filled
+    #[prost(uint64, tag = "5")]
+    pub new_quantity: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplaceResponse {
+    #[prost(uint64, tag = "1")]
+    pub client_order_id: u64,
+    #[prost(uint64, tag = "2")]
+    pub exchange_order_id: u64,
+    #[prost(bool, tag = "3")]
+    pub replaced: bool,
+    #[prost(enumeration = "super::common::RejectReason", tag = "4")]
+    pub reject_reason: i32,
+    #[prost(string, tag = "5")]
+    pub error_message: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
+}
+/// Two-sided quotes in several symbols at once. Each side is a leg: it
+/// replaces the user's resting quote on that side, or submits a new limit
+/// order when there isn't one. A zero quantity pulls the quote.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MassQuoteRequest {
+    #[prost(uint64, tag = "1")]
+    pub user_id: u64,
+    /// At most one entry per symbol
+    #[prost(message, repeated, tag = "2")]
+    pub quotes: ::prost::alloc::vec::Vec<QuoteEntry>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteEntry {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    /// Price in dollars (will be converted to cents)
+    #[prost(double, tag = "2")]
+    pub bid_price: f64,
+    /// 0 pulls the bid
+    #[prost(uint64, tag = "3")]
+    pub bid_quantity: u64,
+    #[prost(double, tag = "4")]
+    pub ask_price: f64,
+    /// 0 pulls the ask
+    #[prost(uint64, tag = "5")]
+    pub ask_quantity: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteLegResult {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::common::Side", tag = "2")]
+    pub side: i32,
+    /// 0 when no order was sent for the leg
+    #[prost(uint64, tag = "3")]
+    pub client_order_id: u64,
+    #[prost(uint64, tag = "4")]
+    pub exchange_order_id: u64,
+    #[prost(bool, tag = "5")]
+    pub accepted: bool,
+    #[prost(enumeration = "super::common::RejectReason", tag = "6")]
+    pub reject_reason: i32,
+    #[prost(string, tag = "7")]
+    pub error_message: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MassQuoteResponse {
+    /// Bid then ask, in request order
+    #[prost(message, repeated, tag = "1")]
+    pub legs: ::prost::alloc::vec::Vec<QuoteLegResult>,
+    #[prost(uint32, tag = "2")]
+    pub accepted_count: u32,
+    #[prost(uint32, tag = "3")]
+    pub rejected_count: u32,
+    #[prost(message, optional, tag = "4")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamRequest {
     #[prost(string, tag = "1")]
     pub symbol: ::prost::alloc::string::String,
     /// Optional - for filtering user-specific events
     #[prost(uint64, tag = "2")]
     pub user_id: u64,
+    /// StreamExecutions only: replay the user's executions after this sequence
+    /// before live ones; 0 = live only. OUT_OF_RANGE means resubscribe fresh.
+    #[prost(uint64, tag = "3")]
+    pub resume_from_sequence: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -89,7 +224,14 @@ pub struct ExecutionReport {
     pub leaves_quantity: u64,
     #[prost(message, optional, tag = "10")]
     pub timestamp: ::core::option::Option<super::common::Timestamp>,
+    /// Per user; resume_from_sequence picks up after it
+    #[prost(uint64, tag = "11")]
+    pub sequence: u64,
+    /// The order's tag, as submitted
+    #[prost(string, tag = "12")]
+    pub tag: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TradeReport {
@@ -103,7 +245,32 @@ pub struct TradeReport {
     pub quantity: u64,
     #[prost(message, optional, tag = "5")]
     pub timestamp: ::core::option::Option<super::common::Timestamp>,
+    /// Trades may have been dropped just before this one because the stream
+    /// fell behind (StreamTrades only)
+    #[prost(bool, tag = "6")]
+    pub gap: bool,
+}
+/// Best bid and offer. Bursts are coalesced: clients get the latest quote at
+/// most once per market_data.quote_interval_ms.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteReport {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    /// 0 when there are no bids
+    #[prost(double, tag = "2")]
+    pub bid: f64,
+    #[prost(uint64, tag = "3")]
+    pub bid_size: u64,
+    /// 0 when there are no asks
+    #[prost(double, tag = "4")]
+    pub ask: f64,
+    #[prost(uint64, tag = "5")]
+    pub ask_size: u64,
+    #[prost(message, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
 }
+#[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OrderBookSnapshot {
@@ -119,6 +286,7 @@ pub struct OrderBookSnapshot {
     #[prost(uint32, tag = "5")]
     pub sequence: u32,
 }
+#[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PriceLevel {
@@ -130,12 +298,66 @@ pub struct PriceLevel {
     #[prost(uint32, tag = "3")]
     pub order_count: u32,
 }
+/// StreamOrderBook: one snapshot, then a delta per level change. Each delta's
+/// sequence is one past the previous update's; a jump means an update was
+/// missed and the client should resubscribe for a fresh snapshot.
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BookUpdate {
+    /// Set on a snapshot resent because deltas were missed; the client's book
+    /// went stale at expected_sequence and is replaced by this snapshot
+    #[prost(message, optional, tag = "3")]
+    pub gap: ::core::option::Option<SequenceGap>,
+    #[prost(oneof = "book_update::Update", tags = "1, 2")]
+    pub update: ::core::option::Option<book_update::Update>,
+}
+/// Nested message and enum types in `BookUpdate`.
+pub mod book_update {
+    #[derive(serde::Serialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Update {
+        #[prost(message, tag = "1")]
+        Snapshot(super::OrderBookSnapshot),
+        #[prost(message, tag = "2")]
+        Delta(super::BookDelta),
+    }
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SequenceGap {
+    /// First delta missed
+    #[prost(uint64, tag = "1")]
+    pub expected_sequence: u64,
+    /// Delta that revealed the gap
+    #[prost(uint64, tag = "2")]
+    pub received_sequence: u64,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BookDelta {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub sequence: u32,
+    #[prost(enumeration = "super::common::Side", tag = "3")]
+    pub side: i32,
+    #[prost(enumeration = "BookAction", tag = "4")]
+    pub action: i32,
+    #[prost(message, optional, tag = "5")]
+    pub level: ::core::option::Option<PriceLevel>,
+    #[prost(message, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OrderBookRequest {
     #[prost(string, tag = "1")]
     pub symbol: ::prost::alloc::string::String,
-    /// Number of levels (0 = all)
+    /// Number of levels per side (must be > 0, capped by the server)
     #[prost(uint32, tag = "2")]
     pub depth: u32,
 }
@@ -166,11 +388,117 @@ pub struct OrderStatusResponse {
     pub filled_quantity: u64,
     #[prost(uint64, tag = "8")]
     pub remaining_quantity: u64,
-    /// "OPEN", "FILLED", "CANCELLED", "REJECTED"
+    /// "PENDING_TRIGGER" (held stops), "PENDING_NEW", "OPEN", "PARTIALLY_FILLED", "FILLED", "CANCELLED", "REJECTED"
     #[prost(string, tag = "9")]
     pub status: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "10")]
     pub timestamp: ::core::option::Option<super::common::Timestamp>,
+    /// Volume-weighted, in dollars
+    #[prost(double, tag = "11")]
+    pub average_fill_price: f64,
+    /// As submitted with the order
+    #[prost(string, tag = "12")]
+    pub tag: ::prost::alloc::string::String,
+}
+/// EstimateFill: what an order of `quantity` would fill at if it swept the
+/// book as it stands now (up to the server's maximum book depth)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimateFillRequest {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    /// BUY walks the asks, SELL the bids
+    #[prost(enumeration = "super::common::Side", tag = "2")]
+    pub side: i32,
+    #[prost(uint64, tag = "3")]
+    pub quantity: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimateFillResponse {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::common::Side", tag = "2")]
+    pub side: i32,
+    #[prost(uint64, tag = "3")]
+    pub filled_quantity: u64,
+    /// More than the book holds
+    #[prost(uint64, tag = "4")]
+    pub unfilled_quantity: u64,
+    /// Size-weighted over filled_quantity; 0 if nothing fills
+    #[prost(double, tag = "5")]
+    pub average_price: f64,
+    /// Top of the side walked; 0 if it's empty
+    #[prost(double, tag = "6")]
+    pub best_price: f64,
+    /// Deepest level reached; 0 if nothing fills
+    #[prost(double, tag = "7")]
+    pub worst_price: f64,
+    #[prost(uint32, tag = "8")]
+    pub levels_consumed: u32,
+    /// average_price's distance from best_price against the order, in basis points
+    #[prost(double, tag = "9")]
+    pub impact_bps: f64,
+    /// Of the book snapshot
+    #[prost(message, optional, tag = "10")]
+    pub timestamp: ::core::option::Option<super::common::Timestamp>,
+}
+/// GetPositions: a user's net position per symbol, from their executions
+/// since the server started
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PositionsRequest {
+    #[prost(uint64, tag = "1")]
+    pub user_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PositionsResponse {
+    /// Sorted by symbol; flat symbols left out
+    #[prost(message, repeated, tag = "1")]
+    pub positions: ::prost::alloc::vec::Vec<Position>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Position {
+    #[prost(string, tag = "1")]
+    pub symbol: ::prost::alloc::string::String,
+    /// Positive long, negative short
+    #[prost(int64, tag = "2")]
+    pub quantity: i64,
+    /// Volume-weighted price of the open quantity, in dollars
+    #[prost(double, tag = "3")]
+    pub average_cost: f64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum BookAction {
+    Add = 0,
+    Change = 1,
+    /// Only the level's price is set
+    Delete = 2,
+}
+impl BookAction {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BookAction::Add => "ADD",
+            BookAction::Change => "CHANGE",
+            BookAction::Delete => "DELETE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ADD" => Some(Self::Add),
+            "CHANGE" => Some(Self::Change),
+            "DELETE" => Some(Self::Delete),
+            _ => None,
+        }
+    }
 }
 /// Generated client implementations.
 pub mod trading_service_client {
@@ -303,6 +631,81 @@ pub mod trading_service_client {
                 .insert(GrpcMethod::new("trading.TradingService", "CancelOrder"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn cancel_all_orders(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelAllRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelAllResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/CancelAllOrders",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "CancelAllOrders"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn replace_order(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReplaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReplaceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/ReplaceOrder",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "ReplaceOrder"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn mass_quote(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MassQuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MassQuoteResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/MassQuote",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "MassQuote"));
+            self.inner.unary(req, path, codec).await
+        }
         /// Market data streams
         pub async fn stream_executions(
             &mut self,
@@ -333,7 +736,7 @@ pub mod trading_service_client {
             &mut self,
             request: impl tonic::IntoRequest<super::StreamRequest>,
         ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::OrderBookSnapshot>>,
+            tonic::Response<tonic::codec::Streaming<super::BookUpdate>>,
             tonic::Status,
         > {
             self.inner
@@ -379,6 +782,31 @@ pub mod trading_service_client {
                 .insert(GrpcMethod::new("trading.TradingService", "StreamTrades"));
             self.inner.server_streaming(req, path, codec).await
         }
+        pub async fn stream_quotes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::QuoteReport>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/StreamQuotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "StreamQuotes"));
+            self.inner.server_streaming(req, path, codec).await
+        }
         /// Query operations
         pub async fn get_order_book(
             &mut self,
@@ -430,6 +858,56 @@ pub mod trading_service_client {
                 .insert(GrpcMethod::new("trading.TradingService", "GetOrderStatus"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn estimate_fill(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EstimateFillRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EstimateFillResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/EstimateFill",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "EstimateFill"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_positions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PositionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PositionsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/trading.TradingService/GetPositions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("trading.TradingService", "GetPositions"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -442,12 +920,30 @@ pub mod trading_service_server {
         /// Order operations
         async fn submit_order(
             &self,
-            request: tonic::Request<super::OrderRequest>,
-        ) -> std::result::Result<tonic::Response<super::OrderResponse>, tonic::Status>;
-        async fn cancel_order(
+            request: tonic::Request<super::OrderRequest>,
+        ) -> std::result::Result<tonic::Response<super::OrderResponse>, tonic::Status>;
+        async fn cancel_order(
+            &self,
+            request: tonic::Request<super::CancelRequest>,
+        ) -> std::result::Result<tonic::Response<super::CancelResponse>, tonic::Status>;
+        async fn cancel_all_orders(
+            &self,
+            request: tonic::Request<super::CancelAllRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelAllResponse>,
+            tonic::Status,
+        >;
+        async fn replace_order(
+            &self,
+            request: tonic::Request<super::ReplaceRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReplaceResponse>, tonic::Status>;
+        async fn mass_quote(
             &self,
-            request: tonic::Request<super::CancelRequest>,
-        ) -> std::result::Result<tonic::Response<super::CancelResponse>, tonic::Status>;
+            request: tonic::Request<super::MassQuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::MassQuoteResponse>,
+            tonic::Status,
+        >;
         /// Server streaming response type for the StreamExecutions method.
         type StreamExecutionsStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::ExecutionReport, tonic::Status>,
@@ -464,7 +960,7 @@ pub mod trading_service_server {
         >;
         /// Server streaming response type for the StreamOrderBook method.
         type StreamOrderBookStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::OrderBookSnapshot, tonic::Status>,
+                Item = std::result::Result<super::BookUpdate, tonic::Status>,
             >
             + Send
             + 'static;
@@ -488,6 +984,19 @@ pub mod trading_service_server {
             tonic::Response<Self::StreamTradesStream>,
             tonic::Status,
         >;
+        /// Server streaming response type for the StreamQuotes method.
+        type StreamQuotesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::QuoteReport, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn stream_quotes(
+            &self,
+            request: tonic::Request<super::StreamRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamQuotesStream>,
+            tonic::Status,
+        >;
         /// Query operations
         async fn get_order_book(
             &self,
@@ -503,6 +1012,20 @@ pub mod trading_service_server {
             tonic::Response<super::OrderStatusResponse>,
             tonic::Status,
         >;
+        async fn estimate_fill(
+            &self,
+            request: tonic::Request<super::EstimateFillRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EstimateFillResponse>,
+            tonic::Status,
+        >;
+        async fn get_positions(
+            &self,
+            request: tonic::Request<super::PositionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PositionsResponse>,
+            tonic::Status,
+        >;
     }
     /// Trading Service - handles order submission and market data
     #[derive(Debug)]
@@ -676,6 +1199,145 @@ pub mod trading_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/trading.TradingService/CancelAllOrders" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelAllOrdersSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::UnaryService<super::CancelAllRequest>
+                    for CancelAllOrdersSvc<T> {
+                        type Response = super::CancelAllResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelAllRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::cancel_all_orders(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CancelAllOrdersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/trading.TradingService/ReplaceOrder" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReplaceOrderSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::UnaryService<super::ReplaceRequest>
+                    for ReplaceOrderSvc<T> {
+                        type Response = super::ReplaceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReplaceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::replace_order(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReplaceOrderSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/trading.TradingService/MassQuote" => {
+                    #[allow(non_camel_case_types)]
+                    struct MassQuoteSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::UnaryService<super::MassQuoteRequest>
+                    for MassQuoteSvc<T> {
+                        type Response = super::MassQuoteResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MassQuoteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::mass_quote(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = MassQuoteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/trading.TradingService/StreamExecutions" => {
                     #[allow(non_camel_case_types)]
                     struct StreamExecutionsSvc<T: TradingService>(pub Arc<T>);
@@ -731,7 +1393,7 @@ pub mod trading_service_server {
                         T: TradingService,
                     > tonic::server::ServerStreamingService<super::StreamRequest>
                     for StreamOrderBookSvc<T> {
-                        type Response = super::OrderBookSnapshot;
+                        type Response = super::BookUpdate;
                         type ResponseStream = T::StreamOrderBookStream;
                         type Future = BoxFuture<
                             tonic::Response<Self::ResponseStream>,
@@ -819,6 +1481,53 @@ pub mod trading_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/trading.TradingService/StreamQuotes" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamQuotesSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::ServerStreamingService<super::StreamRequest>
+                    for StreamQuotesSvc<T> {
+                        type Response = super::QuoteReport;
+                        type ResponseStream = T::StreamQuotesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::stream_quotes(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamQuotesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/trading.TradingService/GetOrderBook" => {
                     #[allow(non_camel_case_types)]
                     struct GetOrderBookSvc<T: TradingService>(pub Arc<T>);
@@ -912,6 +1621,98 @@ pub mod trading_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/trading.TradingService/EstimateFill" => {
+                    #[allow(non_camel_case_types)]
+                    struct EstimateFillSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::UnaryService<super::EstimateFillRequest>
+                    for EstimateFillSvc<T> {
+                        type Response = super::EstimateFillResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EstimateFillRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::estimate_fill(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = EstimateFillSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/trading.TradingService/GetPositions" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPositionsSvc<T: TradingService>(pub Arc<T>);
+                    impl<
+                        T: TradingService,
+                    > tonic::server::UnaryService<super::PositionsRequest>
+                    for GetPositionsSvc<T> {
+                        type Response = super::PositionsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PositionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as TradingService>::get_positions(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetPositionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -0,0 +1,171 @@
+use crate::config::{RateLimitConfig, UserRateLimit};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tonic::Status;
+
+/// Refill rate and capacity of one user's bucket
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    per_second: f64,
+    burst: f64,
+}
+
+impl Limit {
+    /// `None` when the rate is zero, meaning unlimited
+    fn new(orders_per_second: f64, burst: Option<u32>) -> Option<Self> {
+        (orders_per_second > 0.0).then(|| Self {
+            per_second: orders_per_second,
+            // Default to one second's worth of orders
+            burst: burst.map_or(orders_per_second.ceil().max(1.0), f64::from),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket rate limiter keyed by user id. Each user starts with a full
+/// bucket of `burst` tokens that refills at `orders_per_second`; every
+/// order or cancel takes one token.
+pub struct RateLimiter {
+    default_limit: Option<Limit>,
+    overrides: HashMap<u64, Option<Limit>>,
+    buckets: DashMap<u64, Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            default_limit: Limit::new(config.orders_per_second, config.burst),
+            overrides: config
+                .overrides
+                .iter()
+                .map(|UserRateLimit { user_id, orders_per_second, burst }| {
+                    (*user_id, Limit::new(*orders_per_second, *burst))
+                })
+                .collect(),
+            buckets: DashMap::new(),
+        }
+    }
+    
+    /// Take a token for `user_id`, or report how long until one is available
+    pub fn try_acquire(&self, user_id: u64) -> Result<(), Duration> {
+        let Some(limit) = self
+            .overrides
+            .get(&user_id)
+            .copied()
+            .unwrap_or(self.default_limit)
+        else {
+            return Ok(());
+        };
+        
+        let now = Instant::now();
+        // The entry guard holds the shard lock, so refill-and-take is atomic
+        let mut bucket = self.buckets.entry(user_id).or_insert_with(|| Bucket {
+            tokens: limit.burst,
+            refilled_at: now,
+        });
+        
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.per_second).min(limit.burst);
+        bucket.refilled_at = now;
+        
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / limit.per_second))
+        }
+    }
+    
+    /// `try_acquire` as a gRPC status
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self, user_id: u64) -> Result<(), Status> {
+        self.try_acquire(user_id).map_err(|retry_after| {
+            Status::resource_exhausted(format!(
+                "Rate limit exceeded for user {}, retry in {}ms",
+                user_id,
+                (retry_after.as_secs_f64() * 1000.0).ceil() as u64
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn rate_limiter(orders_per_second: f64, burst: Option<u32>, overrides: Vec<UserRateLimit>) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            orders_per_second,
+            burst,
+            overrides,
+        })
+    }
+    
+    #[test]
+    fn burst_is_allowed_back_to_back() {
+        let limiter = rate_limiter(10.0, Some(5), Vec::new());
+        for _ in 0..5 {
+            assert!(limiter.try_acquire(1).is_ok());
+        }
+        let retry_after = limiter.try_acquire(1).unwrap_err();
+        assert!(retry_after <= Duration::from_millis(100), "{:?}", retry_after);
+        assert!(limiter.check(1).is_err());
+        
+        // Other users have their own buckets
+        assert!(limiter.try_acquire(2).is_ok());
+        
+        // Without a burst, one second's worth
+        let limiter = rate_limiter(2.5, None, Vec::new());
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(1).is_ok());
+        }
+        assert!(limiter.try_acquire(1).is_err());
+    }
+    
+    #[test]
+    fn tokens_refill_at_the_rate() {
+        let limiter = rate_limiter(50.0, Some(1), Vec::new());
+        assert!(limiter.try_acquire(1).is_ok());
+        let retry_after = limiter.try_acquire(1).unwrap_err();
+        
+        std::thread::sleep(retry_after + Duration::from_millis(5));
+        assert!(limiter.try_acquire(1).is_ok());
+        assert!(limiter.try_acquire(1).is_err());
+    }
+    
+    #[test]
+    fn overrides_replace_the_default_limit() {
+        let overrides = vec![
+            UserRateLimit {
+                user_id: 2,
+                orders_per_second: 1.0,
+                burst: Some(3),
+            },
+            UserRateLimit {
+                user_id: 3,
+                orders_per_second: 0.0,
+                burst: None,
+            },
+        ];
+        let limiter = rate_limiter(1.0, Some(1), overrides);
+        
+        assert!(limiter.try_acquire(1).is_ok());
+        assert!(limiter.try_acquire(1).is_err());
+        
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(2).is_ok());
+        }
+        assert!(limiter.try_acquire(2).is_err());
+        
+        // A zero rate exempts the user
+        for _ in 0..100 {
+            assert!(limiter.try_acquire(3).is_ok());
+        }
+    }
+}
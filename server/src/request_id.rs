@@ -0,0 +1,144 @@
+use futures::future::BoxFuture;
+use std::task::{Context, Poll};
+use tonic::codegen::http::{HeaderValue, Request, Response};
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+/// Metadata key carrying the request id, in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied id we accept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Runs every RPC inside a `rpc` tracing span tagged with its request id,
+/// so log lines from concurrent requests can be told apart. The id comes
+/// from the `x-request-id` header, or a fresh UUID when absent, and is
+/// echoed back in the response headers.
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+    
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+    
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let request_id = match request.headers().get(REQUEST_ID_HEADER) {
+            Some(id) if id.len() <= MAX_REQUEST_ID_LEN && id.to_str().is_ok() => id.clone(),
+            _ => {
+                let id = HeaderValue::from_str(&generate_request_id())
+                    .expect("UUIDs are valid header values");
+                // Handlers see the same id as the span and the response
+                request.headers_mut().insert(REQUEST_ID_HEADER, id.clone());
+                id
+            }
+        };
+        
+        let span = tracing::info_span!(
+            "rpc",
+            request_id = request_id.to_str().unwrap_or_default(),
+            method = request.uri().path(),
+        );
+        
+        let future = span.in_scope(|| self.inner.call(request));
+        
+        Box::pin(
+            async move {
+                let mut response = future.await?;
+                response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Random (version 4) UUID in its hyphenated form
+fn generate_request_id() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // Version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+    
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogFormat;
+    use crate::logging::CapturedLogs;
+    use std::convert::Infallible;
+    
+    /// Run a request through the layer onto a handler that logs a line,
+    /// returning the response and the `rpc` span the line was logged in
+    async fn handle(request: Request<()>) -> (Response<()>, serde_json::Value) {
+        let logs = CapturedLogs::default();
+        let _guard = tracing::subscriber::set_default(logs.subscriber(LogFormat::Json));
+        let handler = tower::service_fn(|_: Request<()>| async {
+            tracing::info!("handling");
+            Ok::<_, Infallible>(Response::new(()))
+        });
+        
+        let response = RequestIdLayer.layer(handler).call(request).await.unwrap();
+        let line = logs.json_lines().pop().expect("the handler logged a line");
+        assert_eq!(line["fields"]["message"], "handling");
+        (response, line["span"].clone())
+    }
+    
+    #[tokio::test]
+    async fn span_carries_the_received_request_id() {
+        let request = Request::builder()
+            .uri("/trading.TradingService/SubmitOrder")
+            .header(REQUEST_ID_HEADER, "abc-123")
+            .body(())
+            .unwrap();
+        
+        let (response, span) = handle(request).await;
+        assert_eq!(span["name"], "rpc");
+        assert_eq!(span["request_id"], "abc-123");
+        assert_eq!(span["method"], "/trading.TradingService/SubmitOrder");
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+    }
+    
+    #[tokio::test]
+    async fn missing_request_id_is_generated_and_echoed() {
+        let request = Request::builder()
+            .uri("/pricing.PricingService/PriceEuropeanCall")
+            .body(())
+            .unwrap();
+        
+        let (response, span) = handle(request).await;
+        let id = span["request_id"].as_str().unwrap();
+        assert_eq!(id.len(), 36, "{}", id);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], id);
+    }
+}
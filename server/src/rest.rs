@@ -0,0 +1,263 @@
+use crate::proto::pricing::{pricing_service_server::PricingService, EuropeanRequest, PriceResponse};
+use crate::services::PricingServiceImpl;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use hyper::server::conn::http1;
+use hyper_util::rt::{TokioIo, TokioTimer};
+use hyper_util::service::TowerToHyperService;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+use tokio::time::Duration;
+use tonic::service::Interceptor;
+use tonic::{Code, Request, Status};
+use tracing::{debug, info, warn};
+
+/// Largest request body accepted; a pricing request is a few hundred bytes
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// How long a client gets to send a request's headers
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Accept HTTP clients on an already bound listener until the process
+/// exits, at most `max_connections` at a time:
+///
+/// - `POST /price/european/call` and `POST /price/european/put` take an
+///   `EuropeanRequest` as JSON (omitted fields take their proto defaults)
+///   and answer with the `PriceResponse` as JSON
+///
+/// Requests run through `auth` and the same `PricingServiceImpl` as the
+/// gRPC service. Failures are answered with a matching HTTP status and a
+/// `{"code": <gRPC code>, "message": ...}` body.
+pub async fn serve<I>(listener: TcpListener, pricing: PricingServiceImpl, auth: I, max_connections: usize)
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    if let Ok(addr) = listener.local_addr() {
+        info!("Pricing REST endpoint available at http://{}/price/european/call", addr);
+    }
+    
+    let app = Router::new()
+        .route("/price/european/call", post(price_european_call::<I>))
+        .route("/price/european/put", post(price_european_put::<I>))
+        .fallback(|| async { error(StatusCode::NOT_FOUND, Code::NotFound, "Not Found") })
+        .method_not_allowed_fallback(|| async {
+            error(StatusCode::METHOD_NOT_ALLOWED, Code::Unimplemented, "Method Not Allowed")
+        })
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .with_state(PricingEndpoint { pricing, auth });
+    
+    // A connection holds its permit until it closes; past the limit new
+    // connections wait in the listener's backlog
+    let connections = Arc::new(Semaphore::new(max_connections));
+    loop {
+        let Ok(permit) = Arc::clone(&connections).acquire_owned().await else {
+            return;
+        };
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let service = TowerToHyperService::new(app.clone());
+                tokio::spawn(async move {
+                    let connection = http1::Builder::new()
+                        .timer(TokioTimer::new())
+                        .header_read_timeout(REQUEST_TIMEOUT)
+                        .serve_connection(TokioIo::new(stream), service);
+                    if let Err(e) = connection.await {
+                        debug!("REST connection from {} failed: {}", peer, e);
+                    }
+                    drop(permit);
+                });
+            }
+            Err(e) => {
+                warn!("Failed to accept REST connection: {}", e);
+            }
+        }
+    }
+}
+
+/// HTTP status for a failed gRPC call
+pub fn http_status(status: &Status) -> StatusCode {
+    match status.code() {
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[derive(Clone)]
+struct PricingEndpoint<I> {
+    pricing: PricingServiceImpl,
+    auth: I,
+}
+
+async fn price_european_call<I: Interceptor + Clone>(
+    State(endpoint): State<PricingEndpoint<I>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    endpoint.price_european(false, &headers, body).await
+}
+
+async fn price_european_put<I: Interceptor + Clone>(
+    State(endpoint): State<PricingEndpoint<I>>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    endpoint.price_european(true, &headers, body).await
+}
+
+impl<I: Interceptor + Clone> PricingEndpoint<I> {
+    /// Authorize the request, then price it as the gRPC call would
+    async fn price_european(&self, put: bool, headers: &HeaderMap, body: Result<Bytes, BytesRejection>) -> Response {
+        let body = match body {
+            Ok(body) => body,
+            Err(rejection) => return error(rejection.status(), Code::InvalidArgument, &rejection.body_text()),
+        };
+        match self.call(put, headers, &body).await {
+            Ok(response) => Json(response).into_response(),
+            Err(status) => error(http_status(&status), status.code(), status.message()),
+        }
+    }
+    
+    async fn call(&self, put: bool, headers: &HeaderMap, body: &[u8]) -> Result<PriceResponse, Status> {
+        let mut request = Request::new(());
+        if let Some(authorization) = headers.get(AUTHORIZATION) {
+            let value = authorization
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| Status::unauthenticated("Invalid authorization header"))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        let (metadata, extensions, ()) = self.auth.clone().call(request)?.into_parts();
+        
+        let european: EuropeanRequest = serde_json::from_slice(body)
+            .map_err(|e| Status::invalid_argument(format!("Invalid request body: {}", e)))?;
+        let request = Request::from_parts(metadata, extensions, european);
+        
+        let response = if put {
+            self.pricing.price_european_put(request).await?
+        } else {
+            self.pricing.price_european_call(request).await?
+        };
+        Ok(response.into_inner())
+    }
+}
+
+fn error(status: StatusCode, code: Code, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "code": code as i32, "message": message }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthInterceptor;
+    use crate::matching::MatchingClient;
+    use crate::pricing::{black_scholes_call, MonteCarloEngine};
+    use crate::pricing_cache::PricingCache;
+    use crate::services::pricing::SimulationDefaults;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::time::timeout;
+    
+    async fn endpoint(max_connections: usize) -> String {
+        let config = crate::config::Config::default();
+        let pricing = PricingServiceImpl::new(
+            Arc::new(MonteCarloEngine::new(2).unwrap()),
+            Arc::new(MatchingClient::without_gateway(100).await),
+            SimulationDefaults::new(&config.monte_carlo),
+            2,
+            Duration::from_secs(5),
+            None,
+            PricingCache::new(0),
+            config.monte_carlo.max_surface_points,
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener, pricing, AuthInterceptor::new(None, false), max_connections));
+        address
+    }
+    
+    /// Send one request, returning the status code and JSON body
+    async fn send(address: &str, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        
+        let mut response = String::new();
+        timeout(Duration::from_secs(10), stream.read_to_string(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+    
+    #[tokio::test]
+    async fn prices_a_european_call() {
+        let address = endpoint(4).await;
+        let body = r#"{"spot":100,"strike":100,"rate":0.05,"volatility":0.2,"time_to_maturity":1}"#;
+        
+        let (status, response) = send(&address, "POST", "/price/european/call", body).await;
+        assert_eq!(status, 200, "{}", response);
+        let price = response["price"].as_f64().unwrap();
+        let expected = black_scholes_call(100.0, 100.0, 0.05, 0.0, 0.2, 1.0);
+        assert!((price - expected).abs() < 0.5, "{} vs {}", price, expected);
+    }
+    
+    #[tokio::test]
+    async fn failures_are_json_errors() {
+        let address = endpoint(4).await;
+        
+        let (status, response) = send(&address, "POST", "/price/european/call", "{").await;
+        assert_eq!(status, 400);
+        assert_eq!(response["code"], Code::InvalidArgument as i32);
+        
+        let (status, response) = send(&address, "POST", "/price/american/call", "{}").await;
+        assert_eq!(status, 404);
+        assert_eq!(response["code"], Code::NotFound as i32);
+        
+        let (status, _) = send(&address, "GET", "/price/european/put", "").await;
+        assert_eq!(status, 405);
+    }
+    
+    #[tokio::test]
+    async fn connections_past_the_limit_wait() {
+        let address = endpoint(1).await;
+        
+        // An idle connection holds the only slot until it closes
+        let idle = TcpStream::connect(&address).await.unwrap();
+        let body = r#"{"spot":100,"strike":100,"rate":0.05,"volatility":0.2,"time_to_maturity":1}"#;
+        let waiting = tokio::spawn({
+            let address = address.clone();
+            async move { send(&address, "POST", "/price/european/call", body).await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!waiting.is_finished());
+        
+        drop(idle);
+        let (status, _) = timeout(Duration::from_secs(10), waiting).await.unwrap().unwrap();
+        assert_eq!(status, 200);
+    }
+}
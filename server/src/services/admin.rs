@@ -0,0 +1,482 @@
+use crate::matching::{ConnectionStats, MatchingClient, OrderStore};
+use crate::order_throttle::OrderThrottle;
+use crate::pricing::{black_scholes_call, MonteCarloEngine};
+use crate::proto::{
+    admin::{
+        admin_service_server::AdminService, ConnectionStats as ProtoConnectionStats,
+        ConnectionStatsRequest, ConnectionStatsResponse, EnginePoolUtilization,
+        EnginePoolUtilizationRequest, ListLiveOrdersRequest, ListLiveOrdersResponse, MessageCount,
+        OrdersInFlightRequest, OrdersInFlightResponse, RefreshConnectionPoolRequest,
+        RefreshConnectionPoolResponse, SelfTestRequest, SelfTestResponse, SymbolInFlight,
+    },
+    pricing::SimulationConfig,
+    Timestamp,
+};
+use crate::services::TradingServiceImpl;
+use crate::symbols;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+/// The self-test's option: a one-year at-the-money European call, worth
+/// about 10.45 under Black-Scholes
+const SELF_TEST_SPOT: f64 = 100.0;
+const SELF_TEST_STRIKE: f64 = 100.0;
+const SELF_TEST_RATE: f64 = 0.05;
+const SELF_TEST_VOLATILITY: f64 = 0.2;
+const SELF_TEST_MATURITY: f64 = 1.0;
+
+/// Fixed so every self-test runs the same paths
+const SELF_TEST_SEED: u64 = 42;
+const SELF_TEST_SIMULATIONS: u64 = 100_000;
+const SELF_TEST_STEPS: u64 = 52;
+
+/// Largest difference from the Black-Scholes price that passes, as a
+/// fraction of it; several standard errors at this simulation count
+const SELF_TEST_TOLERANCE: f64 = 0.01;
+
+/// Operational diagnostics and maintenance for the server's own plumbing
+#[derive(Clone)]
+pub struct AdminServiceImpl {
+    matching_client: Arc<MatchingClient>,
+    order_store: Arc<OrderStore>,
+    engine: Arc<MonteCarloEngine>,
+    order_throttle: Arc<OrderThrottle>,
+}
+
+impl AdminServiceImpl {
+    pub fn new(
+        matching_client: Arc<MatchingClient>,
+        order_store: Arc<OrderStore>,
+        engine: Arc<MonteCarloEngine>,
+        order_throttle: Arc<OrderThrottle>,
+    ) -> Self {
+        Self {
+            matching_client,
+            order_store,
+            engine,
+            order_throttle,
+        }
+    }
+    
+    /// Convert a pooled connection's stats to the gRPC message
+    fn to_proto_stats(stats: ConnectionStats) -> ProtoConnectionStats {
+        let counts = |counts: Vec<_>| {
+            counts
+                .into_iter()
+                .map(|(msg_type, count)| MessageCount {
+                    message_type: format!("{:?}", msg_type),
+                    count,
+                })
+                .collect()
+        };
+        
+        ProtoConnectionStats {
+            slot: stats.slot as u32,
+            address: stats.address,
+            connected: stats.connected,
+            in_flight: stats.in_flight as u32,
+            bytes_sent: stats.bytes_sent,
+            bytes_received: stats.bytes_received,
+            messages_sent: counts(stats.messages_sent),
+            messages_received: counts(stats.messages_received),
+            connected_at: Some(Timestamp {
+                nanos: stats.connected_at,
+            }),
+            reconnects: stats.reconnects,
+            last_error: stats.last_error.unwrap_or_default(),
+        }
+    }
+    
+    /// Price the self-test's option through the library
+    fn self_test_price(engine: &MonteCarloEngine) -> f64 {
+        let config = SimulationConfig {
+            num_simulations: SELF_TEST_SIMULATIONS,
+            num_steps: SELF_TEST_STEPS,
+            seed: SELF_TEST_SEED,
+            antithetic_enabled: true,
+            ..Default::default()
+        };
+        engine.price_european_call(
+            SELF_TEST_SPOT,
+            SELF_TEST_STRIKE,
+            SELF_TEST_RATE,
+            0.0,
+            SELF_TEST_VOLATILITY,
+            SELF_TEST_MATURITY,
+            &config,
+        )
+    }
+    
+    /// Judge a self-test: the library's `price` must be within tolerance
+    /// of the analytic one (a NaN never is), and at least one matching
+    /// engine connection up
+    fn self_test_result(price: f64, live_connections: usize) -> SelfTestResponse {
+        let expected_price = black_scholes_call(
+            SELF_TEST_SPOT,
+            SELF_TEST_STRIKE,
+            SELF_TEST_RATE,
+            0.0,
+            SELF_TEST_VOLATILITY,
+            SELF_TEST_MATURITY,
+        );
+        let error = price - expected_price;
+        let tolerance = SELF_TEST_TOLERANCE * expected_price;
+        
+        let mut failures = Vec::new();
+        let within_tolerance = error.abs() <= tolerance;
+        if !within_tolerance {
+            failures.push(format!(
+                "Monte Carlo price {:.4} is off the Black-Scholes price {:.4} by {:.4}, more than {:.4}",
+                price, expected_price, error, tolerance
+            ));
+        }
+        if live_connections == 0 {
+            failures.push("No matching engine connection is up".to_string());
+        }
+        
+        SelfTestResponse {
+            passed: failures.is_empty(),
+            price,
+            expected_price,
+            error,
+            tolerance,
+            live_connections: live_connections as u32,
+            failures,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl AdminService for AdminServiceImpl {
+    async fn get_connection_stats(
+        &self,
+        _request: Request<ConnectionStatsRequest>,
+    ) -> Result<Response<ConnectionStatsResponse>, Status> {
+        let connections = self
+            .matching_client
+            .connection_stats()
+            .await
+            .into_iter()
+            .map(Self::to_proto_stats)
+            .collect();
+        
+        Ok(Response::new(ConnectionStatsResponse { connections }))
+    }
+    
+    async fn list_live_orders(
+        &self,
+        request: Request<ListLiveOrdersRequest>,
+    ) -> Result<Response<ListLiveOrdersResponse>, Status> {
+        let req = request.into_inner();
+        
+        // Not checked against the tradable symbols: orders may predate a
+        // change to the allow-list
+        let symbol = symbols::normalize(&req.symbol);
+        let symbol = (!symbol.is_empty()).then_some(symbol.as_str());
+        
+        let orders = if req.user_id == 0 {
+            self.order_store.all_live_orders(symbol)
+        } else {
+            self.order_store.live_orders(req.user_id, symbol)
+        };
+        
+        let scale = self.matching_client.price_scale();
+        Ok(Response::new(ListLiveOrdersResponse {
+            orders: orders
+                .into_iter()
+                .map(|order| TradingServiceImpl::to_order_status(scale, order))
+                .collect(),
+        }))
+    }
+    
+    async fn get_engine_pool_utilization(
+        &self,
+        _request: Request<EnginePoolUtilizationRequest>,
+    ) -> Result<Response<EnginePoolUtilization>, Status> {
+        Ok(Response::new(EnginePoolUtilization {
+            contexts: self.engine.pool_size() as u32,
+            in_use: self.engine.contexts_in_use() as u32,
+        }))
+    }
+    
+    async fn refresh_connection_pool(
+        &self,
+        _request: Request<RefreshConnectionPoolRequest>,
+    ) -> Result<Response<RefreshConnectionPoolResponse>, Status> {
+        info!("Refreshing matching engine connection pool");
+        
+        let replaced = self.matching_client.refresh_pool().await.map_err(|e| {
+            error!("Connection pool refresh failed: {:#}", e);
+            Status::unavailable(format!("Connection pool refresh failed: {:#}", e))
+        })?;
+        
+        Ok(Response::new(RefreshConnectionPoolResponse {
+            replaced: replaced as u32,
+        }))
+    }
+    
+    async fn get_orders_in_flight(
+        &self,
+        _request: Request<OrdersInFlightRequest>,
+    ) -> Result<Response<OrdersInFlightResponse>, Status> {
+        Ok(Response::new(OrdersInFlightResponse {
+            symbols: self
+                .order_throttle
+                .in_flight()
+                .into_iter()
+                .map(|(symbol, in_flight)| SymbolInFlight { symbol, in_flight })
+                .collect(),
+            max_in_flight_per_symbol: self.order_throttle.max_in_flight().unwrap_or(0),
+        }))
+    }
+    
+    async fn self_test(
+        &self,
+        _request: Request<SelfTestRequest>,
+    ) -> Result<Response<SelfTestResponse>, Status> {
+        let engine = Arc::clone(&self.engine);
+        let price = tokio::task::spawn_blocking(move || Self::self_test_price(&engine))
+            .await
+            .map_err(|e| Status::internal(format!("Self-test pricing failed: {}", e)))?;
+        
+        let result = Self::self_test_result(price, self.matching_client.active_connections());
+        if result.passed {
+            info!(
+                "Self-test passed: priced {:.4} against {:.4}, {} connections up",
+                result.price, result.expected_price, result.live_connections
+            );
+        } else {
+            error!("Self-test failed: {}", result.failures.join("; "));
+        }
+        
+        Ok(Response::new(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::matching::protocol::{ExecutionMessage, MessageHeader, MessageType, Side};
+    use crate::matching::{ConnectionOptions, TradeHistory};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    
+    /// A gateway that confirms every logon and then ignores what it is
+    /// sent, counting the logons
+    async fn gateway(logons: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let logons = Arc::clone(&logons);
+                tokio::spawn(async move {
+                    let mut logged_on = false;
+                    loop {
+                        let mut header = [0u8; 16];
+                        if stream.read_exact(&mut header).await.is_err() {
+                            return;
+                        }
+                        let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+                        let mut body = vec![0u8; length.saturating_sub(16)];
+                        if stream.read_exact(&mut body).await.is_err() {
+                            return;
+                        }
+                        if !std::mem::replace(&mut logged_on, true) {
+                            logons.fetch_add(1, Ordering::Relaxed);
+                            let mut reply = bytes::BytesMut::new();
+                            MessageHeader::new(MessageType::Logon, 16 + 96).encode(&mut reply);
+                            reply.extend_from_slice(&[0u8; 96]);
+                            stream.write_all(&reply).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+    
+    async fn connected_client(address: String, pool_size: usize) -> Arc<MatchingClient> {
+        let config = Config::default();
+        Arc::new(
+            MatchingClient::new(
+                address,
+                pool_size,
+                pool_size,
+                ConnectionOptions::from(&config.matching_engine),
+                Arc::new(OrderStore::new()),
+                Arc::new(TradeHistory::new(&config.market_data)),
+                false,
+            )
+            .await
+            .unwrap(),
+        )
+    }
+    
+    fn admin(client: Arc<MatchingClient>) -> AdminServiceImpl {
+        let config = Config::default();
+        AdminServiceImpl::new(
+            client,
+            Arc::new(OrderStore::new()),
+            Arc::new(MonteCarloEngine::new(2).unwrap()),
+            Arc::new(OrderThrottle::new(&config.order_throttle)),
+        )
+    }
+    
+    #[test]
+    fn self_test_passes_against_the_library() {
+        let engine = MonteCarloEngine::new(1).unwrap();
+        let price = AdminServiceImpl::self_test_price(&engine);
+        
+        let result = AdminServiceImpl::self_test_result(price, 1);
+        assert!(result.passed, "{:?}", result.failures);
+        assert!(result.failures.is_empty());
+        assert!(result.error.abs() <= result.tolerance);
+    }
+    
+    #[test]
+    fn self_test_fails_on_a_bad_price() {
+        let good = AdminServiceImpl::self_test_result(10.45, 1);
+        assert!(good.passed, "{:?}", good.failures);
+        
+        for price in [good.expected_price * 1.05, 0.0, f64::NAN] {
+            let result = AdminServiceImpl::self_test_result(price, 1);
+            assert!(!result.passed, "{} passed", price);
+            assert_eq!(result.failures.len(), 1);
+            assert!(result.failures[0].contains("Black-Scholes"), "{}", result.failures[0]);
+        }
+    }
+    
+    #[tokio::test]
+    async fn self_test_fails_without_a_gateway_connection() {
+        let admin = admin(Arc::new(MatchingClient::without_gateway(100).await));
+        
+        let result = admin.self_test(Request::new(SelfTestRequest {})).await.unwrap().into_inner();
+        assert!(!result.passed);
+        assert_eq!(result.live_connections, 0);
+        assert_eq!(result.failures, vec!["No matching engine connection is up".to_string()]);
+    }
+    
+    #[tokio::test]
+    async fn connection_stats_list_each_pooled_connection() {
+        let address = gateway(Arc::default()).await;
+        let admin = admin(connected_client(address.clone(), 2).await);
+        
+        let connections = admin
+            .get_connection_stats(Request::new(ConnectionStatsRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .connections;
+        let slots: Vec<_> = connections.iter().map(|stats| stats.slot).collect();
+        assert_eq!(slots, [0, 1]);
+        for stats in &connections {
+            assert!(stats.connected);
+            assert_eq!(stats.address, address);
+            assert_eq!((stats.in_flight, stats.reconnects), (0, 0));
+            assert!(stats.connected_at.as_ref().unwrap().nanos > 0);
+            assert!(stats.last_error.is_empty());
+        }
+    }
+    
+    #[tokio::test]
+    async fn pool_refresh_replaces_every_connection() {
+        let logons = Arc::new(AtomicUsize::new(0));
+        let admin = admin(connected_client(gateway(Arc::clone(&logons)).await, 2).await);
+        assert_eq!(logons.load(Ordering::Relaxed), 2);
+        
+        let response = admin
+            .refresh_connection_pool(Request::new(RefreshConnectionPoolRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.replaced, 2);
+        assert_eq!(logons.load(Ordering::Relaxed), 4);
+        // The replaced connections are logged out in the background
+        let pooled = admin.matching_client.connection_stats().await;
+        assert_eq!(pooled.len(), 2);
+        assert!(pooled.iter().all(|stats| stats.connected && stats.reconnects == 0));
+    }
+    
+    #[tokio::test]
+    async fn pool_refresh_without_a_gateway_is_unavailable() {
+        let admin = admin(Arc::new(MatchingClient::without_gateway(100).await));
+        
+        let status = admin
+            .refresh_connection_pool(Request::new(RefreshConnectionPoolRequest {}))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+    }
+    
+    #[tokio::test]
+    async fn live_orders_are_filtered_by_user_and_symbol() {
+        let admin = admin(Arc::new(MatchingClient::without_gateway(100).await));
+        let orders = &admin.order_store;
+        orders.insert_new(1, 7, "AAPL".to_string(), Side::Buy, 10_000, 100, String::new(), None);
+        orders.insert_new(2, 7, "MSFT".to_string(), Side::Sell, 30_000, 50, String::new(), None);
+        orders.insert_new(3, 8, "AAPL".to_string(), Side::Buy, 9_900, 10, String::new(), None);
+        // Filled, so no longer live
+        orders.insert_new(4, 7, "AAPL".to_string(), Side::Buy, 10_100, 10, String::new(), None);
+        orders.on_execution(&ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: 4,
+            exchange_order_id: 99,
+            execution_id: 1,
+            user_id: 7,
+            side: Side::Buy,
+            fill_price: 10_100,
+            fill_quantity: 10,
+            leaves_quantity: 0,
+            timestamp: 1,
+        });
+        
+        let live = |user_id, symbol: &str| {
+            let request = ListLiveOrdersRequest { user_id, symbol: symbol.to_string() };
+            let admin = admin.clone();
+            async move {
+                let response = admin.list_live_orders(Request::new(request)).await.unwrap();
+                let orders = response.into_inner().orders;
+                let mut ids: Vec<_> = orders.iter().map(|order| order.client_order_id).collect();
+                ids.sort();
+                ids
+            }
+        };
+        assert_eq!(live(0, "").await, [1, 2, 3]);
+        assert_eq!(live(7, "").await, [1, 2]);
+        assert_eq!(live(0, " aapl").await, [1, 3]);
+        assert_eq!(live(7, "AAPL").await, [1]);
+    }
+    
+    #[tokio::test]
+    async fn engine_pool_utilization_reports_the_contexts() {
+        let admin = admin(Arc::new(MatchingClient::without_gateway(100).await));
+        
+        let utilization = admin
+            .get_engine_pool_utilization(Request::new(EnginePoolUtilizationRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((utilization.contexts, utilization.in_use), (2, 0));
+    }
+    
+    #[tokio::test]
+    async fn orders_in_flight_are_reported_per_symbol() {
+        let admin = admin(Arc::new(MatchingClient::without_gateway(100).await));
+        let _first = admin.order_throttle.acquire("AAPL").unwrap();
+        let _second = admin.order_throttle.acquire("AAPL").unwrap();
+        let _other = admin.order_throttle.acquire("MSFT").unwrap();
+        
+        let response = admin
+            .get_orders_in_flight(Request::new(OrdersInFlightRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        let mut symbols: Vec<_> =
+            response.symbols.into_iter().map(|symbol| (symbol.symbol, symbol.in_flight)).collect();
+        symbols.sort();
+        assert_eq!(symbols, [("AAPL".to_string(), 2), ("MSFT".to_string(), 1)]);
+    }
+}
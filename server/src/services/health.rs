@@ -0,0 +1,336 @@
+use crate::proto::health::{
+    health_check_response::ServingStatus, health_server::Health, HealthCheckRequest,
+    HealthCheckResponse,
+};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tonic::{Request, Response, Status};
+use tracing::{debug, info};
+
+/// Standard gRPC health service (grpc.health.v1.Health). Each service name
+/// has a watch channel, so Watch streams see status changes as they happen.
+#[derive(Clone, Default)]
+pub struct HealthServiceImpl {
+    statuses: Arc<DashMap<String, watch::Sender<ServingStatus>>>,
+}
+
+impl HealthServiceImpl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Set the status reported for `service` ("" is the server as a whole)
+    pub fn set_status(&self, service: &str, status: ServingStatus) {
+        let previous = match self.statuses.get(service) {
+            Some(tx) => tx.send_replace(status),
+            None => {
+                self.statuses
+                    .insert(service.to_string(), watch::Sender::new(status));
+                ServingStatus::ServiceUnknown
+            }
+        };
+        
+        if previous != status {
+            info!("Health of {:?} is now {}", service, status.as_str_name());
+        }
+    }
+    
+    /// Report `service` as SERVING while `live` is true and NOT_SERVING
+    /// otherwise, until the sender side goes away
+    pub fn follow(&self, service: &'static str, mut live: watch::Receiver<bool>) {
+        let health = self.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                let status = if *live.borrow_and_update() {
+                    ServingStatus::Serving
+                } else {
+                    ServingStatus::NotServing
+                };
+                health.set_status(service, status);
+                
+                if live.changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    
+    /// Report everything as NOT_SERVING and end open Watch streams, so they
+    /// don't hold up graceful shutdown
+    pub fn shutdown(&self) {
+        for entry in self.statuses.iter() {
+            entry.value().send_replace(ServingStatus::NotServing);
+        }
+        self.statuses.clear();
+    }
+}
+
+#[tonic::async_trait]
+impl Health for HealthServiceImpl {
+    async fn check(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<HealthCheckResponse>, Status> {
+        let service = request.into_inner().service;
+        
+        // Watchers of unknown services leave a SERVICE_UNKNOWN entry behind
+        let status = self
+            .statuses
+            .get(&service)
+            .map(|tx| *tx.borrow())
+            .filter(|status| *status != ServingStatus::ServiceUnknown)
+            .ok_or_else(|| Status::not_found(format!("Unknown service {:?}", service)))?;
+        
+        Ok(Response::new(HealthCheckResponse {
+            status: status as i32,
+        }))
+    }
+    
+    type WatchStream = tokio_stream::wrappers::ReceiverStream<Result<HealthCheckResponse, Status>>;
+    
+    async fn watch(
+        &self,
+        request: Request<HealthCheckRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let service = request.into_inner().service;
+        debug!("Starting health watch for {:?}", service);
+        
+        // Unknown services report SERVICE_UNKNOWN until they're registered
+        let mut status_rx = self
+            .statuses
+            .entry(service)
+            .or_insert_with(|| watch::Sender::new(ServingStatus::ServiceUnknown))
+            .subscribe();
+        
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        
+        // Send the current status, then every change, until the client goes
+        // away or the service shuts down
+        tokio::spawn(async move {
+            loop {
+                let status = *status_rx.borrow_and_update();
+                let response = HealthCheckResponse {
+                    status: status as i32,
+                };
+                if tx.send(Ok(response)).await.is_err() {
+                    break;
+                }
+                
+                tokio::select! {
+                    changed = status_rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                    _ = tx.closed() => break,
+                }
+            }
+        });
+        
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::gateway_gate::GatewayGate;
+    use crate::matching::protocol::{MessageHeader, MessageType};
+    use crate::matching::{ConnectionOptions, MatchingClient, OrderStore, TradeHistory};
+    use crate::pricing::MonteCarloEngine;
+    use crate::pricing_cache::PricingCache;
+    use crate::proto::pricing::{
+        pricing_service_server::PricingService, EuropeanRequest, SimulationConfig,
+    };
+    use crate::services::pricing::{PricingServiceImpl, SimulationDefaults};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::oneshot;
+    use tokio::time::{timeout, Duration};
+    use tokio_stream::StreamExt;
+    use tonic::service::Interceptor;
+    
+    const TRADING: &str = "trading.TradingService";
+    const PRICING: &str = "pricing.PricingService";
+    
+    async fn check(health: &HealthServiceImpl, service: &str) -> Result<ServingStatus, Status> {
+        let request = Request::new(HealthCheckRequest { service: service.to_string() });
+        let response = health.check(request).await?.into_inner();
+        Ok(ServingStatus::try_from(response.status).unwrap())
+    }
+    
+    #[tokio::test]
+    async fn unregistered_service_is_not_found() {
+        let health = HealthServiceImpl::new();
+        let status = check(&health, TRADING).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+    
+    async fn next_status(watch: &mut <HealthServiceImpl as Health>::WatchStream) -> ServingStatus {
+        let response = timeout(Duration::from_secs(1), watch.next()).await.unwrap();
+        ServingStatus::try_from(response.unwrap().unwrap().status).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn followed_service_tracks_liveness() {
+        let health = HealthServiceImpl::new();
+        let (live, rx) = watch::channel(false);
+        health.follow(TRADING, rx);
+        let mut watch = health
+            .watch(Request::new(HealthCheckRequest { service: TRADING.to_string() }))
+            .await
+            .unwrap()
+            .into_inner();
+        
+        // The watch may start before `follow` has reported anything
+        let mut status = next_status(&mut watch).await;
+        if status == ServingStatus::ServiceUnknown {
+            status = next_status(&mut watch).await;
+        }
+        assert_eq!(status, ServingStatus::NotServing);
+        live.send_replace(true);
+        assert_eq!(next_status(&mut watch).await, ServingStatus::Serving);
+        live.send_replace(false);
+        assert_eq!(next_status(&mut watch).await, ServingStatus::NotServing);
+    }
+    
+    /// Accept a connection and confirm its logon
+    async fn accept_logon(listener: &TcpListener) -> TcpStream {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut header = [0u8; 16];
+        stream.read_exact(&mut header).await.unwrap();
+        let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let mut body = vec![0u8; length.saturating_sub(16)];
+        stream.read_exact(&mut body).await.unwrap();
+        
+        let mut reply = bytes::BytesMut::new();
+        MessageHeader::new(MessageType::Logon, 16 + 96).encode(&mut reply);
+        reply.extend_from_slice(&[0u8; 96]);
+        stream.write_all(&reply).await.unwrap();
+        stream
+    }
+    
+    /// A gateway that confirms a single logon, then closes the connection
+    /// and stops listening once `close` fires
+    async fn closing_gateway(close: oneshot::Receiver<()>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let stream = accept_logon(&listener).await;
+            let _ = close.await;
+            drop((stream, listener));
+        });
+        address
+    }
+    
+    /// Wait until `check` reports `expected` for `service`
+    async fn wait_for(health: &HealthServiceImpl, service: &str, expected: ServingStatus) {
+        let reached = async {
+            while check(health, service).await.ok() != Some(expected) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        timeout(Duration::from_secs(5), reached).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn trading_stops_serving_when_the_gateway_connection_drops() {
+        let (close, closed) = oneshot::channel();
+        let address = closing_gateway(closed).await;
+        let config = Config::default();
+        let client = MatchingClient::new(
+            address,
+            1,
+            1,
+            ConnectionOptions::from(&config.matching_engine),
+            Arc::new(OrderStore::new()),
+            Arc::new(TradeHistory::new(&config.market_data)),
+            false,
+        )
+        .await
+        .unwrap();
+        
+        let health = HealthServiceImpl::new();
+        health.follow(TRADING, client.watch_liveness());
+        wait_for(&health, TRADING, ServingStatus::Serving).await;
+        
+        close.send(()).unwrap();
+        wait_for(&health, TRADING, ServingStatus::NotServing).await;
+        
+        client.shutdown().await;
+    }
+    
+    #[tokio::test]
+    async fn degraded_start_prices_until_the_gateway_comes_up() {
+        // Nothing listens on the gateway's address yet
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        
+        let mut config = Config::default();
+        config.matching_engine.connect_timeout_ms = 50;
+        config.matching_engine.connect_attempts = 1;
+        config.matching_engine.reconnect_base_delay_ms = 10;
+        config.matching_engine.reconnect_max_delay_ms = 50;
+        let client = Arc::new(
+            MatchingClient::new(
+                address.to_string(),
+                1,
+                1,
+                ConnectionOptions::from(&config.matching_engine),
+                Arc::new(OrderStore::new()),
+                Arc::new(TradeHistory::new(&config.market_data)),
+                true,
+            )
+            .await
+            .unwrap(),
+        );
+        
+        let health = HealthServiceImpl::new();
+        health.set_status(PRICING, ServingStatus::Serving);
+        health.follow(TRADING, client.watch_liveness());
+        let mut gate = GatewayGate::new(client.watch_liveness(), Ok::<_, Status>);
+        
+        // Pricing doesn't need the gateway; trading is turned away
+        let pricing = PricingServiceImpl::new(
+            Arc::new(MonteCarloEngine::new(1).unwrap()),
+            Arc::clone(&client),
+            SimulationDefaults::new(&config.monte_carlo),
+            1,
+            Duration::from_secs(5),
+            None,
+            PricingCache::new(0),
+            config.monte_carlo.max_surface_points,
+        );
+        let european = EuropeanRequest {
+            spot: 100.0,
+            strike: 100.0,
+            rate: 0.05,
+            volatility: 0.2,
+            time_to_maturity: 1.0,
+            config: Some(SimulationConfig {
+                num_simulations: 1000,
+                seed: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let price = pricing.price_european_call(Request::new(european)).await.unwrap();
+        assert!(price.into_inner().price > 0.0);
+        wait_for(&health, PRICING, ServingStatus::Serving).await;
+        wait_for(&health, TRADING, ServingStatus::NotServing).await;
+        let status = gate.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        
+        // The background retries find the gateway once it's up
+        let listener = TcpListener::bind(address).await.unwrap();
+        let _stream = timeout(Duration::from_secs(5), accept_logon(&listener)).await.unwrap();
+        wait_for(&health, TRADING, ServingStatus::Serving).await;
+        assert!(gate.call(Request::new(())).is_ok());
+        
+        client.shutdown().await;
+    }
+}
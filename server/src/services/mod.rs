@@ -1,5 +1,9 @@
+pub mod admin;
+pub mod health;
 pub mod pricing;
 pub mod trading;
 
+pub use admin::AdminServiceImpl;
+pub use health::HealthServiceImpl;
 pub use pricing::PricingServiceImpl;
 pub use trading::TradingServiceImpl;
@@ -1,34 +1,752 @@
-use crate::pricing::MonteCarloEngine;
+use crate::config::{MonteCarloConfig, SimulationOverrides};
+use crate::matching::MatchingClient;
+use crate::metrics::METRICS;
+use crate::pricing::{GreekSelection, MonteCarloEngine};
+use crate::pricing_cache::{CacheKey, PricingCache};
 use crate::proto::pricing::{
-    pricing_service_server::PricingService, AmericanRequest, AsianRequest, BarrierRequest,
-    BatchRequest, BatchResponse, BermudanRequest, EuropeanRequest, LookbackRequest,
-    MarketPriceRequest, PriceResponse, SimulationConfig,
+    batch_leg, batch_leg_result, pricing_service_server::PricingService, AmericanRequest,
+    AsianRequest, BarrierRequest, BarrierType, BatchLeg, BatchLegResult, BatchRequest,
+    BatchResponse, BermudanRequest, DigitalRequest, DigitalType, EuropeanRequest, Greek,
+    ImpliedVolRequest, ImpliedVolResponse, LegGreeks, LookbackRequest, MarketPriceRequest,
+    PriceResponse, SimulationConfig, SpreadRequest, SurfaceRequest, SurfaceResponse,
 };
+use crate::services::TradingServiceImpl;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{timeout, Duration};
 use tonic::{Request, Response, Status};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+/// Default and maximum bisection steps for implied volatility
+const IMPLIED_VOL_DEFAULT_ITERATIONS: u32 = 100;
+const IMPLIED_VOL_MAX_ITERATIONS: u32 = 500;
+
+/// Default absolute price tolerance for implied volatility
+const IMPLIED_VOL_DEFAULT_TOLERANCE: f64 = 1e-4;
+
+/// Progressive estimates buffered ahead of a slow client before the
+/// simulation waits for it
+const PROGRESSIVE_CHANNEL_CAPACITY: usize = 4;
+
+/// Response metadata key suggesting a variance reduction technique
+const VARIANCE_ADVICE_KEY: &str = "x-variance-advice";
+
+/// Response metadata keys for the variance ratio (on / off) of each
+/// variance reduction technique, when report_variance_reduction is set
+const ANTITHETIC_RATIO_KEY: &str = "x-variance-ratio-antithetic";
+const CONTROL_VARIATES_RATIO_KEY: &str = "x-variance-ratio-control-variates";
+
+/// Response metadata key for the number of paths actually run, when
+/// antithetic pairing rounded an odd num_simulations up
+const SIMULATIONS_RUN_KEY: &str = "x-num-simulations-run";
+
+/// Option types with their own default simulation config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    European,
+    American,
+    Bermudan,
+    Asian,
+    Barrier,
+    Lookback,
+    Digital,
+    Spread,
+}
+
+impl OptionKind {
+    pub const ALL: [OptionKind; 8] = [
+        OptionKind::European,
+        OptionKind::American,
+        OptionKind::Bermudan,
+        OptionKind::Asian,
+        OptionKind::Barrier,
+        OptionKind::Lookback,
+        OptionKind::Digital,
+        OptionKind::Spread,
+    ];
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            OptionKind::European => "european",
+            OptionKind::American => "american",
+            OptionKind::Bermudan => "bermudan",
+            OptionKind::Asian => "asian",
+            OptionKind::Barrier => "barrier",
+            OptionKind::Lookback => "lookback",
+            OptionKind::Digital => "digital",
+            OptionKind::Spread => "spread",
+        }
+    }
+    
+    /// Option type of a mixed batch leg
+    fn of_leg(leg: &BatchLeg) -> Self {
+        use batch_leg::Option as Leg;
+        
+        match leg.option {
+            Some(Leg::AmericanCall(_)) | Some(Leg::AmericanPut(_)) => OptionKind::American,
+            Some(Leg::AsianCall(_)) | Some(Leg::AsianPut(_)) => OptionKind::Asian,
+            Some(Leg::BarrierCall(_)) | Some(Leg::BarrierPut(_)) => OptionKind::Barrier,
+            Some(Leg::LookbackCall(_)) | Some(Leg::LookbackPut(_)) => OptionKind::Lookback,
+            Some(Leg::BermudanCall(_)) | Some(Leg::BermudanPut(_)) => OptionKind::Bermudan,
+            Some(Leg::EuropeanCall(_)) | Some(Leg::EuropeanPut(_)) | None => OptionKind::European,
+        }
+    }
+}
+
+/// Simulation config used for each option type when a request has none,
+/// resolved once from `MonteCarloConfig` and its per-type overrides
+#[derive(Debug, Clone)]
+pub struct SimulationDefaults {
+    by_kind: [SimulationConfig; OptionKind::ALL.len()],
+}
+
+impl SimulationDefaults {
+    pub fn new(config: &MonteCarloConfig) -> Self {
+        let overrides = &config.overrides;
+        let resolve = |kind: OptionKind| {
+            let o: &SimulationOverrides = match kind {
+                OptionKind::European => &overrides.european,
+                OptionKind::American => &overrides.american,
+                OptionKind::Bermudan => &overrides.bermudan,
+                OptionKind::Asian => &overrides.asian,
+                OptionKind::Barrier => &overrides.barrier,
+                OptionKind::Lookback => &overrides.lookback,
+                OptionKind::Digital => &overrides.digital,
+                OptionKind::Spread => &overrides.spread,
+            };
+            SimulationConfig {
+                num_simulations: o.num_simulations.unwrap_or(config.default_simulations),
+                num_steps: o.num_steps.unwrap_or(config.default_steps),
+                seed: 0,
+                antithetic_enabled: o.antithetic.unwrap_or(config.default_antithetic),
+                control_variates_enabled: o
+                    .control_variates
+                    .unwrap_or(config.default_control_variates),
+                stratified_sampling_enabled: o
+                    .stratified_sampling
+                    .unwrap_or(config.default_stratified_sampling),
+                importance_sampling_enabled: false,
+                importance_drift_shift: 0.0,
+                report_variance_reduction: false,
+            }
+        };
+        
+        Self {
+            by_kind: OptionKind::ALL.map(resolve),
+        }
+    }
+    
+    /// Default config for an option type
+    pub fn get(&self, kind: OptionKind) -> &SimulationConfig {
+        &self.by_kind[kind as usize]
+    }
+    
+    /// Log each option type's defaults
+    pub fn log(&self) {
+        for kind in OptionKind::ALL {
+            let config = self.get(kind);
+            info!(
+                "Simulation defaults for {}: simulations={}, steps={}, antithetic={}, control_variates={}, stratified={}",
+                kind.name(),
+                config.num_simulations,
+                config.num_steps,
+                config.antithetic_enabled,
+                config.control_variates_enabled,
+                config.stratified_sampling_enabled
+            );
+        }
+    }
+}
 
 /// Pricing service implementation
 #[derive(Clone)]
 pub struct PricingServiceImpl {
     engine: Arc<MonteCarloEngine>,
+    matching_client: Arc<MatchingClient>,
+    defaults: Arc<SimulationDefaults>,
+    /// One permit per pricing allowed to run at once
+    in_flight: Arc<Semaphore>,
+    queue_timeout: Duration,
+    /// Longest a simulation may run; `None` is unlimited
+    pricing_timeout: Option<Duration>,
+    cache: Arc<PricingCache>,
+    /// Most points a surface request may price
+    max_surface_points: usize,
 }
 
 impl PricingServiceImpl {
-    pub fn new(engine: Arc<MonteCarloEngine>) -> Self {
-        Self { engine }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        engine: Arc<MonteCarloEngine>,
+        matching_client: Arc<MatchingClient>,
+        defaults: SimulationDefaults,
+        max_in_flight: usize,
+        queue_timeout: Duration,
+        pricing_timeout: Option<Duration>,
+        cache: PricingCache,
+        max_surface_points: usize,
+    ) -> Self {
+        Self {
+            engine,
+            matching_client,
+            defaults: Arc::new(defaults),
+            in_flight: Arc::new(Semaphore::new(max_in_flight)),
+            queue_timeout,
+            pricing_timeout,
+            cache: Arc::new(cache),
+            max_surface_points,
+        }
     }
     
-    /// Get config with defaults if not provided
-    fn get_config(config: Option<SimulationConfig>) -> SimulationConfig {
-        config.unwrap_or_else(|| SimulationConfig {
-            num_simulations: 10_000,
-            num_steps: 252,
-            seed: 0,
-            antithetic_enabled: true,
-            control_variates_enabled: false,
-            stratified_sampling_enabled: false,
+    /// Answer from the cache when `key` is in it; otherwise run `price`
+    /// and cache what it returns. `key` is `None` for requests that aren't
+    /// cached.
+    async fn cached<F>(&self, key: Option<CacheKey>, price: F) -> Result<Response<PriceResponse>, Status>
+    where
+        F: std::future::Future<Output = Result<Response<PriceResponse>, Status>>,
+    {
+        let Some(key) = key else {
+            return price.await;
+        };
+        if let Some(response) = self.cache.get(&key) {
+            debug!("Pricing served from cache");
+            return Ok(response);
+        }
+        
+        let response = price.await?;
+        self.cache.insert(key, &response);
+        Ok(response)
+    }
+    
+    /// Run simulation work on the blocking pool once a pricing slot is
+    /// free. Simulations are synchronous FFI calls; run on the async
+    /// workers they would hold up every other RPC, orders included. The
+    /// slot goes with the work, so it stays taken even if the caller gives
+    /// up waiting, or the work outlives its time limit (`finish_within`).
+    async fn simulate<T, F>(&self, deadline: Option<Duration>, work: F) -> Result<T, Status>
+    where
+        F: FnOnce(&MonteCarloEngine) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = self.acquire_slot().await?;
+        let engine = Arc::clone(&self.engine);
+        let task = tokio::task::spawn_blocking(move || {
+            let _slot = slot;
+            work(&engine)
+        });
+        
+        Self::finish_within(self.time_limit(deadline), task)
+            .await?
+            .map_err(|e| Status::internal(format!("Pricing task failed: {}", e)))
+    }
+    
+    /// How long a simulation may run: the configured pricing timeout or
+    /// the time left before the caller's deadline, whichever is shorter
+    fn time_limit(&self, deadline: Option<Duration>) -> Option<Duration> {
+        match (self.pricing_timeout, deadline) {
+            (Some(timeout), Some(deadline)) => Some(timeout.min(deadline)),
+            (timeout, deadline) => timeout.or(deadline),
+        }
+    }
+    
+    /// Wait up to `limit` for simulation work, then give up with
+    /// DEADLINE_EXCEEDED. The library can't interrupt a simulation, so the
+    /// work is abandoned rather than stopped: it runs to the end on its
+    /// blocking thread and its result is thrown away. It keeps its pricing
+    /// slot until then, which stops abandoned work piling up beyond
+    /// `max_in_flight_pricings`.
+    async fn finish_within<F: std::future::Future>(
+        limit: Option<Duration>,
+        work: F,
+    ) -> Result<F::Output, Status> {
+        let Some(limit) = limit else {
+            return Ok(work.await);
+        };
+        
+        timeout(limit, work).await.map_err(|_| {
+            warn!(
+                "Pricing abandoned after {}ms; its simulation runs on in the background",
+                limit.as_millis()
+            );
+            Status::deadline_exceeded(format!(
+                "Pricing did not finish within {}ms",
+                limit.as_millis()
+            ))
+        })
+    }
+    
+    /// Wait for a pricing slot, so only `max_in_flight_pricings` requests
+    /// simulate at once. The slot is held until the permit is dropped.
+    async fn acquire_slot(&self) -> Result<OwnedSemaphorePermit, Status> {
+        match timeout(self.queue_timeout, Arc::clone(&self.in_flight).acquire_owned()).await {
+            Ok(permit) => permit.map_err(|_| Status::unavailable("Pricing is shutting down")),
+            Err(_) => {
+                debug!("No pricing slot free within {:?}", self.queue_timeout);
+                Err(Status::resource_exhausted(format!(
+                    "Too many pricing requests in flight; no slot freed up within {}ms",
+                    self.queue_timeout.as_millis()
+                )))
+            }
+        }
+    }
+    
+    /// Current spot for a symbol: the top-of-book mid, or the last trade
+    /// when the book is one-sided
+    async fn market_spot(&self, symbol: &str) -> Result<f64, Status> {
+        let book = self
+            .matching_client
+            .get_order_book(symbol.to_string(), 1)
+            .await
+            .map_err(|e| {
+                Status::unavailable(format!("Failed to fetch order book for {}: {:#}", symbol, e))
+            })?;
+        
+        let units = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) as f64 / 2.0),
+            _ => self
+                .matching_client
+                .last_trade_price(symbol)
+                .map(|price| price as f64),
+        };
+        
+        match units {
+            Some(units) => Ok(self.matching_client.price_scale().fractional_to_dollars(units)),
+            None => Err(Status::failed_precondition("no market data for symbol")),
+        }
+    }
+    
+    /// Get config with the option type's defaults if not provided. The seed
+    /// is always set, so responses can report it and the caller can
+    /// reproduce the run.
+    fn get_config(&self, kind: OptionKind, config: Option<SimulationConfig>) -> SimulationConfig {
+        let mut config = config.unwrap_or_else(|| self.defaults.get(kind).clone());
+        MonteCarloEngine::resolve_seed(&mut config);
+        config
+    }
+    
+    /// Reject non-finite or non-positive values for a named field
+    #[allow(clippy::result_large_err)]
+    fn require_positive(field: &str, value: f64) -> Result<(), Status> {
+        if value.is_finite() && value > 0.0 {
+            Ok(())
+        } else {
+            Err(Status::invalid_argument(format!(
+                "{} must be a positive finite number, got {}",
+                field, value
+            )))
+        }
+    }
+    
+    /// Reject non-finite values for a named field
+    #[allow(clippy::result_large_err)]
+    fn require_finite(field: &str, value: f64) -> Result<(), Status> {
+        if value.is_finite() {
+            Ok(())
+        } else {
+            Err(Status::invalid_argument(format!(
+                "{} must be a finite number, got {}",
+                field, value
+            )))
+        }
+    }
+    
+    /// Validate the market inputs shared by every option type. Negative
+    /// rates are allowed; `strike` is `None` when the payoff doesn't use it.
+    #[allow(clippy::result_large_err)]
+    fn validate_inputs(
+        spot: f64,
+        strike: Option<f64>,
+        rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        time_to_maturity: f64,
+    ) -> Result<(), Status> {
+        Self::require_positive("spot", spot)?;
+        if let Some(strike) = strike {
+            Self::require_positive("strike", strike)?;
+        }
+        Self::require_finite("rate", rate)?;
+        Self::require_finite("dividend_yield", dividend_yield)?;
+        Self::require_positive("volatility", volatility)?;
+        Self::require_positive("time_to_maturity", time_to_maturity)
+    }
+    
+    /// Exercise dates must be positive and strictly increasing; the last
+    /// one is the option's maturity.
+    #[allow(clippy::result_large_err)]
+    fn validate_exercise_dates(exercise_dates: &[f64]) -> Result<(), Status> {
+        if exercise_dates.is_empty() {
+            return Err(Status::invalid_argument("exercise_dates cannot be empty"));
+        }
+        
+        for (i, date) in exercise_dates.iter().enumerate() {
+            Self::require_positive(&format!("exercise_dates[{}]", i), *date)?;
+        }
+        
+        // An unsorted or repeated date also puts an earlier one past maturity
+        if let Some(i) = exercise_dates.windows(2).position(|pair| pair[0] >= pair[1]) {
+            return Err(Status::invalid_argument(format!(
+                "exercise_dates must be strictly increasing: exercise_dates[{}] = {} is not after exercise_dates[{}] = {}",
+                i + 1,
+                exercise_dates[i + 1],
+                i,
+                exercise_dates[i]
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    fn validate_european(req: &EuropeanRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )
+    }
+    
+    #[allow(clippy::result_large_err)]
+    fn validate_american(req: &AmericanRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )?;
+        if req.num_exercise_points == 0 {
+            return Err(Status::invalid_argument("num_exercise_points must be greater than 0"));
+        }
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    fn validate_asian(req: &AsianRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )?;
+        if req.num_observations == 0 {
+            return Err(Status::invalid_argument("num_observations must be greater than 0"));
+        }
+        Ok(())
+    }
+    
+    #[allow(clippy::result_large_err)]
+    fn validate_barrier(req: &BarrierRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )?;
+        Self::require_positive("barrier_level", req.barrier_level)?;
+        Self::require_finite("rebate", req.rebate)
+    }
+    
+    /// Floating-strike lookbacks ignore `strike`
+    #[allow(clippy::result_large_err)]
+    fn validate_lookback(req: &LookbackRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            req.fixed_strike.then_some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )
+    }
+    
+    /// Bermudans have no separate maturity; the last exercise date is it
+    #[allow(clippy::result_large_err)]
+    fn validate_bermudan(req: &BermudanRequest) -> Result<(), Status> {
+        Self::require_positive("spot", req.spot)?;
+        Self::require_positive("strike", req.strike)?;
+        Self::require_finite("rate", req.rate)?;
+        Self::require_finite("dividend_yield", req.dividend_yield)?;
+        Self::require_positive("volatility", req.volatility)?;
+        Self::validate_exercise_dates(&req.exercise_dates)
+    }
+    
+    /// Digitals pay a fixed amount, so a zero or negative payout is a mistake
+    #[allow(clippy::result_large_err)]
+    fn validate_digital(req: &DigitalRequest) -> Result<(), Status> {
+        Self::validate_inputs(
+            req.spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            req.volatility,
+            req.time_to_maturity,
+        )?;
+        Self::require_positive("payout", req.payout)
+    }
+    
+    /// Both legs need their own positive spot and volatility, and the
+    /// correlation between them must be a valid one
+    #[allow(clippy::result_large_err)]
+    fn validate_spread(req: &SpreadRequest) -> Result<(), Status> {
+        Self::require_positive("spot1", req.spot1)?;
+        Self::require_positive("spot2", req.spot2)?;
+        Self::require_finite("strike", req.strike)?;
+        if req.strike < 0.0 {
+            return Err(Status::invalid_argument(format!(
+                "strike must not be negative, got {}",
+                req.strike
+            )));
+        }
+        Self::require_finite("rate", req.rate)?;
+        Self::require_finite("dividend_yield", req.dividend_yield)?;
+        Self::require_positive("volatility1", req.volatility1)?;
+        Self::require_positive("volatility2", req.volatility2)?;
+        if !(-1.0..=1.0).contains(&req.correlation) {
+            return Err(Status::invalid_argument(format!(
+                "correlation must be between -1 and 1, got {}",
+                req.correlation
+            )));
+        }
+        Self::require_positive("time_to_maturity", req.time_to_maturity)
+    }
+    
+    /// Report the number of paths actually simulated in the response
+    /// metadata when it differs from the requested `num_simulations`
+    fn with_simulations_run(
+        mut response: Response<PriceResponse>,
+        config: &SimulationConfig,
+        simulations: u64,
+    ) -> Response<PriceResponse> {
+        if simulations != config.num_simulations {
+            response.metadata_mut().insert(SIMULATIONS_RUN_KEY, simulations.into());
+        }
+        response
+    }
+    
+    /// Report the simulation count as `with_simulations_run` does and, when
+    /// the request asked for it, measure what antithetic and control
+    /// variates do for this option and report the variance ratios in the
+    /// response metadata
+    fn with_sampling_report<F>(
+        response: Response<PriceResponse>,
+        config: &SimulationConfig,
+        simulations: u64,
+        price: F,
+    ) -> Response<PriceResponse>
+    where
+        F: Fn(&SimulationConfig) -> f64,
+    {
+        let mut response = Self::with_simulations_run(response, config, simulations);
+        if !config.report_variance_reduction {
+            return response;
+        }
+        
+        let report = MonteCarloEngine::variance_reduction(config, price);
+        for (key, ratio) in [
+            (ANTITHETIC_RATIO_KEY, report.antithetic),
+            (CONTROL_VARIATES_RATIO_KEY, report.control_variates),
+        ] {
+            if let Some(value) = ratio.and_then(|ratio| format!("{:.4}", ratio).parse().ok()) {
+                response.metadata_mut().insert(key, value);
+            }
+        }
+        response
+    }
+    
+    /// A digital's payoff jumps at the strike, so its plain Monte Carlo
+    /// estimate has high variance. Without importance sampling, say so in
+    /// the response metadata.
+    fn with_digital_advice(
+        mut response: Response<PriceResponse>,
+        config: &SimulationConfig,
+    ) -> Response<PriceResponse> {
+        if !config.importance_sampling_enabled {
+            response.metadata_mut().insert(
+                VARIANCE_ADVICE_KEY,
+                tonic::metadata::MetadataValue::from_static(
+                    "discontinuous payoff; enable importance_sampling to reduce variance",
+                ),
+            );
+        }
+        response
+    }
+    
+    /// Validate and price a single leg of a mixed batch
+    #[allow(clippy::result_large_err)]
+    fn price_leg(
+        engine: &MonteCarloEngine,
+        leg: BatchLeg,
+        config: &SimulationConfig,
+    ) -> Result<f64, Status> {
+        use batch_leg::Option as Leg;
+        
+        let barrier_type = |req: &BarrierRequest| {
+            BarrierType::try_from(req.barrier_type)
+                .map_err(|_| Status::invalid_argument("Invalid barrier type"))
+        };
+        
+        let leg = leg
+            .option
+            .ok_or_else(|| Status::invalid_argument("Batch leg has no option set"))?;
+        
+        let price = match leg {
+            Leg::EuropeanCall(r) => {
+                Self::validate_european(&r)?;
+                engine.price_european_call(r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity, config)
+            }
+            Leg::EuropeanPut(r) => {
+                Self::validate_european(&r)?;
+                engine.price_european_put(r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity, config)
+            }
+            Leg::AmericanCall(r) => {
+                Self::validate_american(&r)?;
+                engine.price_american_call(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.num_exercise_points, config,
+                )
+            }
+            Leg::AmericanPut(r) => {
+                Self::validate_american(&r)?;
+                engine.price_american_put(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.num_exercise_points, config,
+                )
+            }
+            Leg::AsianCall(r) => {
+                Self::validate_asian(&r)?;
+                engine.price_asian_call(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.num_observations, config,
+                )
+            }
+            Leg::AsianPut(r) => {
+                Self::validate_asian(&r)?;
+                engine.price_asian_put(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.num_observations, config,
+                )
+            }
+            Leg::BarrierCall(r) => {
+                Self::validate_barrier(&r)?;
+                engine.price_barrier_call(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.barrier_level, barrier_type(&r)?, r.rebate, config,
+                )
+            }
+            Leg::BarrierPut(r) => {
+                Self::validate_barrier(&r)?;
+                engine.price_barrier_put(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.barrier_level, barrier_type(&r)?, r.rebate, config,
+                )
+            }
+            Leg::LookbackCall(r) => {
+                Self::validate_lookback(&r)?;
+                engine.price_lookback_call(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.fixed_strike, config,
+                )
+            }
+            Leg::LookbackPut(r) => {
+                Self::validate_lookback(&r)?;
+                engine.price_lookback_put(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    r.fixed_strike, config,
+                )
+            }
+            Leg::BermudanCall(r) => {
+                Self::validate_bermudan(&r)?;
+                engine.price_bermudan_call(r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, &r.exercise_dates, config)
+            }
+            Leg::BermudanPut(r) => {
+                Self::validate_bermudan(&r)?;
+                engine.price_bermudan_put(r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, &r.exercise_dates, config)
+            }
+        };
+        
+        Ok(price)
+    }
+    
+    /// Price a single leg of a mixed batch along with its selected Greeks
+    #[allow(clippy::result_large_err)]
+    fn price_leg_with_greeks(
+        engine: &MonteCarloEngine,
+        leg: BatchLeg,
+        config: &SimulationConfig,
+        selection: GreekSelection,
+    ) -> Result<(f64, Option<LegGreeks>), Status> {
+        use batch_leg::Option as Leg;
+        
+        let european = match &leg.option {
+            Some(Leg::EuropeanCall(r)) => Some((true, r.clone())),
+            Some(Leg::EuropeanPut(r)) => Some((false, r.clone())),
+            _ => None,
+        };
+        if selection.any() && european.is_none() && leg.option.is_some() {
+            return Err(Status::unimplemented(format!(
+                "Greeks are only available for european legs, not {}",
+                OptionKind::of_leg(&leg).name()
+            )));
+        }
+        
+        let price = Self::price_leg(engine, leg, config)?;
+        
+        let greeks = european.filter(|_| selection.any()).map(|(is_call, r)| {
+            let greeks = if is_call {
+                engine.greeks_european_call(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    config, selection,
+                )
+            } else {
+                engine.greeks_european_put(
+                    r.spot, r.strike, r.rate, r.dividend_yield, r.volatility, r.time_to_maturity,
+                    config, selection,
+                )
+            };
+            LegGreeks {
+                delta: greeks.delta,
+                gamma: greeks.gamma,
+                vega: greeks.vega,
+                theta: greeks.theta,
+                rho: greeks.rho,
+            }
+        });
+        
+        Ok((price, greeks))
+    }
+    
+    /// Greeks requested for a batch
+    #[allow(clippy::result_large_err)]
+    fn greek_selection(greeks: &[i32]) -> Result<GreekSelection, Status> {
+        let mut selection = GreekSelection::default();
+        for &greek in greeks {
+            match Greek::try_from(greek) {
+                Ok(Greek::Delta) => selection.delta = true,
+                Ok(Greek::Gamma) => selection.gamma = true,
+                Ok(Greek::Vega) => selection.vega = true,
+                Ok(Greek::Theta) => selection.theta = true,
+                Ok(Greek::Rho) => selection.rho = true,
+                Err(_) => return Err(Status::invalid_argument(format!("Unknown greek {}", greek))),
+            }
+        }
+        Ok(selection)
+    }
+    
+    /// Validate a European request, prefixing errors with its batch position
+    #[allow(clippy::result_large_err)]
+    fn validate_batch_entry(field: &str, index: usize, req: &EuropeanRequest) -> Result<(), Status> {
+        Self::validate_european(req).map_err(|status| {
+            Status::invalid_argument(format!("{}[{}]: {}", field, index, status.message()))
         })
     }
 }
@@ -39,466 +757,1047 @@ impl PricingService for PricingServiceImpl {
         &self,
         request: Request<EuropeanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
+        Self::validate_european(&req)?;
+        let key = self.cache.key("european_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::European, req.config);
         
         debug!(
             "Pricing European call: spot={}, strike={}, ttm={}",
             req.spot, req.strike, req.time_to_maturity
         );
         
-        let start = Instant::now();
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let estimate = engine.estimate_european_call(
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.volatility,
+                req.time_to_maturity,
+                &config,
+            );
+            let price = estimate.price;
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_european_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    config,
+                )
+            };
+            
+            let selection = if req.compute_greeks {
+                GreekSelection::ALL
+            } else {
+                GreekSelection::default()
+            };
+            let greeks = engine.greeks_european_call(
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.volatility,
+                req.time_to_maturity,
+                &config,
+                selection,
+            );
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("european_call", computation_time_ms);
+            
+            info!(
+                "European call priced: ${:.4} in {:.2}ms",
+                price, computation_time_ms
+            );
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    delta: greeks.delta,
+                    gamma: greeks.gamma,
+                    vega: greeks.vega,
+                    theta: greeks.theta,
+                    rho: greeks.rho,
+                    std_error: Some(estimate.std_error),
+                    confidence_95: Some(estimate.confidence_95()),
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                estimate.simulations,
+                price_with,
+            )
+        }))
+        .await
+    }
+    
+    type PriceEuropeanCallProgressiveStream =
+        tokio_stream::wrappers::ReceiverStream<Result<PriceResponse, Status>>;
+    
+    async fn price_european_call_progressive(
+        &self,
+        request: Request<EuropeanRequest>,
+    ) -> Result<Response<Self::PriceEuropeanCallProgressiveStream>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        Self::validate_european(&req)?;
+        let config = self.get_config(OptionKind::European, req.config);
         
-        let price = self.engine.price_european_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            &config,
+        debug!(
+            "Pricing European call progressively: spot={}, strike={}, ttm={}, sims={}",
+            req.spot, req.strike, req.time_to_maturity, config.num_simulations
         );
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let engine = Arc::clone(&self.engine);
+        let slot = self.acquire_slot().await?;
+        let limit = self.time_limit(deadline);
+        let (tx, rx) = tokio::sync::mpsc::channel(PROGRESSIVE_CHANNEL_CAPACITY);
         
-        info!(
-            "European call priced: ${:.4} in {:.2}ms",
-            price, computation_time_ms
-        );
+        // Simulation blocks, so run it off the async workers. Sending fails
+        // once the client cancels the stream, which ends the simulation, as
+        // does running out of time; both take effect at the next estimate.
+        // The slot is held until then.
+        tokio::task::spawn_blocking(move || {
+            let _slot = slot;
+            let start = Instant::now();
+            
+            engine.estimate_european_call_progressive(
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.volatility,
+                req.time_to_maturity,
+                &config,
+                |progress| {
+                    let response = PriceResponse {
+                        price: progress.estimate.price,
+                        computation_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+                        std_error: Some(progress.estimate.std_error),
+                        confidence_95: Some(progress.estimate.confidence_95()),
+                        seed_used: config.seed,
+                        simulations_completed: progress.estimate.simulations,
+                        complete: progress.complete,
+                        ..Default::default()
+                    };
+                    if tx.blocking_send(Ok(response)).is_err() {
+                        return false;
+                    }
+                    
+                    match limit.filter(|limit| !progress.complete && start.elapsed() >= *limit) {
+                        Some(limit) => {
+                            let _ = tx.blocking_send(Err(Status::deadline_exceeded(format!(
+                                "Pricing did not finish within {}ms",
+                                limit.as_millis()
+                            ))));
+                            false
+                        }
+                        None => true,
+                    }
+                },
+            );
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("european_call_progressive", computation_time_ms);
+        });
         
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
-        }))
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
     }
     
     async fn price_european_put(
         &self,
         request: Request<EuropeanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
+        Self::validate_european(&req)?;
+        let key = self.cache.key("european_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::European, req.config);
         
         debug!(
             "Pricing European put: spot={}, strike={}, ttm={}",
             req.spot, req.strike, req.time_to_maturity
         );
         
-        let start = Instant::now();
-        
-        let price = self.engine.price_european_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        info!(
-            "European put priced: ${:.4} in {:.2}ms",
-            price, computation_time_ms
-        );
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let estimate = engine.estimate_european_put(
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.volatility,
+                req.time_to_maturity,
+                &config,
+            );
+            let price = estimate.price;
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_european_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    config,
+                )
+            };
+            
+            let selection = if req.compute_greeks {
+                GreekSelection::ALL
+            } else {
+                GreekSelection::default()
+            };
+            let greeks = engine.greeks_european_put(
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.volatility,
+                req.time_to_maturity,
+                &config,
+                selection,
+            );
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("european_put", computation_time_ms);
+            
+            info!(
+                "European put priced: ${:.4} in {:.2}ms",
+                price, computation_time_ms
+            );
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    delta: greeks.delta,
+                    gamma: greeks.gamma,
+                    vega: greeks.vega,
+                    theta: greeks.theta,
+                    rho: greeks.rho,
+                    std_error: Some(estimate.std_error),
+                    confidence_95: Some(estimate.confidence_95()),
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                estimate.simulations,
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_american_call(
         &self,
         request: Request<AmericanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
-        
-        let price = self.engine.price_american_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.num_exercise_points,
-            &config,
-        );
+        Self::validate_american(&req)?;
+        let key = self.cache.key("american_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::American, req.config);
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_american_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.num_exercise_points,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("american_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_american_put(
         &self,
         request: Request<AmericanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
+        Self::validate_american(&req)?;
+        let key = self.cache.key("american_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::American, req.config);
         
-        let price = self.engine.price_american_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.num_exercise_points,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_american_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.num_exercise_points,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("american_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_asian_call(
         &self,
         request: Request<AsianRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
-        
-        let price = self.engine.price_asian_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.num_observations,
-            &config,
-        );
+        Self::validate_asian(&req)?;
+        let key = self.cache.key("asian_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Asian, req.config);
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_asian_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.num_observations,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("asian_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_asian_put(
         &self,
         request: Request<AsianRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
-        
-        let price = self.engine.price_asian_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.num_observations,
-            &config,
-        );
+        Self::validate_asian(&req)?;
+        let key = self.cache.key("asian_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Asian, req.config);
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_asian_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.num_observations,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("asian_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
 async fn price_barrier_call(
         &self,
         request: Request<BarrierRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
+        Self::validate_barrier(&req)?;
+        let key = self.cache.key("barrier_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Barrier, req.config);
         
         let barrier_type = crate::proto::pricing::BarrierType::try_from(req.barrier_type)
             .map_err(|_| Status::invalid_argument("Invalid barrier type"))?;
         
-        let price = self.engine.price_barrier_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.barrier_level,
-            barrier_type,
-            req.rebate,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_barrier_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.barrier_level,
+                    barrier_type,
+                    req.rebate,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("barrier_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_barrier_put(
         &self,
         request: Request<BarrierRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
+        Self::validate_barrier(&req)?;
+        let key = self.cache.key("barrier_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Barrier, req.config);
         
         let barrier_type = crate::proto::pricing::BarrierType::try_from(req.barrier_type)
             .map_err(|_| Status::invalid_argument("Invalid barrier type"))?;
         
-        let price = self.engine.price_barrier_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.barrier_level,
-            barrier_type,
-            req.rebate,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_barrier_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.barrier_level,
+                    barrier_type,
+                    req.rebate,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("barrier_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_lookback_call(
         &self,
         request: Request<LookbackRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
+        Self::validate_lookback(&req)?;
+        let key = self.cache.key("lookback_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Lookback, req.config);
         
-        let start = Instant::now();
-        
-        let price = self.engine.price_lookback_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.fixed_strike,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_lookback_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.fixed_strike,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("lookback_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_lookback_put(
         &self,
         request: Request<LookbackRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
+        Self::validate_lookback(&req)?;
+        let key = self.cache.key("lookback_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Lookback, req.config);
         
-        let price = self.engine.price_lookback_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            req.time_to_maturity,
-            req.fixed_strike,
-            &config,
-        );
-        
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_lookback_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.fixed_strike,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("lookback_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_bermudan_call(
         &self,
         request: Request<BermudanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
-        
-        let start = Instant::now();
-        
-        let price = self.engine.price_bermudan_call(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            &req.exercise_dates,
-            &config,
-        );
+        Self::validate_bermudan(&req)?;
+        let key = self.cache.key("bermudan_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Bermudan, req.config);
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_bermudan_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    &req.exercise_dates,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("bermudan_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
     
     async fn price_bermudan_put(
         &self,
         request: Request<BermudanRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
+        Self::validate_bermudan(&req)?;
+        let key = self.cache.key("bermudan_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Bermudan, req.config);
         
-        let start = Instant::now();
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_bermudan_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    &req.exercise_dates,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("bermudan_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
+        }))
+        .await
+    }
+    
+    async fn price_digital_call(
+        &self,
+        request: Request<DigitalRequest>,
+    ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        Self::validate_digital(&req)?;
+        let key = self.cache.key("digital_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Digital, req.config);
         
-        let price = self.engine.price_bermudan_put(
-            req.spot,
-            req.strike,
-            req.rate,
-            req.volatility,
-            &req.exercise_dates,
-            &config,
-        );
+        let digital_type = DigitalType::try_from(req.digital_type)
+            .map_err(|_| Status::invalid_argument("Invalid digital type"))?;
         
-        let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-        
-        Ok(Response::new(PriceResponse {
-            price,
-            computation_time_ms,
-            error_message: String::new(),
-            delta: None,
-            gamma: None,
-            vega: None,
-            theta: None,
-            rho: None,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_digital_call(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.payout,
+                    digital_type,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("digital_call", computation_time_ms);
+            
+            let response = Self::with_digital_advice(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+            );
+            Self::with_sampling_report(
+                response,
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
         }))
+        .await
     }
-async fn price_batch(
+    
+    async fn price_digital_put(
         &self,
-        request: Request<BatchRequest>,
-    ) -> Result<Response<BatchResponse>, Status> {
+        request: Request<DigitalRequest>,
+    ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
         let req = request.into_inner();
-        let config = Self::get_config(req.config);
+        Self::validate_digital(&req)?;
+        let key = self.cache.key("digital_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Digital, req.config);
         
-        let start = Instant::now();
+        let digital_type = DigitalType::try_from(req.digital_type)
+            .map_err(|_| Status::invalid_argument("Invalid digital type"))?;
         
-        let mut call_prices = Vec::new();
-        let mut put_prices = Vec::new();
-        
-        // Price all calls
-        for call_req in req.european_calls {
-            let price = self.engine.price_european_call(
-                call_req.spot,
-                call_req.strike,
-                call_req.rate,
-                call_req.volatility,
-                call_req.time_to_maturity,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_digital_put(
+                    req.spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility,
+                    req.time_to_maturity,
+                    req.payout,
+                    digital_type,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("digital_put", computation_time_ms);
+            
+            let response = Self::with_digital_advice(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
                 &config,
             );
-            call_prices.push(price);
-        }
+            Self::with_sampling_report(
+                response,
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
+        }))
+        .await
+    }
+    
+    async fn price_spread_call(
+        &self,
+        request: Request<SpreadRequest>,
+    ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        Self::validate_spread(&req)?;
+        let key = self.cache.key("spread_call", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Spread, req.config);
         
-        // Price all puts
-        for put_req in req.european_puts {
-            let price = self.engine.price_european_put(
-                put_req.spot,
-                put_req.strike,
-                put_req.rate,
-                put_req.volatility,
-                put_req.time_to_maturity,
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_spread_call(
+                    req.spot1,
+                    req.spot2,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility1,
+                    req.volatility2,
+                    req.correlation,
+                    req.time_to_maturity,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("spread_call", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
                 &config,
-            );
-            put_prices.push(price);
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
+        }))
+        .await
+    }
+    
+    async fn price_spread_put(
+        &self,
+        request: Request<SpreadRequest>,
+    ) -> Result<Response<PriceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        Self::validate_spread(&req)?;
+        let key = self.cache.key("spread_put", &req, req.config.as_ref());
+        let config = self.get_config(OptionKind::Spread, req.config);
+        
+        self.cached(key, self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price_with = |config: &SimulationConfig| {
+                engine.price_spread_put(
+                    req.spot1,
+                    req.spot2,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    req.volatility1,
+                    req.volatility2,
+                    req.correlation,
+                    req.time_to_maturity,
+                    config,
+                )
+            };
+            let price = price_with(&config);
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("spread_put", computation_time_ms);
+            
+            Self::with_sampling_report(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+                price_with,
+            )
+        }))
+        .await
+    }
+    
+async fn price_batch(
+        &self,
+        request: Request<BatchRequest>,
+    ) -> Result<Response<BatchResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        for (i, call_req) in req.european_calls.iter().enumerate() {
+            Self::validate_batch_entry("european_calls", i, call_req)?;
+        }
+        for (i, put_req) in req.european_puts.iter().enumerate() {
+            Self::validate_batch_entry("european_puts", i, put_req)?;
         }
+        let selection = Self::greek_selection(&req.greeks)?;
+        let explicit_config = req.config.is_some();
+        let config = self.get_config(OptionKind::European, req.config);
+        
+        // The whole batch counts as one pricing, with one time limit; every
+        // task below holds a share of the slot until it finishes
+        let slot = Arc::new(self.acquire_slot().await?);
+        let start = Instant::now();
+        
+        // European calls and puts run in one task off the async workers
+        let european_task = {
+            let engine = Arc::clone(&self.engine);
+            let slot = Arc::clone(&slot);
+            let config = config.clone();
+            let calls = req.european_calls;
+            let puts = req.european_puts;
+            tokio::task::spawn_blocking(move || {
+                let _slot = slot;
+                let mut call_prices = Vec::new();
+                let mut put_prices = Vec::new();
+                
+                // Price all calls
+                for call_req in calls {
+                    let price = engine.price_european_call(
+                        call_req.spot,
+                        call_req.strike,
+                        call_req.rate,
+                        call_req.dividend_yield,
+                        call_req.volatility,
+                        call_req.time_to_maturity,
+                        &config,
+                    );
+                    call_prices.push(price);
+                }
+                
+                // Price all puts
+                for put_req in puts {
+                    let price = engine.price_european_put(
+                        put_req.spot,
+                        put_req.strike,
+                        put_req.rate,
+                        put_req.dividend_yield,
+                        put_req.volatility,
+                        put_req.time_to_maturity,
+                        &config,
+                    );
+                    put_prices.push(price);
+                }
+                
+                (call_prices, put_prices)
+            })
+        };
+        
+        // Price mixed legs concurrently, in one contiguous chunk per pricing
+        // context so the blocking pool holds no more tasks than can run.
+        // join_all keeps the chunks, and so the results, in input order.
+        // Without a request config each leg gets its option type's defaults,
+        // sharing the batch seed. Greeks are computed with the same seed, in
+        // the leg's task.
+        let legs: Vec<(BatchLeg, SimulationConfig)> = req
+            .legs
+            .into_iter()
+            .map(|leg| {
+                let leg_config = if explicit_config {
+                    config.clone()
+                } else {
+                    SimulationConfig {
+                        seed: config.seed,
+                        ..self.defaults.get(OptionKind::of_leg(&leg)).clone()
+                    }
+                };
+                (leg, leg_config)
+            })
+            .collect();
+        let chunk_size = legs.len().div_ceil(self.engine.pool_size().max(1)).max(1);
+        let leg_tasks = legs.chunks(chunk_size).map(|chunk| {
+            let engine = Arc::clone(&self.engine);
+            let slot = Arc::clone(&slot);
+            let chunk = chunk.to_vec();
+            let len = chunk.len();
+            let task = tokio::task::spawn_blocking(move || {
+                let _slot = slot;
+                chunk
+                    .into_iter()
+                    .map(|(leg, config)| {
+                        Self::price_leg_with_greeks(&engine, leg, &config, selection)
+                            .map_err(|status| status.message().to_string())
+                    })
+                    .collect::<Vec<_>>()
+            });
+            async move { (len, task.await) }
+        });
+        
+        let (leg_joined, european_joined) = Self::finish_within(
+            self.time_limit(deadline),
+            futures::future::join(futures::future::join_all(leg_tasks), european_task),
+        )
+        .await?;
+        
+        // A failed task fails only the legs of its own chunk
+        let leg_results: Vec<BatchLegResult> = leg_joined
+            .into_iter()
+            .flat_map(|(len, joined)| match joined {
+                Ok(results) => results,
+                Err(e) => vec![Err(format!("Pricing task failed: {}", e)); len],
+            })
+            .map(|result| {
+                let (outcome, greeks) = match result {
+                    Ok((price, greeks)) => (batch_leg_result::Outcome::Price(price), greeks),
+                    Err(message) => (batch_leg_result::Outcome::Error(message), None),
+                };
+                BatchLegResult {
+                    outcome: Some(outcome),
+                    greeks,
+                }
+            })
+            .collect();
+        
+        let (call_prices, put_prices) = european_joined
+            .map_err(|e| Status::internal(format!("Pricing task failed: {}", e)))?;
         
         let total_computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        METRICS.record_pricing("batch", total_computation_time_ms);
         
         info!(
-            "Batch priced: {} calls + {} puts in {:.2}ms",
+            "Batch priced: {} calls + {} puts + {} legs in {:.2}ms",
             call_prices.len(),
             put_prices.len(),
+            leg_results.len(),
             total_computation_time_ms
         );
         
@@ -506,6 +1805,151 @@ async fn price_batch(
             european_call_prices: call_prices,
             european_put_prices: put_prices,
             total_computation_time_ms,
+            leg_results,
+            seed_used: config.seed,
+        }))
+    }
+    
+    async fn price_surface(
+        &self,
+        request: Request<SurfaceRequest>,
+    ) -> Result<Response<SurfaceResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        
+        let is_call = match req.option_type.to_ascii_uppercase().as_str() {
+            "CALL" => true,
+            "PUT" => false,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "option_type must be CALL or PUT, got {:?}",
+                    other
+                )))
+            }
+        };
+        
+        if req.strikes.is_empty() || req.maturities.is_empty() {
+            return Err(Status::invalid_argument("strikes and maturities cannot be empty"));
+        }
+        let points = req.strikes.len().saturating_mul(req.maturities.len());
+        if points > self.max_surface_points {
+            return Err(Status::invalid_argument(format!(
+                "Surface of {} strikes x {} maturities is {} points, more than the limit of {}",
+                req.strikes.len(),
+                req.maturities.len(),
+                points,
+                self.max_surface_points
+            )));
+        }
+        
+        Self::require_positive("spot", req.spot)?;
+        Self::require_finite("rate", req.rate)?;
+        Self::require_finite("dividend_yield", req.dividend_yield)?;
+        Self::require_positive("volatility", req.volatility)?;
+        for (i, strike) in req.strikes.iter().enumerate() {
+            Self::require_positive(&format!("strikes[{}]", i), *strike)?;
+        }
+        for (i, maturity) in req.maturities.iter().enumerate() {
+            Self::require_positive(&format!("maturities[{}]", i), *maturity)?;
+        }
+        let selection = Self::greek_selection(&req.greeks)?;
+        let config = self.get_config(OptionKind::European, req.config);
+        
+        // Like a batch, the surface is one pricing with one time limit. The
+        // row-major grid is split into one contiguous chunk per pricing
+        // context, so the blocking pool holds no more tasks than can run;
+        // join_all keeps the chunks, and so the results, in order.
+        let slot = Arc::new(self.acquire_slot().await?);
+        let start = Instant::now();
+        
+        let grid: Vec<(f64, f64)> = req
+            .strikes
+            .iter()
+            .flat_map(|&strike| req.maturities.iter().map(move |&maturity| (strike, maturity)))
+            .collect();
+        let chunk_size = points.div_ceil(self.engine.pool_size().max(1));
+        let tasks = grid.chunks(chunk_size).map(|chunk| {
+            let engine = Arc::clone(&self.engine);
+            let slot = Arc::clone(&slot);
+            let config = config.clone();
+            let chunk = chunk.to_vec();
+            let (spot, rate, dividend_yield, volatility) =
+                (req.spot, req.rate, req.dividend_yield, req.volatility);
+            tokio::task::spawn_blocking(move || {
+                let _slot = slot;
+                chunk
+                    .into_iter()
+                    .map(|(strike, maturity)| {
+                        let (price, greeks) = if is_call {
+                            (
+                                engine.price_european_call(
+                                    spot, strike, rate, dividend_yield, volatility, maturity, &config,
+                                ),
+                                selection.any().then(|| {
+                                    engine.greeks_european_call(
+                                        spot, strike, rate, dividend_yield, volatility, maturity,
+                                        &config, selection,
+                                    )
+                                }),
+                            )
+                        } else {
+                            (
+                                engine.price_european_put(
+                                    spot, strike, rate, dividend_yield, volatility, maturity, &config,
+                                ),
+                                selection.any().then(|| {
+                                    engine.greeks_european_put(
+                                        spot, strike, rate, dividend_yield, volatility, maturity,
+                                        &config, selection,
+                                    )
+                                }),
+                            )
+                        };
+                        let greeks = greeks.map(|greeks| LegGreeks {
+                            delta: greeks.delta,
+                            gamma: greeks.gamma,
+                            vega: greeks.vega,
+                            theta: greeks.theta,
+                            rho: greeks.rho,
+                        });
+                        (price, greeks)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        
+        let joined =
+            Self::finish_within(self.time_limit(deadline), futures::future::join_all(tasks)).await?;
+        
+        let mut prices = Vec::with_capacity(points);
+        let mut greeks = Vec::new();
+        for result in joined {
+            let chunk =
+                result.map_err(|e| Status::internal(format!("Pricing task failed: {}", e)))?;
+            for (price, point_greeks) in chunk {
+                prices.push(price);
+                greeks.extend(point_greeks);
+            }
+        }
+        
+        let total_computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        METRICS.record_pricing("surface", total_computation_time_ms);
+        
+        info!(
+            "Surface priced: {} {} strikes x {} maturities in {:.2}ms",
+            req.option_type,
+            req.strikes.len(),
+            req.maturities.len(),
+            total_computation_time_ms
+        );
+        
+        Ok(Response::new(SurfaceResponse {
+            num_strikes: req.strikes.len() as u32,
+            num_maturities: req.maturities.len() as u32,
+            prices,
+            greeks,
+            total_computation_time_ms,
+            seed_used: config.seed,
         }))
     }
     
@@ -513,14 +1957,624 @@ async fn price_batch(
         &self,
         request: Request<MarketPriceRequest>,
     ) -> Result<Response<PriceResponse>, Status> {
-        let _req = request.into_inner();
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
+        
+        if req.underlying_symbol.is_empty() {
+            return Err(Status::invalid_argument("underlying_symbol cannot be empty"));
+        }
+        
+        let is_call = match req.option_type.to_ascii_uppercase().as_str() {
+            "CALL" => true,
+            "PUT" => false,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "option_type must be CALL or PUT, got {:?}",
+                    other
+                )))
+            }
+        };
+        
+        let style = req.option_style.to_ascii_uppercase();
+        if !style.is_empty() && style != "EUROPEAN" {
+            return Err(Status::unimplemented(format!(
+                "Market-based pricing supports EUROPEAN options only, got {}",
+                style
+            )));
+        }
+        
+        // Zero means "estimate from recent trades"
+        let volatility = if req.volatility != 0.0 {
+            req.volatility
+        } else {
+            self.matching_client
+                .realized_volatility(&req.underlying_symbol)
+                .ok_or_else(|| {
+                    Status::failed_precondition(format!(
+                        "Not enough recent trades in {} to estimate volatility; provide volatility",
+                        req.underlying_symbol
+                    ))
+                })?
+        };
+        
+        let spot = self.market_spot(&req.underlying_symbol).await?;
+        
+        Self::validate_inputs(
+            spot,
+            Some(req.strike),
+            req.rate,
+            req.dividend_yield,
+            volatility,
+            req.time_to_maturity,
+        )?;
+        let config = self.get_config(OptionKind::European, req.config);
+        
+        debug!(
+            "Pricing {} from market: symbol={}, spot={}, strike={}, vol={}, ttm={}",
+            req.option_type, req.underlying_symbol, spot, req.strike, volatility, req.time_to_maturity
+        );
+        
+        self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let price = if is_call {
+                engine.price_european_call(
+                    spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    volatility,
+                    req.time_to_maturity,
+                    &config,
+                )
+            } else {
+                engine.price_european_put(
+                    spot,
+                    req.strike,
+                    req.rate,
+                    req.dividend_yield,
+                    volatility,
+                    req.time_to_maturity,
+                    &config,
+                )
+            };
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing(if is_call { "european_call" } else { "european_put" }, computation_time_ms);
+            
+            info!(
+                "{} {} priced from market spot ${:.2}: ${:.4} in {:.2}ms",
+                req.underlying_symbol, req.option_type, spot, price, computation_time_ms
+            );
+            
+            Self::with_simulations_run(
+                Response::new(PriceResponse {
+                    price,
+                    computation_time_ms,
+                    spot: Some(spot),
+                    seed_used: config.seed,
+                    ..Default::default()
+                }),
+                &config,
+                MonteCarloEngine::simulations_run(&config),
+            )
+        })
+        .await
+    }
+    
+    async fn implied_volatility(
+        &self,
+        request: Request<ImpliedVolRequest>,
+    ) -> Result<Response<ImpliedVolResponse>, Status> {
+        let deadline = TradingServiceImpl::request_deadline(&request);
+        let req = request.into_inner();
         
-        // TODO: Implement market data fetching
-        // This would query the order book for current spot price
-        // and potentially estimate volatility from recent trades
+        let is_call = match req.option_type.to_ascii_uppercase().as_str() {
+            "CALL" => true,
+            "PUT" => false,
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "option_type must be CALL or PUT, got {:?}",
+                    other
+                )))
+            }
+        };
+        
+        Self::require_positive("target_price", req.target_price)?;
+        Self::require_positive("spot", req.spot)?;
+        Self::require_positive("strike", req.strike)?;
+        Self::require_finite("rate", req.rate)?;
+        Self::require_finite("dividend_yield", req.dividend_yield)?;
+        Self::require_positive("time_to_maturity", req.time_to_maturity)?;
+        
+        if req.tolerance < 0.0 || !req.tolerance.is_finite() {
+            return Err(Status::invalid_argument("tolerance must be a non-negative finite number"));
+        }
+        
+        // No volatility reproduces a price outside the no-arbitrage bounds
+        let discounted_strike = req.strike * (-req.rate * req.time_to_maturity).exp();
+        let discounted_spot = req.spot * (-req.dividend_yield * req.time_to_maturity).exp();
+        let (lower_bound, upper_bound) = if is_call {
+            ((discounted_spot - discounted_strike).max(0.0), discounted_spot)
+        } else {
+            ((discounted_strike - discounted_spot).max(0.0), discounted_strike)
+        };
+        
+        if req.target_price < lower_bound {
+            return Err(Status::out_of_range(format!(
+                "target_price {} is below intrinsic value {:.6}",
+                req.target_price, lower_bound
+            )));
+        }
+        
+        if req.target_price >= upper_bound {
+            return Err(Status::out_of_range(format!(
+                "target_price {} is at or above the upper bound {:.6}",
+                req.target_price, upper_bound
+            )));
+        }
+        
+        let max_iterations = match req.max_iterations {
+            0 => IMPLIED_VOL_DEFAULT_ITERATIONS,
+            n => n.min(IMPLIED_VOL_MAX_ITERATIONS),
+        };
+        let tolerance = if req.tolerance > 0.0 {
+            req.tolerance
+        } else {
+            IMPLIED_VOL_DEFAULT_TOLERANCE
+        };
+        let config = self.get_config(OptionKind::European, req.config);
+        
+        self.simulate(deadline, move |engine| {
+            let start = Instant::now();
+            
+            let solved = engine.implied_vol_european(
+                is_call,
+                req.target_price,
+                req.spot,
+                req.strike,
+                req.rate,
+                req.dividend_yield,
+                req.time_to_maturity,
+                tolerance,
+                max_iterations,
+                &config,
+            );
+            
+            let computation_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            METRICS.record_pricing("implied_volatility", computation_time_ms);
+            
+            info!(
+                "Implied vol solved: {:.6} after {} iterations (converged={}) in {:.2}ms",
+                solved.volatility, solved.iterations, solved.converged, computation_time_ms
+            );
+            
+            Response::new(ImpliedVolResponse {
+                implied_volatility: solved.volatility,
+                iterations: solved.iterations,
+                converged: solved.converged,
+                price_error: solved.price_error,
+                computation_time_ms,
+                seed_used: config.seed,
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    async fn service() -> PricingServiceImpl {
+        cached_service(0).await
+    }
+    
+    async fn cached_service(capacity: usize) -> PricingServiceImpl {
+        let config = crate::config::Config::default();
+        PricingServiceImpl::new(
+            Arc::new(MonteCarloEngine::new(2).unwrap()),
+            Arc::new(MatchingClient::without_gateway(100).await),
+            SimulationDefaults::new(&config.monte_carlo),
+            2,
+            Duration::from_secs(5),
+            None,
+            PricingCache::new(capacity),
+            config.monte_carlo.max_surface_points,
+        )
+    }
+    
+    fn european(config: SimulationConfig) -> EuropeanRequest {
+        EuropeanRequest {
+            spot: 100.0,
+            strike: 100.0,
+            rate: 0.05,
+            volatility: 0.2,
+            time_to_maturity: 1.0,
+            config: Some(config),
+            ..Default::default()
+        }
+    }
+    
+    fn simulations_run(response: &Response<PriceResponse>) -> Option<u64> {
+        response
+            .metadata()
+            .get(SIMULATIONS_RUN_KEY)
+            .map(|value| value.to_str().unwrap().parse().unwrap())
+    }
+    
+    #[tokio::test]
+    async fn odd_antithetic_count_reports_the_paths_run() {
+        let service = service().await;
+        let config = SimulationConfig {
+            num_simulations: 15,
+            seed: 7,
+            antithetic_enabled: true,
+            ..Default::default()
+        };
+        
+        let response = service
+            .price_european_call(Request::new(european(config)))
+            .await
+            .unwrap();
+        
+        // Ten one-path sub-runs, each rounded up to an antithetic pair
+        assert_eq!(simulations_run(&response), Some(20));
+    }
+    
+    #[tokio::test]
+    async fn exact_count_reports_nothing() {
+        let service = service().await;
+        let config = SimulationConfig {
+            num_simulations: 1000,
+            seed: 7,
+            ..Default::default()
+        };
+        
+        let response = service
+            .price_european_call(Request::new(european(config)))
+            .await
+            .unwrap();
+        
+        assert_eq!(simulations_run(&response), None);
+    }
+    
+    #[tokio::test]
+    async fn surface_prices_come_back_in_row_major_order() {
+        let service = service().await;
+        let config = SimulationConfig {
+            num_simulations: 1000,
+            seed: 7,
+            ..Default::default()
+        };
+        let (strikes, maturities) = (vec![90.0, 110.0], vec![0.5, 1.0]);
+        
+        let surface = service
+            .price_surface(Request::new(SurfaceRequest {
+                spot: 100.0,
+                rate: 0.05,
+                volatility: 0.2,
+                strikes: strikes.clone(),
+                maturities: maturities.clone(),
+                option_type: "CALL".to_string(),
+                config: Some(config.clone()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((surface.num_strikes, surface.num_maturities), (2, 2));
+        
+        // Each point matches a pricing of its own strike and maturity,
+        // strikes outer and maturities inner
+        let expected: Vec<f64> = strikes
+            .iter()
+            .flat_map(|&strike| maturities.iter().map(move |&maturity| (strike, maturity)))
+            .map(|(strike, maturity)| {
+                service.engine.price_european_call(100.0, strike, 0.05, 0.0, 0.2, maturity, &config)
+            })
+            .collect();
+        assert_eq!(surface.prices, expected);
+    }
+    
+    #[tokio::test]
+    async fn bad_batch_leg_fails_alone() {
+        use batch_leg::Option as Leg;
+        
+        let service = service().await;
+        let config = SimulationConfig {
+            num_simulations: 1000,
+            seed: 7,
+            ..Default::default()
+        };
+        let mut bad = european(config.clone());
+        bad.volatility = -0.2;
+        let legs = vec![
+            Leg::EuropeanCall(european(config.clone())),
+            Leg::EuropeanCall(bad),
+            Leg::EuropeanPut(european(config.clone())),
+        ];
+        let mut legs: Vec<BatchLeg> = legs.into_iter().map(|leg| BatchLeg { option: Some(leg) }).collect();
+        legs.push(BatchLeg { option: None });
+        
+        let response = service
+            .price_batch(Request::new(BatchRequest {
+                legs,
+                config: Some(config),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        
+        let outcomes: Vec<_> = response.leg_results.into_iter().map(|result| result.outcome.unwrap()).collect();
+        assert_eq!(outcomes.len(), 4);
+        assert!(matches!(outcomes[0], batch_leg_result::Outcome::Price(price) if price > 0.0));
+        assert!(
+            matches!(&outcomes[1], batch_leg_result::Outcome::Error(message) if message.contains("volatility")),
+            "{:?}",
+            outcomes[1]
+        );
+        assert!(matches!(outcomes[2], batch_leg_result::Outcome::Price(price) if price > 0.0));
+        assert!(matches!(outcomes[3], batch_leg_result::Outcome::Error(_)));
+    }
+    
+    #[tokio::test]
+    async fn batch_asking_for_delta_gets_only_delta() {
+        use batch_leg::Option as Leg;
+        
+        let service = service().await;
+        let config = SimulationConfig {
+            num_simulations: 1000,
+            seed: 7,
+            ..Default::default()
+        };
+        let response = service
+            .price_batch(Request::new(BatchRequest {
+                legs: vec![BatchLeg { option: Some(Leg::EuropeanCall(european(config.clone()))) }],
+                config: Some(config),
+                greeks: vec![Greek::Delta as i32],
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        
+        let greeks = response.leg_results[0].greeks.as_ref().unwrap();
+        let delta = greeks.delta.unwrap();
+        assert!(delta > 0.0 && delta < 1.0, "{}", delta);
+        assert_eq!((greeks.gamma, greeks.vega, greeks.theta, greeks.rho), (None, None, None, None));
+    }
+    
+    #[tokio::test]
+    async fn bad_exercise_dates_are_rejected() {
+        let service = service().await;
+        let bermudan = |exercise_dates: &[f64]| BermudanRequest {
+            spot: 100.0,
+            strike: 100.0,
+            rate: 0.05,
+            volatility: 0.2,
+            exercise_dates: exercise_dates.to_vec(),
+            config: Some(SimulationConfig {
+                num_simulations: 1000,
+                seed: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        
+        let cases: &[(&[f64], &str)] = &[
+            (&[], "exercise_dates cannot be empty"),
+            (&[-0.25, 0.5, 1.0], "exercise_dates[0] must be a positive"),
+            (&[0.0, 0.5, 1.0], "exercise_dates[0] must be a positive"),
+            (&[0.25, f64::NAN, 1.0], "exercise_dates[1] must be a positive"),
+            (&[0.25, 1.0, 0.5], "exercise_dates[2] = 0.5 is not after exercise_dates[1] = 1"),
+            (&[0.25, 0.5, 0.5, 1.0], "exercise_dates[2] = 0.5 is not after"),
+            // A date past the last one, which is the maturity
+            (&[1.5, 0.5, 1.0], "exercise_dates[1] = 0.5 is not after exercise_dates[0] = 1.5"),
+        ];
+        for (exercise_dates, expected) in cases {
+            let status = service
+                .price_bermudan_call(Request::new(bermudan(exercise_dates)))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{:?}", exercise_dates);
+            let message = status.message();
+            assert!(message.contains(expected), "{:?}: {}", exercise_dates, message);
+        }
+        
+        let valid = bermudan(&[0.25, 0.5, 0.75, 1.0]);
+        let response = service.price_bermudan_call(Request::new(valid)).await.unwrap();
+        assert!(response.into_inner().price > 0.0);
+    }
+    
+    #[tokio::test]
+    async fn bad_pricing_inputs_are_rejected() {
+        let service = service().await;
+        let base = || european(SimulationConfig { num_simulations: 1000, ..Default::default() });
+        
+        let cases = [
+            (EuropeanRequest { spot: f64::NAN, ..base() }, "spot must be a positive finite"),
+            (EuropeanRequest { spot: -100.0, ..base() }, "spot must be a positive finite"),
+            (EuropeanRequest { strike: 0.0, ..base() }, "strike must be a positive finite"),
+            (EuropeanRequest { strike: f64::INFINITY, ..base() }, "strike must be a positive"),
+            (EuropeanRequest { volatility: 0.0, ..base() }, "volatility must be a positive"),
+            (EuropeanRequest { volatility: -0.2, ..base() }, "volatility must be a positive"),
+            (EuropeanRequest { time_to_maturity: 0.0, ..base() }, "time_to_maturity must be"),
+            (EuropeanRequest { time_to_maturity: f64::NAN, ..base() }, "time_to_maturity must be"),
+            (EuropeanRequest { rate: f64::INFINITY, ..base() }, "rate must be a finite number"),
+            (EuropeanRequest { dividend_yield: f64::NAN, ..base() }, "dividend_yield must be"),
+        ];
+        for (request, expected) in cases {
+            let status = service
+                .price_european_call(Request::new(request.clone()))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{:?}", request);
+            assert!(status.message().contains(expected), "{:?}: {}", request, status.message());
+        }
+        
+        for barrier_level in [0.0, -120.0, f64::NAN] {
+            let request = BarrierRequest {
+                spot: 100.0,
+                strike: 100.0,
+                rate: 0.05,
+                volatility: 0.2,
+                time_to_maturity: 1.0,
+                barrier_level,
+                config: Some(SimulationConfig { num_simulations: 1000, ..Default::default() }),
+                ..Default::default()
+            };
+            let status = service.price_barrier_call(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+            let message = status.message();
+            assert!(message.contains("barrier_level must be a positive"), "{}", barrier_level);
+        }
+    }
+    
+    #[tokio::test]
+    async fn echoed_seed_reproduces_the_price() {
+        let service = service().await;
+        let unseeded = SimulationConfig {
+            num_simulations: 1000,
+            ..Default::default()
+        };
+        
+        let first = service
+            .price_european_call(Request::new(european(unseeded.clone())))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_ne!(first.seed_used, 0);
+        
+        let replayed = service
+            .price_european_call(Request::new(european(SimulationConfig {
+                seed: first.seed_used,
+                ..unseeded
+            })))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(replayed.seed_used, first.seed_used);
+        assert_eq!(replayed.price, first.price);
+    }
+    
+    #[tokio::test]
+    async fn second_seeded_request_is_a_cache_hit() {
+        let service = cached_service(8).await;
+        let price = |seed| {
+            let config = SimulationConfig {
+                num_simulations: 1000,
+                seed,
+                ..Default::default()
+            };
+            service.price_european_call(Request::new(european(config)))
+        };
+        
+        let first = price(7).await.unwrap().into_inner();
+        let second = price(7).await.unwrap().into_inner();
+        assert!(!first.cache_hit);
+        assert!(second.cache_hit);
+        assert_eq!(second.price, first.price);
+        
+        // Unseeded requests simulate every time
+        for _ in 0..2 {
+            assert!(!price(0).await.unwrap().into_inner().cache_hit);
+        }
+    }
+    
+    #[tokio::test]
+    async fn american_request_without_config_gets_the_american_defaults() {
+        let service = service().await;
+        let overrides = crate::config::Config::default().monte_carlo.overrides;
+        
+        let american = service.get_config(OptionKind::American, None);
+        let european = service.get_config(OptionKind::European, None);
+        assert_eq!(Some(american.num_steps), overrides.american.num_steps);
+        assert_eq!(Some(european.num_steps), overrides.european.num_steps);
+        assert_ne!(american.num_steps, european.num_steps);
+        assert_ne!(american.seed, 0);
+        
+        // A request's own config wins
+        let explicit = SimulationConfig {
+            num_steps: 10,
+            seed: 7,
+            ..Default::default()
+        };
+        assert_eq!(service.get_config(OptionKind::American, Some(explicit.clone())), explicit);
+    }
+    
+    #[tokio::test]
+    async fn order_submission_is_not_held_up_by_pricing() {
+        use crate::proto::common::{OrderType, Side};
+        use crate::proto::trading::{trading_service_server::TradingService, OrderRequest};
+        
+        let service = service().await;
+        let trading = TradingServiceImpl::for_tests(Arc::new(MatchingClient::without_gateway(100).await));
+        
+        // A batch heavy enough to take a while, started on this test's
+        // single runtime thread
+        let config = SimulationConfig {
+            num_simulations: 2_000_000,
+            seed: 7,
+            ..Default::default()
+        };
+        let heavy = BatchRequest {
+            european_calls: vec![european(config.clone()); 8],
+            config: Some(config),
+            ..Default::default()
+        };
+        let pricing = tokio::spawn({
+            let service = service.clone();
+            async move { service.price_batch(Request::new(heavy)).await }
+        });
+        while service.engine.contexts_in_use() == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        
+        let start = Instant::now();
+        let _ = trading
+            .submit_order(Request::new(OrderRequest {
+                symbol: "AAPL".to_string(),
+                user_id: 7,
+                side: Side::Buy as i32,
+                order_type: OrderType::Limit as i32,
+                price: 100.0,
+                quantity: 10,
+                ..Default::default()
+            }))
+            .await;
+        let latency = start.elapsed();
+        
+        assert!(!pricing.is_finished(), "pricing finished before the order was handled");
+        assert!(latency < Duration::from_millis(100), "order took {:?}", latency);
+        pricing.await.unwrap().unwrap();
+    }
+    
+    #[tokio::test]
+    async fn pricing_past_the_request_deadline_is_abandoned() {
+        let service = service().await;
+        let oversized = european(SimulationConfig {
+            num_simulations: 10_000_000,
+            seed: 7,
+            ..Default::default()
+        });
+        let mut request = Request::new(oversized);
+        request.metadata_mut().insert("grpc-timeout", "20m".parse().unwrap());
+        
+        let start = Instant::now();
+        let status = service.price_european_call(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded, "{}", status.message());
+        assert!(start.elapsed() < Duration::from_secs(1), "gave up after {:?}", start.elapsed());
         
-        Err(Status::unimplemented(
-            "Market-based pricing not yet implemented",
-        ))
+        // The abandoned simulation keeps one slot; the other still prices
+        let small = european(SimulationConfig {
+            num_simulations: 1_000,
+            seed: 7,
+            ..Default::default()
+        });
+        let mut request = Request::new(small);
+        request.metadata_mut().insert("grpc-timeout", "5S".parse().unwrap());
+        service.price_european_call(request).await.unwrap();
     }
 }
@@ -1,47 +1,953 @@
-use crate::matching::{MatchingClient, OrderType as MatchOrderType, Side as MatchSide};
+use crate::auth::{authorize_user, AuthenticatedUser};
+use crate::connection::ClosedConnections;
+use crate::execution_journal::{ExecutionJournal, RecvError, ReplayError, SequencedExecution};
+use crate::idempotency::{Claim, IdempotencyStore, OrderFingerprint};
+use crate::matching::client::{QuoteSubscription, TradeSubscription};
+use crate::matching::protocol::{
+    BookAction as MatchBookAction, BookDeltaMessage, BookLevel, BookSnapshotMessage,
+    ExecutionMessage, QuoteMessage, RejectCode, TradeMessage,
+};
+use crate::matching::{
+    MatchingBackend, MatchingClient, MatchingError, OrderState, OrderStore,
+    OrderType as MatchOrderType, PriceScale, Side as MatchSide,
+};
+use crate::metrics::METRICS;
+use crate::order_limits::OrderLimits;
+use crate::order_throttle::OrderThrottle;
 use crate::proto::{
     common::{OrderType, RejectReason, Side},
     trading::{
-        trading_service_server::TradingService, CancelRequest, CancelResponse,
-        ExecutionReport, OrderBookRequest, OrderBookSnapshot, OrderRequest, OrderResponse,
-        OrderStatusRequest, OrderStatusResponse, StreamRequest, TradeReport,
+        book_update, trading_service_server::TradingService, BookAction, BookDelta, BookUpdate,
+        CancelAllRequest, CancelAllResponse,
+        CancelRequest, CancelResponse, EstimateFillRequest, EstimateFillResponse,
+        ExecutionReport, MassQuoteRequest, MassQuoteResponse, OrderBookRequest,
+        OrderBookSnapshot, OrderRequest, OrderResponse, OrderStatusRequest, OrderStatusResponse,
+        Position, PositionsRequest, PositionsResponse, PriceLevel, QuoteLegResult, QuoteReport, ReplaceRequest, ReplaceResponse, SequenceGap,
+        StreamRequest, TradeReport,
     },
     Timestamp,
 };
+use crate::rate_limit::RateLimiter;
+use crate::stop_orders::{StopOrder, StopOrderBook};
+use crate::symbols::SymbolRegistry;
+use dashmap::DashMap;
+use shared::book::{self, BookError, LevelDelta, OrderBook};
+use shared::{OrderStatus, Price};
+use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info, warn};
 
+/// Maximum number of price levels returned per side by get_order_book
+const MAX_ORDER_BOOK_DEPTH: u32 = 100;
+
+/// Maximum number of symbols in one mass quote
+const MAX_MASS_QUOTE_ENTRIES: usize = 50;
+
+/// Longest order tag accepted, in bytes
+const MAX_ORDER_TAG_LEN: usize = 64;
+
+/// Recent executions kept per user for StreamExecutions to replay
+const EXECUTION_REPLAY_BUFFER: usize = 1000;
+
+/// Error metadata key for the client_order_id of an order whose ack timed
+/// out; it may still be live at the gateway
+const CLIENT_ORDER_ID_KEY: &str = "x-client-order-id";
+
 /// Trading service implementation
 #[derive(Clone)]
 pub struct TradingServiceImpl {
     matching_client: Arc<MatchingClient>,
+    /// Where orders go: the gateway, or the paper-trading matcher
+    backend: Arc<dyn MatchingBackend>,
+    order_store: Arc<OrderStore>,
+    rate_limiter: Arc<RateLimiter>,
+    order_limits: Arc<OrderLimits>,
+    /// Counts and caps the orders awaiting an ack per symbol
+    order_throttle: Arc<OrderThrottle>,
+    /// Normalizes symbols and enforces the allow-list
+    symbols: Arc<SymbolRegistry>,
+    idempotency: Arc<IdempotencyStore>,
+    /// Shortest gap between quotes sent to a StreamQuotes client
+    quote_interval: Duration,
+    /// Each user's resting MassQuote order per symbol and side
+    quotes: Arc<DashMap<(u64, String, MatchSide), u64>>,
+    /// Orders to cancel when the connection they were sent on closes,
+    /// keyed by the peer address
+    disconnect_orders: Arc<DashMap<SocketAddr, Vec<DisconnectOrder>>>,
+    /// Recent executions per user, for streams resuming after a reconnect
+    executions: Arc<ExecutionJournal>,
+    /// Stop orders waiting for a trade through their stop price
+    stops: Arc<StopOrderBook>,
+    /// Fixed-point scale of gateway prices
+    price_scale: PriceScale,
+}
+
+/// An order sent with cancel_on_disconnect
+#[derive(Debug, Clone)]
+struct DisconnectOrder {
+    client_order_id: u64,
+    user_id: u64,
+    symbol: String,
+}
+
+/// What an order would fill against one side of the book if it swept it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct FillEstimate {
+    filled: u64,
+    /// Quantity beyond what the levels hold
+    unfilled: u64,
+    /// Sum of price * quantity over the fills, in fixed-point units
+    notional: u128,
+    /// Price of the deepest level reached, in fixed-point units
+    worst_price: u64,
+    levels_consumed: u32,
+}
+
+impl FillEstimate {
+    /// Take from `levels`, best first, until `quantity` is filled or the
+    /// levels run out
+    fn walk(levels: &[BookLevel], quantity: u64) -> Self {
+        let mut estimate = Self {
+            unfilled: quantity,
+            ..Self::default()
+        };
+        
+        for level in levels {
+            if estimate.unfilled == 0 {
+                break;
+            }
+            let take = level.quantity.min(estimate.unfilled);
+            if take == 0 {
+                continue;
+            }
+            estimate.filled += take;
+            estimate.unfilled -= take;
+            estimate.notional += take as u128 * level.price as u128;
+            estimate.worst_price = level.price;
+            estimate.levels_consumed += 1;
+        }
+        
+        estimate
+    }
+    
+    /// Size-weighted average fill price in fixed-point units, when
+    /// anything fills
+    fn average_price(&self) -> Option<f64> {
+        (self.filled > 0).then(|| self.notional as f64 / self.filled as f64)
+    }
+    
+    /// How much worse the average fill is than `best_price` (the top of
+    /// the book walked), in basis points; 0 when nothing fills
+    fn impact_bps(&self, side: MatchSide, best_price: Option<u64>) -> f64 {
+        match (self.average_price(), best_price) {
+            (Some(average), Some(best)) if best > 0 => {
+                let best = best as f64;
+                let against = match side {
+                    MatchSide::Buy => average - best,
+                    MatchSide::Sell => best - average,
+                };
+                against / best * 10_000.0
+            }
+            _ => 0.0,
+        }
+    }
 }
 
 impl TradingServiceImpl {
-    pub fn new(matching_client: Arc<MatchingClient>) -> Self {
-        Self { matching_client }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        matching_client: Arc<MatchingClient>,
+        backend: Arc<dyn MatchingBackend>,
+        order_store: Arc<OrderStore>,
+        rate_limiter: Arc<RateLimiter>,
+        order_limits: Arc<OrderLimits>,
+        order_throttle: Arc<OrderThrottle>,
+        symbols: Arc<SymbolRegistry>,
+        idempotency: Arc<IdempotencyStore>,
+        quote_interval: Duration,
+    ) -> Self {
+        // Every execution goes through the journal, which numbers it and
+        // feeds the execution streams
+        let price_scale = matching_client.price_scale();
+        let executions = Arc::new(ExecutionJournal::new(EXECUTION_REPLAY_BUFFER));
+        let mut subscription = matching_client.subscribe_executions(None, None);
+        let journal = Arc::clone(&executions);
+        tokio::spawn(async move {
+            while let Some(msg) = subscription.recv().await {
+                journal.record(msg);
+            }
+            journal.close();
+        });
+        
+        let trades = matching_client.subscribe_trades(None);
+        let service = Self {
+            matching_client,
+            backend,
+            order_store,
+            rate_limiter,
+            order_limits,
+            order_throttle,
+            symbols,
+            idempotency,
+            quote_interval,
+            quotes: Arc::new(DashMap::new()),
+            disconnect_orders: Arc::new(DashMap::new()),
+            executions,
+            stops: Arc::new(StopOrderBook::new()),
+            price_scale,
+        };
+        tokio::spawn(service.clone().fire_stop_orders(trades));
+        service
+    }
+    
+    /// Send each held stop order a trade sets off, as a market order or,
+    /// for a stop-limit, a limit order. Runs until the trade feed closes.
+    async fn fire_stop_orders(self, mut trades: TradeSubscription) {
+        while let Some((trade, _)) = trades.recv().await {
+            for stop in self.stops.triggered(&trade.symbol, trade.price) {
+                info!(
+                    "Stop order {} triggered by a trade in {} at {}",
+                    stop.client_order_id, trade.symbol, trade.price
+                );
+                tokio::spawn(self.clone().send_triggered_stop(stop));
+            }
+        }
+    }
+    
+    /// Send the order a stop turns into once triggered. It keeps the
+    /// stop's client_order_id, so the client follows, cancels and sees
+    /// executions for it under the id it was given. An order that can't be
+    /// sent, including one the throttle holds back, is marked rejected.
+    async fn send_triggered_stop(self, stop: StopOrder) {
+        let (order_type, price) = match stop.limit_price {
+            Some(price) => (MatchOrderType::Limit, price),
+            None => (MatchOrderType::Market, 0),
+        };
+        let Some(held) = self.order_store.get(stop.client_order_id, stop.user_id) else {
+            warn!("Triggered stop order {} is no longer tracked", stop.client_order_id);
+            return;
+        };
+        
+        let _in_flight = match self.order_throttle.acquire(&stop.symbol) {
+            Ok(in_flight) => in_flight,
+            Err(status) => {
+                warn!(
+                    "Triggered stop order {} not sent: {}",
+                    stop.client_order_id,
+                    status.message()
+                );
+                METRICS.record_order_rejected("system_error");
+                self.order_store.reject_unsent(held);
+                return;
+            }
+        };
+        
+        METRICS.record_order_submitted(match stop.side {
+            MatchSide::Buy => "buy",
+            MatchSide::Sell => "sell",
+        });
+        
+        match self
+            .backend
+            .submit_order(
+                stop.symbol,
+                stop.user_id,
+                stop.side,
+                order_type,
+                price,
+                stop.quantity,
+                stop.tag,
+                Some(stop.client_order_id),
+                None,
+            )
+            .await
+        {
+            Ok(Ok(ack)) => info!(
+                "Triggered stop order {} sent, exchange_id={}",
+                stop.client_order_id, ack.exchange_order_id
+            ),
+            Ok(Err(reject)) => {
+                let reject_reason = Self::reject_reason_from_code(reject.reason);
+                METRICS
+                    .record_order_rejected(&reject_reason.as_str_name().to_ascii_lowercase());
+                warn!(
+                    "Triggered stop order {} rejected: {}",
+                    stop.client_order_id,
+                    Self::reject_message(reject.reason, reject.text)
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Failed to send triggered stop order {}: {}",
+                    stop.client_order_id, e
+                );
+                // Gone from the store, or never replaced as pending, only if
+                // nothing reached the gateway; a timed-out order may be live
+                let status = self.order_store.status(stop.client_order_id);
+                if status.is_none_or(|status| status == OrderStatus::PendingTrigger) {
+                    self.order_store.reject_unsent(held);
+                }
+            }
+        }
+    }
+    
+    /// A stop must sit beyond the last trade: above it for a buy, below it
+    /// for a sell. One the last trade already crosses would fire at once.
+    /// Before any trade there is nothing to check against.
+    #[allow(clippy::result_large_err)]
+    fn check_stop_price(&self, symbol: &str, side: MatchSide, stop_price: u64) -> Result<(), Status> {
+        let Some(last_price) = self.matching_client.last_trade_price(symbol) else {
+            return Ok(());
+        };
+        
+        let (beyond, direction) = match side {
+            MatchSide::Buy => (stop_price > last_price, "above"),
+            MatchSide::Sell => (stop_price < last_price, "below"),
+        };
+        if beyond {
+            Ok(())
+        } else {
+            Err(Status::invalid_argument(format!(
+                "{:?} stop price {} must be {} the last trade price {} in {}",
+                side,
+                self.price_scale.to_dollars(stop_price),
+                direction,
+                self.price_scale.to_dollars(last_price),
+                symbol
+            )))
+        }
+    }
+    
+    /// Cancel each closed connection's cancel_on_disconnect orders that
+    /// are still live. Runs until the listener goes away.
+    pub async fn cancel_on_disconnect(self, mut closed: ClosedConnections) {
+        while let Some(addr) = closed.recv().await {
+            let Some((_, orders)) = self.disconnect_orders.remove(&addr) else {
+                continue;
+            };
+            
+            for order in orders {
+                // Filled or already cancelled orders have nothing left to cancel
+                if self.stops.cancel(order.client_order_id, order.user_id).is_some() {
+                    self.order_store.cancel_stop(order.client_order_id);
+                    info!(
+                        "Connection {} closed, dropped stop order {}",
+                        addr, order.client_order_id
+                    );
+                    continue;
+                }
+                
+                if self
+                    .order_store
+                    .status(order.client_order_id)
+                    .is_none_or(|status| status.is_terminal())
+                {
+                    continue;
+                }
+                
+                info!(
+                    "Connection {} closed, cancelling order {}",
+                    addr, order.client_order_id
+                );
+                let backend = Arc::clone(&self.backend);
+                tokio::spawn(async move {
+                    if let Err(e) = backend
+                        .cancel_order(order.symbol, order.client_order_id, order.user_id, None)
+                        .await
+                    {
+                        warn!(
+                            "Failed to cancel order {} on disconnect: {:#}",
+                            order.client_order_id, e
+                        );
+                    }
+                });
+            }
+        }
+    }
+    
+    /// Remember an accepted order for cancellation when `addr` disconnects,
+    /// dropping that connection's orders that have since finished
+    fn register_disconnect_order(
+        &self,
+        addr: SocketAddr,
+        client_order_id: u64,
+        user_id: u64,
+        symbol: String,
+    ) {
+        let mut orders = self.disconnect_orders.entry(addr).or_default();
+        orders.retain(|order| {
+            self.order_store
+                .status(order.client_order_id)
+                .is_some_and(|status| !status.is_terminal())
+        });
+        orders.push(DisconnectOrder {
+            client_order_id,
+            user_id,
+            symbol,
+        });
+    }
+    
+    /// Convert a gRPC Side to the matching engine's. The raw value is
+    /// taken because the generated accessor turns unknown values into the
+    /// default side rather than rejecting them.
+    #[allow(clippy::result_large_err)]
+    fn convert_side(side: i32) -> Result<MatchSide, Status> {
+        match Side::try_from(side) {
+            Ok(Side::Buy) => Ok(MatchSide::Buy),
+            Ok(Side::Sell) => Ok(MatchSide::Sell),
+            Err(_) => Err(Status::invalid_argument(format!("Unknown side {}", side))),
+        }
+    }
+    
+    /// Convert a gRPC OrderType to the matching engine's, rejecting
+    /// unknown values as `convert_side` does
+    #[allow(clippy::result_large_err)]
+    fn convert_order_type(order_type: i32) -> Result<MatchOrderType, Status> {
+        match OrderType::try_from(order_type) {
+            Ok(OrderType::Limit) => Ok(MatchOrderType::Limit),
+            Ok(OrderType::Market) => Ok(MatchOrderType::Market),
+            Ok(OrderType::Stop) => Ok(MatchOrderType::Stop),
+            Ok(OrderType::StopLimit) => Ok(MatchOrderType::StopLimit),
+            Err(_) => Err(Status::invalid_argument(format!("Unknown order type {}", order_type))),
+        }
+    }
+    
+    /// Map a gateway reject code onto the gRPC RejectReason. Codes this
+    /// server doesn't know become SYSTEM_ERROR; see `reject_message`.
+    fn reject_reason_from_code(code: u8) -> RejectReason {
+        match RejectCode::try_from(code) {
+            Ok(RejectCode::UnknownSymbol) => RejectReason::InvalidSymbol,
+            Ok(RejectCode::InvalidPrice) => RejectReason::InvalidPrice,
+            Ok(RejectCode::InvalidQuantity) => RejectReason::InvalidQuantity,
+            Ok(RejectCode::DuplicateOrderId) => RejectReason::DuplicateOrderId,
+            Ok(RejectCode::UnknownOrder) => RejectReason::UnknownOrder,
+            Ok(RejectCode::InsufficientFunds) => RejectReason::InsufficientFunds,
+            Ok(RejectCode::MarketClosed) => RejectReason::MarketClosed,
+            Ok(RejectCode::SystemError) => RejectReason::SystemError,
+            Ok(RejectCode::BelowMinimumSize) => RejectReason::BelowMinimumSize,
+            Ok(RejectCode::PriceOutOfBand) => RejectReason::PriceOutOfBand,
+            Ok(RejectCode::SizeTooLarge) => RejectReason::SizeTooLarge,
+            Ok(RejectCode::InsufficientPermissions) => RejectReason::InsufficientPermissions,
+            Err(e) => {
+                warn!("{}, reporting as system error", e);
+                RejectReason::SystemError
+            }
+        }
+    }
+    
+    /// Reject text for the caller. An unknown code is appended, since
+    /// SYSTEM_ERROR alone would hide it.
+    fn reject_message(code: u8, text: String) -> String {
+        if RejectCode::try_from(code).is_ok() {
+            return text;
+        }
+        if text.is_empty() {
+            format!("unknown reject code 0x{:02x}", code)
+        } else {
+            format!("{} (unknown reject code 0x{:02x})", text, code)
+        }
+    }
+    
+    /// Reject user 0, which would share order IDs, limits and executions
+    /// with every other request that left the user unset
+    #[allow(clippy::result_large_err)]
+    fn validate_user_id(user_id: u64) -> Result<(), Status> {
+        if user_id == 0 {
+            return Err(Status::invalid_argument("Invalid user ID"));
+        }
+        Ok(())
+    }
+    
+    /// Convert price from dollars to the gateway's fixed-point units,
+    /// rejecting amounts finer than one unit
+    #[allow(clippy::result_large_err)]
+    fn price_to_fixed(&self, price: f64) -> Result<u64, Status> {
+        self.price_scale.to_fixed(price).ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "Price {} is not a whole multiple of {}",
+                price, self.price_scale
+            ))
+        })
+    }
+    
+    /// Status string reported by GetOrderStatus
+    fn order_status_name(status: OrderStatus) -> &'static str {
+        match status {
+            OrderStatus::PendingTrigger => "PENDING_TRIGGER",
+            OrderStatus::PendingNew => "PENDING_NEW",
+            OrderStatus::New => "OPEN",
+            OrderStatus::PartiallyFilled => "PARTIALLY_FILLED",
+            OrderStatus::Filled => "FILLED",
+            OrderStatus::Cancelled => "CANCELLED",
+            OrderStatus::Rejected => "REJECTED",
+        }
+    }
+    
+    /// Convert a tracked order into the GetOrderStatus response
+    pub(crate) fn to_order_status(scale: PriceScale, order: OrderState) -> OrderStatusResponse {
+        let side = match order.side {
+            MatchSide::Buy => Side::Buy,
+            MatchSide::Sell => Side::Sell,
+        };
+        
+        OrderStatusResponse {
+            client_order_id: order.client_order_id,
+            exchange_order_id: order.exchange_order_id,
+            symbol: order.symbol,
+            side: side as i32,
+            price: scale.to_dollars(order.price),
+            original_quantity: order.original_quantity,
+            filled_quantity: order.filled_quantity,
+            remaining_quantity: order.leaves_quantity,
+            status: Self::order_status_name(order.status).to_string(),
+            timestamp: Some(Timestamp {
+                nanos: order.timestamp,
+            }),
+            average_fill_price: scale.fractional_to_dollars(order.average_fill_price),
+            tag: order.tag,
+        }
+    }
+    
+    /// Convert a matching engine book level into a gRPC PriceLevel
+    fn to_price_level(scale: PriceScale, level: BookLevel) -> PriceLevel {
+        PriceLevel {
+            price: scale.to_dollars(level.price),
+            quantity: level.quantity,
+            order_count: level.order_count,
+        }
+    }
+    
+    /// Time left before the caller's deadline, from the `grpc-timeout`
+    /// header, if it set one
+    pub(crate) fn request_deadline<T>(request: &Request<T>) -> Option<Duration> {
+        let timeout = request.metadata().get("grpc-timeout")?.to_str().ok()?;
+        let (value, unit) = timeout.split_at(timeout.len().checked_sub(1)?);
+        let value: u64 = value.parse().ok()?;
+        
+        match unit {
+            "H" => Some(Duration::from_secs(value.saturating_mul(3600))),
+            "M" => Some(Duration::from_secs(value.saturating_mul(60))),
+            "S" => Some(Duration::from_secs(value)),
+            "m" => Some(Duration::from_millis(value)),
+            "u" => Some(Duration::from_micros(value)),
+            "n" => Some(Duration::from_nanos(value)),
+            _ => None,
+        }
+    }
+    
+    /// Map a matching client error to a gRPC status: a missing or lost
+    /// connection is UNAVAILABLE (retriable), no reply in time is
+    /// DEADLINE_EXCEEDED, a gateway refusal FAILED_PRECONDITION and a
+    /// request that couldn't be encoded INVALID_ARGUMENT. An order that
+    /// timed out waiting for its ack carries its id in the
+    /// `CLIENT_ORDER_ID_KEY` metadata, for checking on or cancelling it.
+    fn matching_error_status(action: &str, e: MatchingError) -> Status {
+        match &e {
+            MatchingError::Timeout(_) => {
+                warn!("{} timed out: {}", action, e);
+                Status::deadline_exceeded(e.to_string())
+            }
+            MatchingError::AckTimeout { client_order_id } => {
+                warn!("{} timed out: {}", action, e);
+                let mut status = Status::deadline_exceeded(e.to_string());
+                status
+                    .metadata_mut()
+                    .insert(CLIENT_ORDER_ID_KEY, (*client_order_id).into());
+                status
+            }
+            MatchingError::Rejected { .. } => {
+                warn!("{} rejected: {}", action, e);
+                Status::failed_precondition(format!("{} failed: {}", action, e))
+            }
+            MatchingError::Io(io) if io.kind() == std::io::ErrorKind::InvalidInput => {
+                warn!("{} not sent: {}", action, e);
+                Status::invalid_argument(format!("{} failed: {}", action, e))
+            }
+            MatchingError::NotConnected(_) | MatchingError::Io(_) | MatchingError::Closed(_) => {
+                error!("{} failed: {}", action, e);
+                Status::unavailable(format!("{} failed: {}", action, e))
+            }
+        }
+    }
+    
+    /// Convert a matching engine execution into a gRPC ExecutionReport,
+    /// labelled with its order's `tag`
+    fn to_execution_report(scale: PriceScale, tag: String, sequenced: SequencedExecution) -> ExecutionReport {
+        let SequencedExecution { sequence, execution: msg } = sequenced;
+        let side = match msg.side {
+            MatchSide::Buy => Side::Buy,
+            MatchSide::Sell => Side::Sell,
+        };
+        
+        ExecutionReport {
+            symbol: msg.symbol,
+            client_order_id: msg.client_order_id,
+            exchange_order_id: msg.exchange_order_id,
+            execution_id: msg.execution_id,
+            user_id: msg.user_id,
+            side: side as i32,
+            fill_price: scale.to_dollars(msg.fill_price),
+            fill_quantity: msg.fill_quantity,
+            leaves_quantity: msg.leaves_quantity,
+            timestamp: Some(Timestamp {
+                nanos: msg.timestamp,
+            }),
+            sequence,
+            tag,
+        }
+    }
+    
+    /// Convert a gateway book snapshot to a gRPC OrderBookSnapshot
+    fn to_order_book_snapshot(scale: PriceScale, snapshot: BookSnapshotMessage) -> OrderBookSnapshot {
+        let to_level = |level| Self::to_price_level(scale, level);
+        OrderBookSnapshot {
+            symbol: snapshot.symbol,
+            bids: snapshot.bids.into_iter().map(to_level).collect(),
+            asks: snapshot.asks.into_iter().map(to_level).collect(),
+            timestamp: Some(Timestamp {
+                nanos: snapshot.timestamp,
+            }),
+            sequence: snapshot.sequence,
+        }
+    }
+    
+    /// Convert a gateway book delta to a gRPC BookDelta
+    fn to_book_delta(scale: PriceScale, msg: BookDeltaMessage) -> BookDelta {
+        let side = match msg.side {
+            MatchSide::Buy => Side::Buy,
+            MatchSide::Sell => Side::Sell,
+        };
+        let action = match msg.action {
+            MatchBookAction::Add => BookAction::Add,
+            MatchBookAction::Change => BookAction::Change,
+            MatchBookAction::Delete => BookAction::Delete,
+        };
+        
+        BookDelta {
+            symbol: msg.symbol,
+            sequence: msg.sequence,
+            side: side as i32,
+            action: action as i32,
+            level: Some(Self::to_price_level(scale, msg.level)),
+            timestamp: Some(Timestamp {
+                nanos: msg.timestamp,
+            }),
+        }
+    }
+    
+    /// The book a gateway snapshot describes, for applying deltas to
+    fn to_order_book(scale: PriceScale, snapshot: &BookSnapshotMessage) -> OrderBook {
+        let to_level = |level: &BookLevel| book::Level {
+            price: Price::new(level.price, scale),
+            quantity: level.quantity,
+            order_count: level.order_count,
+        };
+        OrderBook::from_snapshot(
+            snapshot.sequence as u64,
+            snapshot.bids.iter().map(to_level),
+            snapshot.asks.iter().map(to_level),
+        )
+    }
+    
+    /// The level change a gateway book delta describes
+    fn to_level_delta(scale: PriceScale, msg: &BookDeltaMessage) -> LevelDelta {
+        LevelDelta {
+            side: match msg.side {
+                MatchSide::Buy => shared::Side::Buy,
+                MatchSide::Sell => shared::Side::Sell,
+            },
+            action: match msg.action {
+                MatchBookAction::Add => book::BookAction::Add,
+                MatchBookAction::Change => book::BookAction::Change,
+                MatchBookAction::Delete => book::BookAction::Delete,
+            },
+            level: book::Level {
+                price: Price::new(msg.level.price, scale),
+                quantity: msg.level.quantity,
+                order_count: msg.level.order_count,
+            },
+        }
+    }
+    
+    /// Fetch the deepest snapshot of a symbol's book the service works
+    /// from, for StreamOrderBook and EstimateFill
+    async fn fetch_book_snapshot(
+        matching_client: &MatchingClient,
+        symbol: &str,
+    ) -> Result<BookSnapshotMessage, Status> {
+        matching_client
+            .get_order_book(symbol.to_string(), MAX_ORDER_BOOK_DEPTH)
+            .await
+            .map_err(|e| Self::matching_error_status("Order book query", e))
+    }
+    
+    /// Ask the gateway to resend a symbol's book after a sequence gap
+    async fn resend_book_snapshot(
+        matching_client: &MatchingClient,
+        symbol: &str,
+        gap: &SequenceGap,
+    ) -> Result<BookSnapshotMessage, Status> {
+        matching_client
+            .resend_order_book(
+                symbol.to_string(),
+                MAX_ORDER_BOOK_DEPTH,
+                gap.expected_sequence as u32,
+                gap.received_sequence as u32,
+            )
+            .await
+            .map_err(|e| Self::matching_error_status("Order book resend", e))
+    }
+    
+    /// Convert a gateway trade to a gRPC TradeReport; `gap` if trades may
+    /// have been dropped before it
+    fn to_trade_report(scale: PriceScale, msg: TradeMessage, gap: bool) -> TradeReport {
+        TradeReport {
+            symbol: msg.symbol,
+            trade_id: msg.trade_id,
+            price: scale.to_dollars(msg.price),
+            quantity: msg.quantity,
+            timestamp: Some(Timestamp {
+                nanos: msg.timestamp,
+            }),
+            gap,
+        }
     }
     
-    /// Convert gRPC Side to matching engine Side
-    fn convert_side(side: Side) -> Result<MatchSide, Status> {
-        match side {
-            Side::Buy => Ok(MatchSide::Buy),
-            Side::Sell => Ok(MatchSide::Sell),
+    /// Convert a gateway quote to a gRPC QuoteReport
+    fn to_quote_report(scale: PriceScale, msg: QuoteMessage) -> QuoteReport {
+        QuoteReport {
+            symbol: msg.symbol,
+            bid: scale.to_dollars(msg.bid_price),
+            bid_size: msg.bid_quantity,
+            ask: scale.to_dollars(msg.ask_price),
+            ask_size: msg.ask_quantity,
+            timestamp: Some(Timestamp {
+                nanos: msg.timestamp,
+            }),
         }
     }
     
-    /// Convert gRPC OrderType to matching engine OrderType
-    fn convert_order_type(order_type: OrderType) -> Result<MatchOrderType, Status> {
-        match order_type {
-            OrderType::Limit => Ok(MatchOrderType::Limit),
-            OrderType::Market => Ok(MatchOrderType::Market),
+    /// Mark a mass quote leg as failed
+    fn reject_leg(
+        mut leg: QuoteLegResult,
+        reject_reason: RejectReason,
+        error_message: String,
+    ) -> QuoteLegResult {
+        leg.accepted = false;
+        leg.reject_reason = reject_reason as i32;
+        leg.error_message = error_message;
+        leg
+    }
+    
+    /// Move one side of a user's quote in `symbol` to `price` x `quantity`.
+    /// The resting quote order is replaced if there is one, otherwise a new
+    /// limit order goes in; a zero quantity cancels the resting order.
+    async fn quote_leg(
+        &self,
+        user_id: u64,
+        symbol: &str,
+        side: MatchSide,
+        price: f64,
+        quantity: u64,
+        deadline: tokio::time::Instant,
+    ) -> QuoteLegResult {
+        let remaining = || Some(deadline.saturating_duration_since(tokio::time::Instant::now()));
+        let leg = QuoteLegResult {
+            symbol: symbol.to_string(),
+            side: match side {
+                MatchSide::Buy => Side::Buy,
+                MatchSide::Sell => Side::Sell,
+            } as i32,
+            ..Default::default()
+        };
+        
+        let symbol = match self.symbols.resolve(symbol) {
+            Ok(symbol) => symbol,
+            Err(status) => {
+                return Self::reject_leg(leg, RejectReason::InvalidSymbol, status.message().to_string())
+            }
+        };
+        let symbol = symbol.as_str();
+        let key = (user_id, symbol.to_string(), side);
+        let leg = QuoteLegResult { symbol: symbol.to_string(), ..leg };
+        
+        // Only a quote that can still trade is worth replacing or cancelling
+        let resting = self
+            .quotes
+            .get(&key)
+            .map(|client_order_id| *client_order_id)
+            .filter(|client_order_id| {
+                self.order_store
+                    .status(*client_order_id)
+                    .is_some_and(|status| !status.is_terminal())
+            });
+        
+        if quantity == 0 {
+            let Some(client_order_id) = resting else {
+                self.quotes.remove(&key);
+                return QuoteLegResult { accepted: true, ..leg };
+            };
+            let leg = QuoteLegResult { client_order_id, ..leg };
+            
+            return match self
+                .backend
+                .cancel_order(symbol.to_string(), client_order_id, user_id, remaining())
+                .await
+            {
+                Ok(()) => {
+                    self.quotes.remove(&key);
+                    QuoteLegResult { accepted: true, ..leg }
+                }
+                Err(e) => {
+                    warn!("Failed to pull quote {}: {}", client_order_id, e);
+                    Self::reject_leg(leg, RejectReason::SystemError, e.to_string())
+                }
+            };
+        }
+        
+        let price = match self.price_to_fixed(price) {
+            Ok(price) if price > 0 => price,
+            Ok(_) => {
+                return Self::reject_leg(
+                    leg,
+                    RejectReason::InvalidPrice,
+                    "Quotes must have positive price".to_string(),
+                )
+            }
+            Err(status) => {
+                return Self::reject_leg(leg, RejectReason::InvalidPrice, status.message().to_string())
+            }
+        };
+        if let Err(status) = self.symbols.check_tick(symbol, price) {
+            return Self::reject_leg(leg, RejectReason::InvalidPrice, status.message().to_string());
+        }
+        
+        if let Err(status) = self.order_limits.check(user_id, Some(self.price_scale.to_dollars(price)), quantity) {
+            return Self::reject_leg(leg, RejectReason::SizeTooLarge, status.message().to_string());
+        }
+        
+        if let Some(client_order_id) = resting {
+            match self
+                .backend
+                .replace_order(symbol.to_string(), client_order_id, user_id, price, quantity, remaining())
+                .await
+            {
+                Ok(Ok(replaced)) => {
+                    return QuoteLegResult {
+                        client_order_id: replaced.client_order_id,
+                        exchange_order_id: replaced.exchange_order_id,
+                        accepted: true,
+                        ..leg
+                    };
+                }
+                // Filled or cancelled since we last looked; quote afresh
+                Ok(Err(reject))
+                    if Self::reject_reason_from_code(reject.reason) == RejectReason::UnknownOrder => {}
+                Ok(Err(reject)) => {
+                    let leg = QuoteLegResult { client_order_id, ..leg };
+                    return Self::reject_leg(
+                        leg,
+                        Self::reject_reason_from_code(reject.reason),
+                        Self::reject_message(reject.reason, reject.text),
+                    );
+                }
+                Err(e) => {
+                    warn!("Failed to replace quote {}: {}", client_order_id, e);
+                    let leg = QuoteLegResult { client_order_id, ..leg };
+                    return Self::reject_leg(leg, RejectReason::SystemError, e.to_string());
+                }
+            }
+        }
+        
+        let _in_flight = match self.order_throttle.acquire(symbol) {
+            Ok(in_flight) => in_flight,
+            Err(status) => {
+                return Self::reject_leg(leg, RejectReason::SystemError, status.message().to_string())
+            }
+        };
+        
+        METRICS.record_order_submitted(match side {
+            MatchSide::Buy => "buy",
+            MatchSide::Sell => "sell",
+        });
+        
+        match self
+            .backend
+            .submit_order(
+                symbol.to_string(),
+                user_id,
+                side,
+                MatchOrderType::Limit,
+                price,
+                quantity,
+                String::new(),
+                None,
+                remaining(),
+            )
+            .await
+        {
+            Ok(Ok(ack)) => {
+                self.quotes.insert(key, ack.client_order_id);
+                QuoteLegResult {
+                    client_order_id: ack.client_order_id,
+                    exchange_order_id: ack.exchange_order_id,
+                    accepted: true,
+                    ..leg
+                }
+            }
+            Ok(Err(reject)) => {
+                let reject_reason = Self::reject_reason_from_code(reject.reason);
+                METRICS
+                    .record_order_rejected(&reject_reason.as_str_name().to_ascii_lowercase());
+                
+                let leg = QuoteLegResult { client_order_id: reject.client_order_id, ..leg };
+                let message = Self::reject_message(reject.reason, reject.text);
+                Self::reject_leg(leg, reject_reason, message)
+            }
+            Err(e) => {
+                warn!("Failed to submit quote in {}: {}", symbol, e);
+                Self::reject_leg(leg, RejectReason::SystemError, e.to_string())
+            }
         }
     }
     
-    /// Convert price from dollars to cents (fixed-point)
-    fn price_to_cents(price: f64) -> u64 {
-        (price * 100.0).round() as u64
+    /// Forward quotes to a StreamQuotes client, sending the latest at most
+    /// once per `interval`. Quotes that arrive in between replace the one
+    /// waiting, so a fast-moving book can't back up a slow client.
+    async fn forward_quotes(
+        mut subscription: QuoteSubscription,
+        tx: tokio::sync::mpsc::Sender<Result<QuoteReport, Status>>,
+        interval: Duration,
+        scale: PriceScale,
+    ) {
+        let mut latest = None;
+        let mut next_send = tokio::time::Instant::now();
+        
+        loop {
+            tokio::select! {
+                msg = subscription.recv() => {
+                    let Some(msg) = msg else {
+                        debug!("Quote source closed, ending stream");
+                        break;
+                    };
+                    latest = Some(msg);
+                }
+                _ = tokio::time::sleep_until(next_send), if latest.is_some() => {
+                    let Some(quote) = latest.take() else { continue };
+                    if tx.send(Ok(Self::to_quote_report(scale, quote))).await.is_err() {
+                        break;
+                    }
+                    next_send = tokio::time::Instant::now() + interval;
+                }
+                _ = tx.closed() => {
+                    debug!("Quote stream client disconnected");
+                    break;
+                }
+            }
+        }
     }
 }
 
@@ -51,7 +957,10 @@ impl TradingService for TradingServiceImpl {
         &self,
         request: Request<OrderRequest>,
     ) -> Result<Response<OrderResponse>, Status> {
-        let req = request.into_inner();
+        authorize_user(&request, request.get_ref().user_id)?;
+        let deadline = Self::request_deadline(&request);
+        let remote_addr = request.remote_addr();
+        let mut req = request.into_inner();
         
         debug!(
             "Submitting order: symbol={}, side={:?}, price=${:.2}, qty={}",
@@ -59,69 +968,238 @@ impl TradingService for TradingServiceImpl {
         );
         
         // Validate request
-        if req.symbol.is_empty() {
-            return Err(Status::invalid_argument("Symbol cannot be empty"));
-        }
+        Self::validate_user_id(req.user_id)?;
+        req.symbol = self.symbols.resolve(&req.symbol)?;
+        
+        let disconnect_addr = match (req.cancel_on_disconnect, remote_addr) {
+            (false, _) => None,
+            (true, Some(addr)) => Some(addr),
+            (true, None) => {
+                return Err(Status::failed_precondition(
+                    "cancel_on_disconnect needs a TCP connection",
+                ))
+            }
+        };
         
         if req.quantity == 0 {
             return Err(Status::invalid_argument("Quantity must be greater than 0"));
         }
         
-        if req.order_type() == OrderType::Limit && req.price <= 0.0 {
+        let side = Self::convert_side(req.side)?;
+        let order_type = Self::convert_order_type(req.order_type)?;
+        let is_limit = matches!(order_type, MatchOrderType::Limit | MatchOrderType::StopLimit);
+        if is_limit && req.price <= 0.0 {
             return Err(Status::invalid_argument(
                 "Limit orders must have positive price",
             ));
         }
         
-        // Convert types
-        let side = Self::convert_side(req.side())?;
-        let order_type = Self::convert_order_type(req.order_type())?;
-        let price = Self::price_to_cents(req.price);
+        let is_stop = matches!(order_type, MatchOrderType::Stop | MatchOrderType::StopLimit);
+        if is_stop && req.stop_price <= 0.0 {
+            return Err(Status::invalid_argument(
+                "Stop orders must have positive stop_price",
+            ));
+        }
+        if !is_stop && req.stop_price != 0.0 {
+            return Err(Status::invalid_argument(
+                "stop_price is only for stop and stop-limit orders",
+            ));
+        }
         
-        // Generate client order ID immediately
-        let client_order_id = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+        if req.tag.len() > MAX_ORDER_TAG_LEN {
+            return Err(Status::invalid_argument(format!(
+                "Tag must be at most {} bytes",
+                MAX_ORDER_TAG_LEN
+            )));
+        }
         
-        // Clone what we need for the async task
-        let matching_client = Arc::clone(&self.matching_client);
-        let symbol = req.symbol.clone();
-        let user_id = req.user_id;
-        let quantity = req.quantity;
+        let price = self.price_to_fixed(req.price)?;
+        if is_limit {
+            self.symbols.check_tick(&req.symbol, price)?;
+        }
         
-        // Submit order asynchronously - don't wait for response
-        tokio::spawn(async move {
-            match matching_client
-                .submit_order(symbol.clone(), user_id, side, order_type, price, quantity)
-                .await
+        let stop_price = self.price_to_fixed(req.stop_price)?;
+        if is_stop {
+            self.symbols.check_tick(&req.symbol, stop_price)?;
+            self.check_stop_price(&req.symbol, side, stop_price)?;
+        }
+        
+        // A stop order is valued at its stop price until it becomes a
+        // market order
+        let limit_price = is_limit
+            .then_some(req.price)
+            .or(is_stop.then_some(req.stop_price));
+        self.order_limits.check(req.user_id, limit_price, req.quantity)?;
+        
+        // A retry of an order we've already handled gets the original
+        // response; the gateway never sees it twice
+        let claim = if req.idempotency_key.is_empty() {
+            None
+        } else {
+            let fingerprint = OrderFingerprint {
+                symbol: req.symbol.clone(),
+                side: req.side,
+                order_type: req.order_type,
+                price,
+                stop_price,
+                quantity: req.quantity,
+                client_order_id: req.client_order_id,
+            };
+            match self
+                .idempotency
+                .claim(req.user_id, &req.idempotency_key, fingerprint)
+                .await?
             {
-                Ok(order_id) => {
-                    info!("Order submitted to engine: id={}, symbol={}", order_id, symbol);
-                }
-                Err(e) => {
-                    error!("Failed to submit order to engine: {}", e);
+                Claim::New(guard) => Some(guard),
+                Claim::Existing(response) => {
+                    info!(
+                        "Duplicate order with idempotency key {:?}, returning original response",
+                        req.idempotency_key
+                    );
+                    return Ok(Response::new(response));
                 }
             }
+        };
+        
+        self.rate_limiter.check(req.user_id)?;
+        
+        // Stops wait here for their trigger; see `fire_stop_orders`
+        if is_stop {
+            let client_order_id = self.order_store.next_client_order_id();
+            info!(
+                "Stop order held: id={}, symbol={}, stop_price={}",
+                client_order_id, req.symbol, stop_price
+            );
+            
+            if let Some(addr) = disconnect_addr {
+                self.register_disconnect_order(addr, client_order_id, req.user_id, req.symbol.clone());
+            }
+            let limit_price = (order_type == MatchOrderType::StopLimit).then_some(price);
+            self.order_store.insert_stop(
+                client_order_id,
+                req.user_id,
+                req.symbol.clone(),
+                side,
+                limit_price.unwrap_or(0),
+                req.quantity,
+                req.tag.clone(),
+            );
+            self.stops.hold(StopOrder {
+                client_order_id,
+                user_id: req.user_id,
+                symbol: req.symbol.clone(),
+                side,
+                stop_price,
+                limit_price,
+                quantity: req.quantity,
+                tag: req.tag.clone(),
+            });
+            
+            let response = OrderResponse {
+                client_order_id,
+                exchange_order_id: 0,
+                accepted: true,
+                reject_reason: RejectReason::None as i32,
+                error_message: String::new(),
+                timestamp: Some(Timestamp {
+                    nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+                }),
+            };
+            if let Some(guard) = claim {
+                guard.complete(&response);
+            }
+            return Ok(Response::new(response));
+        }
+        
+        // Counted until the ack, reject or failure below
+        let _in_flight = self.order_throttle.acquire(&req.symbol)?;
+        
+        METRICS.record_order_submitted(match side {
+            MatchSide::Buy => "buy",
+            MatchSide::Sell => "sell",
         });
         
-        // Return immediately with acknowledgment
-        info!("Order accepted (async): id={}, symbol={}", client_order_id, req.symbol);
+        // Wait for the gateway to acknowledge or reject the order
+        let response = self
+            .backend
+            .submit_order(
+                req.symbol.clone(),
+                req.user_id,
+                side,
+                order_type,
+                price,
+                req.quantity,
+                req.tag.clone(),
+                None,
+                deadline,
+            )
+            .await
+            .map_err(|e| Self::matching_error_status("Order submission", e))?;
         
-        Ok(Response::new(OrderResponse {
-            client_order_id,
-            exchange_order_id: 0, // Will be updated when gateway responds
-            accepted: true,
-            reject_reason: RejectReason::None as i32,
-            error_message: String::new(),
-            timestamp: Some(Timestamp {
-                nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
-            }),
-        }))
+        let response = match response {
+            Ok(ack) => {
+                info!(
+                    "Order accepted: id={}, exchange_id={}, symbol={}",
+                    ack.client_order_id, ack.exchange_order_id, req.symbol
+                );
+                
+                if let Some(addr) = disconnect_addr {
+                    self.register_disconnect_order(
+                        addr,
+                        ack.client_order_id,
+                        req.user_id,
+                        req.symbol.clone(),
+                    );
+                }
+                
+                OrderResponse {
+                    client_order_id: ack.client_order_id,
+                    exchange_order_id: ack.exchange_order_id,
+                    accepted: true,
+                    reject_reason: RejectReason::None as i32,
+                    error_message: String::new(),
+                    timestamp: Some(Timestamp {
+                        nanos: ack.timestamp,
+                    }),
+                }
+            }
+            Err(reject) => {
+                info!(
+                    "Order rejected: id={}, reason={}, text={}",
+                    reject.client_order_id, reject.reason, reject.text
+                );
+                
+                let reject_reason = Self::reject_reason_from_code(reject.reason);
+                METRICS
+                    .record_order_rejected(&reject_reason.as_str_name().to_ascii_lowercase());
+                
+                OrderResponse {
+                    client_order_id: reject.client_order_id,
+                    exchange_order_id: 0,
+                    accepted: false,
+                    reject_reason: reject_reason as i32,
+                    error_message: Self::reject_message(reject.reason, reject.text),
+                    timestamp: Some(Timestamp {
+                        nanos: reject.timestamp,
+                    }),
+                }
+            }
+        };
+        
+        if let Some(guard) = claim {
+            guard.complete(&response);
+        }
+        
+        Ok(Response::new(response))
     }
     
     async fn cancel_order(
         &self,
         request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
-        let req = request.into_inner();
+        authorize_user(&request, request.get_ref().user_id)?;
+        let deadline = Self::request_deadline(&request);
+        let mut req = request.into_inner();
         
         debug!(
             "Cancelling order: id={}, symbol={}",
@@ -129,33 +1207,26 @@ impl TradingService for TradingServiceImpl {
         );
         
         // Validate request
-        if req.symbol.is_empty() {
-            return Err(Status::invalid_argument("Symbol cannot be empty"));
-        }
+        Self::validate_user_id(req.user_id)?;
+        req.symbol = self.symbols.resolve(&req.symbol)?;
         
         if req.client_order_id == 0 {
             return Err(Status::invalid_argument("Invalid order ID"));
         }
         
-        // Submit cancel asynchronously
-        let matching_client = Arc::clone(&self.matching_client);
-        let symbol = req.symbol.clone();
-        let client_order_id = req.client_order_id;
-        let user_id = req.user_id;
+        self.rate_limiter.check(req.user_id)?;
         
-        tokio::spawn(async move {
-            match matching_client
-                .cancel_order(symbol.clone(), client_order_id, user_id)
+        // A stop that hasn't triggered yet never reached the gateway
+        if self.stops.cancel(req.client_order_id, req.user_id).is_some() {
+            self.order_store.cancel_stop(req.client_order_id);
+        } else {
+            self.backend
+                .cancel_order(req.symbol.clone(), req.client_order_id, req.user_id, deadline)
                 .await
-            {
-                Ok(()) => {
-                    info!("Order cancelled: id={}", client_order_id);
-                }
-                Err(e) => {
-                    error!("Failed to cancel order: {}", e);
-                }
-            }
-        });
+                .map_err(|e| Self::matching_error_status("Order cancel", e))?;
+        }
+        
+        info!("Order cancelled: id={}", req.client_order_id);
         
         Ok(Response::new(CancelResponse {
             client_order_id: req.client_order_id,
@@ -166,41 +1237,501 @@ impl TradingService for TradingServiceImpl {
             }),
         }))
     }
-
-    // Streaming methods - stub implementations for now
-    type StreamExecutionsStream =
-        tokio_stream::wrappers::ReceiverStream<Result<ExecutionReport, Status>>;
     
-    async fn stream_executions(
+    async fn cancel_all_orders(
         &self,
-        request: Request<StreamRequest>,
-    ) -> Result<Response<Self::StreamExecutionsStream>, Status> {
+        request: Request<CancelAllRequest>,
+    ) -> Result<Response<CancelAllResponse>, Status> {
+        authorize_user(&request, request.get_ref().user_id)?;
+        let deadline = Self::request_deadline(&request);
         let req = request.into_inner();
-        debug!("Starting execution stream for symbol: {}", req.symbol);
         
-        let (_tx, rx) = tokio::sync::mpsc::channel(100);
+        debug!(
+            "Cancelling all orders: user={}, symbol={}",
+            req.user_id, req.symbol
+        );
         
-        warn!("Execution streaming not yet fully implemented");
+        Self::validate_user_id(req.user_id)?;
         
-        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
-    }
-    
-    type StreamOrderBookStream =
-        tokio_stream::wrappers::ReceiverStream<Result<OrderBookSnapshot, Status>>;
-    
-    async fn stream_order_book(
-        &self,
-        request: Request<StreamRequest>,
-    ) -> Result<Response<Self::StreamOrderBookStream>, Status> {
-        let req = request.into_inner();
-        debug!("Starting order book stream for symbol: {}", req.symbol);
+        self.rate_limiter.check(req.user_id)?;
         
-        let (_tx, rx) = tokio::sync::mpsc::channel(100);
+        // Empty symbol means all symbols
+        let symbol = self.symbols.resolve_filter(&req.symbol)?;
         
-        warn!("Order book streaming not yet fully implemented");
+        // Held stops go first; they cancel without a round trip, and are
+        // no longer live by the time the rest are listed
+        let mut results: Vec<CancelResponse> = self
+            .stops
+            .cancel_all(req.user_id, symbol.as_deref())
+            .into_iter()
+            .map(|order| {
+                self.order_store.cancel_stop(order.client_order_id);
+                CancelResponse {
+                    client_order_id: order.client_order_id,
+                    cancelled: true,
+                    error_message: String::new(),
+                    timestamp: Some(Timestamp {
+                        nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+                    }),
+                }
+            })
+            .collect();
         
-        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
-    }
+        let orders = self.order_store.live_orders(req.user_id, symbol.as_deref());
+        
+        let cancels = orders.into_iter().map(|order| async move {
+            // A fill may have finished the order since the snapshot was taken
+            let status = self.order_store.status(order.client_order_id);
+            let result = match status {
+                Some(status) if status.is_terminal() => {
+                    Err(format!("Order already {}", Self::order_status_name(status)))
+                }
+                _ => self
+                    .backend
+                    .cancel_order(order.symbol, order.client_order_id, req.user_id, deadline)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to cancel order {}: {}", order.client_order_id, e);
+                        e.to_string()
+                    }),
+            };
+            
+            CancelResponse {
+                client_order_id: order.client_order_id,
+                cancelled: result.is_ok(),
+                error_message: result.err().unwrap_or_default(),
+                timestamp: Some(Timestamp {
+                    nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+                }),
+            }
+        });
+        
+        results.extend(futures::future::join_all(cancels).await);
+        
+        let cancelled_count = results.iter().filter(|result| result.cancelled).count() as u32;
+        let failed_count = results.len() as u32 - cancelled_count;
+        info!(
+            "Cancel all for user {}: {} cancelled, {} failed",
+            req.user_id, cancelled_count, failed_count
+        );
+        
+        Ok(Response::new(CancelAllResponse {
+            cancelled_count,
+            failed_count,
+            results,
+            timestamp: Some(Timestamp {
+                nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+            }),
+        }))
+    }
+    
+    async fn replace_order(
+        &self,
+        request: Request<ReplaceRequest>,
+    ) -> Result<Response<ReplaceResponse>, Status> {
+        authorize_user(&request, request.get_ref().user_id)?;
+        let deadline = Self::request_deadline(&request);
+        let mut req = request.into_inner();
+        
+        debug!(
+            "Replacing order: id={}, symbol={}, price=${:.2}, qty={}",
+            req.client_order_id, req.symbol, req.new_price, req.new_quantity
+        );
+        
+        // Validate request
+        Self::validate_user_id(req.user_id)?;
+        req.symbol = self.symbols.resolve(&req.symbol)?;
+        
+        if req.client_order_id == 0 {
+            return Err(Status::invalid_argument("Invalid order ID"));
+        }
+        
+        if req.new_quantity == 0 {
+            return Err(Status::invalid_argument("Quantity must be greater than 0"));
+        }
+        
+        if req.new_price <= 0.0 {
+            return Err(Status::invalid_argument("Replaced orders must have positive price"));
+        }
+        
+        if self.stops.is_held(req.client_order_id) {
+            return Err(Status::failed_precondition(
+                "Stop orders can't be replaced before they trigger; cancel and resubmit",
+            ));
+        }
+        
+        // Catch what the gateway would reject before spending a round trip
+        let order = self
+            .order_store
+            .get(req.client_order_id, req.user_id)
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "Unknown order {} for user {}",
+                    req.client_order_id, req.user_id
+                ))
+            })?;
+        
+        if req.new_quantity <= order.filled_quantity {
+            return Err(Status::invalid_argument(format!(
+                "New quantity {} must exceed the {} already filled",
+                req.new_quantity, order.filled_quantity
+            )));
+        }
+        
+        let new_price = self.price_to_fixed(req.new_price)?;
+        self.symbols.check_tick(&req.symbol, new_price)?;
+        
+        self.order_limits
+            .check(req.user_id, Some(req.new_price), req.new_quantity)?;
+        
+        // Wait for the gateway to confirm or reject the replace
+        let response = self
+            .backend
+            .replace_order(
+                req.symbol.clone(),
+                req.client_order_id,
+                req.user_id,
+                new_price,
+                req.new_quantity,
+                deadline,
+            )
+            .await
+            .map_err(|e| Self::matching_error_status("Order replace", e))?;
+        
+        let response = match response {
+            Ok(replaced) => {
+                info!(
+                    "Order replaced: id={}, price={}, qty={}",
+                    replaced.client_order_id, replaced.new_price, replaced.new_quantity
+                );
+                
+                ReplaceResponse {
+                    client_order_id: replaced.client_order_id,
+                    exchange_order_id: replaced.exchange_order_id,
+                    replaced: true,
+                    reject_reason: RejectReason::None as i32,
+                    error_message: String::new(),
+                    timestamp: Some(Timestamp {
+                        nanos: replaced.timestamp,
+                    }),
+                }
+            }
+            Err(reject) => {
+                info!(
+                    "Order replace rejected: id={}, reason={}, text={}",
+                    reject.client_order_id, reject.reason, reject.text
+                );
+                
+                let reject_reason = Self::reject_reason_from_code(reject.reason);
+                
+                // Replacing an order the gateway doesn't know is a caller error
+                if reject_reason == RejectReason::UnknownOrder {
+                    return Err(Status::not_found(format!(
+                        "Unknown order {} for user {}",
+                        req.client_order_id, req.user_id
+                    )));
+                }
+                
+                // e.g. INVALID_QUANTITY when shrinking below the filled quantity
+                ReplaceResponse {
+                    client_order_id: reject.client_order_id,
+                    exchange_order_id: 0,
+                    replaced: false,
+                    reject_reason: reject_reason as i32,
+                    error_message: Self::reject_message(reject.reason, reject.text),
+                    timestamp: Some(Timestamp {
+                        nanos: reject.timestamp,
+                    }),
+                }
+            }
+        };
+        
+        Ok(Response::new(response))
+    }
+    
+    async fn mass_quote(
+        &self,
+        request: Request<MassQuoteRequest>,
+    ) -> Result<Response<MassQuoteResponse>, Status> {
+        authorize_user(&request, request.get_ref().user_id)?;
+        let deadline = Self::request_deadline(&request);
+        let mut req = request.into_inner();
+        
+        debug!(
+            "Mass quote: user={}, symbols={}",
+            req.user_id,
+            req.quotes.len()
+        );
+        
+        Self::validate_user_id(req.user_id)?;
+        
+        if req.quotes.is_empty() {
+            return Err(Status::invalid_argument("Mass quote must contain at least one quote"));
+        }
+        
+        if req.quotes.len() > MAX_MASS_QUOTE_ENTRIES {
+            return Err(Status::invalid_argument(format!(
+                "Mass quote may contain at most {} quotes",
+                MAX_MASS_QUOTE_ENTRIES
+            )));
+        }
+        
+        // Two entries for a symbol would race each other for the same legs,
+        // however the symbol is written. Invalid symbols are left for
+        // `quote_leg` to reject.
+        for entry in &mut req.quotes {
+            if let Ok(symbol) = self.symbols.resolve(&entry.symbol) {
+                entry.symbol = symbol;
+            }
+        }
+        let mut symbols = HashSet::new();
+        if let Some(entry) = req.quotes.iter().find(|entry| !symbols.insert(entry.symbol.as_str())) {
+            return Err(Status::invalid_argument(format!(
+                "Duplicate quote for symbol {}",
+                entry.symbol
+            )));
+        }
+        
+        self.rate_limiter.check(req.user_id)?;
+        
+        // Send every leg at once, spread over the pool, and wait for all the
+        // acks against a single deadline
+        let deadline = tokio::time::Instant::now() + self.matching_client.request_timeout(deadline);
+        let legs = req.quotes.iter().flat_map(|entry| {
+            [
+                self.quote_leg(
+                    req.user_id,
+                    &entry.symbol,
+                    MatchSide::Buy,
+                    entry.bid_price,
+                    entry.bid_quantity,
+                    deadline,
+                ),
+                self.quote_leg(
+                    req.user_id,
+                    &entry.symbol,
+                    MatchSide::Sell,
+                    entry.ask_price,
+                    entry.ask_quantity,
+                    deadline,
+                ),
+            ]
+        });
+        let legs = futures::future::join_all(legs).await;
+        
+        let accepted_count = legs.iter().filter(|leg| leg.accepted).count() as u32;
+        let rejected_count = legs.len() as u32 - accepted_count;
+        info!(
+            "Mass quote for user {}: {} legs accepted, {} rejected",
+            req.user_id, accepted_count, rejected_count
+        );
+        
+        Ok(Response::new(MassQuoteResponse {
+            legs,
+            accepted_count,
+            rejected_count,
+            timestamp: Some(Timestamp {
+                nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+            }),
+        }))
+    }
+    
+    type StreamExecutionsStream =
+        tokio_stream::wrappers::ReceiverStream<Result<ExecutionReport, Status>>;
+    
+    async fn stream_executions(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamExecutionsStream>, Status> {
+        let authenticated = request.extensions().get::<AuthenticatedUser>().copied();
+        if request.get_ref().user_id != 0 {
+            authorize_user(&request, request.get_ref().user_id)?;
+        }
+        let req = request.into_inner();
+        debug!("Starting execution stream for symbol: {}", req.symbol);
+        
+        // Empty symbol / zero user_id mean "no filter", except that
+        // authenticated callers only ever see their own executions
+        let symbol = self.symbols.resolve_filter(&req.symbol)?;
+        let user_id = (req.user_id != 0)
+            .then_some(req.user_id)
+            .or(authenticated.map(|user| user.user_id));
+        let matches = move |msg: &ExecutionMessage| {
+            symbol.as_ref().is_none_or(|s| *s == msg.symbol)
+                && user_id.is_none_or(|u| u == msg.user_id)
+        };
+        
+        // Subscribe before taking the replay so nothing falls in between;
+        // live executions the replay already covered are skipped below
+        let mut subscription = self.executions.subscribe();
+        let replay = match (req.resume_from_sequence, user_id) {
+            (0, _) => Vec::new(),
+            (_, None) => {
+                return Err(Status::invalid_argument(
+                    "resume_from_sequence needs a user_id",
+                ))
+            }
+            (after, Some(user_id)) => self.executions.replay(user_id, after).map_err(|e| match e {
+                ReplayError::TooOld => {
+                    Status::out_of_range("resume point too old, resubscribe fresh")
+                }
+                ReplayError::Unknown => {
+                    Status::out_of_range("resume point unknown, resubscribe fresh")
+                }
+            })?,
+        };
+        let replayed_through = replay
+            .last()
+            .map_or(req.resume_from_sequence, |sequenced| sequenced.sequence);
+        
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let scale = self.price_scale;
+        let order_store = Arc::clone(&self.order_store);
+        let to_report = move |sequenced: SequencedExecution| {
+            let tag = order_store
+                .tag(sequenced.execution.client_order_id)
+                .unwrap_or_default();
+            Self::to_execution_report(scale, tag, sequenced)
+        };
+        
+        // Replay the gap, then forward executions until the client goes away
+        // or the gateway shuts down. Returning drops the subscription, which
+        // unsubscribes from the broadcast.
+        tokio::spawn(async move {
+            for sequenced in replay {
+                if !matches(&sequenced.execution) {
+                    continue;
+                }
+                if tx.send(Ok(to_report(sequenced))).await.is_err() {
+                    return;
+                }
+            }
+            
+            loop {
+                tokio::select! {
+                    msg = subscription.recv() => {
+                        let sequenced = match msg {
+                            Ok(sequenced) => sequenced,
+                            // Executions were dropped while this client
+                            // wasn't keeping up; it resumes from the last
+                            // sequence it got to have them replayed
+                            Err(RecvError::Lagged(skipped)) => {
+                                let _ = tx.send(Err(Status::data_loss(format!(
+                                    "Execution stream fell behind and skipped {} executions, resubscribe with resume_from_sequence",
+                                    skipped
+                                )))).await;
+                                break;
+                            }
+                            Err(RecvError::Closed) => {
+                                debug!("Execution source closed, ending stream");
+                                break;
+                            }
+                        };
+                        if !matches(&sequenced.execution) || sequenced.sequence <= replayed_through {
+                            continue;
+                        }
+                        if tx.send(Ok(to_report(sequenced))).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = tx.closed() => {
+                        debug!("Execution stream client disconnected");
+                        break;
+                    }
+                }
+            }
+        });
+        
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+    
+    type StreamOrderBookStream = tokio_stream::wrappers::ReceiverStream<Result<BookUpdate, Status>>;
+    
+    async fn stream_order_book(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamOrderBookStream>, Status> {
+        let req = request.into_inner();
+        debug!("Starting order book stream for symbol: {}", req.symbol);
+        
+        let symbol = self.symbols.resolve(&req.symbol)?;
+        
+        // Subscribe before fetching the snapshot so no delta falls in
+        // between; deltas the snapshot already covers are skipped below
+        let mut deltas = self.matching_client.subscribe_book_deltas(symbol.clone());
+        let snapshot = Self::fetch_book_snapshot(&self.matching_client, &symbol).await?;
+        let mut book = Self::to_order_book(self.price_scale, &snapshot);
+        
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let matching_client = Arc::clone(&self.matching_client);
+        let scale = self.price_scale;
+        
+        // Send the snapshot, then each delta that applies cleanly to our copy
+        // of the book. A delta that doesn't (a missed sequence, or a level
+        // that doesn't line up) means the client's copy is wrong too, so it
+        // gets a fresh snapshot to start over from, and nothing until then.
+        // For a missed sequence the gateway is asked to resend, and the
+        // snapshot is marked with the gap.
+        tokio::spawn(async move {
+            let update = book_update::Update::Snapshot(Self::to_order_book_snapshot(scale, snapshot));
+            if tx.send(Ok(BookUpdate { update: Some(update), gap: None })).await.is_err() {
+                return;
+            }
+            
+            loop {
+                tokio::select! {
+                    msg = deltas.recv() => {
+                        let Some(msg) = msg else {
+                            debug!("Book delta source closed, ending stream");
+                            break;
+                        };
+                        let (update, gap) = match book.apply(msg.sequence as u64, &Self::to_level_delta(scale, &msg)) {
+                            Ok(true) => (book_update::Update::Delta(Self::to_book_delta(scale, msg)), None),
+                            // Covered by the snapshot, or another pooled
+                            // connection's copy of a delta already sent
+                            Ok(false) => continue,
+                            Err(e) => {
+                                warn!("Order book for {} out of sync ({}), resending snapshot", symbol, e);
+                                let gap = match e {
+                                    BookError::Gap { expected, received } => Some(SequenceGap {
+                                        expected_sequence: expected,
+                                        received_sequence: received,
+                                    }),
+                                    _ => None,
+                                };
+                                let snapshot = match &gap {
+                                    Some(gap) => {
+                                        Self::resend_book_snapshot(&matching_client, &symbol, gap).await
+                                    }
+                                    None => Self::fetch_book_snapshot(&matching_client, &symbol).await,
+                                };
+                                match snapshot {
+                                    Ok(snapshot) => {
+                                        book = Self::to_order_book(scale, &snapshot);
+                                        let snapshot = Self::to_order_book_snapshot(scale, snapshot);
+                                        (book_update::Update::Snapshot(snapshot), gap)
+                                    }
+                                    Err(status) => {
+                                        let _ = tx.send(Err(status)).await;
+                                        break;
+                                    }
+                                }
+                            }
+                        };
+                        if tx.send(Ok(BookUpdate { update: Some(update), gap })).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = tx.closed() => {
+                        debug!("Order book stream client disconnected");
+                        break;
+                    }
+                }
+            }
+        });
+        
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
     
     type StreamTradesStream = tokio_stream::wrappers::ReceiverStream<Result<TradeReport, Status>>;
     
@@ -211,9 +1742,59 @@ impl TradingService for TradingServiceImpl {
         let req = request.into_inner();
         debug!("Starting trade stream for symbol: {}", req.symbol);
         
-        let (_tx, rx) = tokio::sync::mpsc::channel(100);
+        // Empty symbol means all symbols
+        let symbol = self.symbols.resolve_filter(&req.symbol)?;
+        let mut subscription = self.matching_client.subscribe_trades(symbol);
         
-        warn!("Trade streaming not yet fully implemented");
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+        let scale = self.price_scale;
+        
+        // Forward trades in arrival order until the client goes away or the
+        // gateway shuts down. Returning drops the subscription.
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    msg = subscription.recv() => {
+                        let Some((msg, gap)) = msg else {
+                            debug!("Trade source closed, ending stream");
+                            break;
+                        };
+                        if tx.send(Ok(Self::to_trade_report(scale, msg, gap))).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = tx.closed() => {
+                        debug!("Trade stream client disconnected");
+                        break;
+                    }
+                }
+            }
+        });
+        
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+    
+    type StreamQuotesStream = tokio_stream::wrappers::ReceiverStream<Result<QuoteReport, Status>>;
+    
+    async fn stream_quotes(
+        &self,
+        request: Request<StreamRequest>,
+    ) -> Result<Response<Self::StreamQuotesStream>, Status> {
+        let req = request.into_inner();
+        debug!("Starting quote stream for symbol: {}", req.symbol);
+        
+        let symbol = self.symbols.resolve(&req.symbol)?;
+        let subscription = self.matching_client.subscribe_quotes(symbol);
+        
+        // Queue at most one quote for a slow client; newer ones replace it
+        // in `forward_quotes` rather than piling up here
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(Self::forward_quotes(
+            subscription,
+            tx,
+            self.quote_interval,
+            self.price_scale,
+        ));
         
         Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
     }
@@ -222,34 +1803,1455 @@ impl TradingService for TradingServiceImpl {
         &self,
         request: Request<OrderBookRequest>,
     ) -> Result<Response<OrderBookSnapshot>, Status> {
-        let req = request.into_inner();
+        let mut req = request.into_inner();
         debug!(
             "Getting order book for symbol: {}, depth: {}",
             req.symbol, req.depth
         );
         
-        warn!("Order book query not yet implemented");
+        // Validate request
+        req.symbol = self.symbols.resolve(&req.symbol)?;
         
-        Ok(Response::new(OrderBookSnapshot {
-            symbol: req.symbol,
-            bids: vec![],
-            asks: vec![],
-            timestamp: Some(Timestamp {
-                nanos: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
-            }),
-            sequence: 0,
-        }))
+        if req.depth == 0 {
+            return Err(Status::invalid_argument("Depth must be greater than 0"));
+        }
+        
+        let depth = req.depth.min(MAX_ORDER_BOOK_DEPTH);
+        
+        let snapshot = self
+            .matching_client
+            .get_order_book(req.symbol, depth)
+            .await
+            .map_err(|e| Self::matching_error_status("Order book query", e))?;
+        
+        Ok(Response::new(Self::to_order_book_snapshot(self.price_scale, snapshot)))
     }
     
     async fn get_order_status(
         &self,
         request: Request<OrderStatusRequest>,
     ) -> Result<Response<OrderStatusResponse>, Status> {
+        authorize_user(&request, request.get_ref().user_id)?;
         let req = request.into_inner();
         debug!("Getting order status for id: {}", req.client_order_id);
         
-        warn!("Order status query not yet implemented");
+        let order = self
+            .order_store
+            .get(req.client_order_id, req.user_id)
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "Unknown order {} for user {}",
+                    req.client_order_id, req.user_id
+                ))
+            })?;
+        
+        Ok(Response::new(Self::to_order_status(self.price_scale, order)))
+    }
+    
+    async fn get_positions(
+        &self,
+        request: Request<PositionsRequest>,
+    ) -> Result<Response<PositionsResponse>, Status> {
+        authorize_user(&request, request.get_ref().user_id)?;
+        let req = request.into_inner();
+        debug!("Getting positions for user: {}", req.user_id);
+        
+        Self::validate_user_id(req.user_id)?;
         
-        Err(Status::unimplemented("Order status query not yet implemented"))
+        let positions = self
+            .order_store
+            .positions(req.user_id)
+            .into_iter()
+            .map(|(symbol, position)| Position {
+                symbol,
+                quantity: position.quantity,
+                average_cost: self.price_scale.fractional_to_dollars(position.average_cost),
+            })
+            .collect();
+        
+        Ok(Response::new(PositionsResponse { positions }))
+    }
+    
+    async fn estimate_fill(
+        &self,
+        request: Request<EstimateFillRequest>,
+    ) -> Result<Response<EstimateFillResponse>, Status> {
+        let mut req = request.into_inner();
+        debug!(
+            "Estimating fill: symbol={}, side={:?}, qty={}",
+            req.symbol, req.side, req.quantity
+        );
+        
+        // Validate request
+        req.symbol = self.symbols.resolve(&req.symbol)?;
+        
+        if req.quantity == 0 {
+            return Err(Status::invalid_argument("Quantity must be greater than 0"));
+        }
+        
+        let side = Self::convert_side(req.side)?;
+        let snapshot = Self::fetch_book_snapshot(&self.matching_client, &req.symbol).await?;
+        
+        // A buy takes liquidity from the asks, a sell from the bids
+        let levels = match side {
+            MatchSide::Buy => &snapshot.asks,
+            MatchSide::Sell => &snapshot.bids,
+        };
+        let estimate = FillEstimate::walk(levels, req.quantity);
+        let best_price = levels.first().map(|level| level.price);
+        let average_price = estimate.average_price();
+        let impact_bps = estimate.impact_bps(side, best_price);
+        
+        let scale = self.price_scale;
+        Ok(Response::new(EstimateFillResponse {
+            symbol: req.symbol,
+            side: req.side,
+            filled_quantity: estimate.filled,
+            unfilled_quantity: estimate.unfilled,
+            average_price: average_price.map_or(0.0, |price| scale.fractional_to_dollars(price)),
+            best_price: best_price.map_or(0.0, |price| scale.to_dollars(price)),
+            worst_price: scale.to_dollars(estimate.worst_price),
+            levels_consumed: estimate.levels_consumed,
+            impact_bps,
+            timestamp: Some(Timestamp {
+                nanos: snapshot.timestamp,
+            }),
+        }))
+    }
+}
+
+#[cfg(test)]
+impl TradingServiceImpl {
+    /// The service with default limits, sending orders straight to
+    /// `matching_client`, for tests of what sits in front of it
+    pub(crate) fn for_tests(matching_client: Arc<MatchingClient>) -> Self {
+        let config = crate::config::Config::default();
+        let price_scale = matching_client.price_scale();
+        Self::new(
+            Arc::clone(&matching_client),
+            matching_client,
+            Arc::new(OrderStore::new()),
+            Arc::new(RateLimiter::new(&config.rate_limit)),
+            Arc::new(OrderLimits::new(&config.order_limits)),
+            Arc::new(OrderThrottle::new(&config.order_throttle)),
+            Arc::new(SymbolRegistry::new(&config.symbols, price_scale)),
+            Arc::new(IdempotencyStore::new(&config.idempotency)),
+            Duration::from_millis(config.market_data.quote_interval_ms),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, OrderLimitConfig, OrderThrottleConfig};
+    use crate::matching::client::{
+        ConnectionOptions, IncomingMessage, OrderAckResult, OrderReplaceResult,
+    };
+    use crate::matching::protocol::{MessageHeader, MessageType, OrderAckMessage};
+    use crate::matching::TradeHistory;
+    use crate::proto::trading::QuoteEntry;
+    use bytes::BufMut;
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::sync::{mpsc, watch};
+    use tokio::time::timeout;
+    
+    /// An order `FakeBackend` was asked to send
+    #[derive(Debug)]
+    struct Submitted {
+        client_order_id: Option<u64>,
+        order_type: MatchOrderType,
+        price: u64,
+        quantity: u64,
+        tag: String,
+    }
+    
+    /// Acks every order, reporting each on a channel and holding the ack
+    /// while `hold_acks` is set, and cancels every order but those in
+    /// `failing_cancels`, recording each asked for
+    struct FakeBackend {
+        submitted: mpsc::UnboundedSender<Submitted>,
+        hold_acks: watch::Receiver<bool>,
+        next_client_order_id: AtomicU64,
+        liveness: watch::Sender<bool>,
+        failing_cancels: Arc<Mutex<HashSet<u64>>>,
+        cancels: Arc<Mutex<Vec<u64>>>,
+    }
+    
+    #[tonic::async_trait]
+    impl MatchingBackend for FakeBackend {
+        async fn submit_order(
+            &self,
+            _symbol: String,
+            user_id: u64,
+            _side: MatchSide,
+            order_type: MatchOrderType,
+            price: u64,
+            quantity: u64,
+            tag: String,
+            client_order_id: Option<u64>,
+            _deadline: Option<Duration>,
+        ) -> Result<OrderAckResult, MatchingError> {
+            let _ = self.submitted.send(Submitted {
+                client_order_id,
+                order_type,
+                price,
+                quantity,
+                tag,
+            });
+            let _ = self.hold_acks.clone().wait_for(|hold| !*hold).await;
+            Ok(Ok(OrderAckMessage {
+                client_order_id: client_order_id.unwrap_or_else(|| {
+                    self.next_client_order_id.fetch_add(1, Ordering::Relaxed)
+                }),
+                exchange_order_id: 99,
+                user_id,
+                timestamp: 0,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            _symbol: String,
+            client_order_id: u64,
+            _user_id: u64,
+            _deadline: Option<Duration>,
+        ) -> Result<(), MatchingError> {
+            self.cancels.lock().push(client_order_id);
+            if self.failing_cancels.lock().contains(&client_order_id) {
+                return Err(MatchingError::Timeout(format!(
+                    "No reply to cancel of order {}",
+                    client_order_id
+                )));
+            }
+            Ok(())
+        }
+        
+        async fn replace_order(
+            &self,
+            _symbol: String,
+            _client_order_id: u64,
+            _user_id: u64,
+            _new_price: u64,
+            _new_quantity: u64,
+            _deadline: Option<Duration>,
+        ) -> Result<OrderReplaceResult, MatchingError> {
+            Err(MatchingError::NotConnected("fake backend".to_string()))
+        }
+        
+        fn watch_liveness(&self) -> watch::Receiver<bool> {
+            self.liveness.subscribe()
+        }
+    }
+    
+    struct Harness {
+        service: TradingServiceImpl,
+        client: Arc<MatchingClient>,
+        order_store: Arc<OrderStore>,
+        order_throttle: Arc<OrderThrottle>,
+        submitted: mpsc::UnboundedReceiver<Submitted>,
+        hold_acks: watch::Sender<bool>,
+        failing_cancels: Arc<Mutex<HashSet<u64>>>,
+        cancels: Arc<Mutex<Vec<u64>>>,
+    }
+    
+    async fn harness(throttle: OrderThrottleConfig) -> Harness {
+        harness_with(Config {
+            order_throttle: throttle,
+            ..Config::default()
+        })
+        .await
+    }
+    
+    async fn harness_with(config: Config) -> Harness {
+        let client = Arc::new(MatchingClient::without_gateway(100).await);
+        let (submitted_tx, submitted) = mpsc::unbounded_channel();
+        let (hold_acks, hold_acks_rx) = watch::channel(false);
+        let (failing_cancels, cancels) = (Arc::default(), Arc::default());
+        let backend = Arc::new(FakeBackend {
+            submitted: submitted_tx,
+            hold_acks: hold_acks_rx,
+            next_client_order_id: AtomicU64::new(1),
+            liveness: watch::channel(true).0,
+            failing_cancels: Arc::clone(&failing_cancels),
+            cancels: Arc::clone(&cancels),
+        });
+        let order_store = Arc::new(OrderStore::new());
+        let order_throttle = Arc::new(OrderThrottle::new(&config.order_throttle));
+        let service = TradingServiceImpl::new(
+            Arc::clone(&client),
+            backend,
+            Arc::clone(&order_store),
+            Arc::new(RateLimiter::new(&config.rate_limit)),
+            Arc::new(OrderLimits::new(&config.order_limits)),
+            Arc::clone(&order_throttle),
+            Arc::new(SymbolRegistry::new(&config.symbols, client.price_scale())),
+            Arc::new(IdempotencyStore::new(&config.idempotency)),
+            Duration::from_millis(config.market_data.quote_interval_ms),
+        );
+        Harness {
+            service,
+            client,
+            order_store,
+            order_throttle,
+            submitted,
+            hold_acks,
+            failing_cancels,
+            cancels,
+        }
+    }
+    
+    fn trade(client: &MatchingClient, trade_id: u64, price: u64) {
+        client.publish(IncomingMessage::Trade(TradeMessage {
+            symbol: "AAPL".to_string(),
+            trade_id,
+            price,
+            quantity: 100,
+            timestamp: trade_id,
+        }));
+    }
+    
+    fn stop_request(side: Side, order_type: OrderType, price: f64, stop_price: f64) -> OrderRequest {
+        OrderRequest {
+            symbol: "AAPL".to_string(),
+            user_id: 7,
+            side: side as i32,
+            order_type: order_type as i32,
+            price,
+            quantity: 10,
+            stop_price,
+            ..Default::default()
+        }
+    }
+    
+    #[tokio::test]
+    async fn stop_price_must_be_beyond_the_last_trade() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        
+        // Nothing to compare against before the first trade
+        assert!(h.service.check_stop_price("AAPL", MatchSide::Buy, 9_000).is_ok());
+        
+        trade(&h.client, 1, 10_000);
+        assert!(h.service.check_stop_price("AAPL", MatchSide::Buy, 10_100).is_ok());
+        assert!(h.service.check_stop_price("AAPL", MatchSide::Sell, 9_900).is_ok());
+        
+        for (side, stop_price) in [
+            (MatchSide::Buy, 9_900),
+            (MatchSide::Buy, 10_000),
+            (MatchSide::Sell, 10_100),
+            (MatchSide::Sell, 10_000),
+        ] {
+            let err = h.service.check_stop_price("AAPL", side, stop_price).unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+            assert!(err.message().contains("last trade price 100"), "{}", err.message());
+        }
+        
+        // Other symbols have no last trade yet
+        assert!(h.service.check_stop_price("MSFT", MatchSide::Buy, 9_000).is_ok());
+    }
+    
+    #[tokio::test]
+    async fn stop_on_the_wrong_side_is_rejected_at_submit() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        trade(&h.client, 1, 10_000);
+        
+        let err = h
+            .service
+            .submit_order(Request::new(stop_request(Side::Sell, OrderType::Stop, 0.0, 101.0)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        
+        let err = h
+            .service
+            .submit_order(Request::new(stop_request(Side::Buy, OrderType::Stop, 0.0, 0.0)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+    
+    #[tokio::test]
+    async fn trade_through_the_stop_sends_the_child_under_the_stop_id() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        trade(&h.client, 1, 10_000);
+        
+        let response = h
+            .service
+            .submit_order(Request::new(stop_request(Side::Buy, OrderType::StopLimit, 102.0, 101.0)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.accepted);
+        let id = response.client_order_id;
+        let held = h.order_store.get(id, 7).unwrap();
+        assert_eq!(held.status, OrderStatus::PendingTrigger);
+        
+        // Below the stop: still held
+        trade(&h.client, 2, 10_050);
+        assert!(timeout(Duration::from_millis(100), h.submitted.recv()).await.is_err());
+        
+        trade(&h.client, 3, 10_100);
+        let child = timeout(Duration::from_secs(1), h.submitted.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(child.client_order_id, Some(id));
+        assert_eq!(child.order_type, MatchOrderType::Limit);
+        assert_eq!((child.price, child.quantity), (10_200, 10));
+        
+        // Each stop fires once, however many trades cross it
+        trade(&h.client, 4, 10_200);
+        assert!(timeout(Duration::from_millis(100), h.submitted.recv()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn triggered_stop_is_rejected_when_the_throttle_is_full() {
+        let mut h = harness(OrderThrottleConfig {
+            max_in_flight_per_symbol: 1,
+        })
+        .await;
+        trade(&h.client, 1, 10_000);
+        
+        let id = h
+            .service
+            .submit_order(Request::new(stop_request(Side::Sell, OrderType::Stop, 0.0, 99.0)))
+            .await
+            .unwrap()
+            .into_inner()
+            .client_order_id;
+        
+        let in_flight = h.order_throttle.acquire("AAPL").unwrap();
+        trade(&h.client, 2, 9_900);
+        assert!(timeout(Duration::from_millis(200), h.submitted.recv()).await.is_err());
+        assert_eq!(h.order_store.get(id, 7).unwrap().status, OrderStatus::Rejected);
+        drop(in_flight);
+    }
+    
+    #[tokio::test]
+    async fn order_past_the_in_flight_limit_is_throttled() {
+        let mut h = harness(OrderThrottleConfig {
+            max_in_flight_per_symbol: 2,
+        })
+        .await;
+        h.hold_acks.send_replace(true);
+        
+        let service = h.service.clone();
+        let submit = move || {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .submit_order(Request::new(limit_request(100.0, 10)))
+                    .await
+            })
+        };
+        let held = [submit(), submit()];
+        for _ in &held {
+            h.submitted.recv().await.unwrap();
+        }
+        assert_eq!(h.order_throttle.in_flight(), vec![("AAPL".to_string(), 2)]);
+        
+        let status = submit().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted, "{}", status.message());
+        assert!(h.submitted.try_recv().is_err());
+        
+        // Once acked, the held orders make room again
+        h.hold_acks.send_replace(false);
+        for order in held {
+            order.await.unwrap().unwrap();
+        }
+        submit().await.unwrap().unwrap();
+        assert_eq!(h.order_throttle.in_flight(), vec![("AAPL".to_string(), 0)]);
+    }
+    
+    #[test]
+    fn book_levels_keep_the_gateway_price_scale() {
+        let scale = PriceScale::new(1000);
+        let level = |price| BookLevel {
+            price,
+            quantity: 10,
+            order_count: 1,
+        };
+        let snapshot = BookSnapshotMessage {
+            symbol: "AAPL".to_string(),
+            request_id: 1,
+            timestamp: 0,
+            sequence: 1,
+            bids: vec![level(100_002), level(100_001)],
+            asks: vec![],
+        };
+        let mut book = TradingServiceImpl::to_order_book(scale, &snapshot);
+        let bids: Vec<f64> = book.bids().map(|level| level.price.to_dollars()).collect();
+        assert_eq!(bids, [100.002, 100.001]);
+        
+        let delta = BookDeltaMessage {
+            symbol: "AAPL".to_string(),
+            sequence: 2,
+            side: MatchSide::Buy,
+            action: MatchBookAction::Add,
+            level: level(100_001),
+            timestamp: 0,
+        };
+        let err = book
+            .apply(2, &TradingServiceImpl::to_level_delta(scale, &delta))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Buy level $100.001 already exists");
+    }
+    
+    fn levels(levels: &[(u64, u64)]) -> Vec<BookLevel> {
+        levels
+            .iter()
+            .map(|&(price, quantity)| BookLevel {
+                price,
+                quantity,
+                order_count: 1,
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn fill_estimate_walks_the_book_for_its_average_price() {
+        let asks = levels(&[(10_000, 100), (10_010, 200), (10_050, 500)]);
+        
+        let estimate = FillEstimate::walk(&asks, 100);
+        assert_eq!((estimate.filled, estimate.unfilled, estimate.levels_consumed), (100, 0, 1));
+        assert_eq!(estimate.average_price(), Some(10_000.0));
+        assert_eq!(estimate.impact_bps(MatchSide::Buy, Some(10_000)), 0.0);
+        
+        // 100 @ 100.00, 200 @ 100.10, 100 @ 100.50
+        let estimate = FillEstimate::walk(&asks, 400);
+        assert_eq!((estimate.filled, estimate.unfilled, estimate.levels_consumed), (400, 0, 3));
+        assert_eq!(estimate.worst_price, 10_050);
+        assert_eq!(estimate.average_price(), Some(10_017.5));
+        assert!((estimate.impact_bps(MatchSide::Buy, Some(10_000)) - 17.5).abs() < 1e-9);
+        
+        // Selling into bids: the average is below the best bid
+        let bids = levels(&[(10_000, 100), (9_900, 100)]);
+        let estimate = FillEstimate::walk(&bids, 200);
+        assert_eq!(estimate.average_price(), Some(9_950.0));
+        assert!((estimate.impact_bps(MatchSide::Sell, Some(10_000)) - 50.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn fill_estimate_reports_what_the_book_cannot_fill() {
+        let asks = levels(&[(10_000, 100), (10_010, 0), (10_020, 50)]);
+        let estimate = FillEstimate::walk(&asks, 500);
+        assert_eq!((estimate.filled, estimate.unfilled, estimate.levels_consumed), (150, 350, 2));
+        assert_eq!(estimate.worst_price, 10_020);
+        
+        let estimate = FillEstimate::walk(&[], 500);
+        assert_eq!((estimate.filled, estimate.unfilled), (0, 500));
+        assert_eq!(estimate.average_price(), None);
+        assert_eq!(estimate.impact_bps(MatchSide::Buy, None), 0.0);
+    }
+    
+    #[test]
+    fn gateway_reject_codes_map_to_reject_reasons() {
+        let table = [
+            (0x01, RejectReason::InvalidSymbol),
+            (0x02, RejectReason::InvalidPrice),
+            (0x03, RejectReason::InvalidQuantity),
+            (0x04, RejectReason::DuplicateOrderId),
+            (0x05, RejectReason::UnknownOrder),
+            (0x06, RejectReason::InsufficientFunds),
+            (0x07, RejectReason::MarketClosed),
+            (0x08, RejectReason::SystemError),
+            (0x09, RejectReason::BelowMinimumSize),
+            (0x0A, RejectReason::PriceOutOfBand),
+            (0x0B, RejectReason::SizeTooLarge),
+            (0x0C, RejectReason::InsufficientPermissions),
+        ];
+        for (code, reason) in table {
+            assert_eq!(TradingServiceImpl::reject_reason_from_code(code), reason, "code 0x{:02x}", code);
+            assert_eq!(TradingServiceImpl::reject_message(code, "text".to_string()), "text");
+        }
+        
+        // Unknown codes are system errors, with the code kept in the text
+        for code in [0x00, 0x0D, 0xFF] {
+            assert_eq!(TradingServiceImpl::reject_reason_from_code(code), RejectReason::SystemError);
+        }
+        assert_eq!(
+            TradingServiceImpl::reject_message(0x0D, "text".to_string()),
+            "text (unknown reject code 0x0d)"
+        );
+        assert_eq!(TradingServiceImpl::reject_message(0x0D, String::new()), "unknown reject code 0x0d");
+    }
+    
+    #[test]
+    fn request_deadline_reads_the_grpc_timeout() {
+        let deadline = |timeout: &str| {
+            let mut request = Request::new(());
+            request.metadata_mut().insert("grpc-timeout", timeout.parse().unwrap());
+            TradingServiceImpl::request_deadline(&request)
+        };
+        assert_eq!(deadline("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(deadline("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(deadline("5S"), Some(Duration::from_secs(5)));
+        assert_eq!(deadline("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(deadline("40u"), Some(Duration::from_micros(40)));
+        assert_eq!(deadline("7n"), Some(Duration::from_nanos(7)));
+        
+        for malformed in ["", "5", "5x", "xS", "-5S"] {
+            assert_eq!(deadline(malformed), None, "{:?}", malformed);
+        }
+        assert_eq!(TradingServiceImpl::request_deadline(&Request::new(())), None);
+    }
+    
+    #[test]
+    fn ack_timeout_status_carries_the_order_id() {
+        let status = TradingServiceImpl::matching_error_status(
+            "Order submission",
+            MatchingError::AckTimeout { client_order_id: 42 },
+        );
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(status.metadata().get(CLIENT_ORDER_ID_KEY).unwrap(), "42");
+    }
+    
+    fn error_code(e: MatchingError) -> tonic::Code {
+        TradingServiceImpl::matching_error_status("Order submission", e).code()
+    }
+    
+    #[test]
+    fn not_connected_is_unavailable() {
+        let code = error_code(MatchingError::NotConnected("no connection".to_string()));
+        assert_eq!(code, tonic::Code::Unavailable);
+    }
+    
+    #[test]
+    fn timeout_is_deadline_exceeded() {
+        let code = error_code(MatchingError::Timeout("no reply".to_string()));
+        assert_eq!(code, tonic::Code::DeadlineExceeded);
+    }
+    
+    #[test]
+    fn gateway_reject_is_failed_precondition() {
+        let code = error_code(MatchingError::Rejected { reason: 3, text: "bad logon".to_string() });
+        assert_eq!(code, tonic::Code::FailedPrecondition);
+    }
+    
+    #[test]
+    fn unencodable_request_is_invalid_argument() {
+        let e = std::io::Error::new(std::io::ErrorKind::InvalidInput, "symbol too long");
+        assert_eq!(error_code(MatchingError::Io(e)), tonic::Code::InvalidArgument);
+    }
+    
+    #[test]
+    fn io_failure_is_unavailable() {
+        let e = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "write failed");
+        assert_eq!(error_code(MatchingError::Io(e)), tonic::Code::Unavailable);
+    }
+    
+    #[test]
+    fn closed_connection_is_unavailable() {
+        let code = error_code(MatchingError::Closed("connection closed".to_string()));
+        assert_eq!(code, tonic::Code::Unavailable);
+    }
+    
+    fn replace_request(client_order_id: u64, new_quantity: u64) -> ReplaceRequest {
+        ReplaceRequest {
+            symbol: "AAPL".to_string(),
+            user_id: 7,
+            client_order_id,
+            new_price: 101.0,
+            new_quantity,
+        }
+    }
+    
+    #[tokio::test]
+    async fn replace_of_an_unknown_order_is_not_found() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        
+        let status = h
+            .service
+            .replace_order(Request::new(replace_request(42, 100)))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound, "{}", status.message());
+    }
+    
+    #[tokio::test]
+    async fn replace_to_no_more_than_filled_is_rejected() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        h.order_store
+            .insert_new(42, 7, "AAPL".to_string(), MatchSide::Buy, 10_000, 100, String::new(), None);
+        h.order_store.on_execution(&ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: 42,
+            exchange_order_id: 99,
+            execution_id: 1,
+            user_id: 7,
+            side: MatchSide::Buy,
+            fill_price: 10_000,
+            fill_quantity: 60,
+            leaves_quantity: 40,
+            timestamp: 0,
+        });
+        
+        for new_quantity in [30, 60] {
+            let status = h
+                .service
+                .replace_order(Request::new(replace_request(42, new_quantity)))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", status.message());
+        }
+    }
+    
+    /// A gateway that accepts the logon and confirms every replace with
+    /// exchange order id 99
+    async fn replacing_gateway() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut logon = true;
+            loop {
+                let mut header = [0u8; 16];
+                if stream.read_exact(&mut header).await.is_err() {
+                    return;
+                }
+                let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+                let mut body = vec![0u8; length.saturating_sub(16)];
+                if stream.read_exact(&mut body).await.is_err() {
+                    return;
+                }
+                
+                let mut reply = bytes::BytesMut::new();
+                if std::mem::take(&mut logon) {
+                    MessageHeader::new(MessageType::Logon, 16 + 96).encode(&mut reply);
+                    reply.put_bytes(0, 96);
+                } else if header[1] == MessageType::ReplaceOrder as u8 {
+                    // Echo the order id, price and quantity after the symbol
+                    MessageHeader::new(MessageType::OrderReplaced, 16 + 40).encode(&mut reply);
+                    reply.put_slice(&body[16..24]);
+                    reply.put_u64(99);
+                    reply.put_slice(&body[32..48]);
+                    reply.put_u64(1);
+                } else {
+                    continue;
+                }
+                stream.write_all(&reply).await.unwrap();
+            }
+        });
+        address
+    }
+    
+    /// A trading service on a real matching client connected to `address`
+    async fn gateway_service(address: String, order_store: Arc<OrderStore>) -> TradingServiceImpl {
+        let config = Config::default();
+        let client = Arc::new(
+            MatchingClient::new(
+                address,
+                1,
+                1,
+                ConnectionOptions::from(&config.matching_engine),
+                Arc::clone(&order_store),
+                Arc::new(TradeHistory::new(&config.market_data)),
+                false,
+            )
+            .await
+            .unwrap(),
+        );
+        TradingServiceImpl::new(
+            Arc::clone(&client),
+            client.clone(),
+            order_store,
+            Arc::new(RateLimiter::new(&config.rate_limit)),
+            Arc::new(OrderLimits::new(&config.order_limits)),
+            Arc::new(OrderThrottle::new(&config.order_throttle)),
+            Arc::new(SymbolRegistry::new(&config.symbols, client.price_scale())),
+            Arc::new(IdempotencyStore::new(&config.idempotency)),
+            Duration::from_millis(config.market_data.quote_interval_ms),
+        )
+    }
+    
+    #[tokio::test]
+    async fn replace_is_confirmed_by_the_gateway() {
+        let order_store = Arc::new(OrderStore::new());
+        let service = gateway_service(replacing_gateway().await, Arc::clone(&order_store)).await;
+        order_store.insert_new(42, 7, "AAPL".to_string(), MatchSide::Buy, 10_000, 100, String::new(), None);
+        
+        let response = timeout(
+            Duration::from_secs(5),
+            service.replace_order(Request::new(replace_request(42, 150))),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .into_inner();
+        assert!(response.replaced, "{}", response.error_message);
+        assert_eq!((response.client_order_id, response.exchange_order_id), (42, 99));
+        
+        let order = order_store.get(42, 7).unwrap();
+        assert_eq!(order.price, service.price_scale.to_fixed(101.0).unwrap());
+        assert_eq!(order.original_quantity, 150);
+    }
+    
+    #[tokio::test]
+    async fn status_of_an_unknown_order_is_not_found() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        h.order_store
+            .insert_new(42, 7, "AAPL".to_string(), MatchSide::Buy, 10_000, 100, String::new(), None);
+        
+        // Unknown id, and a known id asked for by another user
+        for (client_order_id, user_id) in [(43, 7), (42, 8)] {
+            let status = h
+                .service
+                .get_order_status(Request::new(OrderStatusRequest {
+                    client_order_id,
+                    user_id,
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::NotFound, "{}", status.message());
+        }
+        
+        let found = h
+            .service
+            .get_order_status(Request::new(OrderStatusRequest {
+                client_order_id: 42,
+                user_id: 7,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(found.client_order_id, 42);
+    }
+    
+    #[tokio::test]
+    async fn cancel_all_lists_the_cancels_that_failed() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        for id in 1..=4 {
+            h.order_store
+                .insert_new(id, 7, "AAPL".to_string(), MatchSide::Buy, 10_000, 100, String::new(), None);
+        }
+        // Another user's order is left alone
+        h.order_store
+            .insert_new(5, 8, "AAPL".to_string(), MatchSide::Buy, 10_000, 100, String::new(), None);
+        h.failing_cancels.lock().extend([2, 4]);
+        
+        let response = h
+            .service
+            .cancel_all_orders(Request::new(CancelAllRequest {
+                user_id: 7,
+                symbol: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        
+        assert_eq!((response.cancelled_count, response.failed_count), (2, 2));
+        let outcomes: Vec<_> = response
+            .results
+            .iter()
+            .map(|result| (result.client_order_id, result.cancelled))
+            .collect();
+        assert_eq!(outcomes, vec![(1, true), (2, false), (3, true), (4, false)]);
+        for failed in response.results.iter().filter(|result| !result.cancelled) {
+            assert!(failed.error_message.contains("No reply"), "{}", failed.error_message);
+        }
+    }
+    
+    fn limit_request(price: f64, quantity: u64) -> OrderRequest {
+        OrderRequest {
+            symbol: "AAPL".to_string(),
+            user_id: 7,
+            side: Side::Buy as i32,
+            order_type: OrderType::Limit as i32,
+            price,
+            quantity,
+            ..Default::default()
+        }
+    }
+    
+    #[tokio::test]
+    async fn user_zero_is_rejected() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        
+        let status = h
+            .service
+            .submit_order(Request::new(OrderRequest {
+                user_id: 0,
+                ..limit_request(100.0, 10)
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", status.message());
+        assert!(h.submitted.try_recv().is_err());
+        
+        let status = h
+            .service
+            .cancel_order(Request::new(CancelRequest {
+                symbol: "AAPL".to_string(),
+                user_id: 0,
+                client_order_id: 42,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", status.message());
+    }
+    
+    #[tokio::test]
+    async fn invalid_orders_are_rejected_before_sending() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        let stop = |stop_price| OrderRequest {
+            order_type: OrderType::Stop as i32,
+            price: 0.0,
+            stop_price,
+            ..limit_request(100.0, 10)
+        };
+        
+        let base = || limit_request(100.0, 10);
+        
+        let cases = [
+            (OrderRequest { user_id: 0, ..base() }, "Invalid user ID"),
+            (OrderRequest { symbol: String::new(), ..base() }, "Symbol cannot be empty"),
+            (OrderRequest { symbol: "TOOLONGSYMBOL1234".to_string(), ..base() }, "longer"),
+            (limit_request(100.0, 0), "Quantity must be greater than 0"),
+            (limit_request(0.0, 10), "Limit orders must have positive price"),
+            (limit_request(-1.0, 10), "Limit orders must have positive price"),
+            (limit_request(100.001, 10), "not a whole multiple"),
+            (stop(0.0), "Stop orders must have positive stop_price"),
+            (OrderRequest { stop_price: 99.0, ..base() }, "only for stop"),
+            (OrderRequest { tag: "x".repeat(65), ..base() }, "Tag must be at most 64"),
+            // Unknown enum values aren't taken for the default
+            (OrderRequest { side: 7, ..base() }, "Unknown side 7"),
+            (OrderRequest { order_type: 9, ..base() }, "Unknown order type 9"),
+        ];
+        for (request, expected) in cases {
+            let status = h.service.submit_order(Request::new(request.clone())).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{:?}", request);
+            assert!(status.message().contains(expected), "{:?}: {}", request, status.message());
+        }
+        assert!(h.submitted.try_recv().is_err());
+    }
+    
+    #[tokio::test]
+    async fn orders_over_the_limits_are_rejected() {
+        let mut h = harness_with(Config {
+            order_limits: OrderLimitConfig {
+                max_order_quantity: 1_000,
+                max_order_notional: 50_000.0,
+                ..OrderLimitConfig::default()
+            },
+            ..Config::default()
+        })
+        .await;
+        
+        for (request, limit) in [
+            (limit_request(10.0, 1_001), "max_order_quantity"),
+            (limit_request(100.0, 501), "max_order_notional"),
+        ] {
+            let status = h.service.submit_order(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::FailedPrecondition, "{}", status.message());
+            assert!(status.message().contains(limit), "{}", status.message());
+        }
+        assert!(h.submitted.try_recv().is_err());
+        
+        // 1,000 at $49.99 is just under both
+        h.service
+            .submit_order(Request::new(limit_request(49.99, 1_000)))
+            .await
+            .unwrap();
+        assert_eq!(h.submitted.recv().await.unwrap().quantity, 1_000);
+    }
+    
+    #[tokio::test]
+    async fn mass_quote_reports_each_leg() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        
+        let response = h
+            .service
+            .mass_quote(Request::new(MassQuoteRequest {
+                user_id: 7,
+                quotes: vec![
+                    QuoteEntry {
+                        symbol: "AAPL".to_string(),
+                        bid_price: 99.0,
+                        bid_quantity: 100,
+                        ask_price: 101.0,
+                        ask_quantity: 100,
+                    },
+                    // The ask is off the cent tick
+                    QuoteEntry {
+                        symbol: "msft".to_string(),
+                        bid_price: 299.0,
+                        bid_quantity: 50,
+                        ask_price: 301.005,
+                        ask_quantity: 50,
+                    },
+                ],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        
+        assert_eq!((response.accepted_count, response.rejected_count), (3, 1));
+        let legs: Vec<_> = response
+            .legs
+            .iter()
+            .map(|leg| (leg.symbol.as_str(), leg.side, leg.accepted))
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                ("AAPL", Side::Buy as i32, true),
+                ("AAPL", Side::Sell as i32, true),
+                ("MSFT", Side::Buy as i32, true),
+                ("MSFT", Side::Sell as i32, false),
+            ]
+        );
+        let rejected = &response.legs[3];
+        assert_eq!(rejected.reject_reason, RejectReason::InvalidPrice as i32);
+        assert!(!rejected.error_message.is_empty());
+        
+        // Only the accepted legs reached the gateway
+        let mut prices = Vec::new();
+        while let Ok(submitted) = h.submitted.try_recv() {
+            prices.push(submitted.price);
+        }
+        prices.sort();
+        assert_eq!(prices, vec![9_900, 10_100, 29_900]);
+    }
+    
+    #[tokio::test]
+    async fn disconnect_cancels_resting_orders_but_not_filled_ones() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        let (closed_tx, closed) = mpsc::unbounded_channel();
+        tokio::spawn(h.service.clone().cancel_on_disconnect(closed));
+        
+        let addr: SocketAddr = "10.0.0.1:50000".parse().unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let mut request = Request::new(OrderRequest {
+                cancel_on_disconnect: true,
+                ..limit_request(100.0, 10)
+            });
+            request.extensions_mut().insert(tonic::transport::server::TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some(addr),
+            });
+            let response = h.service.submit_order(request).await.unwrap().into_inner();
+            // What the matching client records as it sends the order
+            h.order_store.insert_new(
+                response.client_order_id,
+                7,
+                "AAPL".to_string(),
+                MatchSide::Buy,
+                10_000,
+                10,
+                String::new(),
+                None,
+            );
+            ids.push(response.client_order_id);
+        }
+        h.order_store.on_execution(&ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: ids[1],
+            exchange_order_id: 99,
+            execution_id: 1,
+            user_id: 7,
+            side: MatchSide::Buy,
+            fill_price: 10_000,
+            fill_quantity: 10,
+            leaves_quantity: 0,
+            timestamp: 0,
+        });
+        
+        // Another connection closing leaves the orders alone
+        closed_tx.send("10.0.0.2:50000".parse().unwrap()).unwrap();
+        closed_tx.send(addr).unwrap();
+        timeout(Duration::from_secs(1), async {
+            while h.cancels.lock().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(*h.cancels.lock(), vec![ids[0]]);
+    }
+    
+    #[tokio::test]
+    async fn reconnecting_stream_replays_the_gap_then_goes_live() {
+        let h = harness(OrderThrottleConfig::default()).await;
+        let execution = |execution_id| ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: 1,
+            exchange_order_id: 99,
+            execution_id,
+            user_id: 7,
+            side: MatchSide::Buy,
+            fill_price: 10_000,
+            fill_quantity: 10,
+            leaves_quantity: 0,
+            timestamp: 0,
+        };
+        for execution_id in 1..=3 {
+            h.client.publish(IncomingMessage::Execution(execution(execution_id)));
+        }
+        timeout(Duration::from_secs(1), async {
+            while h.service.executions.replay(7, 0).map_or(0, |replay| replay.len()) < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        
+        let stream = |resume_from_sequence| {
+            h.service.stream_executions(Request::new(StreamRequest {
+                user_id: 7,
+                resume_from_sequence,
+                ..Default::default()
+            }))
+        };
+        let mut reports = stream(1).await.unwrap().into_inner().into_inner();
+        h.client.publish(IncomingMessage::Execution(execution(4)));
+        for sequence in 2..=4 {
+            let report = timeout(Duration::from_secs(1), reports.recv())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!((report.sequence, report.execution_id), (sequence, sequence));
+        }
+        
+        // Past the latest execution, e.g. from before a restart
+        let status = stream(99).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange, "{}", status.message());
+        
+        // Overrun the buffer so the first executions are dropped
+        drop(reports);
+        for execution_id in 5..=EXECUTION_REPLAY_BUFFER as u64 + 2 {
+            h.service.executions.record(execution(execution_id));
+        }
+        let status = stream(1).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange, "{}", status.message());
+        assert!(status.message().contains("too old"), "{}", status.message());
+    }
+    
+    #[tokio::test]
+    async fn tag_follows_the_order_to_its_executions() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        let mut reports = h
+            .service
+            .stream_executions(Request::new(StreamRequest {
+                user_id: 7,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .into_inner();
+        
+        let response = h
+            .service
+            .submit_order(Request::new(OrderRequest {
+                tag: "basket-7".to_string(),
+                ..limit_request(100.0, 10)
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let submitted = h.submitted.recv().await.unwrap();
+        assert_eq!(submitted.tag, "basket-7");
+        // What the matching client records as it sends the order
+        h.order_store.insert_new(
+            response.client_order_id,
+            7,
+            "AAPL".to_string(),
+            MatchSide::Buy,
+            submitted.price,
+            submitted.quantity,
+            submitted.tag,
+            None,
+        );
+        
+        h.client.publish(IncomingMessage::Execution(ExecutionMessage {
+            symbol: "AAPL".to_string(),
+            client_order_id: response.client_order_id,
+            exchange_order_id: 99,
+            execution_id: 1,
+            user_id: 7,
+            side: MatchSide::Buy,
+            fill_price: submitted.price,
+            fill_quantity: 10,
+            leaves_quantity: 0,
+            timestamp: 0,
+        }));
+        let report = timeout(Duration::from_secs(1), reports.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.client_order_id, response.client_order_id);
+        assert_eq!(report.tag, "basket-7");
+        
+        let status = h
+            .service
+            .get_order_status(Request::new(OrderStatusRequest {
+                client_order_id: response.client_order_id,
+                user_id: 7,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(status.tag, "basket-7");
+    }
+    
+    #[tokio::test]
+    async fn over_length_tag_is_rejected() {
+        let mut h = harness(OrderThrottleConfig::default()).await;
+        
+        let status = h
+            .service
+            .submit_order(Request::new(OrderRequest {
+                tag: "x".repeat(MAX_ORDER_TAG_LEN + 1),
+                ..limit_request(100.0, 10)
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", status.message());
+        assert!(h.submitted.try_recv().is_err());
+        
+        h.service
+            .submit_order(Request::new(OrderRequest {
+                tag: "x".repeat(MAX_ORDER_TAG_LEN),
+                ..limit_request(100.0, 10)
+            }))
+            .await
+            .unwrap();
+        assert_eq!(h.submitted.recv().await.unwrap().tag.len(), MAX_ORDER_TAG_LEN);
+    }
+    
+    /// A gateway whose AAPL book starts at sequence 1. It answers the first
+    /// snapshot request, then sends delta 2 and, skipping 3, delta 4. Each
+    /// resend request is reported on `resends` and answered with a
+    /// snapshot at sequence 4.
+    async fn gapping_book_gateway(resends: mpsc::UnboundedSender<(u32, u32)>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        fn symbol(buf: &mut bytes::BytesMut) {
+            let mut bytes = [0u8; 16];
+            bytes[..4].copy_from_slice(b"AAPL");
+            buf.put_slice(&bytes);
+        }
+        fn snapshot(request_id: &[u8], sequence: u32, bid: u64) -> bytes::BytesMut {
+            let mut buf = bytes::BytesMut::new();
+            MessageHeader::new(MessageType::BookSnapshot, 16 + 40 + 24).encode(&mut buf);
+            symbol(&mut buf);
+            buf.put_slice(request_id);
+            buf.put_u64(0);
+            buf.put_u32(sequence);
+            buf.put_u16(1);
+            buf.put_u16(0);
+            buf.put_u64(bid);
+            buf.put_u64(100);
+            buf.put_u32(1);
+            buf.put_u32(0);
+            buf
+        }
+        fn delta(sequence: u32, price: u64) -> bytes::BytesMut {
+            let mut buf = bytes::BytesMut::new();
+            MessageHeader::new(MessageType::BookDelta, 16 + 56).encode(&mut buf);
+            symbol(&mut buf);
+            buf.put_u32(sequence);
+            buf.put_u8(MatchSide::Buy as u8);
+            buf.put_u8(MatchBookAction::Add as u8);
+            buf.put_u16(0);
+            buf.put_u64(price);
+            buf.put_u64(50);
+            buf.put_u32(1);
+            buf.put_u32(0);
+            buf.put_u64(0);
+            buf
+        }
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut logon = true;
+            loop {
+                let mut header = [0u8; 16];
+                if stream.read_exact(&mut header).await.is_err() {
+                    return;
+                }
+                let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+                let mut body = vec![0u8; length.saturating_sub(16)];
+                if stream.read_exact(&mut body).await.is_err() {
+                    return;
+                }
+                
+                let mut reply = bytes::BytesMut::new();
+                // The request id follows the symbol
+                let request_id = body.get(16..24).unwrap_or_default();
+                if std::mem::take(&mut logon) {
+                    MessageHeader::new(MessageType::Logon, 16 + 96).encode(&mut reply);
+                    reply.put_bytes(0, 96);
+                } else if header[1] == MessageType::BookSnapshotRequest as u8 {
+                    reply.extend_from_slice(&snapshot(request_id, 1, 10_000));
+                    reply.extend_from_slice(&delta(2, 9_900));
+                    reply.extend_from_slice(&delta(4, 9_800));
+                } else if header[1] == MessageType::ResendRequest as u8 {
+                    let sequence = |at: usize| u32::from_be_bytes(body[at..at + 4].try_into().unwrap());
+                    let _ = resends.send((sequence(24), sequence(28)));
+                    reply.extend_from_slice(&snapshot(request_id, 4, 10_100));
+                } else {
+                    continue;
+                }
+                stream.write_all(&reply).await.unwrap();
+            }
+        });
+        address
+    }
+    
+    #[tokio::test]
+    async fn book_gap_is_resent_as_a_fresh_snapshot() {
+        let (resends_tx, mut resends) = mpsc::unbounded_channel();
+        let address = gapping_book_gateway(resends_tx).await;
+        let service = gateway_service(address, Arc::new(OrderStore::new())).await;
+        let mut updates = service
+            .stream_order_book(Request::new(StreamRequest {
+                symbol: "AAPL".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .into_inner();
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            let update = timeout(Duration::from_secs(1), updates.recv()).await.unwrap();
+            received.push(update.unwrap().unwrap());
+        }
+        let [first, second, third] = <[BookUpdate; 3]>::try_from(received).unwrap();
+        
+        assert!(matches!(first.update, Some(book_update::Update::Snapshot(ref s)) if s.sequence == 1));
+        assert_eq!(first.gap, None);
+        assert!(matches!(second.update, Some(book_update::Update::Delta(ref d)) if d.sequence == 2));
+        
+        // Delta 4 is never passed on: the gap is resent, and the client
+        // gets the fresh snapshot instead
+        match third.update {
+            Some(book_update::Update::Snapshot(snapshot)) => {
+                assert_eq!(snapshot.sequence, 4);
+                assert_eq!(snapshot.bids[0].price, 101.0);
+            }
+            update => panic!("expected a snapshot, got {:?}", update),
+        }
+        assert_eq!(
+            third.gap,
+            Some(SequenceGap {
+                expected_sequence: 3,
+                received_sequence: 4,
+            })
+        );
+        assert_eq!(resends.recv().await, Some((3, 4)));
+    }
+    
+    fn quote(timestamp: u64, bid_price: u64) -> IncomingMessage {
+        IncomingMessage::Quote(QuoteMessage {
+            symbol: "AAPL".to_string(),
+            bid_price,
+            bid_quantity: 100,
+            ask_price: bid_price + 10,
+            ask_quantity: 100,
+            timestamp,
+        })
+    }
+    
+    #[tokio::test]
+    async fn quote_burst_within_an_interval_delivers_only_the_latest() {
+        let mut config = Config::default();
+        config.market_data.quote_interval_ms = 200;
+        let h = harness_with(config).await;
+        let mut quotes = h
+            .service
+            .stream_quotes(Request::new(StreamRequest {
+                symbol: "AAPL".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .into_inner();
+        
+        // The first quote goes straight out and starts the interval
+        h.client.publish(quote(1, 10_000));
+        let first = timeout(Duration::from_secs(1), quotes.recv()).await.unwrap().unwrap().unwrap();
+        assert_eq!(first.bid, 100.0);
+        
+        for (timestamp, bid_price) in [(2, 10_010), (3, 10_020), (4, 10_030)] {
+            h.client.publish(quote(timestamp, bid_price));
+        }
+        let latest = timeout(Duration::from_secs(1), quotes.recv()).await.unwrap();
+        let latest = latest.unwrap().unwrap();
+        assert_eq!((latest.bid, latest.ask), (100.3, 100.4));
+        assert_eq!(latest.timestamp.unwrap().nanos, 4);
+        
+        // Nothing else was queued behind it
+        assert!(timeout(Duration::from_millis(400), quotes.recv()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn slow_execution_stream_lags_without_stalling_a_fast_one() {
+        // Well past both a stream's queue and the journal's channel
+        const EXECUTIONS: u64 = 2_000;
+        
+        let h = harness(OrderThrottleConfig::default()).await;
+        let stream = || {
+            h.service.stream_executions(Request::new(StreamRequest {
+                user_id: 7,
+                ..Default::default()
+            }))
+        };
+        let mut slow = stream().await.unwrap().into_inner().into_inner();
+        let mut fast = stream().await.unwrap().into_inner().into_inner();
+        let fast_reader = tokio::spawn(async move {
+            let mut sequences = Vec::new();
+            while sequences.len() < EXECUTIONS as usize {
+                let report = fast.recv().await.unwrap().unwrap();
+                sequences.push(report.sequence);
+            }
+            sequences
+        });
+        
+        for execution_id in 1..=EXECUTIONS {
+            h.service.executions.record(ExecutionMessage {
+                symbol: "AAPL".to_string(),
+                client_order_id: 1,
+                exchange_order_id: 99,
+                execution_id,
+                user_id: 7,
+                side: MatchSide::Buy,
+                fill_price: 10_000,
+                fill_quantity: 1,
+                leaves_quantity: 0,
+                timestamp: 0,
+            });
+            // Recording never waits; give the streams a chance to keep up
+            tokio::task::yield_now().await;
+        }
+        
+        let sequences = timeout(Duration::from_secs(5), fast_reader).await.unwrap().unwrap();
+        assert_eq!(sequences, (1..=EXECUTIONS).collect::<Vec<_>>());
+        
+        // The slow stream gets what was queued for it, then is told to resync
+        let mut delivered = 0;
+        let status = loop {
+            match timeout(Duration::from_secs(1), slow.recv()).await.unwrap().unwrap() {
+                Ok(report) => {
+                    delivered += 1;
+                    assert_eq!(report.sequence, delivered);
+                }
+                Err(status) => break status,
+            }
+        };
+        assert!(delivered < EXECUTIONS, "{}", delivered);
+        assert_eq!(status.code(), tonic::Code::DataLoss, "{}", status.message());
+        assert!(slow.recv().await.is_none());
     }
 }
@@ -0,0 +1,93 @@
+use crate::matching::protocol::Side;
+use dashmap::DashMap;
+
+/// A stop or stop-limit order held by the server until the market trades
+/// through its stop price
+#[derive(Debug, Clone)]
+pub struct StopOrder {
+    pub client_order_id: u64,
+    pub user_id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub stop_price: u64,         // Price in fixed-point units
+    /// Price of the limit order sent on trigger; `None` sends a market order
+    pub limit_price: Option<u64>,
+    pub quantity: u64,
+    pub tag: String,
+}
+
+impl StopOrder {
+    /// Whether a trade at `price` sets the order off: at or above the stop
+    /// for a buy, at or below it for a sell
+    pub fn triggers_at(&self, price: u64) -> bool {
+        match self.side {
+            Side::Buy => price >= self.stop_price,
+            Side::Sell => price <= self.stop_price,
+        }
+    }
+}
+
+/// Stop orders waiting for their trigger, keyed by client_order_id. Stops
+/// are held here rather than at the gateway, so they work whether or not
+/// it supports them natively; the market or limit order sent on trigger
+/// keeps the stop's client_order_id.
+#[derive(Debug, Default)]
+pub struct StopOrderBook {
+    orders: DashMap<u64, StopOrder>,
+}
+
+impl StopOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Hold an order until a trade triggers it
+    pub fn hold(&self, order: StopOrder) {
+        self.orders.insert(order.client_order_id, order);
+    }
+    
+    /// Whether an order is still waiting for its trigger
+    pub fn is_held(&self, client_order_id: u64) -> bool {
+        self.orders.contains_key(&client_order_id)
+    }
+    
+    /// Drop a held order, if it belongs to `user_id`. `None` if it isn't
+    /// held, having triggered or never existed.
+    pub fn cancel(&self, client_order_id: u64, user_id: u64) -> Option<StopOrder> {
+        self.orders
+            .remove_if(&client_order_id, |_, order| order.user_id == user_id)
+            .map(|(_, order)| order)
+    }
+    
+    /// Drop a user's held orders, optionally for one symbol, oldest first
+    pub fn cancel_all(&self, user_id: u64, symbol: Option<&str>) -> Vec<StopOrder> {
+        self.take(|order| {
+            order.user_id == user_id && symbol.is_none_or(|symbol| order.symbol == symbol)
+        })
+    }
+    
+    /// Take out the orders a trade in `symbol` at `price` triggers, oldest
+    /// first. Each order is handed out once, however many trades cross it.
+    pub fn triggered(&self, symbol: &str, price: u64) -> Vec<StopOrder> {
+        self.take(|order| order.symbol == symbol && order.triggers_at(price))
+    }
+    
+    fn take<F>(&self, matches: F) -> Vec<StopOrder>
+    where
+        F: Fn(&StopOrder) -> bool,
+    {
+        let ids: Vec<u64> = self
+            .orders
+            .iter()
+            .filter(|order| matches(order))
+            .map(|order| order.client_order_id)
+            .collect();
+        
+        let mut orders: Vec<StopOrder> = ids
+            .into_iter()
+            .filter_map(|id| self.orders.remove(&id).map(|(_, order)| order))
+            .collect();
+        orders.sort_by_key(|order| order.client_order_id);
+        orders
+    }
+}
@@ -0,0 +1,175 @@
+use crate::config::SymbolConfig;
+use crate::matching::{protocol, PriceScale};
+use std::collections::{HashMap, HashSet};
+use tonic::Status;
+
+/// Canonical form of a symbol: surrounding whitespace trimmed, uppercased
+pub fn normalize(symbol: &str) -> String {
+    symbol.trim().to_ascii_uppercase()
+}
+
+/// Symbols the trading service accepts, and the price increment each
+/// trades in. Symbols are normalized first, so "aapl", " AAPL " and "AAPL"
+/// are the same symbol. An empty registry allows every symbol the gateway
+/// protocol can carry.
+pub struct SymbolRegistry {
+    allowed: HashSet<String>,
+    /// Tick sizes in fixed-point units
+    tick_sizes: HashMap<String, u64>,
+    default_tick_size: u64,
+    price_scale: PriceScale,
+}
+
+impl SymbolRegistry {
+    /// `config` must have passed `Config::validate`, which checks the tick
+    /// sizes against `price_scale`
+    pub fn new(config: &SymbolConfig, price_scale: PriceScale) -> Self {
+        let to_units = |tick_size: f64| {
+            price_scale
+                .to_fixed(tick_size)
+                .expect("tick sizes are validated with the config")
+        };
+        
+        Self {
+            allowed: config.allowed.iter().map(|symbol| normalize(symbol)).collect(),
+            tick_sizes: config
+                .tick_sizes
+                .iter()
+                .map(|tick| (normalize(&tick.symbol), to_units(tick.tick_size)))
+                .collect(),
+            default_tick_size: to_units(config.default_tick_size),
+            price_scale,
+        }
+    }
+    
+    /// Normalize a requested symbol, rejecting one that is empty, can't go
+    /// on the wire or isn't tradable
+    #[allow(clippy::result_large_err)]
+    pub fn resolve(&self, symbol: &str) -> Result<String, Status> {
+        let symbol = normalize(symbol);
+        if symbol.is_empty() {
+            return Err(Status::invalid_argument("Symbol cannot be empty"));
+        }
+        protocol::validate_symbol(&symbol).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        
+        if !self.allowed.is_empty() && !self.allowed.contains(&symbol) {
+            return Err(Status::invalid_argument(format!(
+                "Symbol {} is not tradable",
+                symbol
+            )));
+        }
+        Ok(symbol)
+    }
+    
+    /// `resolve` for an optional symbol filter, where empty means every
+    /// symbol
+    #[allow(clippy::result_large_err)]
+    pub fn resolve_filter(&self, symbol: &str) -> Result<Option<String>, Status> {
+        if symbol.is_empty() {
+            return Ok(None);
+        }
+        self.resolve(symbol).map(Some)
+    }
+    
+    /// Price increment of a resolved symbol, in fixed-point units
+    pub fn tick_size(&self, symbol: &str) -> u64 {
+        self.tick_sizes
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.default_tick_size)
+    }
+    
+    /// Reject a price (fixed-point units) between two ticks of a resolved
+    /// symbol
+    #[allow(clippy::result_large_err)]
+    pub fn check_tick(&self, symbol: &str, price: u64) -> Result<(), Status> {
+        let tick_size = self.tick_size(symbol);
+        if !price.is_multiple_of(tick_size) {
+            return Err(Status::invalid_argument(format!(
+                "Price {} is not a multiple of the {} tick size {}",
+                self.price_scale.to_dollars(price),
+                symbol,
+                self.price_scale.to_dollars(tick_size)
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SymbolTickSize;
+    
+    fn registry(allowed: &[&str]) -> SymbolRegistry {
+        let config = SymbolConfig {
+            allowed: allowed.iter().map(|symbol| symbol.to_string()).collect(),
+            ..SymbolConfig::default()
+        };
+        SymbolRegistry::new(&config, PriceScale::new(100))
+    }
+    
+    #[test]
+    fn symbols_are_normalized() {
+        let registry = registry(&[]);
+        for symbol in ["aapl", " AAPL ", "AAPL", "\tAapl\n"] {
+            assert_eq!(registry.resolve(symbol).unwrap(), "AAPL");
+        }
+        
+        for symbol in ["", "   "] {
+            let status = registry.resolve(symbol).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+        assert_eq!(registry.resolve_filter("").unwrap(), None);
+        assert_eq!(registry.resolve_filter(" msft").unwrap(), Some("MSFT".to_string()));
+    }
+    
+    #[test]
+    fn only_allowed_symbols_resolve() {
+        // The allow-list is normalized too
+        let registry = registry(&["aapl", " MSFT"]);
+        assert_eq!(registry.resolve("Aapl").unwrap(), "AAPL");
+        assert_eq!(registry.resolve("msft ").unwrap(), "MSFT");
+        
+        let status = registry.resolve("GOOG").unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("GOOG"), "{}", status.message());
+        assert!(registry.resolve_filter("goog").is_err());
+    }
+    
+    #[test]
+    fn empty_registry_allows_every_symbol() {
+        let registry = registry(&[]);
+        for symbol in ["AAPL", "GOOG", "BRK.B"] {
+            assert_eq!(registry.resolve(symbol).unwrap(), symbol);
+        }
+    }
+    
+    #[test]
+    fn prices_must_be_on_the_tick() {
+        let config = SymbolConfig {
+            default_tick_size: 0.01,
+            tick_sizes: vec![SymbolTickSize {
+                symbol: "brk.a".to_string(),
+                tick_size: 0.25,
+            }],
+            ..SymbolConfig::default()
+        };
+        let scale = PriceScale::new(1000);
+        let registry = SymbolRegistry::new(&config, scale);
+        let price = |dollars| scale.to_fixed(dollars).unwrap();
+        
+        registry.check_tick("AAPL", price(150.01)).unwrap();
+        let status = registry.check_tick("AAPL", price(150.001)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("tick size 0.01"), "{}", status.message());
+        
+        // A symbol's own tick replaces the default, which the rest fall
+        // back to
+        assert_eq!(registry.tick_size("BRK.A"), price(0.25));
+        assert_eq!(registry.tick_size("GOOG"), price(0.01));
+        registry.check_tick("BRK.A", price(500.75)).unwrap();
+        assert!(registry.check_tick("BRK.A", price(500.10)).is_err());
+        registry.check_tick("GOOG", price(500.10)).unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use crate::proto::trading::{trading_service_server::TradingService, StreamRequest};
+use crate::rest;
+use crate::services::TradingServiceImpl;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing::{debug, info, warn};
+
+/// Largest message accepted from a client. Clients have nothing to send but
+/// control frames, whose payloads are at most 125 bytes.
+const MAX_CLIENT_PAYLOAD: usize = 4 * 1024;
+
+/// A close frame's payload is at most 125 bytes, 2 of them the code
+const MAX_CLOSE_REASON: usize = 123;
+
+/// Accept WebSocket clients on an already bound listener until the process
+/// exits, authorizing them with `auth`
+pub async fn serve<I>(listener: TcpListener, trading: TradingServiceImpl, auth: I)
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    if let Ok(addr) = listener.local_addr() {
+        info!("WebSocket bridge available at ws://{}/trades and /book", addr);
+    }
+    
+    let app = Router::new()
+        .route("/trades", get(trades::<I>))
+        .route("/book", get(book::<I>))
+        .with_state(WebSocketBridge { trading, auth });
+    if let Err(e) = axum::serve(listener, app).await {
+        warn!("WebSocket bridge stopped: {}", e);
+    }
+}
+
+/// Relays the trading service's market data streams to WebSocket clients
+/// that can't use gRPC-Web, one JSON text frame per streamed message:
+///
+/// - `/trades?symbol=AAPL`: `TradeReport`s; leave out the symbol for every
+///   symbol
+/// - `/book?symbol=AAPL`: `BookUpdate`s, a snapshot followed by level deltas
+///
+/// The stream ends with a close frame, carrying the gRPC error message if
+/// it failed. Browsers can't set headers on a WebSocket, so a bearer token
+/// may also be passed as `?access_token=`; either way the request goes
+/// through the same interceptor as the gRPC trading service.
+#[derive(Clone)]
+struct WebSocketBridge<I> {
+    trading: TradingServiceImpl,
+    auth: I,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamParams {
+    #[serde(default)]
+    symbol: String,
+    access_token: Option<String>,
+}
+
+impl<I: Interceptor + Clone> WebSocketBridge<I> {
+    /// Authorize the handshake, returning the stream request to open
+    #[allow(clippy::result_large_err)]
+    fn authorize(&self, headers: &HeaderMap, params: StreamParams) -> Result<Request<StreamRequest>, Status> {
+        let mut metadata = Request::new(());
+        let authorization = match headers.get(AUTHORIZATION) {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .map_err(|_| Status::unauthenticated("Invalid authorization"))?
+                    .to_string(),
+            ),
+            None => params.access_token.map(|token| format!("Bearer {}", token)),
+        };
+        if let Some(authorization) = authorization {
+            let value = authorization
+                .parse()
+                .map_err(|_| Status::unauthenticated("Invalid authorization"))?;
+            metadata.metadata_mut().insert("authorization", value);
+        }
+        let (metadata, extensions, ()) = self.auth.clone().call(metadata)?.into_parts();
+        
+        let stream_request = StreamRequest {
+            symbol: params.symbol,
+            user_id: 0,
+            resume_from_sequence: 0,
+        };
+        Ok(Request::from_parts(metadata, extensions, stream_request))
+    }
+}
+
+/// Open the trade stream, then accept the handshake. The stream is opened
+/// first so a bad symbol or token is still an HTTP error.
+async fn trades<I>(
+    State(bridge): State<WebSocketBridge<I>>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    let updates = match bridge.authorize(&headers, params) {
+        Ok(request) => bridge.trading.stream_trades(request).await,
+        Err(status) => Err(status),
+    };
+    match updates {
+        Ok(updates) => accept(upgrade, updates.into_inner()),
+        Err(status) => reject(status),
+    }
+}
+
+/// Likewise for the order book stream
+async fn book<I>(
+    State(bridge): State<WebSocketBridge<I>>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response
+where
+    I: Interceptor + Clone + Send + Sync + 'static,
+{
+    let updates = match bridge.authorize(&headers, params) {
+        Ok(request) => bridge.trading.stream_order_book(request).await,
+        Err(status) => Err(status),
+    };
+    match updates {
+        Ok(updates) => accept(upgrade, updates.into_inner()),
+        Err(status) => reject(status),
+    }
+}
+
+/// Answer the handshake with an HTTP error
+fn reject(status: Status) -> Response {
+    (rest::http_status(&status), format!("{}\n", status.message())).into_response()
+}
+
+/// Accept the handshake and relay `updates` over the socket
+fn accept<S, T>(upgrade: WebSocketUpgrade, updates: S) -> Response
+where
+    S: Stream<Item = Result<T, Status>> + Unpin + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    upgrade
+        .max_message_size(MAX_CLIENT_PAYLOAD)
+        .max_frame_size(MAX_CLIENT_PAYLOAD)
+        .on_upgrade(|socket| relay(socket, updates))
+}
+
+/// Send each update as a JSON text frame until the stream ends or the
+/// client closes. Pings are answered by the socket itself; anything else
+/// the client sends is ignored.
+async fn relay<S, T>(mut socket: WebSocket, mut updates: S)
+where
+    S: Stream<Item = Result<T, Status>> + Unpin + Send,
+    T: Serialize + Send,
+{
+    loop {
+        tokio::select! {
+            update = updates.next() => match update {
+                Some(Ok(update)) => {
+                    let json = match serde_json::to_string(&update) {
+                        Ok(json) => json,
+                        Err(e) => return close(socket, close_code::ERROR, &e.to_string()).await,
+                    };
+                    if let Err(e) = socket.send(Message::Text(json)).await {
+                        debug!("WebSocket client went away: {}", e);
+                        return;
+                    }
+                }
+                Some(Err(status)) => return close(socket, close_code::ERROR, status.message()).await,
+                None => return close(socket, close_code::NORMAL, "").await,
+            },
+            message = socket.recv() => match message {
+                // The client closed, or its connection dropped
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Send a close frame, its reason cut to fit
+async fn close(mut socket: WebSocket, code: u16, reason: &str) {
+    let mut end = reason.len().min(MAX_CLOSE_REASON);
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    
+    let frame = CloseFrame {
+        code,
+        reason: reason[..end].to_string().into(),
+    };
+    if let Err(e) = socket.send(Message::Close(Some(frame))).await {
+        debug!("Failed to close WebSocket: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthInterceptor;
+    use crate::matching::client::IncomingMessage;
+    use crate::matching::protocol::TradeMessage;
+    use crate::matching::MatchingClient;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::time::{timeout, Duration};
+    use tokio_tungstenite::tungstenite;
+    
+    async fn bridge(auth: AuthInterceptor) -> (String, Arc<MatchingClient>) {
+        let client = Arc::new(MatchingClient::without_gateway(100).await);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener, TradingServiceImpl::for_tests(Arc::clone(&client)), auth));
+        (address, client)
+    }
+    
+    #[tokio::test]
+    async fn handshake_answers_the_rfc_6455_example_key() {
+        let (address, _client) = bridge(AuthInterceptor::new(None, false)).await;
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        stream
+            .write_all(
+                b"GET /trades HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                  Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8; 1];
+            timeout(Duration::from_secs(1), stream.read_exact(&mut byte))
+                .await
+                .unwrap()
+                .unwrap();
+            response.push(byte[0]);
+        }
+        let response = String::from_utf8(response).unwrap().to_ascii_lowercase();
+        assert!(response.starts_with("http/1.1 101"), "{}", response);
+        assert!(
+            response.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo=\r\n"),
+            "{}",
+            response
+        );
+    }
+    
+    #[tokio::test]
+    async fn published_trade_arrives_as_json() {
+        let (address, client) = bridge(AuthInterceptor::new(None, false)).await;
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/trades?symbol=aapl", address))
+            .await
+            .unwrap();
+        
+        // The stream is open once the handshake completes
+        for (trade_id, symbol) in [(1, "MSFT"), (2, "AAPL")] {
+            client.publish(IncomingMessage::Trade(TradeMessage {
+                symbol: symbol.to_string(),
+                trade_id,
+                price: 10_025,
+                quantity: 300,
+                timestamp: trade_id,
+            }));
+        }
+        
+        let message = timeout(Duration::from_secs(1), socket.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let tungstenite::Message::Text(json) = message else {
+            panic!("expected a text frame, got {:?}", message);
+        };
+        let trade: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(trade["symbol"], "AAPL");
+        assert_eq!(trade["price"], 100.25);
+        assert_eq!(trade["quantity"], 300);
+    }
+    
+    #[tokio::test]
+    async fn missing_token_is_an_http_error() {
+        let (address, _client) = bridge(AuthInterceptor::new(Some("key"), true)).await;
+        let err = tokio_tungstenite::connect_async(format!("ws://{}/book?symbol=AAPL", address))
+            .await
+            .unwrap_err();
+        let tungstenite::Error::Http(response) = err else {
+            panic!("expected an HTTP error, got {:?}", err);
+        };
+        assert_eq!(response.status(), 401);
+    }
+}
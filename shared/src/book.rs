@@ -0,0 +1,210 @@
+use crate::{Price, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a level delta changes the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BookAction {
+    /// A new price level
+    Add,
+    /// New quantity and order count for an existing level
+    Change,
+    /// The level is gone
+    Delete,
+}
+
+/// Aggregated orders at one price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Level {
+    pub price: Price,
+    pub quantity: u64,
+    pub order_count: u32,
+}
+
+/// A change to one price level. For `Delete` only the side and price
+/// matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelDelta {
+    pub side: Side,
+    pub action: BookAction,
+    pub level: Level,
+}
+
+/// Why a delta couldn't be applied. Either way the book no longer matches
+/// the source and needs a fresh snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BookError {
+    #[error("sequence gap: expected {expected}, got {received}")]
+    Gap { expected: u64, received: u64 },
+    #[error("{side} level {price} already exists")]
+    LevelExists { side: Side, price: Price },
+    #[error("no {side} level at {price}")]
+    MissingLevel { side: Side, price: Price },
+}
+
+/// A price-level book rebuilt from a snapshot and the deltas after it.
+/// Every delta carries the next sequence number, so a missed delta shows
+/// up as a gap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderBook {
+    sequence: u64,
+    bids: BTreeMap<Price, Level>,
+    asks: BTreeMap<Price, Level>,
+}
+
+impl OrderBook {
+    /// The book as of `sequence`
+    pub fn from_snapshot(
+        sequence: u64,
+        bids: impl IntoIterator<Item = Level>,
+        asks: impl IntoIterator<Item = Level>,
+    ) -> Self {
+        Self {
+            sequence,
+            bids: bids.into_iter().map(|level| (level.price, level)).collect(),
+            asks: asks.into_iter().map(|level| (level.price, level)).collect(),
+        }
+    }
+    
+    /// Sequence of the latest snapshot or delta applied
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+    
+    /// Apply the delta numbered `sequence`. Returns `false` for a delta
+    /// the book already reflects (sequence not past the current one). On
+    /// error the book is left unchanged.
+    pub fn apply(&mut self, sequence: u64, delta: &LevelDelta) -> Result<bool, BookError> {
+        if sequence <= self.sequence {
+            return Ok(false);
+        }
+        if sequence != self.sequence + 1 {
+            return Err(BookError::Gap {
+                expected: self.sequence + 1,
+                received: sequence,
+            });
+        }
+        
+        let LevelDelta { side, action, level } = *delta;
+        let levels = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let exists = levels.contains_key(&level.price);
+        match action {
+            BookAction::Add if exists => {
+                return Err(BookError::LevelExists { side, price: level.price });
+            }
+            BookAction::Change | BookAction::Delete if !exists => {
+                return Err(BookError::MissingLevel { side, price: level.price });
+            }
+            BookAction::Add | BookAction::Change => {
+                levels.insert(level.price, level);
+            }
+            BookAction::Delete => {
+                levels.remove(&level.price);
+            }
+        }
+        
+        self.sequence = sequence;
+        Ok(true)
+    }
+    
+    /// Bid levels, best (highest) first
+    pub fn bids(&self) -> impl Iterator<Item = &Level> {
+        self.bids.values().rev()
+    }
+    
+    /// Ask levels, best (lowest) first
+    pub fn asks(&self) -> impl Iterator<Item = &Level> {
+        self.asks.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn level(cents: u64, quantity: u64) -> Level {
+        Level {
+            price: Price::from_cents(cents),
+            quantity,
+            order_count: 1,
+        }
+    }
+    
+    fn delta(side: Side, action: BookAction, cents: u64, quantity: u64) -> LevelDelta {
+        LevelDelta {
+            side,
+            action,
+            level: level(cents, quantity),
+        }
+    }
+    
+    fn prices<'a>(levels: impl Iterator<Item = &'a Level>) -> Vec<(u64, u64)> {
+        levels.map(|l| (l.price.units(), l.quantity)).collect()
+    }
+    
+    fn book() -> OrderBook {
+        OrderBook::from_snapshot(
+            10,
+            [level(9_900, 100), level(10_000, 200)],
+            [level(10_100, 300), level(10_200, 400)],
+        )
+    }
+    
+    #[test]
+    fn deltas_after_the_snapshot_update_the_book() {
+        let mut book = book();
+        assert_eq!(book.apply(11, &delta(Side::Buy, BookAction::Add, 10_050, 50)), Ok(true));
+        assert_eq!(book.apply(12, &delta(Side::Sell, BookAction::Change, 10_100, 150)), Ok(true));
+        assert_eq!(book.sequence(), 12);
+        
+        assert_eq!(prices(book.bids()), [(10_050, 50), (10_000, 200), (9_900, 100)]);
+        assert_eq!(prices(book.asks()), [(10_100, 150), (10_200, 400)]);
+    }
+    
+    #[test]
+    fn delete_removes_the_level() {
+        let mut book = book();
+        assert_eq!(book.apply(11, &delta(Side::Buy, BookAction::Delete, 10_000, 0)), Ok(true));
+        assert_eq!(book.apply(12, &delta(Side::Sell, BookAction::Delete, 10_100, 0)), Ok(true));
+        
+        assert_eq!(prices(book.bids()), [(9_900, 100)]);
+        assert_eq!(prices(book.asks()), [(10_200, 400)]);
+    }
+    
+    #[test]
+    fn stale_deltas_are_skipped_and_gaps_are_errors() {
+        let mut book = book();
+        assert_eq!(book.apply(10, &delta(Side::Buy, BookAction::Delete, 10_000, 0)), Ok(false));
+        assert_eq!(
+            book.apply(12, &delta(Side::Buy, BookAction::Delete, 10_000, 0)),
+            Err(BookError::Gap {
+                expected: 11,
+                received: 12
+            })
+        );
+        assert_eq!(book, self::book());
+    }
+    
+    #[test]
+    fn inconsistent_deltas_leave_the_book_unchanged() {
+        let mut book = book();
+        assert_eq!(
+            book.apply(11, &delta(Side::Buy, BookAction::Add, 10_000, 10)),
+            Err(BookError::LevelExists {
+                side: Side::Buy,
+                price: Price::from_cents(10_000)
+            })
+        );
+        assert_eq!(
+            book.apply(11, &delta(Side::Sell, BookAction::Change, 10_000, 10)),
+            Err(BookError::MissingLevel {
+                side: Side::Sell,
+                price: Price::from_cents(10_000)
+            })
+        );
+        assert_eq!(book, self::book());
+    }
+}
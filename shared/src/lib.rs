@@ -1,4 +1,8 @@
+pub mod book;
+
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Common domain types shared across the platform
 
@@ -12,6 +16,312 @@ pub enum Side {
 pub enum OrderType {
     Limit,
     Market,
+    /// Market order once the market trades through the stop price
+    Stop,
+    /// Limit order once the market trades through the stop price
+    StopLimit,
+}
+
+/// Dollar amounts are first rounded to this many millionths of a unit, so
+/// binary representation error (1.005 is stored as 1.00499999...) can't
+/// flip a half-unit the wrong way or make a whole number of units look
+/// fractional
+const SUB_UNIT_PRECISION: f64 = 1e6;
+
+/// Fixed-point scale of prices: how many integer price units make a
+/// dollar. 100 means prices are in cents, 1000 in tenths of a cent.
+///
+/// Prices that are a whole number of units convert to fixed-point and back
+/// without drift: `to_dollars(to_fixed(p)) == p`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub struct PriceScale(u64);
+
+impl PriceScale {
+    /// Prices in cents
+    pub const CENTS: PriceScale = PriceScale(100);
+    
+    /// `units_per_dollar` must be positive
+    pub fn new(units_per_dollar: u64) -> Self {
+        assert!(units_per_dollar > 0, "price scale must be positive");
+        Self(units_per_dollar)
+    }
+    
+    pub const fn units_per_dollar(self) -> u64 {
+        self.0
+    }
+    
+    /// Convert a dollar amount that must be a whole number of units.
+    /// `None` for negative, non-finite, out of range or finer amounts.
+    pub fn to_fixed(self, dollars: f64) -> Option<u64> {
+        let units = self.scale(dollars)?;
+        if units.fract() != 0.0 {
+            return None;
+        }
+        Self::checked_units(units)
+    }
+    
+    /// Round a dollar amount to the nearest unit, halves rounding up.
+    /// `None` for negative, non-finite or out of range amounts.
+    pub fn round_to_fixed(self, dollars: f64) -> Option<u64> {
+        Self::checked_units(self.scale(dollars)?.round())
+    }
+    
+    pub fn to_dollars(self, units: u64) -> f64 {
+        units as f64 / self.0 as f64
+    }
+    
+    /// Convert a fixed-point amount that needn't be whole, such as an
+    /// average fill price or a mid
+    pub fn fractional_to_dollars(self, units: f64) -> f64 {
+        units / self.0 as f64
+    }
+    
+    /// Dollars as units, rounded to `SUB_UNIT_PRECISION`
+    fn scale(self, dollars: f64) -> Option<f64> {
+        if !dollars.is_finite() || dollars < 0.0 {
+            return None;
+        }
+        let units = dollars * self.0 as f64;
+        // Beyond 2^53 there are no fractional units left to round
+        if units >= (1u64 << 53) as f64 {
+            return Some(units);
+        }
+        Some((units * SUB_UNIT_PRECISION).round() / SUB_UNIT_PRECISION)
+    }
+    
+    fn checked_units(units: f64) -> Option<u64> {
+        // u64::MAX as f64 rounds up to 2^64, which is itself out of range
+        (units < u64::MAX as f64).then_some(units as u64)
+    }
+    
+    /// Decimal places of a unit, when it is a power of ten of a dollar
+    fn decimals(self) -> Option<usize> {
+        let decimals = self.0.ilog10();
+        (10u64.pow(decimals) == self.0).then_some(decimals as usize)
+    }
+}
+
+impl Default for PriceScale {
+    fn default() -> Self {
+        Self::CENTS
+    }
+}
+
+impl TryFrom<u64> for PriceScale {
+    type Error = &'static str;
+    
+    fn try_from(units_per_dollar: u64) -> Result<Self, Self::Error> {
+        if units_per_dollar == 0 {
+            return Err("price scale must be positive");
+        }
+        Ok(Self(units_per_dollar))
+    }
+}
+
+impl From<PriceScale> for u64 {
+    fn from(scale: PriceScale) -> Self {
+        scale.0
+    }
+}
+
+/// The smallest representable price, e.g. "$0.001" at 1000 units per dollar
+impl fmt::Display for PriceScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.decimals() {
+            Some(decimals) => write!(f, "${:.*}", decimals, self.to_dollars(1)),
+            None => write!(f, "$1/{}", self.0),
+        }
+    }
+}
+
+/// A non-negative price in integer units of its `PriceScale`, matching the
+/// gateway's fixed-point representation. Prices at different scales
+/// compare by value.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Price {
+    units: u64,
+    scale: PriceScale,
+}
+
+impl Price {
+    pub const ZERO: Price = Price::from_cents(0);
+    
+    pub const fn new(units: u64, scale: PriceScale) -> Self {
+        Self { units, scale }
+    }
+    
+    pub const fn from_cents(cents: u64) -> Self {
+        Self::new(cents, PriceScale::CENTS)
+    }
+    
+    pub const fn units(self) -> u64 {
+        self.units
+    }
+    
+    pub const fn scale(self) -> PriceScale {
+        self.scale
+    }
+    
+    /// Round a dollar amount to the nearest cent, halves rounding up.
+    /// `None` for negative, non-finite or out of range amounts.
+    pub fn from_dollars(dollars: f64) -> Option<Self> {
+        PriceScale::CENTS.round_to_fixed(dollars).map(Self::from_cents)
+    }
+    
+    /// Convert a dollar amount that must already be a whole number of cents
+    pub fn from_dollars_exact(dollars: f64) -> Option<Self> {
+        PriceScale::CENTS.to_fixed(dollars).map(Self::from_cents)
+    }
+    
+    pub fn to_dollars(self) -> f64 {
+        self.scale.to_dollars(self.units)
+    }
+    
+    /// `None` on overflow, or if the prices are at different scales
+    pub fn checked_add(self, other: Price) -> Option<Price> {
+        (self.scale == other.scale)
+            .then(|| self.units.checked_add(other.units))?
+            .map(|units| Price::new(units, self.scale))
+    }
+    
+    /// `None` on underflow, or if the prices are at different scales
+    pub fn checked_sub(self, other: Price) -> Option<Price> {
+        (self.scale == other.scale)
+            .then(|| self.units.checked_sub(other.units))?
+            .map(|units| Price::new(units, self.scale))
+    }
+    
+    /// Price times a quantity, e.g. an order's notional
+    pub fn checked_mul(self, quantity: u64) -> Option<Price> {
+        self.units
+            .checked_mul(quantity)
+            .map(|units| Price::new(units, self.scale))
+    }
+    
+    /// Units and scale divided by their greatest common divisor, the same
+    /// for every representation of a value
+    fn reduced(self) -> (u64, u64) {
+        let (mut a, mut b) = (self.units, self.scale.0);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        (self.units / a, self.scale.0 / a)
+    }
+}
+
+impl PartialEq for Price {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Price {}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Price {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self.scale == other.scale {
+            return self.units.cmp(&other.units);
+        }
+        let lhs = self.units as u128 * other.scale.0 as u128;
+        let rhs = other.units as u128 * self.scale.0 as u128;
+        lhs.cmp(&rhs)
+    }
+}
+
+impl std::hash::Hash for Price {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.reduced().hash(state);
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_dollar = self.scale.0;
+        match self.scale.decimals() {
+            Some(0) => write!(f, "${}", self.units),
+            Some(decimals) => write!(
+                f,
+                "${}.{:0width$}",
+                self.units / per_dollar,
+                self.units % per_dollar,
+                width = decimals
+            ),
+            None => write!(f, "${}", self.to_dollars()),
+        }
+    }
+}
+
+/// Error returned when parsing a `Side` or `OrderType` from a string
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid {kind}: {input:?}")]
+pub struct ParseError {
+    kind: &'static str,
+    input: String,
+}
+
+impl ParseError {
+    fn new(kind: &'static str, input: &str) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "Buy",
+            Side::Sell => "Sell",
+        })
+    }
+}
+
+impl FromStr for Side {
+    type Err = ParseError;
+    
+    /// Case-insensitive; accepts "buy"/"b"/"bid" and "sell"/"s"/"ask"/"offer"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "buy" | "b" | "bid" => Ok(Side::Buy),
+            "sell" | "s" | "ask" | "offer" => Ok(Side::Sell),
+            _ => Err(ParseError::new("side", s)),
+        }
+    }
+}
+
+impl fmt::Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OrderType::Limit => "Limit",
+            OrderType::Market => "Market",
+            OrderType::Stop => "Stop",
+            OrderType::StopLimit => "StopLimit",
+        })
+    }
+}
+
+impl FromStr for OrderType {
+    type Err = ParseError;
+    
+    /// Case-insensitive; accepts "limit"/"lmt"/"l", "market"/"mkt"/"m",
+    /// "stop"/"stp" and "stoplimit"/"stop_limit"/"stop-limit"/"stplmt"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "limit" | "lmt" | "l" => Ok(OrderType::Limit),
+            "market" | "mkt" | "m" => Ok(OrderType::Market),
+            "stop" | "stp" => Ok(OrderType::Stop),
+            "stoplimit" | "stop_limit" | "stop-limit" | "stplmt" => Ok(OrderType::StopLimit),
+            _ => Err(ParseError::new("order type", s)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,16 +330,214 @@ pub struct Order {
     pub symbol: String,
     pub side: Side,
     pub order_type: OrderType,
-    pub price: f64,
+    pub price: Price,
     pub quantity: u64,
     pub user_id: u64,
 }
 
+/// Lifecycle state of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// A stop order held until a trade reaches its stop price; nothing
+    /// has been sent to the exchange yet
+    PendingTrigger,
+    /// Sent to the exchange, not yet acknowledged
+    PendingNew,
+    /// Acknowledged and resting, nothing filled yet
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderStatus {
+    /// No further updates can follow a terminal status
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected
+        )
+    }
+    
+    /// Whether an order in this status may move to `next`. Fills can
+    /// arrive before the acknowledgement, so a pending order may go
+    /// straight to (partially) filled. A held stop is sent once triggered,
+    /// or cancelled, or rejected if it couldn't be sent.
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+        
+        matches!(
+            (self, next),
+            (PendingTrigger, PendingNew | Cancelled | Rejected)
+                | (PendingNew, New | Rejected)
+                | (PendingNew | New | PartiallyFilled, PartiallyFilled | Filled | Cancelled)
+        )
+    }
+}
+
+/// An order together with its fill progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderState {
+    pub order: Order,
+    pub status: OrderStatus,
+    pub filled_quantity: u64,
+    pub leaves_quantity: u64,
+}
+
+impl OrderState {
+    /// A freshly sent order, nothing filled
+    pub fn new(order: Order) -> Self {
+        Self {
+            leaves_quantity: order.quantity,
+            order,
+            status: OrderStatus::PendingNew,
+            filled_quantity: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub id: u64,
     pub symbol: String,
-    pub price: f64,
+    pub price: Price,
     pub quantity: u64,
     pub timestamp: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasher, RandomState};
+    
+    #[test]
+    fn whole_units_round_trip_at_scale_1000() {
+        let scale = PriceScale::new(1000);
+        for units in (0..200_000).chain([9_007_199_254_740, 123_456_789_001]) {
+            let dollars = scale.to_dollars(units);
+            assert_eq!(scale.to_fixed(dollars), Some(units), "{}", dollars);
+            assert_eq!(Price::new(units, scale).to_dollars(), dollars);
+        }
+        
+        assert_eq!(scale.to_fixed(100.005), Some(100_005));
+        assert_eq!(scale.to_fixed(100.0005), None);
+        assert_eq!(scale.round_to_fixed(100.0005), Some(100_001));
+        assert_eq!(scale.to_string(), "$0.001");
+    }
+    
+    #[test]
+    fn prices_at_different_scales_compare_by_value() {
+        let mills = PriceScale::new(1000);
+        assert_eq!(Price::new(100_500, mills), Price::from_cents(10_050));
+        assert!(Price::new(100_501, mills) > Price::from_cents(10_050));
+        assert!(Price::new(100_499, mills) < Price::from_cents(10_050));
+        
+        let state = RandomState::new();
+        assert_eq!(
+            state.hash_one(Price::new(100_500, mills)),
+            state.hash_one(Price::from_cents(10_050))
+        );
+        
+        assert_eq!(Price::new(100_500, mills).checked_add(Price::from_cents(1)), None);
+    }
+    
+    #[test]
+    fn price_displays_at_its_scale() {
+        assert_eq!(Price::from_cents(10_050).to_string(), "$100.50");
+        assert_eq!(Price::new(100_005, PriceScale::new(1000)).to_string(), "$100.005");
+        assert_eq!(Price::new(7, PriceScale::new(1)).to_string(), "$7");
+        assert_eq!(Price::new(3, PriceScale::new(4)).to_string(), "$0.75");
+    }
+    
+    #[test]
+    fn cent_prices_round_trip_through_dollars() {
+        for cents in (0..100_000).chain([1_000_000_007, 9_007_199_254_740_991]) {
+            let price = Price::from_cents(cents);
+            assert_eq!(Price::from_dollars_exact(price.to_dollars()), Some(price), "{}", price);
+            assert_eq!(Price::from_dollars(price.to_dollars()), Some(price), "{}", price);
+        }
+        
+        assert_eq!(Price::from_dollars(1.005), Some(Price::from_cents(101)));
+        assert_eq!(Price::from_dollars(100.004), Some(Price::from_cents(10_000)));
+    }
+    
+    #[test]
+    fn invalid_dollar_amounts_are_rejected() {
+        for dollars in [-0.01, -100.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e20] {
+            assert_eq!(Price::from_dollars(dollars), None, "{}", dollars);
+            assert_eq!(Price::from_dollars_exact(dollars), None, "{}", dollars);
+        }
+        
+        // Sub-cent amounts only convert by rounding
+        for dollars in [100.005, 0.001, 99.999_9] {
+            assert_eq!(Price::from_dollars_exact(dollars), None, "{}", dollars);
+            assert!(Price::from_dollars(dollars).is_some(), "{}", dollars);
+        }
+    }
+    
+    #[test]
+    fn side_and_order_type_round_trip_through_strings() {
+        for side in [Side::Buy, Side::Sell] {
+            assert_eq!(side.to_string().parse::<Side>(), Ok(side));
+        }
+        for order_type in [OrderType::Limit, OrderType::Market, OrderType::Stop, OrderType::StopLimit] {
+            assert_eq!(order_type.to_string().parse::<OrderType>(), Ok(order_type));
+        }
+        
+        assert_eq!(" BID ".parse::<Side>(), Ok(Side::Buy));
+        assert_eq!("Offer".parse::<Side>(), Ok(Side::Sell));
+        assert_eq!("MKT".parse::<OrderType>(), Ok(OrderType::Market));
+        assert_eq!("stop-limit".parse::<OrderType>(), Ok(OrderType::StopLimit));
+    }
+    
+    #[test]
+    fn unknown_side_and_order_type_strings_are_rejected() {
+        for input in ["", "long", "buyy"] {
+            assert_eq!(input.parse::<Side>(), Err(ParseError::new("side", input)));
+        }
+        for input in ["", "ioc", "stop limit"] {
+            assert_eq!(input.parse::<OrderType>(), Err(ParseError::new("order type", input)));
+        }
+        assert_eq!("long".parse::<Side>().unwrap_err().to_string(), "invalid side: \"long\"");
+    }
+    
+    const STATUSES: [OrderStatus; 7] = [
+        OrderStatus::PendingTrigger,
+        OrderStatus::PendingNew,
+        OrderStatus::New,
+        OrderStatus::PartiallyFilled,
+        OrderStatus::Filled,
+        OrderStatus::Cancelled,
+        OrderStatus::Rejected,
+    ];
+    
+    #[test]
+    fn only_filled_cancelled_and_rejected_are_terminal() {
+        let terminal: Vec<_> = STATUSES.into_iter().filter(OrderStatus::is_terminal).collect();
+        assert_eq!(terminal, [OrderStatus::Filled, OrderStatus::Cancelled, OrderStatus::Rejected]);
+    }
+    
+    #[test]
+    fn terminal_statuses_allow_no_transitions() {
+        for from in STATUSES.into_iter().filter(OrderStatus::is_terminal) {
+            for to in STATUSES {
+                assert!(!from.can_transition_to(to), "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+    
+    #[test]
+    fn permitted_transitions() {
+        use OrderStatus::*;
+        
+        let allowed = |from: OrderStatus| -> Vec<OrderStatus> {
+            STATUSES.into_iter().filter(|to| from.can_transition_to(*to)).collect()
+        };
+        assert_eq!(allowed(PendingTrigger), [PendingNew, Cancelled, Rejected]);
+        // A fill can overtake the ack
+        assert_eq!(allowed(PendingNew), [New, PartiallyFilled, Filled, Cancelled, Rejected]);
+        assert_eq!(allowed(New), [PartiallyFilled, Filled, Cancelled]);
+        assert_eq!(allowed(PartiallyFilled), [PartiallyFilled, Filled, Cancelled]);
+    }
+}